@@ -1,7 +1,7 @@
-use criterion::{Criterion, criterion_group, criterion_main};
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
 use egui_sgr::{
-    AnsiSpanBuffer, AnsiStreamParser, EguiAnsiTheme, ansi_to_layout_job, ansi_to_spans,
-    spans_to_layout_job,
+    AnsiSpanBuffer, AnsiStreamParser, EguiAnsiTheme, ansi_bytes_to_layout_job, ansi_to_layout_job,
+    ansi_to_spans, ansi_to_text, spans_to_layout_job,
 };
 use std::hint::black_box;
 
@@ -23,6 +23,11 @@ const TRUECOLOR_DENSE_SAMPLE: &str = "\
 \x1b[48;2;30;30;30;38;2;180;220;255msoft\x1b[0m \
 \x1b[4:3;58:5:51munderline\x1b[0m";
 
+const PLAIN_LINE: &str = "plain log line without sgr\n";
+
+const LOG_LINE: &str = "\x1b[2m2024-05-01T12:00:00Z\x1b[0m \x1b[32mINFO\x1b[0m \
+server: request served, method=GET path=/api/v1/items status=200 elapsed=12ms\n";
+
 const STREAM_CHUNKS: &[&[u8]] = &[
     b"\x1b[32mstream ",
     b"keeps ",
@@ -33,68 +38,126 @@ const STREAM_CHUNKS: &[&[u8]] = &[
 ];
 
 fn bench_one_shot_parse(c: &mut Criterion) {
-    c.bench_function("ansi_to_spans/mixed", |b| {
-        b.iter(|| ansi_to_spans(black_box(MIXED_SAMPLE)));
-    });
-}
+    let long_log = LOG_LINE.repeat(512);
+    let mut group = c.benchmark_group("ansi_to_spans");
+
+    for (name, input) in [
+        ("mixed", MIXED_SAMPLE),
+        ("sgr_dense", SGR_DENSE_SAMPLE),
+        ("long_log", long_log.as_str()),
+    ] {
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_function(name, |b| {
+            b.iter(|| ansi_to_spans(black_box(input)));
+        });
+    }
 
-fn bench_sgr_dense_parse(c: &mut Criterion) {
-    c.bench_function("ansi_to_spans/sgr_dense", |b| {
-        b.iter(|| ansi_to_spans(black_box(SGR_DENSE_SAMPLE)));
+    group.finish();
+
+    // Style runs over one text buffer, so parsing is measured without an
+    // allocation per span.
+    let mut group = c.benchmark_group("ansi_to_text");
+    group.throughput(Throughput::Bytes(long_log.len() as u64));
+    group.bench_function("long_log", |b| {
+        b.iter(|| ansi_to_text(black_box(long_log.as_str())));
     });
+    group.finish();
 }
 
 fn bench_layout_job(c: &mut Criterion) {
     let theme = EguiAnsiTheme::default();
+    let long_plain = PLAIN_LINE.repeat(256);
+    let long_mixed = MIXED_SAMPLE.repeat(64);
+    let long_log = LOG_LINE.repeat(512);
+    let mut group = c.benchmark_group("ansi_to_layout_job");
+
+    for (name, input) in [
+        ("mixed", MIXED_SAMPLE),
+        ("sgr_dense", SGR_DENSE_SAMPLE),
+        ("truecolor_dense", TRUECOLOR_DENSE_SAMPLE),
+        ("long_plain", long_plain.as_str()),
+        ("long_mixed", long_mixed.as_str()),
+        ("long_log", long_log.as_str()),
+    ] {
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_function(name, |b| {
+            b.iter(|| ansi_to_layout_job(black_box(input), black_box(&theme)));
+        });
+    }
 
-    c.bench_function("ansi_to_layout_job/mixed", |b| {
-        b.iter(|| ansi_to_layout_job(black_box(MIXED_SAMPLE), black_box(&theme)));
-    });
+    group.finish();
+}
+
+fn bench_pathological_layout_job(c: &mut Criterion) {
+    let theme = EguiAnsiTheme::default();
+    let many_params = format!("\x1b[{}mtext", "1;".repeat(4096));
+    let unterminated_escapes = "\x1b[31".repeat(4096);
+    let long_osc = format!("\x1b]0;{}\x07after", "t".repeat(64 * 1024));
+    let style_per_char = "\x1b[31mx\x1b[32my".repeat(2048);
+    let invalid_utf8 = b"ok \xFF\xFE ".repeat(4096);
+    let mut group = c.benchmark_group("pathological");
+
+    for (name, input) in [
+        ("many_params", many_params.as_bytes()),
+        ("unterminated_escapes", unterminated_escapes.as_bytes()),
+        ("long_osc", long_osc.as_bytes()),
+        ("style_per_char", style_per_char.as_bytes()),
+        ("invalid_utf8", invalid_utf8.as_slice()),
+    ] {
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_function(name, |b| {
+            b.iter(|| ansi_bytes_to_layout_job(black_box(input), black_box(&theme)));
+        });
+    }
+
+    group.finish();
 }
 
 fn bench_parse_then_layout_job(c: &mut Criterion) {
     let theme = EguiAnsiTheme::default();
+    let mut group = c.benchmark_group("parse_then_spans_to_layout_job");
+    group.throughput(Throughput::Bytes(MIXED_SAMPLE.len() as u64));
 
-    c.bench_function("parse_then_spans_to_layout_job/mixed", |b| {
+    group.bench_function("mixed", |b| {
         b.iter(|| {
             let spans = ansi_to_spans(black_box(MIXED_SAMPLE));
             spans_to_layout_job(black_box(&spans), black_box(&theme))
         });
     });
-}
 
-fn bench_sgr_dense_layout_job(c: &mut Criterion) {
-    let theme = EguiAnsiTheme::default();
+    group.finish();
+}
 
-    c.bench_function("ansi_to_layout_job/sgr_dense", |b| {
-        b.iter(|| ansi_to_layout_job(black_box(SGR_DENSE_SAMPLE), black_box(&theme)));
-    });
+fn stream_bytes() -> u64 {
+    STREAM_CHUNKS.iter().map(|chunk| chunk.len() as u64).sum()
 }
 
-fn bench_truecolor_dense_layout_job(c: &mut Criterion) {
-    let theme = EguiAnsiTheme::default();
+fn bench_stream_parser(c: &mut Criterion) {
+    let mut group = c.benchmark_group("AnsiStreamParser");
+    group.throughput(Throughput::Bytes(stream_bytes()));
 
-    c.bench_function("ansi_to_layout_job/truecolor_dense", |b| {
-        b.iter(|| ansi_to_layout_job(black_box(TRUECOLOR_DENSE_SAMPLE), black_box(&theme)));
-    });
-}
+    group.bench_function("chunked", |b| {
+        b.iter(|| {
+            let mut parser = AnsiStreamParser::new();
+            let mut spans = Vec::new();
 
-fn bench_long_plain_layout_job(c: &mut Criterion) {
-    let theme = EguiAnsiTheme::default();
-    let long_plain = "plain log line without sgr\n".repeat(256);
+            for chunk in STREAM_CHUNKS {
+                spans.extend(parser.push_bytes(black_box(chunk)));
+            }
+            spans.extend(parser.finish());
 
-    c.bench_function("ansi_to_layout_job/long_plain", |b| {
-        b.iter(|| ansi_to_layout_job(black_box(&long_plain), black_box(&theme)));
+            spans
+        });
     });
-}
 
-fn bench_stream_parser(c: &mut Criterion) {
-    c.bench_function("AnsiStreamParser/chunked", |b| {
+    let long_log = LOG_LINE.repeat(512);
+    group.throughput(Throughput::Bytes(long_log.len() as u64));
+    group.bench_function("long_log_4k_chunks", |b| {
         b.iter(|| {
             let mut parser = AnsiStreamParser::new();
             let mut spans = Vec::new();
 
-            for chunk in STREAM_CHUNKS {
+            for chunk in long_log.as_bytes().chunks(4096) {
                 spans.extend(parser.push_bytes(black_box(chunk)));
             }
             spans.extend(parser.finish());
@@ -102,12 +165,16 @@ fn bench_stream_parser(c: &mut Criterion) {
             spans
         });
     });
+
+    group.finish();
 }
 
 fn bench_span_buffer(c: &mut Criterion) {
     let theme = EguiAnsiTheme::default();
+    let mut group = c.benchmark_group("AnsiSpanBuffer");
+    group.throughput(Throughput::Bytes(stream_bytes()));
 
-    c.bench_function("AnsiSpanBuffer/chunked_to_layout_job", |b| {
+    group.bench_function("chunked_to_layout_job", |b| {
         b.iter(|| {
             let mut buffer = AnsiSpanBuffer::new();
 
@@ -119,17 +186,16 @@ fn bench_span_buffer(c: &mut Criterion) {
             buffer.to_layout_job(black_box(&theme))
         });
     });
+
+    group.finish();
 }
 
 criterion_group!(
     benches,
     bench_one_shot_parse,
-    bench_sgr_dense_parse,
     bench_layout_job,
+    bench_pathological_layout_job,
     bench_parse_then_layout_job,
-    bench_sgr_dense_layout_job,
-    bench_truecolor_dense_layout_job,
-    bench_long_plain_layout_job,
     bench_stream_parser,
     bench_span_buffer
 );
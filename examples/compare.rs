@@ -0,0 +1,166 @@
+use eframe::{App, Frame, egui};
+use egui::{Color32, FontId, RichText};
+use egui_sgr::{AlignKind, AlignedRow, AnsiDocument, EguiAnsiTheme, align_documents};
+use std::path::PathBuf;
+
+const CHANGED: Color32 = Color32::from_rgb(70, 60, 20);
+const LEFT_ONLY: Color32 = Color32::from_rgb(80, 30, 30);
+const RIGHT_ONLY: Color32 = Color32::from_rgb(30, 70, 35);
+
+/// One side of the comparison.
+#[derive(Default)]
+struct Side {
+    path: String,
+    document: AnsiDocument,
+    error: Option<String>,
+}
+
+impl Side {
+    fn open(&mut self) {
+        let path = PathBuf::from(self.path.trim());
+        match std::fs::read(&path) {
+            Ok(bytes) => {
+                self.document = AnsiDocument::new();
+                self.document.append(&bytes);
+                if self.document.has_open_line() {
+                    self.document.append(b"\n");
+                }
+                self.error = None;
+            }
+            Err(error) => self.error = Some(format!("{}: {error}", path.display())),
+        }
+    }
+}
+
+struct Compare {
+    left: Side,
+    right: Side,
+    rows: Vec<AlignedRow>,
+    changes_only: bool,
+    theme: EguiAnsiTheme,
+}
+
+impl Compare {
+    fn new(left: Option<String>, right: Option<String>) -> Self {
+        let mut theme = EguiAnsiTheme::default();
+        theme.default_format.font_id = FontId::monospace(13.0);
+        let mut compare = Self {
+            left: Side::default(),
+            right: Side::default(),
+            rows: Vec::new(),
+            changes_only: false,
+            theme,
+        };
+        for (side, path) in [(&mut compare.left, left), (&mut compare.right, right)] {
+            if let Some(path) = path {
+                side.path = path;
+                side.open();
+            }
+        }
+        compare.realign();
+        compare
+    }
+
+    fn realign(&mut self) {
+        self.rows = align_documents(&self.left.document, &self.right.document);
+    }
+
+    fn show_cell(&self, ui: &mut egui::Ui, side: &Side, index: Option<usize>, fill: Color32) {
+        let job = index
+            .and_then(|index| side.document.line(index))
+            .map(|line| line.text.to_layout_job(&self.theme));
+        egui::Frame::NONE.fill(fill).show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            match job {
+                Some(job) => ui.label(job),
+                None => ui.label(""),
+            };
+        });
+    }
+}
+
+impl App for Compare {
+    fn ui(&mut self, ui: &mut egui::Ui, _frame: &mut Frame) {
+        let mut reopened = false;
+        ui.horizontal(|ui| {
+            for (label, side) in [("Left", &mut self.left), ("Right", &mut self.right)] {
+                ui.label(label);
+                ui.add(egui::TextEdit::singleline(&mut side.path).desired_width(280.0));
+                if ui.button("Open").clicked() {
+                    side.open();
+                    reopened = true;
+                }
+                if let Some(error) = &side.error {
+                    ui.label(RichText::new(error).color(Color32::LIGHT_RED));
+                }
+                ui.separator();
+            }
+            ui.checkbox(&mut self.changes_only, "Changes only");
+        });
+        if reopened {
+            self.realign();
+        }
+
+        let changed = self
+            .rows
+            .iter()
+            .filter(|row| row.kind != AlignKind::Equal)
+            .count();
+        ui.weak(format!("{} rows, {changed} differ", self.rows.len()));
+        ui.separator();
+
+        let rows: Vec<AlignedRow> = self
+            .rows
+            .iter()
+            .filter(|row| !self.changes_only || row.kind != AlignKind::Equal)
+            .copied()
+            .collect();
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+
+        // Both columns live in one scroll area, so they always scroll together.
+        egui::Frame::NONE
+            .fill(self.theme.default_background)
+            .show(ui, |ui| {
+                egui::ScrollArea::both().auto_shrink(false).show_rows(
+                    ui,
+                    row_height,
+                    rows.len(),
+                    |ui, range| {
+                        ui.spacing_mut().item_spacing.y = 0.0;
+                        let column_width = (ui.available_width() - 8.0) / 2.0;
+                        for row in &rows[range] {
+                            let (left_fill, right_fill) = match row.kind {
+                                AlignKind::Equal => (Color32::TRANSPARENT, Color32::TRANSPARENT),
+                                AlignKind::Changed => (CHANGED, CHANGED),
+                                AlignKind::LeftOnly => (LEFT_ONLY, Color32::TRANSPARENT),
+                                AlignKind::RightOnly => (Color32::TRANSPARENT, RIGHT_ONLY),
+                            };
+                            ui.horizontal(|ui| {
+                                ui.allocate_ui(egui::vec2(column_width, row_height), |ui| {
+                                    self.show_cell(ui, &self.left, row.left, left_fill);
+                                });
+                                ui.allocate_ui(egui::vec2(column_width, row_height), |ui| {
+                                    self.show_cell(ui, &self.right, row.right, right_fill);
+                                });
+                            });
+                        }
+                    },
+                );
+            });
+    }
+}
+
+fn main() -> Result<(), eframe::Error> {
+    let mut args = std::env::args().skip(1);
+    let compare = Compare::new(args.next(), args.next());
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default().with_inner_size([1200.0, 760.0]),
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "egui_sgr Compare",
+        options,
+        Box::new(|_cc| Ok(Box::new(compare))),
+    )
+}
@@ -1,7 +1,8 @@
 use eframe::{App, Frame, egui};
 use egui::{Color32, RichText, Sense, Vec2};
 use egui_sgr::{
-    AnsiColor, AnsiSpan, AnsiSpanBuffer, EguiAnsiTheme, ansi_to_spans, spans_to_layout_job,
+    AnsiColor, AnsiSpan, AnsiSpanBuffer, EguiAnsiTheme, ansi_to_spans, escape_for_display,
+    spans_to_layout_job, unescape_from_display,
 };
 
 const PRESETS: &[Preset] = &[
@@ -57,7 +58,7 @@ impl Default for AnsiColorDemo {
     fn default() -> Self {
         Self {
             selected_preset: 3,
-            custom_input: display_ansi_sequence(PRESETS[3].input),
+            custom_input: escape_for_display(PRESETS[3].input),
             stream_buffer: AnsiSpanBuffer::new(),
             stream_cursor: 0,
         }
@@ -79,7 +80,7 @@ impl App for AnsiColorDemo {
         ui.separator();
 
         let theme = EguiAnsiTheme::default();
-        let parsed_input = parse_user_input(&self.custom_input);
+        let parsed_input = unescape_from_display(&self.custom_input);
         let spans = ansi_to_spans(&parsed_input);
         let job = spans_to_layout_job(&spans, &theme);
 
@@ -103,7 +104,7 @@ impl AnsiColorDemo {
                     .clicked()
                 {
                     self.selected_preset = index;
-                    self.custom_input = display_ansi_sequence(preset.input);
+                    self.custom_input = escape_for_display(preset.input);
                 }
             }
         });
@@ -190,7 +191,7 @@ impl AnsiColorDemo {
 
         ui.horizontal_wrapped(|ui| {
             for (index, chunk) in STREAM.iter().enumerate() {
-                let text = display_ansi_sequence(chunk);
+                let text = escape_for_display(chunk);
                 if index < self.stream_cursor {
                     ui.label(RichText::new(text).monospace().strong());
                 } else {
@@ -216,7 +217,7 @@ impl AnsiColorDemo {
 
         ui.add_space(8.0);
         ui.collapsing("Escaped input", |ui| {
-            ui.monospace(display_ansi_sequence(parsed_input));
+            ui.monospace(escape_for_display(parsed_input));
         });
 
         ui.add_space(8.0);
@@ -243,7 +244,7 @@ impl AnsiColorDemo {
 
                 for (index, span) in spans.iter().enumerate() {
                     ui.monospace(index.to_string());
-                    ui.monospace(visible_debug_text(&span.text));
+                    ui.monospace(escape_for_display(&span.text));
                     ui.monospace(color_label(span.style.foreground));
                     ui.monospace(color_label(span.style.background));
                     ui.monospace(style_label(span));
@@ -269,23 +270,6 @@ impl AnsiColorDemo {
     }
 }
 
-fn display_ansi_sequence(input: &str) -> String {
-    input.replace('\x1b', "\\x1b")
-}
-
-fn parse_user_input(input: &str) -> String {
-    input
-        .replace("\\x1b", "\x1b")
-        .replace("\\x1B", "\x1b")
-        .replace("\\X1b", "\x1b")
-        .replace("\\X1B", "\x1b")
-        .replace("\\033", "\x1b")
-}
-
-fn visible_debug_text(text: &str) -> String {
-    text.replace('\n', "\\n").replace('\r', "\\r")
-}
-
 fn color_label(color: AnsiColor) -> String {
     match color {
         AnsiColor::Default => "default".to_owned(),
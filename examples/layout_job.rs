@@ -1,5 +1,5 @@
 use eframe::{App, Frame, egui};
-use egui_sgr::{EguiAnsiTheme, ansi_to_layout_job};
+use egui_sgr::{AnsiUiExt as _, EguiAnsiTheme, ansi_to_layout_job};
 
 struct LayoutJobExample {
     input: String,
@@ -30,6 +30,10 @@ impl App for LayoutJobExample {
         let job = ansi_to_layout_job(&ansi, &theme);
 
         ui.label(job.clone());
+        ui.horizontal(|ui| {
+            ui.weak("ui.ansi_monospace:");
+            ui.ansi_monospace(&ansi);
+        });
 
         ui.add_space(8.0);
         egui::Grid::new("layout_job_sections")
@@ -1,5 +1,5 @@
 use eframe::{App, Frame, egui};
-use egui_sgr::{AnsiColor, AnsiSpanBuffer, EguiAnsiTheme};
+use egui_sgr::{AnsiColor, AnsiSpanBuffer, EguiAnsiTheme, escape_for_display};
 
 const CHUNKS: &[&str] = &[
     "\x1b[32mstream ",
@@ -44,7 +44,7 @@ impl App for StreamingExample {
 
         ui.horizontal_wrapped(|ui| {
             for (index, chunk) in CHUNKS.iter().enumerate() {
-                let text = display_ansi_sequence(chunk);
+                let text = escape_for_display(chunk);
                 if index < self.next_chunk {
                     ui.monospace(text);
                 } else {
@@ -86,10 +86,6 @@ impl StreamingExample {
     }
 }
 
-fn display_ansi_sequence(input: &str) -> String {
-    input.replace('\x1b', "\\x1b")
-}
-
 fn color_label(color: AnsiColor) -> String {
     match color {
         AnsiColor::Default => "default".to_owned(),
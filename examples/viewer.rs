@@ -0,0 +1,697 @@
+use eframe::{App, Frame, egui};
+use egui::text::LayoutJob;
+use egui::{Color32, FontId, RichText};
+use egui_sgr::{
+    AnsiColor, AnsiDocument, AnsiFile, AnsiFileError, AnsiFileKind, AnsiSpan, AnsiStyle, AnsiText,
+    AsciicastPlayer, ColorRole, ColorUsage, CommonLogFormat, DroppedAnsiFile, EguiAnsiTheme,
+    HtmlOptions, JsonLogFormat, LogRow, LogfmtFormat, OverlayLayer, OverlayStyle, TextEncoding,
+    color_usage, highlight_color, highlight_matches, json_to_text, lines_with_color,
+    logfmt_to_text, spans_to_ansi, spans_to_html,
+};
+use regex::Regex;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+const MAX_DROP_LEN: usize = 256 * 1024 * 1024;
+const SEARCH_HIGHLIGHT: Color32 = Color32::from_rgb(120, 90, 0);
+const PAGE_SEPARATOR: &str = "──────────────── page break ────────────────";
+/// Lines wider than this are folded until their marker is clicked.
+const FOLD_COLUMNS: usize = 2000;
+
+/// Classic VGA text-mode colors, which most `.ans` art is drawn for.
+const VGA: [Color32; 16] = [
+    Color32::from_rgb(0, 0, 0),
+    Color32::from_rgb(170, 0, 0),
+    Color32::from_rgb(0, 170, 0),
+    Color32::from_rgb(170, 85, 0),
+    Color32::from_rgb(0, 0, 170),
+    Color32::from_rgb(170, 0, 170),
+    Color32::from_rgb(0, 170, 170),
+    Color32::from_rgb(170, 170, 170),
+    Color32::from_rgb(85, 85, 85),
+    Color32::from_rgb(255, 85, 85),
+    Color32::from_rgb(85, 255, 85),
+    Color32::from_rgb(255, 255, 85),
+    Color32::from_rgb(85, 85, 255),
+    Color32::from_rgb(255, 85, 255),
+    Color32::from_rgb(85, 255, 255),
+    Color32::from_rgb(255, 255, 255),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Palette {
+    Xterm,
+    Vga,
+    Light,
+}
+
+impl Palette {
+    const ALL: [Self; 3] = [Self::Xterm, Self::Vga, Self::Light];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Xterm => "xterm",
+            Self::Vga => "VGA",
+            Self::Light => "Light",
+        }
+    }
+
+    fn theme(self, bold_is_bright: bool) -> EguiAnsiTheme {
+        let mut theme = EguiAnsiTheme::default();
+        match self {
+            Self::Xterm => theme.bold_foreground = Some(Color32::WHITE),
+            Self::Vga => {
+                theme.palette[..16].copy_from_slice(&VGA);
+                theme.default_foreground = VGA[7];
+                theme.default_background = VGA[0];
+                theme.bold_foreground = Some(VGA[15]);
+            }
+            Self::Light => {
+                theme.default_foreground = Color32::from_rgb(30, 30, 30);
+                theme.default_background = Color32::from_rgb(250, 250, 250);
+            }
+        }
+        theme.default_format.font_id = FontId::monospace(13.0);
+        theme.bold_is_bright = bold_is_bright;
+        theme
+    }
+}
+
+/// Loaded file contents.
+enum Content {
+    Empty,
+    Document(Box<AnsiDocument>),
+    Cast(Box<AsciicastPlayer>),
+}
+
+struct Viewer {
+    path: String,
+    loaded: Option<PathBuf>,
+    content: Content,
+    details: String,
+    error: Option<String>,
+    search: String,
+    matches: Option<usize>,
+    palette: Palette,
+    bold_is_bright: bool,
+    legend: Vec<ColorUsage>,
+    picked: Option<(AnsiColor, ColorRole)>,
+    only_picked: bool,
+    selection: Option<Range<usize>>,
+    encoding: Option<TextEncoding>,
+    /// Character offsets in the shown text where each page starts.
+    pages: Vec<usize>,
+    page: usize,
+    scroll_to_page: bool,
+    /// Whether log lines are split into timestamp, level, source, and
+    /// message columns.
+    columns: bool,
+}
+
+impl Default for Viewer {
+    fn default() -> Self {
+        Self {
+            path: String::new(),
+            loaded: None,
+            content: Content::Empty,
+            details: String::new(),
+            error: None,
+            search: String::new(),
+            matches: None,
+            palette: Palette::Xterm,
+            bold_is_bright: true,
+            legend: Vec::new(),
+            picked: None,
+            only_picked: false,
+            selection: None,
+            encoding: None,
+            pages: Vec::new(),
+            page: 0,
+            scroll_to_page: false,
+            columns: false,
+        }
+    }
+}
+
+impl App for Viewer {
+    fn ui(&mut self, ui: &mut egui::Ui, _frame: &mut Frame) {
+        // Only take the first drop, and leave oversized files alone.
+        let dropped = DroppedAnsiFile::from_input(ui.ctx());
+        if let Some(file) = dropped.into_iter().next() {
+            if file.byte_len().is_some_and(|len| len > MAX_DROP_LEN) {
+                self.error = Some(format!("{} is too large", file.name));
+            } else {
+                let path = file
+                    .path
+                    .clone()
+                    .unwrap_or_else(|| PathBuf::from(&file.name));
+                let encoding = self.encoding;
+                let loaded = file.load_with(|kind, bytes| choose_encoding(encoding, kind, bytes));
+                self.show_file(path, loaded);
+            }
+        }
+
+        self.show_toolbar(ui);
+        ui.separator();
+
+        let theme = self.palette.theme(self.bold_is_bright);
+        if !self.legend.is_empty() {
+            egui::Panel::right("legend")
+                .resizable(false)
+                .show_inside(ui, |ui| self.show_legend(ui, &theme));
+        }
+
+        let job = match &mut self.content {
+            Content::Empty => {
+                ui.centered_and_justified(|ui| {
+                    ui.label("Open a .log, .ans, or .cast file, or drop one here.");
+                });
+                return;
+            }
+            Content::Document(_) if self.only_picked && self.picked.is_some() => {
+                self.shown_text().to_layout_job(&theme)
+            }
+            Content::Document(document) => document.to_layout_job(&theme),
+            Content::Cast(player) => {
+                show_playback(ui, player);
+                if player.is_playing() {
+                    player.advance(f64::from(ui.input(|input| input.stable_dt)));
+                    ui.ctx().request_repaint();
+                }
+                player.to_layout_job(&theme)
+            }
+        };
+
+        egui::Frame::NONE
+            .fill(theme.default_background)
+            .inner_margin(6.0)
+            .show(ui, |ui| {
+                egui::ScrollArea::both()
+                    .auto_shrink(false)
+                    .stick_to_bottom(matches!(self.content, Content::Cast(_)))
+                    .show(ui, |ui| {
+                        if let Content::Document(document) = &self.content
+                            && self.columns
+                        {
+                            show_columns(ui, document, &theme);
+                        } else if matches!(self.content, Content::Document(_)) {
+                            let scroll_to = std::mem::take(&mut self.scroll_to_page)
+                                .then(|| self.pages.get(self.page).copied())
+                                .flatten();
+                            let (selection, clicked) = show_selectable(ui, job, scroll_to);
+                            if clicked && let Some(range) = &selection {
+                                self.expand_fold_at(range.start);
+                            }
+                            self.selection = selection;
+                        } else {
+                            ui.label(job);
+                        }
+                    });
+            });
+    }
+}
+
+impl Viewer {
+    fn show_legend(&mut self, ui: &mut egui::Ui, theme: &EguiAnsiTheme) {
+        ui.heading("Colors");
+        ui.checkbox(&mut self.only_picked, "Only picked lines");
+        ui.separator();
+        let mut clicked = None;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for usage in &self.legend {
+                let entry = (usage.color, usage.role);
+                let swatch = match usage.role {
+                    ColorRole::Foreground => RichText::new("Aa").color(resolve(usage.color, theme)),
+                    ColorRole::Background => {
+                        RichText::new("  ").background_color(resolve(usage.color, theme))
+                    }
+                };
+                ui.horizontal(|ui| {
+                    ui.label(swatch.monospace());
+                    let label = format!("{} lines, {} chars", usage.lines, usage.chars);
+                    if ui
+                        .selectable_label(self.picked == Some(entry), label)
+                        .clicked()
+                    {
+                        clicked = Some(entry);
+                    }
+                });
+            }
+        });
+
+        if let Some(entry) = clicked {
+            self.picked = (self.picked != Some(entry)).then_some(entry);
+            self.search.clear();
+            self.update_search();
+        }
+    }
+
+    fn show_toolbar(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("File");
+            let path = ui.add(egui::TextEdit::singleline(&mut self.path).desired_width(360.0));
+            let submitted =
+                path.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter));
+            if ui.button("Open").clicked() || submitted {
+                self.open(PathBuf::from(self.path.trim()));
+            }
+            if let Some(error) = &self.error {
+                ui.label(RichText::new(error).color(Color32::LIGHT_RED));
+            } else {
+                ui.weak(&self.details);
+            }
+
+            let paged = self.pages.len() > 1 && !(self.only_picked && self.picked.is_some());
+            if paged {
+                ui.separator();
+                let last = self.pages.len() - 1;
+                if ui
+                    .add_enabled(self.page > 0, egui::Button::new("◀"))
+                    .clicked()
+                {
+                    self.page -= 1;
+                    self.scroll_to_page = true;
+                }
+                ui.label(format!("Page {}/{}", self.page + 1, last + 1));
+                if ui
+                    .add_enabled(self.page < last, egui::Button::new("▶"))
+                    .clicked()
+                {
+                    self.page += 1;
+                    self.scroll_to_page = true;
+                }
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Search");
+            let search = ui.add(
+                egui::TextEdit::singleline(&mut self.search)
+                    .hint_text("regex")
+                    .desired_width(240.0),
+            );
+            if search.changed() {
+                self.update_search();
+            }
+            match self.matches {
+                Some(count) => ui.weak(format!("{count} matches")),
+                None if self.search.is_empty() => ui.weak(""),
+                None => ui.label(RichText::new("invalid regex").color(Color32::LIGHT_RED)),
+            };
+
+            ui.separator();
+            egui::ComboBox::from_id_salt("palette")
+                .selected_text(self.palette.name())
+                .show_ui(ui, |ui| {
+                    for palette in Palette::ALL {
+                        ui.selectable_value(&mut self.palette, palette, palette.name());
+                    }
+                });
+            ui.checkbox(&mut self.bold_is_bright, "Bold is bright");
+            ui.add_enabled_ui(matches!(self.content, Content::Document(_)), |ui| {
+                ui.checkbox(&mut self.columns, "Columns")
+                    .on_hover_text("Split log lines into timestamp, level, source, and message");
+            });
+            let encoding_name = |encoding: Option<TextEncoding>| {
+                encoding.map_or("Auto encoding", TextEncoding::name)
+            };
+            let previous = self.encoding;
+            egui::ComboBox::from_id_salt("encoding")
+                .selected_text(encoding_name(self.encoding))
+                .show_ui(ui, |ui| {
+                    let encodings = [
+                        None,
+                        Some(TextEncoding::Utf8),
+                        Some(TextEncoding::Latin1),
+                        Some(TextEncoding::Cp437),
+                    ];
+                    for encoding in encodings {
+                        ui.selectable_value(&mut self.encoding, encoding, encoding_name(encoding));
+                    }
+                });
+            if self.encoding != previous
+                && let Some(loaded) = self.loaded.clone()
+                && loaded.exists()
+            {
+                self.open(loaded);
+            }
+
+            ui.separator();
+            ui.add_enabled_ui(!matches!(self.content, Content::Empty), |ui| {
+                if ui.button("Copy text").clicked() {
+                    let spans = self.spans();
+                    let text: String = spans.iter().map(|span| span.text.as_str()).collect();
+                    ui.ctx().copy_text(text);
+                }
+                if ui.button("Copy ANSI").clicked() {
+                    ui.ctx().copy_text(spans_to_ansi(&self.spans()));
+                }
+                let has_selection = self
+                    .selection
+                    .as_ref()
+                    .is_some_and(|range| !range.is_empty());
+                if ui
+                    .add_enabled(has_selection, egui::Button::new("Copy as HTML"))
+                    .on_hover_text("Copy the selected text as styled HTML")
+                    .clicked()
+                {
+                    ui.ctx().copy_text(self.selection_html());
+                }
+                if ui.button("Export HTML").clicked() {
+                    self.export_html();
+                }
+            });
+        });
+    }
+
+    fn open(&mut self, path: PathBuf) {
+        let file = std::fs::read(&path)
+            .map_err(AnsiFileError::from)
+            .and_then(|bytes| {
+                let kind = AnsiFileKind::detect(&path.to_string_lossy(), Some(&bytes));
+                let encoding = choose_encoding(self.encoding, kind, &bytes);
+                AnsiFile::load_with_encoding(kind, &bytes, encoding)
+            });
+        self.show_file(path, file);
+    }
+
+    fn show_file(&mut self, path: PathBuf, file: Result<AnsiFile, AnsiFileError>) {
+        let file = match file {
+            Ok(file) => file,
+            Err(error) => {
+                self.error = Some(format!("{}: {error}", path.display()));
+                return;
+            }
+        };
+        self.error = None;
+
+        match file {
+            AnsiFile::Art(art) => {
+                self.details = match &art.sauce {
+                    Some(sauce) => {
+                        format!("{} by {} ({} cols)", sauce.title, sauce.author, art.width())
+                    }
+                    None => format!("ANSI art, {} cols", art.width()),
+                };
+                let mut document = AnsiDocument::new();
+                document.append_str(&art.text.to_ansi());
+                document.append_str("\n");
+                self.content = Content::Document(Box::new(document));
+            }
+            AnsiFile::Cast(cast) => {
+                self.details = format!(
+                    "{}x{}, {:.1}s",
+                    cast.header.width,
+                    cast.header.height,
+                    cast.duration()
+                );
+                let mut player = AsciicastPlayer::new(*cast);
+                player.play();
+                self.content = Content::Cast(Box::new(player));
+            }
+            AnsiFile::Log(mut document) => {
+                self.details = format!("{} lines", document.line_count());
+                document.set_page_separator(Some(PAGE_SEPARATOR.to_owned()));
+                document.set_fold_columns(Some(FOLD_COLUMNS));
+                self.content = Content::Document(document);
+            }
+        }
+
+        self.picked = None;
+        (self.legend, self.pages) = match &self.content {
+            Content::Document(document) => (color_usage(document), page_offsets(document)),
+            _ => (Vec::new(), Vec::new()),
+        };
+        self.page = 0;
+        self.path = path.display().to_string();
+        self.loaded = Some(path);
+        self.update_search();
+    }
+
+    fn update_search(&mut self) {
+        self.matches = None;
+        let Content::Document(document) = &mut self.content else {
+            return;
+        };
+        if !self.search.is_empty() {
+            self.picked = None;
+        }
+        if let Some((color, role)) = self.picked {
+            let style = OverlayStyle::underline(SEARCH_HIGHLIGHT);
+            highlight_color(document, color, role, style);
+        } else if self.search.is_empty() {
+            document.clear_overlays(OverlayLayer::SEARCH);
+        } else if let Ok(regex) = Regex::new(&self.search) {
+            let style = OverlayStyle::highlight(SEARCH_HIGHLIGHT);
+            self.matches = Some(highlight_matches(document, &regex, style));
+        }
+    }
+
+    /// Returns the document text as shown, which is what selections index.
+    /// Expands the folded line whose marker is at character `offset` of the
+    /// shown text.
+    fn expand_fold_at(&mut self, offset: usize) {
+        let Content::Document(document) = &mut self.content else {
+            return;
+        };
+        if self.only_picked && self.picked.is_some() {
+            return;
+        }
+        let text = document.to_text().text;
+        let byte = text
+            .char_indices()
+            .nth(offset)
+            .map_or(text.len(), |(byte, _)| byte);
+        if let Some(line) = document.fold_marker_at(byte) {
+            document.set_line_expanded(line, true);
+            self.pages = page_offsets(document);
+        }
+    }
+
+    fn shown_text(&self) -> AnsiText {
+        let Content::Document(document) = &self.content else {
+            return AnsiText::new();
+        };
+        let Some((color, role)) = self.picked.filter(|_| self.only_picked) else {
+            return document.to_text();
+        };
+        let mut text = AnsiText::new();
+        let picked = lines_with_color(document, color, role);
+        for line in picked.into_iter().filter_map(|index| document.line(index)) {
+            for (run, style) in line.text.iter() {
+                text.push_str(run, style);
+            }
+            text.push_str("\n", AnsiStyle::default());
+        }
+        text
+    }
+
+    fn selection_html(&self) -> String {
+        let Some(chars) = self.selection.clone() else {
+            return String::new();
+        };
+        let text = self.shown_text();
+        let byte_at = |index: usize| {
+            text.text
+                .char_indices()
+                .nth(index)
+                .map_or(text.text.len(), |(offset, _)| offset)
+        };
+        let options = HtmlOptions {
+            theme: self.palette.theme(self.bold_is_bright),
+            wrap_in_pre: true,
+        };
+        text.slice(byte_at(chars.start)..byte_at(chars.end))
+            .to_html(&options)
+    }
+
+    fn spans(&self) -> Vec<AnsiSpan> {
+        match &self.content {
+            Content::Empty => Vec::new(),
+            Content::Document(document) => document.to_text().to_spans(),
+            Content::Cast(player) => player.buffer().spans().to_vec(),
+        }
+    }
+
+    fn export_html(&mut self) {
+        let Some(loaded) = &self.loaded else {
+            return;
+        };
+        let spans = self.spans();
+        let options = HtmlOptions {
+            theme: self.palette.theme(self.bold_is_bright),
+            wrap_in_pre: true,
+        };
+        let target = html_path(loaded);
+        match std::fs::write(&target, spans_to_html(&spans, &options)) {
+            Ok(()) => self.details = format!("exported {}", target.display()),
+            Err(error) => self.error = Some(format!("{}: {error}", target.display())),
+        }
+    }
+}
+
+fn show_playback(ui: &mut egui::Ui, player: &mut AsciicastPlayer) {
+    ui.horizontal(|ui| {
+        if player.is_playing() {
+            if ui.button("Pause").clicked() {
+                player.pause();
+            }
+        } else if ui.button("Play").clicked() {
+            if player.is_finished() {
+                player.seek(0.0);
+            }
+            player.play();
+        }
+
+        let duration = player.cast().duration();
+        let mut position = player.position();
+        let slider = egui::Slider::new(&mut position, 0.0..=duration).suffix("s");
+        if ui.add(slider).changed() {
+            player.seek(position);
+        }
+    });
+}
+
+/// Returns the override, or a guess for logs and the usual encoding for art
+/// and recordings.
+fn choose_encoding(
+    encoding: Option<TextEncoding>,
+    kind: AnsiFileKind,
+    bytes: &[u8],
+) -> TextEncoding {
+    match (encoding, kind) {
+        (Some(encoding), _) => encoding,
+        (None, AnsiFileKind::Log) => TextEncoding::detect(bytes),
+        (None, kind) => kind.default_encoding(),
+    }
+}
+
+/// Returns the character offsets of the pages in the document text, starting
+/// with the top of the document.
+fn page_offsets(document: &AnsiDocument) -> Vec<usize> {
+    let text = document.to_text().text;
+    let line_offsets = document.line_offsets();
+    let mut pages = vec![0];
+    for index in document.page_starts() {
+        // Pages start at their separator line.
+        let offset = line_offsets[index] - PAGE_SEPARATOR.len() - 1;
+        if offset > 0 {
+            pages.push(text[..offset].chars().count());
+        }
+    }
+    pages
+}
+
+/// Shows the document as a table, with lines that are not log records in the
+/// message column. JSON and logfmt records expand to the whole highlighted
+/// record, with JSON pretty-printed.
+fn show_columns(ui: &mut egui::Ui, document: &AnsiDocument, theme: &EguiAnsiTheme) {
+    let structure = (JsonLogFormat, (LogfmtFormat, CommonLogFormat));
+    egui::Grid::new("log_columns")
+        .striped(true)
+        .spacing([12.0, 2.0])
+        .show(ui, |ui| {
+            for (index, line) in document.lines().enumerate() {
+                let mut row = line.log_row(&structure).unwrap_or_else(|| LogRow {
+                    message: line.text.clone(),
+                    ..LogRow::default()
+                });
+                if let Some(level) = row.log_level
+                    && row
+                        .level
+                        .runs
+                        .iter()
+                        .all(|run| run.style == AnsiStyle::default())
+                {
+                    row.level = AnsiText::new().with_str(&row.level.text, level.style());
+                }
+                for cell in [&row.timestamp, &row.level, &row.source] {
+                    ui.label(cell.to_layout_job(theme));
+                }
+                let record = &line.text.text;
+                let highlighted = json_to_text(record, false).or_else(|| logfmt_to_text(record));
+                if let Some(highlighted) = highlighted {
+                    let summary = if row.message.text == *record {
+                        &highlighted
+                    } else {
+                        &row.message
+                    };
+                    egui::CollapsingHeader::new(summary.to_layout_job(theme))
+                        .id_salt(index)
+                        .show(ui, |ui| {
+                            let expanded =
+                                json_to_text(record, true).unwrap_or(highlighted.clone());
+                            ui.label(expanded.to_layout_job(theme));
+                        });
+                } else {
+                    ui.label(row.message.to_layout_job(theme));
+                }
+                ui.end_row();
+            }
+        });
+}
+
+/// Shows `job` as selectable read-only text, optionally scrolling the
+/// character `scroll_to` to the top, and returns the selected character
+/// range and whether the text was clicked.
+fn show_selectable(
+    ui: &mut egui::Ui,
+    job: LayoutJob,
+    scroll_to: Option<usize>,
+) -> (Option<Range<usize>>, bool) {
+    let text = job.text.clone();
+    let mut text = text.as_str();
+    let mut layouter = |ui: &egui::Ui, _: &dyn egui::TextBuffer, _wrap_width: f32| {
+        ui.fonts_mut(|fonts| fonts.layout_job(job.clone()))
+    };
+    let output = egui::TextEdit::multiline(&mut text)
+        .layouter(&mut layouter)
+        .frame(egui::Frame::NONE)
+        .desired_width(f32::INFINITY)
+        .show(ui);
+    if let Some(offset) = scroll_to {
+        let rect = output
+            .galley
+            .pos_from_cursor(egui::text::CCursor::new(offset))
+            .translate(output.galley_pos.to_vec2());
+        ui.scroll_to_rect(rect, Some(egui::Align::TOP));
+    }
+    let selection = output
+        .cursor_range
+        .map(|range| range.as_sorted_char_range());
+    (selection, output.response.clicked())
+}
+
+fn resolve(color: AnsiColor, theme: &EguiAnsiTheme) -> Color32 {
+    match color {
+        AnsiColor::Default => theme.default_foreground,
+        AnsiColor::Indexed(index) => theme.palette[usize::from(index)],
+        AnsiColor::Rgb(r, g, b) => Color32::from_rgb(r, g, b),
+    }
+}
+
+fn html_path(path: &Path) -> PathBuf {
+    let mut target = path.as_os_str().to_owned();
+    target.push(".html");
+    PathBuf::from(target)
+}
+
+fn main() -> Result<(), eframe::Error> {
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default()
+            .with_inner_size([1040.0, 760.0])
+            .with_drag_and_drop(true),
+        ..Default::default()
+    };
+
+    let mut viewer = Viewer::default();
+    if let Some(path) = std::env::args_os().nth(1) {
+        viewer.open(PathBuf::from(path));
+    }
+
+    eframe::run_native(
+        "egui_sgr Viewer",
+        options,
+        Box::new(|_cc| Ok(Box::new(viewer))),
+    )
+}
@@ -0,0 +1,185 @@
+use eframe::{App, Frame, egui};
+use egui::{FontId, Key, RichText, TextEdit};
+use egui_sgr::{AnsiSession, AnsiWorkspace, SharedAnsiDocument, TerminalProfile};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+const LINES: &[&str] = &[
+    "\x1b[32mINFO\x1b[0m request served in 12ms",
+    "\x1b[33mWARN\x1b[0m cache miss for \x1b[1muser:42\x1b[0m",
+    "\x1b[31mERROR\x1b[0m upstream timed out",
+    "\x1b[36mDEBUG\x1b[0m retrying with backoff",
+];
+
+/// Thread appending to one session's document until it is dropped.
+struct Source {
+    stop: Arc<AtomicBool>,
+}
+
+impl Source {
+    fn spawn(document: SharedAnsiDocument, ctx: egui::Context, period: Duration) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::clone(&stop);
+        thread::spawn(move || {
+            for line in LINES.iter().cycle() {
+                if stopped.load(Ordering::Relaxed) {
+                    break;
+                }
+                document.lock().append(format!("{line}\n"));
+                ctx.request_repaint();
+                thread::sleep(period);
+            }
+        });
+        Self { stop }
+    }
+}
+
+impl Drop for Source {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Draws an [`AnsiWorkspace`]: the library keeps the tabs and unread counts,
+/// the app owns the tab chrome and the sources feeding each session.
+struct Workspace {
+    workspace: AnsiWorkspace,
+    /// Sources in tab order, so closing a tab stops its thread.
+    sources: Vec<Source>,
+    profile: TerminalProfile,
+    /// Tab being renamed, with the title being edited.
+    renaming: Option<(usize, String)>,
+    next_id: usize,
+}
+
+impl Workspace {
+    fn new(ctx: &egui::Context) -> Self {
+        let profile = TerminalProfile {
+            name: "Services".to_owned(),
+            scrollback_lines: Some(1_000),
+            ..TerminalProfile::default()
+        }
+        .with_font(FontId::monospace(13.0));
+        let mut workspace = Self {
+            workspace: AnsiWorkspace::new(),
+            sources: Vec::new(),
+            profile,
+            renaming: None,
+            next_id: 1,
+        };
+        workspace.add(ctx);
+        workspace.add(ctx);
+        workspace
+    }
+
+    fn add(&mut self, ctx: &egui::Context) {
+        let id = self.next_id;
+        self.next_id += 1;
+        let document = SharedAnsiDocument::default();
+        let period = Duration::from_millis(300 * id as u64);
+        self.sources
+            .push(Source::spawn(document.clone(), ctx.clone(), period));
+        let session =
+            AnsiSession::new(format!("service {id}"), document).with_profile(&self.profile);
+        self.workspace.add(session);
+    }
+
+    fn close(&mut self, index: usize) {
+        if self.workspace.close(index).is_some() {
+            self.sources.remove(index);
+        }
+        self.renaming = None;
+    }
+
+    /// Returns the title of tab `index`, with its unread count.
+    fn tab_title(&self, index: usize) -> RichText {
+        let title = &self.workspace.sessions()[index].title;
+        match self.workspace.unread_lines(index) {
+            0 => RichText::new(title),
+            unread => RichText::new(format!("{title} ({unread})")).strong(),
+        }
+    }
+
+    fn tab_bar(&mut self, ui: &mut egui::Ui) {
+        let mut close = None;
+        let mut add = false;
+        ui.horizontal_wrapped(|ui| {
+            for index in 0..self.workspace.len() {
+                if let Some((renaming, title)) = &mut self.renaming
+                    && *renaming == index
+                {
+                    let edit = ui.add(TextEdit::singleline(title).desired_width(120.0));
+                    edit.request_focus();
+                    if ui.input(|input| input.key_pressed(Key::Escape)) {
+                        self.renaming = None;
+                    } else if edit.lost_focus() {
+                        self.workspace.rename(index, std::mem::take(title));
+                        self.renaming = None;
+                    }
+                    continue;
+                }
+
+                let active = index == self.workspace.active_index();
+                let tab = ui
+                    .selectable_label(active, self.tab_title(index))
+                    .on_hover_text("Double-click to rename");
+                if tab.clicked() {
+                    self.workspace.set_active(index);
+                }
+                if tab.double_clicked() {
+                    let title = self.workspace.sessions()[index].title.clone();
+                    self.renaming = Some((index, title));
+                }
+                if ui.small_button("×").on_hover_text("Close").clicked() {
+                    close = Some(index);
+                }
+                ui.separator();
+            }
+            add = ui.button("+").on_hover_text("New session").clicked();
+        });
+
+        if let Some(index) = close {
+            self.close(index);
+        }
+        if add {
+            self.add(ui.ctx());
+        }
+    }
+}
+
+impl App for Workspace {
+    fn ui(&mut self, ui: &mut egui::Ui, _frame: &mut Frame) {
+        self.tab_bar(ui);
+        ui.separator();
+
+        let Some(session) = self.workspace.active() else {
+            ui.weak("No sessions; click + to start one.");
+            return;
+        };
+        let theme = &session.profile.theme;
+        let job = session.document.lock().to_layout_job(theme);
+        egui::Frame::NONE
+            .fill(theme.default_background)
+            .show(ui, |ui| {
+                egui::ScrollArea::vertical()
+                    .auto_shrink(false)
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| ui.label(job));
+            });
+    }
+}
+
+fn main() -> Result<(), eframe::Error> {
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default().with_inner_size([900.0, 600.0]),
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "egui_sgr Workspace",
+        options,
+        Box::new(|cc| Ok(Box::new(Workspace::new(&cc.egui_ctx)))),
+    )
+}
@@ -0,0 +1,20 @@
+#![no_main]
+
+use egui_sgr::{ansi_bytes_to_spans, ansi_to_spans, spans_to_ansi};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &[u8]| {
+    let spans = ansi_bytes_to_spans(input);
+
+    // Invalid bytes become U+FFFD, so visible text is at most three bytes per
+    // input byte.
+    let visible: usize = spans.iter().map(|span| span.text.len()).sum();
+    assert!(visible <= input.len() * 3);
+
+    // Re-emitted output parses back to the same spans, and re-emitting those
+    // is a fixed point.
+    let emitted = spans_to_ansi(&spans);
+    let reparsed = ansi_to_spans(&emitted);
+    assert_eq!(reparsed, spans);
+    assert_eq!(spans_to_ansi(&reparsed), emitted);
+});
@@ -0,0 +1,43 @@
+#![no_main]
+
+use egui_sgr::{AnsiDocument, AnsiStreamParser, ansi_bytes_to_spans};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &[u8]| {
+    let Some((&chunk_len, input)) = input.split_first() else {
+        return;
+    };
+    let chunk_len = usize::from(chunk_len).max(1);
+
+    // Feeding the same bytes in chunks yields the same visible text and
+    // styles as parsing them at once.
+    let mut parser = AnsiStreamParser::new();
+    let mut streamed = String::new();
+    let mut styles = Vec::new();
+    for chunk in input.chunks(chunk_len) {
+        for span in parser.push_bytes(chunk) {
+            streamed.push_str(&span.text);
+            styles.extend(span.text.chars().map(|_| span.style));
+        }
+    }
+    for span in parser.finish() {
+        streamed.push_str(&span.text);
+        styles.extend(span.text.chars().map(|_| span.style));
+    }
+
+    let spans = ansi_bytes_to_spans(input);
+    let whole: String = spans.iter().map(|span| span.text.as_str()).collect();
+    let whole_styles: Vec<_> = spans
+        .iter()
+        .flat_map(|span| span.text.chars().map(move |_| span.style))
+        .collect();
+    assert_eq!(streamed, whole);
+    assert_eq!(styles, whole_styles);
+
+    // The retained line model stays within its cap.
+    let mut document = AnsiDocument::<()>::new().with_max_lines(16);
+    for chunk in input.chunks(chunk_len) {
+        document.append(chunk);
+    }
+    assert!(document.line_count() <= 16);
+});
@@ -0,0 +1,16 @@
+#![no_main]
+
+use egui_sgr::{AnsiLimits, ansi_bytes_to_spans, try_ansi_bytes_to_spans};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &[u8]| {
+    let limits = AnsiLimits {
+        max_input_len: 4096,
+        max_sequence_len: 64,
+    };
+
+    // Accepted input parses exactly like the lenient parser.
+    if let Ok(spans) = try_ansi_bytes_to_spans(input, &limits) {
+        assert_eq!(spans, ansi_bytes_to_spans(input));
+    }
+});
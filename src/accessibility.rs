@@ -0,0 +1,82 @@
+use crate::{AnsiColor, AnsiStyle, AnsiText, ColorMetric, EguiAnsiTheme, nearest_color};
+use ecolor::Color32;
+
+const COLOR_NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+/// Returns the basic color name ("red", "cyan", ...) closest to `color`.
+///
+/// Indexed system colors map directly; other colors are matched against the
+/// theme's 16 system colors, so the name describes what is on screen.
+/// Default colors have no name.
+#[must_use]
+pub fn color_name(color: AnsiColor, theme: &EguiAnsiTheme) -> Option<&'static str> {
+    let index = match color {
+        AnsiColor::Default => return None,
+        AnsiColor::Indexed(index @ 0..=15) => usize::from(index),
+        AnsiColor::Indexed(index) => nearest_system(theme.palette[usize::from(index)], theme)?,
+        AnsiColor::Rgb(r, g, b) => nearest_system(Color32::from_rgb(r, g, b), theme)?,
+    };
+    Some(COLOR_NAMES[index % 8])
+}
+
+/// Describes styled text for screen readers.
+///
+/// The visible text is followed by the color of each colored stretch in
+/// parentheses, so `"\x1b[31merror\x1b[0m: missing file"` becomes
+/// `"error (red): missing file"`. Black and white are not announced because
+/// they usually just restate the theme, and hidden text is skipped like it is
+/// on screen. Pass the result as the accessible label of the widget showing
+/// the text:
+///
+/// ```rust
+/// # #[cfg(feature = "egui")]
+/// # fn show(ui: &mut egui::Ui, text: &egui_sgr::AnsiText, theme: &egui_sgr::EguiAnsiTheme) {
+/// let response = ui.label(text.to_layout_job(theme));
+/// let description = egui_sgr::accessible_description(text, theme);
+/// response.widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Label, true, &description));
+/// # }
+/// ```
+#[must_use]
+pub fn accessible_description(text: &AnsiText, theme: &EguiAnsiTheme) -> String {
+    let mut output = String::with_capacity(text.text.len());
+    let mut open = None;
+
+    for (run, style) in text.iter() {
+        if style.hidden {
+            continue;
+        }
+        let name = announced_color(&style, theme);
+        if name != open {
+            close_color(&mut output, open);
+            open = name;
+        }
+        output.push_str(run);
+    }
+
+    close_color(&mut output, open);
+    output
+}
+
+fn announced_color(style: &AnsiStyle, theme: &EguiAnsiTheme) -> Option<&'static str> {
+    color_name(style.foreground, theme).filter(|name| !matches!(*name, "black" | "white"))
+}
+
+/// Appends the color annotation, keeping trailing whitespace after it so
+/// `"error (red)\n"` reads naturally.
+fn close_color(output: &mut String, name: Option<&str>) {
+    let Some(name) = name else {
+        return;
+    };
+    let content_end = output.trim_end().len();
+    let trailing = output.split_off(content_end);
+    output.push_str(" (");
+    output.push_str(name);
+    output.push(')');
+    output.push_str(&trailing);
+}
+
+fn nearest_system(color: Color32, theme: &EguiAnsiTheme) -> Option<usize> {
+    nearest_color(color, &theme.palette[..16], ColorMetric::Oklab)
+}
@@ -0,0 +1,442 @@
+use crate::{AnsiColor, AnsiSpan, AnsiStreamParser, AnsiStyle, AnsiText, TextEncoding};
+#[cfg(feature = "egui")]
+use crate::{EguiAnsiTheme, spans_to_layout_job};
+#[cfg(feature = "egui")]
+use egui::text::LayoutJob;
+
+/// CP437 glyphs for the bytes `0x80..=0xFF`.
+pub(crate) const CP437_HIGH: [char; 128] = [
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å', 'É', 'æ', 'Æ',
+    'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', '¢', '£', '¥', '₧', 'ƒ', 'á', 'í', 'ó', 'ú', 'ñ', 'Ñ',
+    'ª', 'º', '¿', '⌐', '¬', '½', '¼', '¡', '«', '»', '░', '▒', '▓', '│', '┤', '╡', '╢', '╖', '╕',
+    '╣', '║', '╗', '╝', '╜', '╛', '┐', '└', '┴', '┬', '├', '─', '┼', '╞', '╟', '╚', '╔', '╩', '╦',
+    '╠', '═', '╬', '╧', '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫', '╪', '┘', '┌', '█', '▄', '▌', '▐',
+    '▀', 'α', 'ß', 'Γ', 'π', 'Σ', 'σ', 'µ', 'τ', 'Φ', 'Θ', 'Ω', 'δ', '∞', 'φ', 'ε', '∩', '≡', '±',
+    '≥', '≤', '⌠', '⌡', '÷', '≈', '°', '∙', '·', '√', 'ⁿ', '²', '■', '\u{a0}',
+];
+
+/// CP437 glyphs for the bytes `0x00..=0x1F`; `0x7F` is `⌂`.
+const CP437_LOW: [char; 32] = [
+    '\0', '☺', '☻', '♥', '♦', '♣', '♠', '•', '◘', '○', '◙', '♂', '♀', '♪', '♫', '☼', '►', '◄', '↕',
+    '‼', '¶', '§', '▬', '↨', '↑', '↓', '→', '←', '∟', '↔', '▲', '▼',
+];
+
+const SAUCE_LEN: usize = 128;
+const COMMENT_LINE_LEN: usize = 64;
+const EOF_MARKER: u8 = 0x1a;
+
+/// Decodes CP437 bytes, as used by DOS-era ANSI art, to Unicode.
+///
+/// ESC, line feed, and carriage return stay control characters so escape
+/// sequences still parse; every other byte becomes its CP437 glyph.
+#[must_use]
+pub fn cp437_to_string(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&byte| match byte {
+            b'\x1b' | b'\n' | b'\r' => char::from(byte),
+            0x00..=0x1f => CP437_LOW[usize::from(byte)],
+            0x7f => '⌂',
+            0x20..=0x7e => char::from(byte),
+            0x80..=0xff => CP437_HIGH[usize::from(byte - 0x80)],
+        })
+        .collect()
+}
+
+/// SAUCE metadata record appended to ANSI art files.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SauceRecord {
+    /// Title of the piece.
+    pub title: String,
+    /// Author handle.
+    pub author: String,
+    /// Group or company.
+    pub group: String,
+    /// Creation date as `CCYYMMDD`.
+    pub date: String,
+    /// SAUCE data type; 1 is character-based art.
+    pub data_type: u8,
+    /// SAUCE file type; for character data, 1 is ANSi.
+    pub file_type: u8,
+    /// Character width of the canvas, when set.
+    pub width: Option<u16>,
+    /// Number of lines, when set.
+    pub height: Option<u16>,
+    /// Whether blink should be shown as bright backgrounds ("iCE colors").
+    pub ice_colors: bool,
+    /// Comment lines stored before the record.
+    pub comments: Vec<String>,
+}
+
+impl SauceRecord {
+    /// Parses the SAUCE record at the end of `bytes`, if there is one.
+    #[must_use]
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        Some(split_sauce(bytes)?.1)
+    }
+}
+
+/// Splits a file into its art content and SAUCE record.
+fn split_sauce(bytes: &[u8]) -> Option<(&[u8], SauceRecord)> {
+    let start = bytes.len().checked_sub(SAUCE_LEN)?;
+    let record = &bytes[start..];
+    if !record.starts_with(b"SAUCE00") {
+        return None;
+    }
+
+    let field = |from: usize, len: usize| {
+        cp437_to_string(&record[from..from + len])
+            .trim_end_matches([' ', '\0'])
+            .to_owned()
+    };
+    let word = |from: usize| u16::from_le_bytes([record[from], record[from + 1]]);
+    let dimension = |value: u16| (value != 0).then_some(value);
+
+    let comment_count = usize::from(record[104]);
+    let comments_len = 5 + comment_count * COMMENT_LINE_LEN;
+    let mut content_end = start;
+    let mut comments = Vec::new();
+    if comment_count > 0
+        && let Some(comments_start) = start.checked_sub(comments_len)
+        && bytes[comments_start..].starts_with(b"COMNT")
+    {
+        comments = bytes[comments_start + 5..start]
+            .chunks(COMMENT_LINE_LEN)
+            .map(|line| {
+                cp437_to_string(line)
+                    .trim_end_matches([' ', '\0'])
+                    .to_owned()
+            })
+            .collect();
+        content_end = comments_start;
+    }
+
+    let record = SauceRecord {
+        title: field(7, 35),
+        author: field(42, 20),
+        group: field(62, 20),
+        date: field(82, 8),
+        data_type: record[94],
+        file_type: record[95],
+        width: dimension(word(96)),
+        height: dimension(word(98)),
+        ice_colors: record[105] & 1 != 0,
+        comments,
+    };
+    Some((&bytes[..content_end], record))
+}
+
+/// ANSI art file (`.ans`) decoded into styled text.
+///
+/// Content is decoded from CP437, cut at the DOS end-of-file marker, and
+/// drawn on a canvas of the SAUCE width (80 columns by default) that wraps
+/// the way a terminal would. The cursor movement art uses is applied:
+/// forward, back, up, and down (`CSI C`, `D`, `A`, `B`), absolute position
+/// (`CSI H`, `f`), and save and restore (`CSI s`/`u`, `ESC 7`/`8`). Moves are
+/// clamped to the canvas, whose height is the SAUCE height (25 rows by
+/// default) or the rows drawn so far. Erase and scroll sequences are
+/// ignored. With the SAUCE iCE colors flag, blinking text gets a bright
+/// background instead.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AnsiArt {
+    /// SAUCE metadata, when the file has a record.
+    pub sauce: Option<SauceRecord>,
+    /// Styled content, one line per canvas row.
+    pub text: AnsiText,
+}
+
+impl AnsiArt {
+    /// Default canvas width when there is no SAUCE width.
+    pub const DEFAULT_WIDTH: usize = 80;
+
+    /// Decodes an ANSI art file.
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self::from_bytes_with_encoding(bytes, TextEncoding::Cp437)
+    }
+
+    /// Decodes an ANSI art file whose text is in `encoding` rather than
+    /// CP437, such as art saved by a modern UTF-8 editor.
+    ///
+    /// Only CP437 maps control bytes other than ESC, line feed, and carriage
+    /// return to glyphs.
+    #[must_use]
+    pub fn from_bytes_with_encoding(bytes: &[u8], encoding: TextEncoding) -> Self {
+        let (content, sauce) = match split_sauce(bytes) {
+            Some((content, sauce)) => (content, Some(sauce)),
+            None => (bytes, None),
+        };
+        let content = memchr::memchr(EOF_MARKER, content).map_or(content, |end| &content[..end]);
+
+        let mut art = Self {
+            sauce,
+            text: AnsiText::new(),
+        };
+        let decoded = match encoding {
+            TextEncoding::Cp437 => cp437_to_string(content),
+            _ => encoding.decode(content).into_owned(),
+        };
+        let ice_colors = art.sauce.as_ref().is_some_and(|sauce| sauce.ice_colors);
+        art.text = draw(&decoded, art.width(), art.height(), ice_colors);
+        art
+    }
+
+    /// Returns the canvas width in columns.
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.sauce
+            .as_ref()
+            .and_then(|sauce| sauce.width)
+            .map_or(Self::DEFAULT_WIDTH, usize::from)
+    }
+
+    /// Returns the canvas height in rows, the SAUCE height or a 25-row DOS
+    /// screen.
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.sauce
+            .as_ref()
+            .and_then(|sauce| sauce.height)
+            .map_or(DEFAULT_HEIGHT, usize::from)
+    }
+
+    /// Converts the art to spans.
+    #[must_use]
+    pub fn to_spans(&self) -> Vec<AnsiSpan> {
+        self.text.to_spans()
+    }
+
+    /// Converts the art to an egui layout job.
+    #[cfg(feature = "egui")]
+    #[must_use]
+    pub fn to_layout_job(&self, theme: &EguiAnsiTheme) -> LayoutJob {
+        spans_to_layout_job(&self.to_spans(), theme)
+    }
+}
+
+/// Default canvas height, a DOS text screen, when there is no SAUCE height.
+const DEFAULT_HEIGHT: usize = 25;
+
+/// Character grid that ANSI art is drawn on.
+///
+/// Art files position the cursor to draw: forward to skip cells, up to
+/// overdraw earlier rows, absolutely, and through a saved position. The
+/// canvas keeps the cursor and the cells written so far; movement is clamped
+/// to the canvas, so hostile counts cannot allocate more than the input
+/// itself could draw.
+struct Canvas {
+    width: usize,
+    height: usize,
+    rows: Vec<Vec<(char, AnsiStyle)>>,
+    row: usize,
+    col: usize,
+    saved: (usize, usize),
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width: width.max(1),
+            height: height.max(1),
+            rows: vec![Vec::new()],
+            row: 0,
+            col: 0,
+            saved: (0, 0),
+        }
+    }
+
+    /// Writes `c` at the cursor, wrapping first if the cursor is past the
+    /// last column the way a terminal would.
+    fn put(&mut self, c: char, style: AnsiStyle) {
+        match c {
+            '\r' => self.col = 0,
+            '\n' => self.next_line(),
+            c => {
+                if self.col >= self.width {
+                    self.next_line();
+                }
+                let row = &mut self.rows[self.row];
+                if row.len() < self.col {
+                    row.resize(self.col, (' ', AnsiStyle::default()));
+                }
+                if let Some(cell) = row.get_mut(self.col) {
+                    *cell = (c, style);
+                } else {
+                    row.push((c, style));
+                }
+                self.col += 1;
+            }
+        }
+    }
+
+    /// Moves the cursor right by `count` cells, stopping after the last
+    /// column. Cells not yet drawn become blanks in `style`.
+    fn forward(&mut self, count: usize, style: AnsiStyle) {
+        let col = self.col.saturating_add(count).min(self.width);
+        let row = &mut self.rows[self.row];
+        if row.len() < col {
+            row.resize(self.col.max(row.len()), (' ', AnsiStyle::default()));
+            row.resize(col, (' ', style));
+        }
+        self.col = col;
+    }
+
+    /// Moves the cursor to the start of the next row, growing the canvas
+    /// past its height as text keeps coming.
+    fn next_line(&mut self) {
+        self.row += 1;
+        self.col = 0;
+        if self.rows.len() <= self.row {
+            self.rows.push(Vec::new());
+        }
+    }
+
+    /// Moves the cursor for a control sequence, keeping it within the rows
+    /// drawn so far or the canvas height, whichever is larger.
+    fn move_to(&mut self, row: usize, col: usize) {
+        let limit = self.rows.len().max(self.height);
+        self.row = row.min(limit - 1);
+        self.col = col.min(self.width - 1);
+        if self.rows.len() <= self.row {
+            self.rows.resize_with(self.row + 1, Vec::new);
+        }
+    }
+
+    /// Applies a cursor control sequence with final byte `action`, returning
+    /// `false` for other sequences.
+    fn control(&mut self, action: char, params: &str, style: AnsiStyle) -> bool {
+        let mut values = params.split(';').map(|param| match param {
+            "" => 1,
+            param => param.parse::<usize>().unwrap_or(usize::MAX).max(1),
+        });
+        let mut count = || values.next().unwrap_or(1);
+        match action {
+            'A' => self.move_to(self.row.saturating_sub(count()), self.col),
+            'B' => self.move_to(self.row.saturating_add(count()), self.col),
+            'C' => self.forward(count(), style),
+            'D' => self.col = self.col.saturating_sub(count()),
+            'H' | 'f' => {
+                let row = count() - 1;
+                let col = count() - 1;
+                self.move_to(row, col);
+            }
+            's' => self.saved = (self.row, self.col),
+            'u' => self.move_to(self.saved.0, self.saved.1),
+            _ => return false,
+        }
+        true
+    }
+
+    fn into_text(self) -> AnsiText {
+        let mut text = AnsiText::new();
+        let mut buffer = [0; 4];
+        for (index, row) in self.rows.iter().enumerate() {
+            if index > 0 {
+                text.push_str("\n", AnsiStyle::default());
+            }
+            for &(c, style) in row {
+                text.push_str(c.encode_utf8(&mut buffer), style);
+            }
+        }
+        text
+    }
+}
+
+/// Draws decoded art on a canvas, applying cursor movement and, with iCE
+/// colors, showing blink as a bright background.
+fn draw(input: &str, width: usize, height: usize, ice_colors: bool) -> AnsiText {
+    let mut canvas = Canvas::new(width, height);
+    let mut parser = AnsiStreamParser::new();
+    let mut blink = false;
+    let bright = |mut style: AnsiStyle, blink: bool| {
+        if ice_colors
+            && blink
+            && let AnsiColor::Indexed(index @ 0..8) = style.background
+        {
+            style.background = AnsiColor::Indexed(index + 8);
+        }
+        style
+    };
+    let draw_spans = |canvas: &mut Canvas, spans: Vec<AnsiSpan>, blink: bool| {
+        for span in spans {
+            let style = bright(span.style, blink);
+            for c in span.text.chars() {
+                canvas.put(c, style);
+            }
+        }
+    };
+
+    let mut rest = input;
+    while !rest.is_empty() {
+        let text_end = rest.find('\x1b').unwrap_or(rest.len());
+        if text_end > 0 {
+            let spans = parser.push_bytes(&rest.as_bytes()[..text_end]);
+            draw_spans(&mut canvas, spans, blink);
+            rest = &rest[text_end..];
+            continue;
+        }
+
+        if let Some(after) = rest.strip_prefix("\x1b7") {
+            canvas.saved = (canvas.row, canvas.col);
+            rest = after;
+            continue;
+        }
+        if let Some(after) = rest.strip_prefix("\x1b8") {
+            canvas.move_to(canvas.saved.0, canvas.saved.1);
+            rest = after;
+            continue;
+        }
+
+        let after = rest.strip_prefix("\x1b[").unwrap_or("");
+        let params_len = after
+            .bytes()
+            .take_while(|byte| byte.is_ascii_digit() || *byte == b';')
+            .count();
+        let params = &after[..params_len];
+        match after[params_len..].chars().next() {
+            Some(action)
+                if canvas.control(action, params, bright(*parser.current_style(), blink)) =>
+            {
+                rest = &after[params_len + 1..];
+            }
+            Some('m') => {
+                let len = 2 + params_len + 1;
+                let spans = parser.push_bytes(&rest.as_bytes()[..len]);
+                draw_spans(&mut canvas, spans, blink);
+                blink = sgr_blink(params, blink);
+                rest = &rest[len..];
+            }
+            _ => {
+                // Other sequences go to the parser, which keeps their state
+                // across the text pushed after them.
+                let spans = parser.push_bytes(b"\x1b");
+                draw_spans(&mut canvas, spans, blink);
+                rest = &rest[1..];
+            }
+        }
+    }
+    let spans = parser.finish();
+    draw_spans(&mut canvas, spans, blink);
+    canvas.into_text()
+}
+
+/// Returns whether blink is on after the SGR parameters `params`.
+fn sgr_blink(params: &str, mut blink: bool) -> bool {
+    let mut params = params.split(';');
+    while let Some(param) = params.next() {
+        match param {
+            "" | "0" | "25" => blink = false,
+            "5" | "6" => blink = true,
+            "38" | "48" | "58" => {
+                let skip = match params.next() {
+                    Some("5") => 1,
+                    Some("2") => 3,
+                    _ => 0,
+                };
+                params.by_ref().take(skip).for_each(drop);
+            }
+            _ => {}
+        }
+    }
+    blink
+}
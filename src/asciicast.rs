@@ -0,0 +1,336 @@
+use crate::AnsiSpanBuffer;
+#[cfg(feature = "egui")]
+use crate::EguiAnsiTheme;
+#[cfg(feature = "egui")]
+use egui::text::LayoutJob;
+use std::fmt;
+use std::io::{self, Write};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
+
+/// Header line of an asciicast v2 recording.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AsciicastHeader {
+    /// Format version; always 2.
+    pub version: u8,
+    /// Terminal width in columns.
+    pub width: u16,
+    /// Terminal height in rows.
+    pub height: u16,
+    /// Unix timestamp of the start of the recording.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
+    /// Title of the recording.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+/// One event of an asciicast v2 recording.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsciicastEvent {
+    /// Seconds since the start of the recording.
+    pub time: f64,
+    /// Event code: `"o"` for output, `"i"` for input, `"m"` for markers,
+    /// `"r"` for resizes.
+    pub code: String,
+    /// Event payload, such as the output text.
+    pub data: String,
+}
+
+/// Parsed asciicast v2 recording.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Asciicast {
+    /// Recording header.
+    pub header: AsciicastHeader,
+    /// Events in file order.
+    pub events: Vec<AsciicastEvent>,
+}
+
+/// Error returned when an asciicast file cannot be parsed.
+#[derive(Debug)]
+pub enum AsciicastError {
+    /// The file has no header line.
+    MissingHeader,
+    /// The header declares a version other than 2.
+    UnsupportedVersion(u8),
+    /// A line is not valid JSON or does not have the expected shape.
+    InvalidLine {
+        /// 1-based line number.
+        line: usize,
+        /// Underlying JSON error.
+        source: serde_json::Error,
+    },
+    /// An event time is negative or not finite.
+    InvalidTime {
+        /// 1-based line number.
+        line: usize,
+    },
+}
+
+impl fmt::Display for AsciicastError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingHeader => f.write_str("asciicast file has no header"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported asciicast version {version}")
+            }
+            Self::InvalidLine { line, source } => {
+                write!(f, "invalid asciicast line {line}: {source}")
+            }
+            Self::InvalidTime { line } => write!(f, "invalid asciicast event time on line {line}"),
+        }
+    }
+}
+
+impl std::error::Error for AsciicastError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidLine { source, .. } => Some(source),
+            Self::MissingHeader | Self::UnsupportedVersion(_) | Self::InvalidTime { .. } => None,
+        }
+    }
+}
+
+impl Asciicast {
+    /// Parses an asciicast v2 file.
+    ///
+    /// Event times must be finite and not negative.
+    pub fn parse(input: &str) -> Result<Self, AsciicastError> {
+        let mut lines = input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty());
+
+        let (index, header) = lines.next().ok_or(AsciicastError::MissingHeader)?;
+        let header: AsciicastHeader =
+            serde_json::from_str(header).map_err(|source| AsciicastError::InvalidLine {
+                line: index + 1,
+                source,
+            })?;
+        if header.version != 2 {
+            return Err(AsciicastError::UnsupportedVersion(header.version));
+        }
+
+        let events = lines
+            .map(|(index, line)| {
+                let (time, code, data): (f64, _, _) =
+                    serde_json::from_str(line).map_err(|source| AsciicastError::InvalidLine {
+                        line: index + 1,
+                        source,
+                    })?;
+                if !time.is_finite() || time < 0.0 {
+                    return Err(AsciicastError::InvalidTime { line: index + 1 });
+                }
+                Ok(AsciicastEvent { time, code, data })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { header, events })
+    }
+
+    /// Returns the time of the last event in seconds.
+    #[must_use]
+    pub fn duration(&self) -> f64 {
+        self.events.last().map_or(0.0, |event| event.time)
+    }
+}
+
+/// Replays an asciicast recording into an [`AnsiSpanBuffer`].
+///
+/// The player only tracks playback state; drive it from the UI with
+/// [`Self::advance`] every frame and render [`Self::to_layout_job`]. Output
+/// is linearized like all other input, so recordings of full-screen
+/// programs that move the cursor will not replay faithfully.
+pub struct AsciicastPlayer {
+    cast: Asciicast,
+    position: f64,
+    next_event: usize,
+    playing: bool,
+    speed: f64,
+    buffer: AnsiSpanBuffer,
+}
+
+impl AsciicastPlayer {
+    /// Creates a paused player at the start of `cast`.
+    #[must_use]
+    pub fn new(cast: Asciicast) -> Self {
+        Self {
+            cast,
+            position: 0.0,
+            next_event: 0,
+            playing: false,
+            speed: 1.0,
+            buffer: AnsiSpanBuffer::new(),
+        }
+    }
+
+    /// Returns the recording being played.
+    #[must_use]
+    pub fn cast(&self) -> &Asciicast {
+        &self.cast
+    }
+
+    /// Starts or resumes playback.
+    pub fn play(&mut self) {
+        self.playing = true;
+    }
+
+    /// Pauses playback.
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    /// Returns `true` while playing.
+    #[must_use]
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Returns the playback speed multiplier.
+    #[must_use]
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    /// Sets the playback speed multiplier; negative values are clamped to 0.
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed = speed.max(0.0);
+    }
+
+    /// Returns the playback position in seconds.
+    #[must_use]
+    pub fn position(&self) -> f64 {
+        self.position
+    }
+
+    /// Returns `true` once every event has been replayed.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.next_event == self.cast.events.len()
+    }
+
+    /// Advances playback by `dt` wall-clock seconds when playing.
+    ///
+    /// Returns `true` if new output was appended. Playback pauses at the end.
+    /// A negative or non-finite `dt` is ignored; use [`Self::seek`] to go
+    /// back.
+    pub fn advance(&mut self, dt: f64) -> bool {
+        if !self.playing || !dt.is_finite() || dt < 0.0 {
+            return false;
+        }
+
+        let target = self.position + dt * self.speed;
+        let changed = self.replay_until(target);
+        if self.is_finished() {
+            self.playing = false;
+        }
+        changed
+    }
+
+    /// Moves to `position` seconds, replaying from the start when seeking
+    /// backwards.
+    pub fn seek(&mut self, position: f64) {
+        let position = position.min(self.cast.duration()).max(0.0);
+        if position < self.position {
+            self.buffer.clear();
+            self.next_event = 0;
+        }
+        self.replay_until(position);
+    }
+
+    fn replay_until(&mut self, target: f64) -> bool {
+        let mut changed = false;
+        while let Some(event) = self.cast.events.get(self.next_event)
+            && event.time <= target
+        {
+            if event.code == "o" {
+                self.buffer.push_str(&event.data);
+                changed = true;
+            }
+            self.next_event += 1;
+        }
+        self.position = target.min(self.cast.duration());
+        changed
+    }
+
+    /// Returns the output replayed so far.
+    #[must_use]
+    pub fn buffer(&self) -> &AnsiSpanBuffer {
+        &self.buffer
+    }
+
+    /// Converts the output replayed so far to an egui layout job.
+    #[cfg(feature = "egui")]
+    #[must_use]
+    pub fn to_layout_job(&self, theme: &EguiAnsiTheme) -> LayoutJob {
+        self.buffer.to_layout_job(theme)
+    }
+}
+
+/// Writes an asciicast v2 recording from timestamped output chunks.
+///
+/// Chunks from a PTY or any other source are stamped with the time since
+/// the recorder was created. Byte chunks may split UTF-8 sequences; the
+/// incomplete tail is held until the next chunk.
+pub struct AsciicastRecorder<W: Write> {
+    writer: W,
+    start: Instant,
+    pending: Vec<u8>,
+}
+
+impl<W: Write> AsciicastRecorder<W> {
+    /// Writes `header` and starts the clock.
+    pub fn new(mut writer: W, header: &AsciicastHeader) -> io::Result<Self> {
+        serde_json::to_writer(&mut writer, header)?;
+        writer.write_all(b"\n")?;
+        Ok(Self {
+            writer,
+            start: Instant::now(),
+            pending: Vec::new(),
+        })
+    }
+
+    /// Records an output chunk at the current time.
+    pub fn record_str(&mut self, chunk: &str) -> io::Result<()> {
+        let time = self.start.elapsed().as_secs_f64();
+        self.record_event(time, "o", chunk)
+    }
+
+    /// Records raw output bytes at the current time.
+    pub fn record_bytes(&mut self, chunk: &[u8]) -> io::Result<()> {
+        self.pending.extend_from_slice(chunk);
+        let valid_len = match std::str::from_utf8(&self.pending) {
+            Ok(text) => text.len(),
+            // Keep an incomplete trailing sequence for the next chunk.
+            Err(error) if error.error_len().is_none() => error.valid_up_to(),
+            Err(_) => self.pending.len(),
+        };
+        if valid_len == 0 {
+            return Ok(());
+        }
+
+        let text = String::from_utf8_lossy(&self.pending[..valid_len]).into_owned();
+        self.pending.drain(..valid_len);
+        self.record_str(&text)
+    }
+
+    /// Records an event with an explicit time and code, such as `"i"` for
+    /// input or `"m"` for a marker.
+    pub fn record_event(&mut self, time: f64, code: &str, data: &str) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, &(time, code, data))?;
+        self.writer.write_all(b"\n")
+    }
+
+    /// Flushes held bytes and returns the writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        if !self.pending.is_empty() {
+            let text = String::from_utf8_lossy(&self.pending).into_owned();
+            self.pending.clear();
+            self.record_str(&text)?;
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
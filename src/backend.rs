@@ -0,0 +1,61 @@
+use crate::{AnsiSpan, AnsiStreamParser, ParseStats};
+use alloc::vec::Vec;
+
+static NO_STATS: ParseStats = ParseStats::EMPTY;
+
+/// Streaming parser that turns bytes into styled spans.
+///
+/// [`AnsiDocument`](crate::AnsiDocument) and
+/// [`AnsiSpanBuffer`](crate::AnsiSpanBuffer) parse through this trait, so the
+/// built-in vte-based [`AnsiStreamParser`] can be swapped for another parser
+/// or a terminal emulator core via their `with_backend` builders.
+///
+/// Like [`AnsiStreamParser`], a backend must carry style state and
+/// incomplete sequences from one chunk to the next. Documents pass newline
+/// and form feed bytes through on their own so the backend stays in step
+/// with the stream; a trailing newline in the returned text is dropped.
+pub trait AnsiBackend: Send {
+    /// Pushes a byte chunk and returns the visible spans it produced.
+    #[must_use]
+    fn push_bytes(&mut self, chunk: &[u8]) -> Vec<AnsiSpan>;
+
+    /// Ends the stream, returning any text still held back, and resets
+    /// parser state.
+    #[must_use]
+    fn finish(&mut self) -> Vec<AnsiSpan>;
+
+    /// Clears all parser and style state.
+    fn reset(&mut self);
+
+    /// Returns counters for the input pushed so far.
+    ///
+    /// Backends that do not count return empty stats.
+    fn stats(&self) -> &ParseStats {
+        &NO_STATS
+    }
+
+    /// Clears [`Self::stats`].
+    fn reset_stats(&mut self) {}
+}
+
+impl AnsiBackend for AnsiStreamParser {
+    fn push_bytes(&mut self, chunk: &[u8]) -> Vec<AnsiSpan> {
+        AnsiStreamParser::push_bytes(self, chunk)
+    }
+
+    fn finish(&mut self) -> Vec<AnsiSpan> {
+        AnsiStreamParser::finish(self)
+    }
+
+    fn reset(&mut self) {
+        AnsiStreamParser::reset(self);
+    }
+
+    fn stats(&self) -> &ParseStats {
+        AnsiStreamParser::stats(self)
+    }
+
+    fn reset_stats(&mut self) {
+        AnsiStreamParser::reset_stats(self);
+    }
+}
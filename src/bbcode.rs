@@ -0,0 +1,91 @@
+use crate::resolve::resolve_style;
+use crate::{AnsiSpan, AnsiStyle, EguiAnsiTheme, ansi_to_text};
+use std::fmt::Write;
+
+/// Converts ANSI text to BBCode for posting console output on forums.
+///
+/// Colors are resolved through the theme like HTML export and written as
+/// `[color=#rrggbb]`; text in the theme's default foreground is left
+/// uncolored so it follows the forum's own text color. BBCode has no
+/// background markup, so backgrounds are dropped. Text containing `[` is
+/// wrapped in `[noparse]` so log lines cannot open or close tags.
+#[must_use]
+pub fn ansi_to_bbcode(input: &str, theme: &EguiAnsiTheme) -> String {
+    let text = ansi_to_text(input);
+    runs_to_bbcode(text.iter(), theme)
+}
+
+/// Converts parsed spans to BBCode.
+#[must_use]
+pub fn spans_to_bbcode(spans: &[AnsiSpan], theme: &EguiAnsiTheme) -> String {
+    runs_to_bbcode(
+        spans.iter().map(|span| (span.text.as_str(), span.style)),
+        theme,
+    )
+}
+
+fn runs_to_bbcode<'a>(
+    runs: impl Iterator<Item = (&'a str, AnsiStyle)>,
+    theme: &EguiAnsiTheme,
+) -> String {
+    let mut bbcode = String::new();
+
+    for (text, style) in runs {
+        if text.is_empty() {
+            continue;
+        }
+
+        let resolved = resolve_style(&style, theme).opaque(theme.default_background);
+        let mut closing = Vec::new();
+
+        if resolved.foreground != theme.default_foreground {
+            let [r, g, b, _] = resolved.foreground.to_array();
+            let _ = write!(bbcode, "[color=#{r:02x}{g:02x}{b:02x}]");
+            closing.push("[/color]");
+        }
+        for (enabled, open, close) in [
+            (resolved.bold, "[b]", "[/b]"),
+            (resolved.italic, "[i]", "[/i]"),
+            (resolved.underline.is_some(), "[u]", "[/u]"),
+            (resolved.strikethrough, "[s]", "[/s]"),
+        ] {
+            if enabled {
+                bbcode.push_str(open);
+                closing.push(close);
+            }
+        }
+
+        push_escaped(&mut bbcode, text);
+        for close in closing.into_iter().rev() {
+            bbcode.push_str(close);
+        }
+    }
+
+    bbcode
+}
+
+/// Appends `text`, wrapped in `[noparse]` if it contains a tag opener.
+///
+/// A literal `[/noparse]` in the text would end the wrapper early, so its
+/// `[` is written in a `[noparse]` block of its own.
+fn push_escaped(bbcode: &mut String, text: &str) {
+    const CLOSE: &str = "[/noparse]";
+    if !text.contains('[') {
+        bbcode.push_str(text);
+        return;
+    }
+
+    bbcode.push_str("[noparse]");
+    let lower = text.to_ascii_lowercase();
+    let mut start = 0;
+    while let Some(found) = lower[start..].find(CLOSE) {
+        let at = start + found;
+        bbcode.push_str(&text[start..at]);
+        bbcode.push_str("[/noparse][noparse][[/noparse]");
+        bbcode.push_str(&text[at + 1..at + CLOSE.len()]);
+        bbcode.push_str("[noparse]");
+        start = at + CLOSE.len();
+    }
+    bbcode.push_str(&text[start..]);
+    bbcode.push_str(CLOSE);
+}
@@ -0,0 +1,210 @@
+use crate::AnsiText;
+use unicode_bidi::{BidiClass, ParagraphBidiInfo, bidi_class};
+use unicode_segmentation::UnicodeSegmentation as _;
+
+/// Styled text reordered for display, with offsets back to the stored text.
+///
+/// Parsed text is always kept in logical order, the order in which it was
+/// written, so search, copying, and export see Arabic and Hebrew words as
+/// typed. [`AnsiText::to_visual`] reorders each line with the Unicode
+/// Bidirectional Algorithm just before rendering: right-to-left runs are
+/// reversed by grapheme cluster, so combining marks stay on their base
+/// character, brackets inside them are mirrored, and every character keeps
+/// its style. Each line is its own paragraph, with its direction taken from
+/// its first strong character, so a log line starting in Hebrew reads from
+/// the right while English lines are unaffected.
+///
+/// Positions in the rendered text, such as a hit-tested cursor, map back to
+/// the stored text with [`Self::logical_offset`], and stored positions, such
+/// as search matches, map forward with [`Self::visual_offset`].
+///
+/// For cell grids, such as rows converted from terminal cells, the same
+/// policy applies per row: cells stay in logical order in the row, the row
+/// is reordered as one line for display, and display column `n` is the
+/// `n`-th column of the reordered row. A wide character keeps both of its
+/// columns together, so mapping a column back goes through the character's
+/// byte offset rather than the column.
+///
+/// ```rust
+/// use egui_sgr::ansi_to_text;
+///
+/// let text = ansi_to_text("id: \x1b[31mשלום\x1b[0m (ok)");
+/// let visual = text.to_visual();
+/// assert_eq!(visual.text().text, "id: םולש (ok)");
+/// assert_eq!(visual.text().style_at(4), text.style_at(10));
+/// assert_eq!(visual.logical_offset(4), Some(10));
+/// assert_eq!(visual.visual_offset(4), Some(10));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BidiText {
+    text: AnsiText,
+    /// Logical byte offset of each character of `text`, in visual order, or
+    /// empty if nothing was reordered.
+    logical: Vec<usize>,
+    /// Visual byte offset of each character of `text`, in visual order.
+    visual: Vec<usize>,
+    /// Logical and visual byte offsets of each character, sorted by logical
+    /// offset, or empty if nothing was reordered.
+    by_logical: Vec<(usize, usize)>,
+}
+
+impl BidiText {
+    /// Returns the text in display order.
+    #[must_use]
+    pub fn text(&self) -> &AnsiText {
+        &self.text
+    }
+
+    /// Returns the text in display order, consuming `self`.
+    #[must_use]
+    pub fn into_text(self) -> AnsiText {
+        self.text
+    }
+
+    /// Returns `true` if any line contained right-to-left text, so the
+    /// display order differs from the stored order.
+    #[must_use]
+    pub fn is_reordered(&self) -> bool {
+        !self.logical.is_empty()
+    }
+
+    /// Maps a byte offset of the displayed text to the byte offset of the
+    /// same character in the stored text.
+    ///
+    /// Returns `None` past the end of the text.
+    #[must_use]
+    pub fn logical_offset(&self, visual_byte: usize) -> Option<usize> {
+        if visual_byte >= self.text.text.len() {
+            return None;
+        }
+        if !self.is_reordered() {
+            return Some(visual_byte);
+        }
+        let index = self.visual.partition_point(|&start| start <= visual_byte) - 1;
+        Some(self.logical[index])
+    }
+
+    /// Maps a byte offset of the stored text to the byte offset of the same
+    /// character in the displayed text.
+    ///
+    /// Returns `None` past the end of the text.
+    #[must_use]
+    pub fn visual_offset(&self, logical_byte: usize) -> Option<usize> {
+        if logical_byte >= self.text.text.len() {
+            return None;
+        }
+        if !self.is_reordered() {
+            return Some(logical_byte);
+        }
+        let index = self
+            .by_logical
+            .partition_point(|&(start, _)| start <= logical_byte);
+        index.checked_sub(1).map(|index| self.by_logical[index].1)
+    }
+
+    /// Appends the character at `logical` in `source`, in its style.
+    fn push_char(&mut self, c: char, logical: usize, source: &AnsiText) {
+        let style = source.style_at(logical).unwrap_or_default();
+        self.logical.push(logical);
+        self.visual.push(self.text.text.len());
+        self.text.push_str(c.encode_utf8(&mut [0; 4]), style);
+    }
+}
+
+/// Returns `true` if `text` contains a right-to-left character or an
+/// explicit right-to-left embedding, override, or isolate.
+pub(crate) fn has_rtl(text: &str) -> bool {
+    text.chars().any(|c| {
+        matches!(
+            bidi_class(c),
+            BidiClass::R | BidiClass::AL | BidiClass::RLE | BidiClass::RLO | BidiClass::RLI
+        )
+    })
+}
+
+/// Returns the mirrored form of a bracket shown right to left.
+fn mirror(c: char) -> char {
+    match c {
+        '(' => ')',
+        ')' => '(',
+        '[' => ']',
+        ']' => '[',
+        '{' => '}',
+        '}' => '{',
+        '<' => '>',
+        '>' => '<',
+        '«' => '»',
+        '»' => '«',
+        _ => c,
+    }
+}
+
+impl AnsiText {
+    /// Reorders each line into display order, keeping styles.
+    ///
+    /// Text without right-to-left characters is returned unchanged. See
+    /// [`BidiText`] for the ordering rules and offset mapping.
+    #[must_use]
+    pub fn to_visual(&self) -> BidiText {
+        profile_scope!("egui_sgr::to_visual");
+        if !has_rtl(&self.text) {
+            return BidiText {
+                text: self.clone(),
+                ..BidiText::default()
+            };
+        }
+
+        let mut visual = BidiText::default();
+        let mut line_start = 0;
+        for line in self.text.split_inclusive('\n') {
+            let content = line.strip_suffix('\n').unwrap_or(line);
+            // `unicode-bidi` panics on the runs of an empty paragraph.
+            let runs = if content.is_empty() {
+                (Vec::new(), Vec::new())
+            } else {
+                ParagraphBidiInfo::new(content, None).visual_runs(0..content.len())
+            };
+            let (levels, runs) = runs;
+            for run in runs {
+                let rtl = levels[run.start].is_rtl();
+                let mut clusters: Vec<_> = content[run.clone()].grapheme_indices(true).collect();
+                if rtl {
+                    clusters.reverse();
+                }
+                for (cluster_offset, cluster) in clusters {
+                    for (offset, c) in cluster.char_indices() {
+                        let logical = line_start + run.start + cluster_offset + offset;
+                        let c = if rtl { mirror(c) } else { c };
+                        visual.push_char(c, logical, self);
+                    }
+                }
+            }
+            if content.len() < line.len() {
+                visual.push_char('\n', line_start + content.len(), self);
+            }
+            line_start += line.len();
+        }
+        visual.by_logical = visual
+            .logical
+            .iter()
+            .copied()
+            .zip(visual.visual.iter().copied())
+            .collect();
+        visual.by_logical.sort_unstable();
+        visual
+    }
+}
+
+#[cfg(feature = "egui")]
+impl AnsiText {
+    /// Converts the styled text to an egui layout job in display order.
+    ///
+    /// egui lays text out left to right in stored order, so mixed-direction
+    /// lines come out scrambled with [`Self::to_layout_job`]. This reorders
+    /// them with [`Self::to_visual`] first; text copied from the resulting
+    /// galley is in display order.
+    #[must_use]
+    pub fn to_bidi_layout_job(&self, theme: &crate::EguiAnsiTheme) -> egui::text::LayoutJob {
+        self.to_visual().text().to_layout_job(theme)
+    }
+}
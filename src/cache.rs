@@ -0,0 +1,116 @@
+use crate::{EguiAnsiTheme, ansi_to_layout_job};
+use egui::text::LayoutJob;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Arc;
+
+/// Per-line cache of rendered [`LayoutJob`]s.
+///
+/// Log views usually redraw the same lines every frame. Entries are keyed by
+/// the line content hash, the wrap width, and a caller-controlled options
+/// epoch, so scrolling a static log does not reparse ANSI or rebuild layout
+/// jobs. egui then reuses its own galley cache for the returned jobs.
+#[derive(Debug, Default)]
+pub struct LayoutJobCache {
+    epoch: u64,
+    generation: u64,
+    entries: HashMap<CacheKey, CacheEntry>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct CacheKey {
+    content: u64,
+    wrap_width: u32,
+    epoch: u64,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    job: Arc<LayoutJob>,
+    last_used: u64,
+}
+
+impl LayoutJobCache {
+    /// Creates an empty cache.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the cached layout job for `line`, rendering it on a miss.
+    ///
+    /// `wrap_width` is stored in the job's [`TextWrapping::max_width`](egui::text::TextWrapping::max_width).
+    #[must_use]
+    pub fn layout_job(
+        &mut self,
+        line: &str,
+        wrap_width: f32,
+        theme: &EguiAnsiTheme,
+    ) -> Arc<LayoutJob> {
+        profile_scope!("egui_sgr::cached_layout_job");
+        let key = CacheKey {
+            content: content_hash(line),
+            wrap_width: wrap_width.to_bits(),
+            epoch: self.epoch,
+        };
+        let generation = self.generation;
+        let entry = self.entries.entry(key).or_insert_with(|| {
+            let mut job = ansi_to_layout_job(line, theme);
+            job.wrap.max_width = wrap_width;
+            CacheEntry {
+                job: Arc::new(job),
+                last_used: generation,
+            }
+        });
+        entry.last_used = generation;
+        Arc::clone(&entry.job)
+    }
+
+    /// Returns the current options epoch.
+    #[must_use]
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Sets the options epoch.
+    ///
+    /// Change the epoch whenever the theme or other render options change.
+    /// Entries from older epochs are no longer returned and are dropped by the
+    /// next [`Self::evict_unused`] calls.
+    pub fn set_epoch(&mut self, epoch: u64) {
+        self.epoch = epoch;
+    }
+
+    /// Drops entries that were not used since the previous call.
+    ///
+    /// Call this once per frame, after all visible lines were requested.
+    pub fn evict_unused(&mut self) {
+        let generation = self.generation;
+        self.entries
+            .retain(|_, entry| entry.last_used == generation);
+        self.generation = generation.wrapping_add(1);
+    }
+
+    /// Returns the number of cached layout jobs.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no layout jobs are cached.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes all cached layout jobs.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+fn content_hash(line: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    line.hash(&mut hasher);
+    hasher.finish()
+}
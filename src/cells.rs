@@ -0,0 +1,177 @@
+use crate::{AnsiColor, AnsiIntensity, AnsiSpan, AnsiStyle, AnsiText, UnderlineStyle};
+
+#[cfg(feature = "termwiz")]
+mod wezterm {
+    use super::*;
+    use termwiz::cell::{Cell, CellAttributes, Intensity, Underline};
+    use termwiz::color::ColorAttribute;
+
+    impl From<ColorAttribute> for AnsiColor {
+        fn from(color: ColorAttribute) -> Self {
+            match color {
+                ColorAttribute::TrueColorWithPaletteFallback(rgba, _)
+                | ColorAttribute::TrueColorWithDefaultFallback(rgba) => {
+                    let (r, g, b, _) = rgba.to_srgb_u8();
+                    Self::Rgb(r, g, b)
+                }
+                ColorAttribute::PaletteIndex(index) => Self::Indexed(index),
+                ColorAttribute::Default => Self::Default,
+            }
+        }
+    }
+
+    impl From<&CellAttributes> for AnsiStyle {
+        fn from(attrs: &CellAttributes) -> Self {
+            let underline_color = attrs.underline_color();
+            Self {
+                foreground: attrs.foreground().into(),
+                background: attrs.background().into(),
+                underline_color: (underline_color != ColorAttribute::Default)
+                    .then(|| underline_color.into()),
+                intensity: match attrs.intensity() {
+                    Intensity::Normal => AnsiIntensity::Normal,
+                    Intensity::Bold => AnsiIntensity::Bold,
+                    Intensity::Half => AnsiIntensity::Faint,
+                },
+                italic: attrs.italic(),
+                underline: match attrs.underline() {
+                    Underline::None => UnderlineStyle::None,
+                    Underline::Single => UnderlineStyle::Single,
+                    Underline::Double => UnderlineStyle::Double,
+                    Underline::Curly => UnderlineStyle::Curly,
+                    Underline::Dotted => UnderlineStyle::Dotted,
+                    Underline::Dashed => UnderlineStyle::Dashed,
+                },
+                strikethrough: attrs.strikethrough(),
+                reverse: attrs.reverse(),
+                hidden: attrs.invisible(),
+            }
+        }
+    }
+
+    /// Converts a row of termwiz cells into spans.
+    ///
+    /// Pass the visible cells of a line, for example from
+    /// `Line::visible_cells`, so wide characters are not followed by their
+    /// padding cells. Adjacent cells with the same style are merged.
+    pub fn termwiz_cells_to_spans<'a>(cells: impl IntoIterator<Item = &'a Cell>) -> Vec<AnsiSpan> {
+        let mut text = AnsiText::new();
+        for cell in cells {
+            text.push_str(cell.str(), AnsiStyle::from(cell.attrs()));
+        }
+        text.to_spans()
+    }
+}
+
+#[cfg(feature = "alacritty_terminal")]
+mod alacritty {
+    use super::*;
+    use alacritty_terminal::term::cell::{Cell, Flags};
+    use alacritty_terminal::vte::ansi::{Color, NamedColor};
+
+    impl From<Color> for AnsiColor {
+        fn from(color: Color) -> Self {
+            match color {
+                Color::Spec(rgb) => Self::Rgb(rgb.r, rgb.g, rgb.b),
+                Color::Indexed(index) => Self::Indexed(index),
+                Color::Named(named) => named_color(named),
+            }
+        }
+    }
+
+    fn named_color(named: NamedColor) -> AnsiColor {
+        match named {
+            NamedColor::Black | NamedColor::DimBlack => AnsiColor::Indexed(0),
+            NamedColor::Red | NamedColor::DimRed => AnsiColor::Indexed(1),
+            NamedColor::Green | NamedColor::DimGreen => AnsiColor::Indexed(2),
+            NamedColor::Yellow | NamedColor::DimYellow => AnsiColor::Indexed(3),
+            NamedColor::Blue | NamedColor::DimBlue => AnsiColor::Indexed(4),
+            NamedColor::Magenta | NamedColor::DimMagenta => AnsiColor::Indexed(5),
+            NamedColor::Cyan | NamedColor::DimCyan => AnsiColor::Indexed(6),
+            NamedColor::White | NamedColor::DimWhite => AnsiColor::Indexed(7),
+            NamedColor::BrightBlack => AnsiColor::Indexed(8),
+            NamedColor::BrightRed => AnsiColor::Indexed(9),
+            NamedColor::BrightGreen => AnsiColor::Indexed(10),
+            NamedColor::BrightYellow => AnsiColor::Indexed(11),
+            NamedColor::BrightBlue => AnsiColor::Indexed(12),
+            NamedColor::BrightMagenta => AnsiColor::Indexed(13),
+            NamedColor::BrightCyan => AnsiColor::Indexed(14),
+            NamedColor::BrightWhite => AnsiColor::Indexed(15),
+            NamedColor::Foreground
+            | NamedColor::Background
+            | NamedColor::Cursor
+            | NamedColor::BrightForeground
+            | NamedColor::DimForeground => AnsiColor::Default,
+        }
+    }
+
+    impl From<&Cell> for AnsiStyle {
+        fn from(cell: &Cell) -> Self {
+            let flags = cell.flags;
+            let underline = if flags.contains(Flags::DOUBLE_UNDERLINE) {
+                UnderlineStyle::Double
+            } else if flags.contains(Flags::UNDERCURL) {
+                UnderlineStyle::Curly
+            } else if flags.contains(Flags::DOTTED_UNDERLINE) {
+                UnderlineStyle::Dotted
+            } else if flags.contains(Flags::DASHED_UNDERLINE) {
+                UnderlineStyle::Dashed
+            } else if flags.contains(Flags::UNDERLINE) {
+                UnderlineStyle::Single
+            } else {
+                UnderlineStyle::None
+            };
+
+            Self {
+                foreground: cell.fg.into(),
+                background: cell.bg.into(),
+                underline_color: cell.underline_color().map(AnsiColor::from),
+                intensity: if flags.contains(Flags::DIM) {
+                    AnsiIntensity::Faint
+                } else if flags.contains(Flags::BOLD) {
+                    AnsiIntensity::Bold
+                } else {
+                    AnsiIntensity::Normal
+                },
+                italic: flags.contains(Flags::ITALIC),
+                underline,
+                strikethrough: flags.contains(Flags::STRIKEOUT),
+                reverse: flags.contains(Flags::INVERSE),
+                hidden: flags.contains(Flags::HIDDEN),
+            }
+        }
+    }
+
+    /// Converts a row of alacritty_terminal grid cells into spans.
+    ///
+    /// Wide-character spacer cells are skipped and zero-width characters are
+    /// kept with their base character. Adjacent cells with the same style are
+    /// merged.
+    pub fn alacritty_cells_to_spans<'a>(
+        cells: impl IntoIterator<Item = &'a Cell>,
+    ) -> Vec<AnsiSpan> {
+        let mut text = AnsiText::new();
+        let mut grapheme = String::new();
+
+        for cell in cells {
+            if cell
+                .flags
+                .intersects(Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER)
+            {
+                continue;
+            }
+
+            grapheme.clear();
+            grapheme.push(cell.c);
+            grapheme.extend(cell.zerowidth().into_iter().flatten());
+            text.push_str(&grapheme, AnsiStyle::from(cell));
+        }
+
+        text.to_spans()
+    }
+}
+
+#[cfg(feature = "alacritty_terminal")]
+pub use alacritty::alacritty_cells_to_spans;
+#[cfg(feature = "termwiz")]
+pub use wezterm::termwiz_cells_to_spans;
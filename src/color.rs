@@ -0,0 +1,149 @@
+use ecolor::Color32;
+
+/// Color-difference formula used to compare colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ColorMetric {
+    /// Euclidean distance in sRGB weighted by the mean red level. Cheapest,
+    /// and close enough for picking between very different colors.
+    Redmean,
+    /// CIE76: Euclidean distance in CIELAB (D65). A difference of about 2.3
+    /// is just noticeable.
+    Cie76,
+    /// Euclidean distance in OKLab, which tracks perceived hue and lightness
+    /// more evenly than CIELAB, especially for blues.
+    #[default]
+    Oklab,
+}
+
+impl ColorMetric {
+    /// Returns the difference between `a` and `b`; alpha is ignored.
+    ///
+    /// Values are only comparable within one metric.
+    #[must_use]
+    pub fn distance(self, a: Color32, b: Color32) -> f32 {
+        match self {
+            Self::Redmean => redmean(a, b),
+            Self::Cie76 => euclidean(Lab::from_color(a).to_array(), Lab::from_color(b).to_array()),
+            Self::Oklab => euclidean(
+                Oklab::from_color(a).to_array(),
+                Oklab::from_color(b).to_array(),
+            ),
+        }
+    }
+}
+
+/// Returns the index of the candidate closest to `color` under `metric`, or
+/// `None` if there are no candidates.
+///
+/// Use it to snap arbitrary colors to the active palette, for example
+/// `nearest_color(color, &theme.palette[..16], ColorMetric::Oklab)`.
+#[must_use]
+pub fn nearest_color(color: Color32, candidates: &[Color32], metric: ColorMetric) -> Option<usize> {
+    candidates
+        .iter()
+        .map(|candidate| metric.distance(color, *candidate))
+        .enumerate()
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(index, _)| index)
+}
+
+/// Color in the CIELAB space with a D65 white point.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Lab {
+    /// Lightness, 0 to 100.
+    pub l: f32,
+    /// Green-red axis.
+    pub a: f32,
+    /// Blue-yellow axis.
+    pub b: f32,
+}
+
+impl Lab {
+    /// Converts an sRGB color; alpha is ignored.
+    #[must_use]
+    pub fn from_color(color: Color32) -> Self {
+        let [r, g, b] = linear_rgb(color);
+        let x = (0.412_456_4 * r + 0.357_576_1 * g + 0.180_437_5 * b) / 0.950_47;
+        let y = 0.212_672_9 * r + 0.715_152_2 * g + 0.072_175 * b;
+        let z = (0.019_333_9 * r + 0.119_192 * g + 0.950_304_1 * b) / 1.088_83;
+        let (fx, fy, fz) = (lab_f(x), lab_f(y), lab_f(z));
+        Self {
+            l: 116.0 * fy - 16.0,
+            a: 500.0 * (fx - fy),
+            b: 200.0 * (fy - fz),
+        }
+    }
+
+    fn to_array(self) -> [f32; 3] {
+        [self.l, self.a, self.b]
+    }
+}
+
+/// Color in the OKLab perceptual space.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Oklab {
+    /// Perceived lightness, 0 to 1.
+    pub l: f32,
+    /// Green-red axis.
+    pub a: f32,
+    /// Blue-yellow axis.
+    pub b: f32,
+}
+
+impl Oklab {
+    /// Converts an sRGB color; alpha is ignored.
+    #[must_use]
+    pub fn from_color(color: Color32) -> Self {
+        let [r, g, b] = linear_rgb(color);
+        let l = (0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+        let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+        let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+        Self {
+            l: 0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+            a: 1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+            b: 0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+        }
+    }
+
+    fn to_array(self) -> [f32; 3] {
+        [self.l, self.a, self.b]
+    }
+}
+
+fn linear_rgb(color: Color32) -> [f32; 3] {
+    let [r, g, b, _] = color.to_array();
+    [r, g, b].map(|channel| {
+        let channel = f32::from(channel) / 255.0;
+        if channel <= 0.040_45 {
+            channel / 12.92
+        } else {
+            ((channel + 0.055) / 1.055).powf(2.4)
+        }
+    })
+}
+
+fn lab_f(t: f32) -> f32 {
+    const EPSILON: f32 = 216.0 / 24389.0;
+    const KAPPA: f32 = 24389.0 / 27.0;
+    if t > EPSILON {
+        t.cbrt()
+    } else {
+        (KAPPA * t + 16.0) / 116.0
+    }
+}
+
+fn euclidean(a: [f32; 3], b: [f32; 3]) -> f32 {
+    let [d0, d1, d2] = [a[0] - b[0], a[1] - b[1], a[2] - b[2]];
+    (d0 * d0 + d1 * d1 + d2 * d2).sqrt()
+}
+
+fn redmean(a: Color32, b: Color32) -> f32 {
+    let mean_red = (f32::from(a.r()) + f32::from(b.r())) / 2.0;
+    let dr = f32::from(a.r()) - f32::from(b.r());
+    let dg = f32::from(a.g()) - f32::from(b.g());
+    let db = f32::from(a.b()) - f32::from(b.b());
+    ((2.0 + mean_red / 256.0) * dr * dr
+        + 4.0 * dg * dg
+        + (2.0 + (255.0 - mean_red) / 256.0) * db * db)
+        .sqrt()
+}
@@ -0,0 +1,121 @@
+use crate::{AnsiColor, AnsiStyle, AnsiText};
+use regex::Regex;
+
+/// Rule-based colorizer for plain logs.
+///
+/// Each rule pairs a regex with a style for the whole match or for one
+/// capture group. Rules only apply to text that carries no ANSI colors, so
+/// output that is already colored by its producer passes through unchanged.
+/// Later rules win where matches overlap; attributes such as bold that the
+/// text already has are replaced inside styled ranges.
+///
+/// ```rust
+/// use egui_sgr::{AnsiColor, AnsiStyle, Colorizer};
+/// use regex::Regex;
+///
+/// let colorizer = Colorizer::new()
+///     .with_rule(Regex::new(r"^\d{2}:\d{2}:\d{2}").unwrap(), AnsiStyle::new().faint())
+///     .with_group_rule(
+///         Regex::new(r"\b(ERROR|WARN)\b").unwrap(),
+///         1,
+///         AnsiStyle::new().fg(AnsiColor::Indexed(1)).bold(),
+///     );
+/// let line = colorizer.colorize_ansi("12:00:01 ERROR disk full");
+/// assert_eq!(line.runs.len(), 4);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Colorizer {
+    rules: Vec<ColorizeRule>,
+}
+
+#[derive(Debug, Clone)]
+struct ColorizeRule {
+    regex: Regex,
+    group: usize,
+    style: AnsiStyle,
+}
+
+impl Colorizer {
+    /// Creates a colorizer without rules.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a rule styling every whole match of `regex`.
+    #[must_use]
+    pub fn with_rule(self, regex: Regex, style: AnsiStyle) -> Self {
+        self.with_group_rule(regex, 0, style)
+    }
+
+    /// Adds a rule styling only capture group `group` of each match.
+    ///
+    /// Matches where the group did not participate are skipped.
+    #[must_use]
+    pub fn with_group_rule(mut self, regex: Regex, group: usize, style: AnsiStyle) -> Self {
+        self.rules.push(ColorizeRule {
+            regex,
+            group,
+            style,
+        });
+        self
+    }
+
+    /// Returns `true` if no rules are registered.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Parses one line of ANSI input and colorizes it.
+    #[must_use]
+    pub fn colorize_ansi(&self, input: &str) -> AnsiText {
+        let mut text = AnsiText::new();
+        text.push_ansi(input);
+        self.colorize(&text)
+    }
+
+    /// Returns `text` with the rules applied, or an unchanged copy if it
+    /// already uses a foreground or background color.
+    ///
+    /// Rules see the whole text at once, so pass one line at a time, such as
+    /// each [`AnsiLine::text`](crate::AnsiLine::text) of a document.
+    #[must_use]
+    pub fn colorize(&self, text: &AnsiText) -> AnsiText {
+        profile_scope!("egui_sgr::colorize");
+        if self.rules.is_empty() || has_colors(text) {
+            return text.clone();
+        }
+
+        let mut styles = vec![AnsiStyle::default(); text.text.len()];
+        for run in &text.runs {
+            styles[run.range.clone()].fill(run.style);
+        }
+        for rule in &self.rules {
+            for captures in rule.regex.captures_iter(&text.text) {
+                if let Some(group) = captures.get(rule.group) {
+                    styles[group.range()].fill(rule.style);
+                }
+            }
+        }
+
+        let mut colored = AnsiText::new();
+        let mut start = 0;
+        for (offset, _) in text.text.char_indices().skip(1) {
+            if styles[offset] != styles[start] {
+                colored.push_str(&text.text[start..offset], styles[start]);
+                start = offset;
+            }
+        }
+        if start < text.text.len() {
+            colored.push_str(&text.text[start..], styles[start]);
+        }
+        colored
+    }
+}
+
+fn has_colors(text: &AnsiText) -> bool {
+    text.runs.iter().any(|run| {
+        run.style.foreground != AnsiColor::Default || run.style.background != AnsiColor::Default
+    })
+}
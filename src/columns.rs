@@ -0,0 +1,317 @@
+use crate::{AnsiStyle, AnsiText, ansi_to_text, strip_ansi};
+use std::borrow::Cow;
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Returns the display width of `input` in terminal columns.
+///
+/// Escape sequences are ignored, and width is measured per grapheme cluster:
+/// East Asian wide characters and emoji count as two columns, including ZWJ
+/// sequences, skin tones, and emoji presentation selectors such as `❤️`,
+/// while combining marks add nothing. Control characters, including tabs and
+/// newlines, count as zero, so measure one line at a time.
+#[must_use]
+pub fn visible_width(input: &str) -> usize {
+    graphemes(&strip_ansi(input))
+        .map(|(_, _, width)| width)
+        .sum()
+}
+
+/// Shortens `input` to at most `max_cols` display columns.
+///
+/// The cut falls on a column boundary: escape sequences, wide glyphs, and
+/// grapheme clusters such as emoji sequences are never split. When
+/// text is removed, `ellipsis` is appended in the style of the removed text,
+/// and a final reset closes any open style. Input that already fits is
+/// returned borrowed and unchanged.
+///
+/// The result is re-emitted from the parsed styles, so escape sequences other
+/// than SGR are dropped from truncated lines.
+#[must_use]
+pub fn truncate_ansi<'a>(input: &'a str, max_cols: usize, ellipsis: &str) -> Cow<'a, str> {
+    if visible_width(input) <= max_cols {
+        return Cow::Borrowed(input);
+    }
+
+    let ellipsis_width = visible_width(ellipsis);
+    let (budget, ellipsis) = if ellipsis_width <= max_cols {
+        (max_cols - ellipsis_width, ellipsis)
+    } else {
+        (max_cols, "")
+    };
+
+    let text = ansi_to_text(input);
+    let mut output = AnsiText::new();
+    let mut used = 0;
+    let mut cut_style = None;
+
+    for (offset, cluster, width) in graphemes(&text.text) {
+        if used + width > budget {
+            cut_style = text.style_at(offset);
+            break;
+        }
+        used += width;
+        push_cluster(&mut output, &text, offset, cluster);
+    }
+
+    output.push_str(ellipsis, cut_style.unwrap_or_default());
+    Cow::Owned(output.to_ansi())
+}
+
+/// Wraps `input` into lines of at most `cols` display columns.
+///
+/// Lines break at column boundaries, like a terminal with auto-wrap, and at
+/// every `\n`. Each returned line is self-contained: it re-opens the styles
+/// active where it starts and ends with a reset if it is styled, so lines can
+/// be printed, exported, or rendered independently. A glyph wider than the
+/// remaining space moves to the next line, and grapheme clusters are never
+/// split; `cols` below 1 is treated as 1.
+///
+/// Escape sequences other than SGR are dropped.
+#[must_use]
+pub fn wrap_ansi(input: &str, cols: usize) -> Vec<String> {
+    let cols = cols.max(1);
+    let text = ansi_to_text(input);
+    let mut lines = Vec::new();
+    let mut line = AnsiText::new();
+    let mut used = 0;
+
+    for (offset, cluster, width) in graphemes(&text.text) {
+        if cluster.ends_with('\n') {
+            lines.push(std::mem::take(&mut line).to_ansi());
+            used = 0;
+            continue;
+        }
+
+        if used + width > cols && used > 0 {
+            lines.push(std::mem::take(&mut line).to_ansi());
+            used = 0;
+        }
+        used += width;
+        push_cluster(&mut line, &text, offset, cluster);
+    }
+
+    if !line.is_empty() {
+        lines.push(line.to_ansi());
+    }
+    lines
+}
+
+/// Returns the part of a line covering display columns `cols`.
+///
+/// The result starts with the styles active at `cols.start` and ends with a
+/// reset if it is styled, which makes it suitable for horizontal scrolling
+/// and column extraction on raw ANSI lines. A wide glyph cut by either edge
+/// is replaced by spaces in its style, so the slice is exactly as wide as the
+/// covered columns.
+///
+/// Escape sequences other than SGR are dropped.
+#[must_use]
+pub fn slice_ansi(input: &str, cols: Range<usize>) -> String {
+    let text = ansi_to_text(input);
+    let mut output = AnsiText::new();
+    let mut column = 0;
+    let mut kept_previous = false;
+
+    for (offset, cluster, width) in graphemes(&text.text) {
+        if width > 0 && column >= cols.end {
+            break;
+        }
+        let end = column + width;
+
+        if width == 0 {
+            if kept_previous {
+                push_cluster(&mut output, &text, offset, cluster);
+            }
+        } else if column >= cols.start && end <= cols.end {
+            push_cluster(&mut output, &text, offset, cluster);
+            kept_previous = true;
+        } else {
+            let visible = end.min(cols.end).saturating_sub(column.max(cols.start));
+            let style = text.style_at(offset).unwrap_or_default();
+            output.push_str(&" ".repeat(visible), style);
+            kept_previous = false;
+        }
+        column = end;
+    }
+
+    output.to_ansi()
+}
+
+/// Preview of a long line, made by [`AnsiText::fold`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct FoldedText {
+    /// The leading columns of the text, with their styles.
+    pub preview: AnsiText,
+    /// Number of characters left out of the preview.
+    pub hidden_chars: usize,
+}
+
+impl FoldedText {
+    /// Returns the label for expanding the text, such as `… +12,345 chars`.
+    #[must_use]
+    pub fn marker(&self) -> String {
+        let digits = self.hidden_chars.to_string();
+        let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+        for (index, digit) in digits.chars().enumerate() {
+            if index > 0 && (digits.len() - index).is_multiple_of(3) {
+                grouped.push(',');
+            }
+            grouped.push(digit);
+        }
+        format!("… +{grouped} chars")
+    }
+}
+
+impl AnsiText {
+    /// Cuts the text to its first `max_cols` display columns, or returns
+    /// `None` if it already fits.
+    ///
+    /// Only the preview is measured, so folding a multi-megabyte line costs
+    /// little more than counting its characters. Wide glyphs and grapheme
+    /// clusters are never split, as in [`truncate_ansi`].
+    #[must_use]
+    pub fn fold(&self, max_cols: usize) -> Option<FoldedText> {
+        let mut used = 0;
+        let mut cut = None;
+        for (offset, _, width) in graphemes(&self.text) {
+            if used + width > max_cols {
+                cut = Some(offset);
+                break;
+            }
+            used += width;
+        }
+        let cut = cut?;
+        Some(FoldedText {
+            preview: self.slice(0..cut),
+            hidden_chars: self.text[cut..].chars().count(),
+        })
+    }
+}
+
+/// Aligns tab- or space-separated columns across consecutive lines.
+///
+/// Cells end at a tab or at a run of two or more spaces after some content,
+/// and the last cell of a line is never padded. Following elastic tabstops,
+/// a column's width is shared by the consecutive lines that all have a cell
+/// in it, so a table lines up without widening unrelated lines above or
+/// below. Cells are padded with spaces in the separator's style to their
+/// column's widest cell plus `gap` (at least 1).
+///
+/// Widths are display columns as counted by [`visible_width`], so render the
+/// result with a monospace font.
+#[must_use]
+pub fn align_columns<'a>(
+    lines: impl IntoIterator<Item = &'a AnsiText>,
+    gap: usize,
+) -> Vec<AnsiText> {
+    let gap = gap.max(1);
+    let rows: Vec<Vec<Cell>> = lines.into_iter().map(split_cells).collect();
+    let mut widths: Vec<Vec<usize>> = rows.iter().map(|cells| vec![0; cells.len() - 1]).collect();
+    let column_count = widths.iter().map(Vec::len).max().unwrap_or(0);
+
+    for column in 0..column_count {
+        let mut row = 0;
+        while row < rows.len() {
+            let start = row;
+            let mut width = 0;
+            while row < rows.len() && widths[row].len() > column {
+                width = width.max(rows[row][column].width);
+                row += 1;
+            }
+            for padded in &mut widths[start..row] {
+                padded[column] = width + gap;
+            }
+            row = row.max(start + 1);
+        }
+    }
+
+    rows.into_iter()
+        .zip(widths)
+        .map(|(cells, widths)| {
+            let mut text = AnsiText::new();
+            for (index, cell) in cells.into_iter().enumerate() {
+                for (part, style) in cell.text.iter() {
+                    text.push_str(part, style);
+                }
+                if let Some(&width) = widths.get(index) {
+                    text.push_str(&" ".repeat(width - cell.width), cell.separator);
+                }
+            }
+            text
+        })
+        .collect()
+}
+
+/// One cell of a line split by [`align_columns`].
+#[derive(Default)]
+struct Cell {
+    text: AnsiText,
+    width: usize,
+    separator: AnsiStyle,
+}
+
+fn split_cells(line: &AnsiText) -> Vec<Cell> {
+    let clusters: Vec<_> = graphemes(&line.text).collect();
+    let mut cells = Vec::new();
+    let mut cell = Cell::default();
+    let mut index = 0;
+
+    while index < clusters.len() {
+        let (offset, cluster, _) = clusters[index];
+        let style = line.style_at(offset).unwrap_or_default();
+        if cluster == "\t" {
+            cell.separator = style;
+            cells.push(std::mem::take(&mut cell));
+            index += 1;
+            continue;
+        }
+
+        let spaces = clusters[index..]
+            .iter()
+            .take_while(|(_, cluster, _)| *cluster == " ")
+            .count();
+        if spaces >= 2 && !cell.text.is_empty() && index + spaces < clusters.len() {
+            cell.separator = style;
+            cells.push(std::mem::take(&mut cell));
+            index += spaces;
+            continue;
+        }
+
+        for &(offset, cluster, width) in &clusters[index..index + spaces.max(1)] {
+            cell.width += width;
+            push_cluster(&mut cell.text, line, offset, cluster);
+        }
+        index += spaces.max(1);
+    }
+
+    cells.push(cell);
+    cells
+}
+
+/// Iterates over the grapheme clusters of `text` with their byte offsets and
+/// display widths.
+///
+/// A cluster is one user-perceived character: a base character with its
+/// combining marks and variation selectors, or a whole emoji ZWJ or modifier
+/// sequence. It is measured as a whole, so a family emoji or `❤️` is two
+/// columns wide; clusters starting with a control character are zero.
+pub(crate) fn graphemes(text: &str) -> impl Iterator<Item = (usize, &str, usize)> + '_ {
+    text.grapheme_indices(true).map(|(offset, cluster)| {
+        let width = if cluster.starts_with(char::is_control) {
+            0
+        } else {
+            cluster.width()
+        };
+        (offset, cluster, width)
+    })
+}
+
+/// Appends the cluster at `offset` in `text` to `output`, keeping the styles
+/// of its characters.
+fn push_cluster(output: &mut AnsiText, text: &AnsiText, offset: usize, cluster: &str) {
+    for (part, style) in text.slice(offset..offset + cluster.len()).iter() {
+        output.push_str(part, style);
+    }
+}
@@ -0,0 +1,257 @@
+use crate::{AnsiDocument, OverlayLayer, OverlayStyle};
+
+/// Edit cost after which [`align_documents`] stops searching for the
+/// smallest diff and pairs the remaining lines in order.
+///
+/// The trace grows with the square of the cost, so this keeps it near 2 MB.
+const MAX_EDIT_COST: usize = 512;
+
+/// How the two sides of an [`AlignedRow`] relate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AlignKind {
+    /// Both lines have the same visible text.
+    Equal,
+    /// Both sides have a line, with different text.
+    Changed,
+    /// The line only exists on the left.
+    LeftOnly,
+    /// The line only exists on the right.
+    RightOnly,
+}
+
+/// One row of a side-by-side comparison.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AlignedRow {
+    /// Index of the left document's retained line, if the row has one.
+    pub left: Option<usize>,
+    /// Index of the right document's retained line, if the row has one.
+    pub right: Option<usize>,
+    /// How the two lines relate.
+    pub kind: AlignKind,
+}
+
+/// Aligns the retained lines of two documents for side-by-side display.
+///
+/// Lines are matched by a diff of their visible text, so the same output in
+/// different colors counts as equal. Within each stretch of differences,
+/// removed and added lines are paired up as [`AlignKind::Changed`] rows and
+/// the surplus becomes one-sided rows, which keeps both columns scrolling in
+/// step. Very different inputs stop the diff early and pair the remaining
+/// lines in order.
+#[must_use]
+pub fn align_documents<L: Default, R: Default>(
+    left: &AnsiDocument<L>,
+    right: &AnsiDocument<R>,
+) -> Vec<AlignedRow> {
+    profile_scope!("egui_sgr::align_documents");
+    let left: Vec<&str> = left.lines().map(|line| line.text.text.as_str()).collect();
+    let right: Vec<&str> = right.lines().map(|line| line.text.text.as_str()).collect();
+
+    let prefix = left
+        .iter()
+        .zip(&right)
+        .take_while(|(left, right)| left == right)
+        .count();
+    let suffix = left[prefix..]
+        .iter()
+        .rev()
+        .zip(right[prefix..].iter().rev())
+        .take_while(|(left, right)| left == right)
+        .count();
+    let left_middle = &left[prefix..left.len() - suffix];
+    let right_middle = &right[prefix..right.len() - suffix];
+
+    let mut rows: Vec<_> = (0..prefix).map(|index| equal_row(index, index)).collect();
+    match myers(left_middle, right_middle) {
+        Some(ops) => push_ops(&mut rows, &ops, prefix),
+        None => push_changes(
+            &mut rows,
+            (prefix..prefix + left_middle.len()).collect(),
+            (prefix..prefix + right_middle.len()).collect(),
+        ),
+    }
+    rows.extend(
+        (0..suffix)
+            .map(|offset| equal_row(left.len() - suffix + offset, right.len() - suffix + offset)),
+    );
+    rows
+}
+
+/// Highlights the lines that differ between `left` and `right` with
+/// `style`, on [`OverlayLayer::COMPARE`], and returns the number of rows
+/// that differ.
+///
+/// `rows` must come from [`align_documents`] for the same documents.
+/// Previous overlays on that layer are replaced in both documents.
+pub fn highlight_changes<L: Default, R: Default>(
+    left: &mut AnsiDocument<L>,
+    right: &mut AnsiDocument<R>,
+    rows: &[AlignedRow],
+    style: OverlayStyle,
+) -> usize {
+    left.clear_overlays(OverlayLayer::COMPARE);
+    right.clear_overlays(OverlayLayer::COMPARE);
+
+    let mut changed = 0;
+    for row in rows.iter().filter(|row| row.kind != AlignKind::Equal) {
+        changed += 1;
+        if let Some(index) = row.left {
+            mark_line(left, index, style);
+        }
+        if let Some(index) = row.right {
+            mark_line(right, index, style);
+        }
+    }
+    changed
+}
+
+fn mark_line<M: Default>(document: &mut AnsiDocument<M>, index: usize, style: OverlayStyle) {
+    let Some(len) = document.line(index).map(|line| line.text.text.len()) else {
+        return;
+    };
+    let line = document.first_line_number() + index as u64;
+    document.add_overlay(OverlayLayer::COMPARE, line, 0..len, style);
+}
+
+fn equal_row(left: usize, right: usize) -> AlignedRow {
+    AlignedRow {
+        left: Some(left),
+        right: Some(right),
+        kind: AlignKind::Equal,
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Appends rows for `ops`, which start at line `start` on both sides.
+fn push_ops(rows: &mut Vec<AlignedRow>, ops: &[Op], start: usize) {
+    let (mut left, mut right) = (start, start);
+    let mut deleted = Vec::new();
+    let mut inserted = Vec::new();
+    for op in ops {
+        match op {
+            Op::Equal => {
+                push_changes(
+                    rows,
+                    std::mem::take(&mut deleted),
+                    std::mem::take(&mut inserted),
+                );
+                rows.push(equal_row(left, right));
+                left += 1;
+                right += 1;
+            }
+            Op::Delete => {
+                deleted.push(left);
+                left += 1;
+            }
+            Op::Insert => {
+                inserted.push(right);
+                right += 1;
+            }
+        }
+    }
+    push_changes(rows, deleted, inserted);
+}
+
+/// Pairs removed and added lines into changed rows, then appends the rest.
+fn push_changes(rows: &mut Vec<AlignedRow>, deleted: Vec<usize>, inserted: Vec<usize>) {
+    let paired = deleted.len().max(inserted.len());
+    for index in 0..paired {
+        let left = deleted.get(index).copied();
+        let right = inserted.get(index).copied();
+        let kind = match (left, right) {
+            (Some(_), Some(_)) => AlignKind::Changed,
+            (Some(_), None) => AlignKind::LeftOnly,
+            _ => AlignKind::RightOnly,
+        };
+        rows.push(AlignedRow { left, right, kind });
+    }
+}
+
+/// Myers' shortest edit script, or `None` once the cost passes
+/// [`MAX_EDIT_COST`].
+fn myers(left: &[&str], right: &[&str]) -> Option<Vec<Op>> {
+    let (n, m) = (left.len(), right.len());
+    if n == 0 || m == 0 {
+        let mut ops = vec![Op::Delete; n];
+        ops.resize(n + m, Op::Insert);
+        return Some(ops);
+    }
+    // Every line beyond the shorter side costs one edit.
+    if n.abs_diff(m) > MAX_EDIT_COST {
+        return None;
+    }
+
+    let max = (n + m).min(MAX_EDIT_COST);
+    let offset = max + 1;
+    let mut v = vec![0usize; 2 * max + 3];
+    // trace[d] holds the furthest x per diagonal k in -d..=d after step d.
+    let mut trace: Vec<Vec<usize>> = Vec::new();
+
+    for d in 0..=max {
+        let mut done = false;
+        for k in (-(d as isize)..=d as isize).step_by(2) {
+            let index = (offset as isize + k) as usize;
+            let mut x = if k == -(d as isize) || (k != d as isize && v[index - 1] < v[index + 1]) {
+                v[index + 1]
+            } else {
+                v[index - 1] + 1
+            };
+            let mut y = (x as isize - k) as usize;
+            while x < n && y < m && left[x] == right[y] {
+                x += 1;
+                y += 1;
+            }
+            v[index] = x;
+            if x >= n && y >= m {
+                done = true;
+                break;
+            }
+        }
+        trace.push(v[offset - d..=offset + d].to_vec());
+        if done {
+            return Some(backtrack(&trace, n, m));
+        }
+    }
+    None
+}
+
+fn backtrack(trace: &[Vec<usize>], n: usize, m: usize) -> Vec<Op> {
+    let mut ops = Vec::with_capacity(n.max(m));
+    let (mut x, mut y) = (n, m);
+
+    for d in (1..trace.len()).rev() {
+        let previous = &trace[d - 1];
+        let d = d as isize;
+        let furthest = |k: isize| previous[(k + d - 1) as usize];
+        let k = x as isize - y as isize;
+        let previous_k = if k == -d || (k != d && furthest(k - 1) < furthest(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let previous_x = furthest(previous_k);
+        let previous_y = (previous_x as isize - previous_k) as usize;
+
+        while x > previous_x && y > previous_y {
+            ops.push(Op::Equal);
+            x -= 1;
+            y -= 1;
+        }
+        ops.push(if previous_k == k + 1 {
+            Op::Insert
+        } else {
+            Op::Delete
+        });
+        x = previous_x;
+        y = previous_y;
+    }
+    ops.extend(std::iter::repeat_n(Op::Equal, x));
+    ops.reverse();
+    ops
+}
@@ -0,0 +1,69 @@
+use std::collections::BTreeSet;
+
+/// Lines of an [`AnsiDocument`](crate::AnsiDocument) changed since the
+/// damage was last taken.
+///
+/// Lines are identified by absolute line number, like overlays, so a view
+/// can keep one rendered job per line and rebuild only the damaged ones.
+/// Appends damage the open line and everything after it; overlay, fold, and
+/// [`line_mut`](crate::AnsiDocument::line_mut) changes damage single lines;
+/// changes that affect every line, such as a new fold width or
+/// [`clear`](crate::AnsiDocument::clear), damage the whole document.
+/// Evicted lines are not reported; drop cached lines numbered below
+/// [`first_line_number`](crate::AnsiDocument::first_line_number).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DocumentDamage {
+    full: bool,
+    lines: BTreeSet<u64>,
+    /// Every line from this one on is damaged.
+    tail: Option<u64>,
+}
+
+impl DocumentDamage {
+    /// Returns `true` if nothing changed.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        !self.full && self.lines.is_empty() && self.tail.is_none()
+    }
+
+    /// Returns `true` if every line must be rebuilt.
+    #[must_use]
+    pub fn is_full(&self) -> bool {
+        self.full
+    }
+
+    /// Returns `true` if the line with absolute number `line` changed.
+    #[must_use]
+    pub fn contains(&self, line: u64) -> bool {
+        self.full || self.tail.is_some_and(|tail| line >= tail) || self.lines.contains(&line)
+    }
+
+    /// Returns the first damaged line, if the damage is limited to lines.
+    #[must_use]
+    pub fn first_line(&self) -> Option<u64> {
+        let first = self.lines.first().copied();
+        match (first, self.tail) {
+            _ if self.full => None,
+            (Some(line), Some(tail)) => Some(line.min(tail)),
+            (line, tail) => line.or(tail),
+        }
+    }
+
+    pub(crate) fn add_line(&mut self, line: u64) {
+        if !self.contains(line) {
+            self.lines.insert(line);
+        }
+    }
+
+    pub(crate) fn add_tail(&mut self, from: u64) {
+        self.tail = Some(self.tail.map_or(from, |tail| tail.min(from)));
+        self.lines.retain(|&line| line < from);
+    }
+
+    pub(crate) fn add_all(&mut self) {
+        *self = Self {
+            full: true,
+            ..Self::default()
+        };
+    }
+}
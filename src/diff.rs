@@ -0,0 +1,137 @@
+use crate::{AnsiColor, AnsiIntensity, AnsiStyle, AnsiText};
+
+/// Post-pass that styles unified-diff output line by line.
+///
+/// Added and removed lines get a background, hunk headers (`@@`) and file
+/// headers (`diff`, `index`, `---`, `+++`) get their own style. Styles only
+/// fill fields the line left at their defaults, so colors from
+/// `git diff --color` are kept and gain the backgrounds on top.
+///
+/// The highlighter tracks whether it is inside a hunk, which tells a removed
+/// `-- text` line apart from a `--- file` header, so feed it consecutive
+/// lines of one diff.
+#[derive(Debug, Clone)]
+pub struct DiffHighlighter {
+    added: AnsiStyle,
+    removed: AnsiStyle,
+    hunk_header: AnsiStyle,
+    file_header: AnsiStyle,
+    in_hunk: bool,
+}
+
+impl Default for DiffHighlighter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DiffHighlighter {
+    /// Creates a highlighter with dark green and red backgrounds, cyan hunk
+    /// headers, and bold file headers.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            added: AnsiStyle::new().bg(AnsiColor::Rgb(0x1d, 0x3b, 0x26)),
+            removed: AnsiStyle::new().bg(AnsiColor::Rgb(0x4b, 0x1f, 0x24)),
+            hunk_header: AnsiStyle::new().fg(AnsiColor::Indexed(6)),
+            file_header: AnsiStyle::new().bold(),
+            in_hunk: false,
+        }
+    }
+
+    /// Sets the backgrounds of added and removed lines.
+    #[must_use]
+    pub fn with_backgrounds(mut self, added: AnsiColor, removed: AnsiColor) -> Self {
+        self.added = self.added.bg(added);
+        self.removed = self.removed.bg(removed);
+        self
+    }
+
+    /// Sets the style of `@@` hunk headers.
+    #[must_use]
+    pub fn with_hunk_header_style(mut self, style: AnsiStyle) -> Self {
+        self.hunk_header = style;
+        self
+    }
+
+    /// Sets the style of file header lines.
+    #[must_use]
+    pub fn with_file_header_style(mut self, style: AnsiStyle) -> Self {
+        self.file_header = style;
+        self
+    }
+
+    /// Styles one line of diff output, without its newline.
+    #[must_use]
+    pub fn highlight_line(&mut self, line: &AnsiText) -> AnsiText {
+        let text = line.text.as_str();
+        let style = if text.starts_with("@@") {
+            self.in_hunk = true;
+            Some(self.hunk_header)
+        } else if self.in_hunk && text.starts_with('+') {
+            Some(self.added)
+        } else if self.in_hunk && text.starts_with('-') {
+            Some(self.removed)
+        } else if self.in_hunk && (text.is_empty() || text.starts_with([' ', '\\'])) {
+            None
+        } else {
+            self.in_hunk = false;
+            ["diff ", "index ", "--- ", "+++ "]
+                .iter()
+                .any(|prefix| text.starts_with(prefix))
+                .then_some(self.file_header)
+        };
+
+        let Some(style) = style else {
+            return line.clone();
+        };
+        let mut styled = AnsiText::new();
+        for (part, base) in line.iter() {
+            styled.push_str(part, fill_defaults(base, style));
+        }
+        styled
+    }
+
+    /// Parses multi-line ANSI diff output and styles each line.
+    #[must_use]
+    pub fn highlight(&mut self, input: &str) -> AnsiText {
+        profile_scope!("egui_sgr::highlight_diff");
+        let mut parsed = AnsiText::new();
+        parsed.push_ansi(input);
+
+        let mut styled = AnsiText::new();
+        let mut line = AnsiText::new();
+        for (part, style) in parsed.iter() {
+            let mut rest = part;
+            while let Some(newline) = rest.find('\n') {
+                line.push_str(&rest[..newline], style);
+                for (part, style) in self.highlight_line(&line).iter() {
+                    styled.push_str(part, style);
+                }
+                styled.push_str("\n", style);
+                line = AnsiText::new();
+                rest = &rest[newline + 1..];
+            }
+            line.push_str(rest, style);
+        }
+        for (part, style) in self.highlight_line(&line).iter() {
+            styled.push_str(part, style);
+        }
+        styled
+    }
+}
+
+/// Fills the fields of `base` that are still at their defaults from `extra`.
+fn fill_defaults(mut base: AnsiStyle, extra: AnsiStyle) -> AnsiStyle {
+    if base.foreground == AnsiColor::Default {
+        base.foreground = extra.foreground;
+    }
+    if base.background == AnsiColor::Default {
+        base.background = extra.background;
+    }
+    if base.intensity == AnsiIntensity::Normal {
+        base.intensity = extra.intensity;
+    }
+    base.italic |= extra.italic;
+    base
+}
@@ -0,0 +1,925 @@
+use crate::DocumentDamage;
+#[cfg(feature = "egui")]
+use crate::EguiAnsiTheme;
+use crate::columns::graphemes;
+use crate::search::{self, AnsiMatch, SearchPattern};
+use crate::{
+    AnsiBackend, AnsiIntensity, AnsiOverlay, AnsiSpan, AnsiStreamParser, AnsiStyle, AnsiText,
+    FoldedText, IntoAnsiSource, OverlayLayer, OverlayStyle, ParseStats,
+};
+#[cfg(feature = "egui")]
+use egui::text::LayoutJob;
+use std::collections::{HashSet, VecDeque};
+use std::io::{self, Read};
+use std::ops::Range;
+use std::sync::Arc;
+
+const FORM_FEED: u8 = 0x0c;
+
+/// One line of an [`AnsiDocument`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AnsiLine<M = ()> {
+    /// Raw bytes of the line as received, escapes included, without the
+    /// trailing newline.
+    pub raw: Vec<u8>,
+    /// Parsed visible text. Styles opened on earlier lines carry over.
+    pub text: AnsiText,
+    /// Caller-defined metadata, such as a timestamp or source name.
+    pub meta: M,
+    /// Whether a form feed came before this line, so it starts a new page.
+    pub page_break: bool,
+    /// Tags attached by the [segment hook](AnsiDocument::with_segment_hook),
+    /// in text order.
+    pub tags: Vec<SegmentTag>,
+}
+
+impl<M> AnsiLine<M> {
+    /// Returns the tag covering byte `offset` of the visible text, if any.
+    #[must_use]
+    pub fn tag_at(&self, offset: usize) -> Option<&Arc<str>> {
+        self.tags
+            .iter()
+            .find(|tag| tag.range.contains(&offset))
+            .map(|tag| &tag.tag)
+    }
+}
+
+/// Caller-defined tag on part of an [`AnsiLine`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SegmentTag {
+    /// Byte range into the line's visible text, as parsed.
+    pub range: Range<usize>,
+    /// Tag returned by the segment hook, such as a request id.
+    pub tag: Arc<str>,
+}
+
+/// Position of a line in [`AnsiDocument::to_text`].
+struct ShownLine {
+    /// Byte offset where the line's text starts.
+    start: usize,
+    /// Range of the fold marker, if the line is folded. The preview ends one
+    /// space before it.
+    marker: Option<Range<usize>>,
+}
+
+type SegmentHook = Box<dyn FnMut(&AnsiStyle, &str) -> Option<Arc<str>> + Send>;
+
+/// Retained multi-line ANSI document.
+///
+/// Output is appended in arbitrary chunks and split into lines as it
+/// arrives; escape and UTF-8 sequences split across chunks are reassembled,
+/// and styles carry across lines as in a terminal. The last line stays open
+/// until its newline arrives. With [`Self::with_max_lines`], the oldest lines
+/// are evicted, and [`Self::first_line_number`] keeps absolute line numbers
+/// stable for caches keyed by line.
+///
+/// Overlays add styling such as search highlights on top of the parsed
+/// styles. They are kept apart from the text, composited when rendering,
+/// and dropped together with the lines they cover.
+///
+/// A form feed ends the current line like a newline and marks the next line
+/// as the start of a page; see [`Self::page_starts`] and
+/// [`Self::with_page_separator`].
+///
+/// With [`Self::with_fold_columns`], very long lines are rendered as a short
+/// preview until they are expanded.
+pub struct AnsiDocument<M = ()> {
+    parser: Box<dyn AnsiBackend>,
+    lines: VecDeque<AnsiLine<M>>,
+    open: bool,
+    page_pending: bool,
+    page_separator: Option<String>,
+    segment_hook: Option<SegmentHook>,
+    max_lines: Option<usize>,
+    evicted: u64,
+    overlays: Vec<AnsiOverlay>,
+    fold_columns: Option<usize>,
+    /// Absolute numbers of lines expanded past the fold.
+    expanded: HashSet<u64>,
+    version: u64,
+    damage: DocumentDamage,
+}
+
+impl<M: Default> Default for AnsiDocument<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: Default> AnsiDocument<M> {
+    /// Creates an empty document without a retention cap.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            parser: Box::new(AnsiStreamParser::new()),
+            lines: VecDeque::new(),
+            open: false,
+            page_pending: false,
+            page_separator: None,
+            segment_hook: None,
+            max_lines: None,
+            evicted: 0,
+            overlays: Vec::new(),
+            fold_columns: None,
+            expanded: HashSet::new(),
+            version: 0,
+            damage: DocumentDamage::default(),
+        }
+    }
+
+    /// Parses appended output with `backend` instead of the built-in
+    /// [`AnsiStreamParser`].
+    #[must_use]
+    pub fn with_backend(mut self, backend: impl AnsiBackend + 'static) -> Self {
+        self.parser = Box::new(backend);
+        self
+    }
+
+    /// Keeps at most `max_lines` lines, evicting the oldest first.
+    #[must_use]
+    pub fn with_max_lines(mut self, max_lines: usize) -> Self {
+        self.set_max_lines(Some(max_lines));
+        self
+    }
+
+    /// Changes the retention cap, evicting lines if needed.
+    pub fn set_max_lines(&mut self, max_lines: Option<usize>) {
+        self.version += 1;
+        self.max_lines = max_lines.map(|max| max.max(1));
+        self.evict();
+    }
+
+    /// Shows a line of `separator` text, in a faint style, before every
+    /// line that starts a page.
+    ///
+    /// The separator becomes part of [`Self::to_text`] and the layout job,
+    /// but not of the lines, so search and overlays are unaffected.
+    #[must_use]
+    pub fn with_page_separator(mut self, separator: impl Into<String>) -> Self {
+        self.set_page_separator(Some(separator.into()));
+        self
+    }
+
+    /// Changes or removes the page separator.
+    pub fn set_page_separator(&mut self, separator: Option<String>) {
+        self.version += 1;
+        self.damage.add_all();
+        self.page_separator = separator;
+    }
+
+    /// Renders lines wider than `max_cols` display columns as their first
+    /// `max_cols` columns followed by a faint `… +12,345 chars` marker.
+    ///
+    /// Laying out a single multi-megabyte line, such as minified JSON or a
+    /// base64 blob, is slow; folded, it costs no more than a short line. The
+    /// lines themselves are unchanged, so search and copying see the full
+    /// text. Expand a line with [`Self::set_line_expanded`], for example when
+    /// its marker is clicked; see [`Self::fold_marker_at`].
+    ///
+    /// ```rust
+    /// use egui_sgr::AnsiDocument;
+    ///
+    /// let mut document = AnsiDocument::<()>::new().with_fold_columns(4);
+    /// document.append(format!("\x1b[32m{}\x1b[0m\nok\n", "x".repeat(1004)));
+    /// assert_eq!(document.to_text().text, "xxxx … +1,000 chars\nok");
+    ///
+    /// let line = document.fold_marker_at(6).unwrap();
+    /// document.set_line_expanded(line, true);
+    /// assert_eq!(document.to_text().text.len(), 1004 + 1 + 2);
+    /// ```
+    #[must_use]
+    pub fn with_fold_columns(mut self, max_cols: usize) -> Self {
+        self.set_fold_columns(Some(max_cols));
+        self
+    }
+
+    /// Changes or turns off line folding. Expanded lines stay expanded.
+    pub fn set_fold_columns(&mut self, max_cols: Option<usize>) {
+        self.version += 1;
+        self.damage.add_all();
+        self.fold_columns = max_cols;
+    }
+
+    /// Expands or folds again the line with absolute number `line`.
+    pub fn set_line_expanded(&mut self, line: u64, expanded: bool) {
+        self.version += 1;
+        self.damage.add_line(line);
+        if expanded {
+            self.expanded.insert(line);
+        } else {
+            self.expanded.remove(&line);
+        }
+    }
+
+    /// Returns `true` if the line with absolute number `line` was expanded
+    /// with [`Self::set_line_expanded`].
+    #[must_use]
+    pub fn is_line_expanded(&self, line: u64) -> bool {
+        self.expanded.contains(&line)
+    }
+
+    /// Returns the absolute number of the folded line whose marker covers
+    /// `byte_offset` of [`Self::to_text`], such as a clicked position in the
+    /// rendered job.
+    #[must_use]
+    pub fn fold_marker_at(&self, byte_offset: usize) -> Option<u64> {
+        self.shown_lines()
+            .take_while(|line| line.start <= byte_offset)
+            .position(|line| {
+                line.marker
+                    .as_ref()
+                    .is_some_and(|marker| marker.contains(&byte_offset))
+            })
+            .map(|index| self.evicted + index as u64)
+    }
+
+    /// Returns the byte offset in [`Self::to_text`] where each retained line
+    /// starts, after any page separator, for mapping lines to positions in
+    /// the rendered job.
+    #[must_use]
+    pub fn line_offsets(&self) -> Vec<usize> {
+        self.shown_lines().map(|line| line.start).collect()
+    }
+
+    /// Tags parsed text with whatever `hook` returns for it.
+    ///
+    /// The hook sees each span with its style as it is appended, and
+    /// returned tags are stored on the line as [`SegmentTag`]s. Text split
+    /// across chunks reaches the hook in pieces; equal tags on adjacent
+    /// pieces merge into one range.
+    ///
+    /// ```rust
+    /// use egui_sgr::{AnsiColor, AnsiDocument};
+    ///
+    /// let mut document = AnsiDocument::<()>::new().with_segment_hook(|style, text| {
+    ///     (style.foreground == AnsiColor::Indexed(1)).then(|| format!("error: {text}").into())
+    /// });
+    /// document.append_str("build \x1b[31mfailed\x1b[0m\n");
+    /// let line = document.line(0).unwrap();
+    /// assert_eq!(line.tag_at(8).map(|tag| &**tag), Some("error: failed"));
+    /// assert_eq!(line.tag_at(0), None);
+    /// ```
+    #[must_use]
+    pub fn with_segment_hook(
+        mut self,
+        hook: impl FnMut(&AnsiStyle, &str) -> Option<Arc<str>> + Send + 'static,
+    ) -> Self {
+        self.segment_hook = Some(Box::new(hook));
+        self
+    }
+
+    /// Appends a chunk of raw output, given as text or bytes.
+    pub fn append<'a>(&mut self, chunk: impl IntoAnsiSource<'a>) {
+        self.append_bytes(&chunk.into_ansi_bytes());
+    }
+
+    /// Appends a string chunk.
+    pub fn append_str(&mut self, chunk: &str) {
+        self.append_bytes(chunk.as_bytes());
+    }
+
+    /// Reads `reader` to the end, appending output as it arrives, and
+    /// returns the number of bytes read.
+    ///
+    /// Lines read before an error are kept, so the caller can retry or
+    /// report a partial read.
+    pub fn append_reader(&mut self, mut reader: impl Read) -> io::Result<u64> {
+        let mut chunk = [0; 8192];
+        let mut total = 0;
+        loop {
+            match reader.read(&mut chunk) {
+                Ok(0) => return Ok(total),
+                Ok(len) => {
+                    self.append_bytes(&chunk[..len]);
+                    total += len as u64;
+                }
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    /// Ends the current stream, as when a connection drops.
+    ///
+    /// Unfinished escape, OSC, or UTF-8 sequences are discarded and the style
+    /// resets, so the next append starts a fresh stream on a new line. An
+    /// open line with no visible text, such as one holding only a cut-off
+    /// escape, is dropped rather than kept as a blank line.
+    pub fn finish(&mut self) {
+        self.version += 1;
+        self.damage_tail();
+        let spans = self.parser.finish();
+        if !spans.is_empty() {
+            self.open_line();
+            self.push_spans(spans);
+        }
+        if std::mem::take(&mut self.open)
+            && self
+                .lines
+                .back()
+                .is_some_and(|line| line.text.text.is_empty())
+        {
+            let line = self.lines.pop_back().expect("an open line exists");
+            self.page_pending |= line.page_break;
+        }
+        self.evict();
+    }
+
+    fn append_bytes(&mut self, chunk: &[u8]) {
+        self.version += 1;
+        profile_scope!("egui_sgr::document_append");
+        self.damage_tail();
+        let mut rest = chunk;
+        loop {
+            let end = memchr::memchr2(b'\n', FORM_FEED, rest).unwrap_or(rest.len());
+            let segment = &rest[..end];
+            if !segment.is_empty() {
+                let spans = self.parser.push_bytes(segment);
+                self.open_line().raw.extend_from_slice(segment);
+                self.push_spans(spans);
+            }
+
+            let Some(&separator) = rest.get(end) else {
+                break;
+            };
+            // The separator only ends the line; it still goes through the
+            // parser so sequence state stays in step with the bytes. A UTF-8
+            // sequence it cuts short comes back as a replacement character
+            // that belongs to the line being closed.
+            let spans = self.parser.push_bytes(&[separator]);
+            if separator == b'\n' || self.open || !spans.is_empty() {
+                self.open_line();
+                self.push_spans(spans);
+                self.open = false;
+            }
+            if separator == FORM_FEED {
+                self.page_pending = true;
+            }
+            rest = &rest[end + 1..];
+        }
+        self.evict();
+    }
+
+    /// Appends the lines of `other` after the lines of this document.
+    ///
+    /// Both documents were parsed independently, so no style leaks between
+    /// them. An open last line here is closed first. Further appends
+    /// continue `other`'s stream, including its open line and style state.
+    pub fn append_document(&mut self, other: Self) {
+        self.version += 1;
+        let start = self.evicted + self.lines.len() as u64;
+        self.damage.add_tail(start);
+        self.overlays
+            .extend(other.overlays.into_iter().map(|mut overlay| {
+                overlay.line = overlay.line - other.evicted + start;
+                overlay
+            }));
+        self.expanded.extend(
+            other
+                .expanded
+                .into_iter()
+                .map(|line| line - other.evicted + start),
+        );
+        self.lines.extend(other.lines);
+        self.parser = other.parser;
+        self.open = other.open;
+        self.page_pending = other.page_pending;
+        self.evict();
+    }
+
+    /// Inserts the lines of `other` before line `index`.
+    ///
+    /// An `index` at or past the end behaves like [`Self::append_document`].
+    /// Otherwise `other`'s open line is closed, and this document's stream
+    /// state is unchanged, so inserting a header does not disturb output that
+    /// is still arriving.
+    pub fn insert_document(&mut self, index: usize, other: Self) {
+        self.version += 1;
+        if index >= self.lines.len() {
+            self.append_document(other);
+            return;
+        }
+
+        let start = self.evicted + index as u64;
+        let inserted = other.lines.len() as u64;
+        self.damage.add_tail(start);
+        for overlay in &mut self.overlays {
+            if overlay.line >= start {
+                overlay.line += inserted;
+            }
+        }
+        self.overlays
+            .extend(other.overlays.into_iter().map(|mut overlay| {
+                overlay.line = overlay.line - other.evicted + start;
+                overlay
+            }));
+        self.expanded = self
+            .expanded
+            .drain()
+            .map(|line| if line >= start { line + inserted } else { line })
+            .chain(
+                other
+                    .expanded
+                    .into_iter()
+                    .map(|line| line - other.evicted + start),
+            )
+            .collect();
+
+        let tail = self.lines.split_off(index);
+        self.lines.extend(other.lines);
+        self.lines.extend(tail);
+        self.evict();
+    }
+
+    /// Returns the number of retained lines, including an unfinished last
+    /// line.
+    #[must_use]
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Returns `true` if no lines are retained.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Returns the absolute number of the first retained line, which is the
+    /// count of lines evicted so far.
+    #[must_use]
+    pub fn first_line_number(&self) -> u64 {
+        self.evicted
+    }
+
+    /// Returns a retained line by index.
+    #[must_use]
+    pub fn line(&self, index: usize) -> Option<&AnsiLine<M>> {
+        self.lines.get(index)
+    }
+
+    /// Returns a retained line mutably, for updating its metadata.
+    pub fn line_mut(&mut self, index: usize) -> Option<&mut AnsiLine<M>> {
+        self.version += 1;
+        self.damage.add_line(self.evicted + index as u64);
+        self.lines.get_mut(index)
+    }
+
+    /// Iterates over the retained lines, oldest first.
+    pub fn lines(&self) -> impl ExactSizeIterator<Item = &AnsiLine<M>> + DoubleEndedIterator {
+        self.lines.iter()
+    }
+
+    /// Returns a number that changes whenever the document does: on
+    /// appends, eviction, overlay and fold changes, and calls to
+    /// [`Self::line_mut`].
+    ///
+    /// Views sharing one document, for example through a
+    /// [`SharedAnsiDocument`](crate::SharedAnsiDocument), compare it with the
+    /// version they last rendered to skip rebuilding unchanged output.
+    #[must_use]
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Returns `true` while the last line is waiting for its newline.
+    #[must_use]
+    pub fn has_open_line(&self) -> bool {
+        self.open
+    }
+
+    /// Returns the lines changed since the damage was last taken.
+    #[must_use]
+    pub fn damage(&self) -> &DocumentDamage {
+        &self.damage
+    }
+
+    /// Returns and resets the lines changed since the last call.
+    ///
+    /// Unlike [`Self::version`], damage says which lines changed, so a view
+    /// keeping one rendered job per line, such as a full-screen program's
+    /// output redrawn every second, rebuilds only those lines. Each call
+    /// consumes the damage, so only one view per document should take it;
+    /// other views compare versions instead.
+    ///
+    /// ```rust
+    /// use egui_sgr::AnsiDocument;
+    ///
+    /// let mut document = AnsiDocument::<()>::new();
+    /// document.append("one\ntwo\nthr");
+    /// let _ = document.take_damage();
+    ///
+    /// document.append("ee\nfour\n");
+    /// let damage = document.take_damage();
+    /// assert!(!damage.contains(1));
+    /// assert!(damage.contains(2) && damage.contains(3));
+    /// assert!(document.take_damage().is_empty());
+    /// ```
+    pub fn take_damage(&mut self) -> DocumentDamage {
+        std::mem::take(&mut self.damage)
+    }
+
+    /// Returns counters for all input appended so far, including evicted and
+    /// cleared lines.
+    #[must_use]
+    pub fn stats(&self) -> &ParseStats {
+        self.parser.stats()
+    }
+
+    /// Clears [`Self::stats`].
+    pub fn reset_stats(&mut self) {
+        self.parser.reset_stats();
+    }
+
+    /// Removes all lines and resets parser state. Eviction counting resumes
+    /// after the removed lines.
+    pub fn clear(&mut self) {
+        self.version += 1;
+        self.damage.add_all();
+        self.evicted += self.lines.len() as u64;
+        self.lines.clear();
+        self.overlays.clear();
+        self.expanded.clear();
+        self.open = false;
+        self.page_pending = false;
+        self.parser.reset();
+    }
+
+    /// Adds an overlay styling the byte range `range` of the visible text of
+    /// the line with absolute number `line`.
+    ///
+    /// Ranges past the end of the line are clipped when rendering. Overlays
+    /// for lines that are not retained are ignored.
+    pub fn add_overlay(
+        &mut self,
+        layer: OverlayLayer,
+        line: u64,
+        range: Range<usize>,
+        style: OverlayStyle,
+    ) {
+        self.version += 1;
+        let retained = self.evicted..self.evicted + self.lines.len() as u64;
+        if retained.contains(&line) && !range.is_empty() {
+            self.damage.add_line(line);
+            self.overlays.push(AnsiOverlay {
+                layer,
+                line,
+                range,
+                style,
+            });
+        }
+    }
+
+    /// Adds an overlay covering the terminal columns `columns` of the line
+    /// with absolute number `line`.
+    ///
+    /// Columns are counted like [`visible_width`](crate::visible_width); a
+    /// wide character is covered if the range touches any of its columns.
+    pub fn add_overlay_columns(
+        &mut self,
+        layer: OverlayLayer,
+        line: u64,
+        columns: Range<usize>,
+        style: OverlayStyle,
+    ) {
+        let Some(text) = line
+            .checked_sub(self.evicted)
+            .and_then(|index| self.lines.get(usize::try_from(index).ok()?))
+            .map(|line| line.text.text.as_str())
+        else {
+            return;
+        };
+
+        let mut column = 0;
+        let mut start = None;
+        let mut end = text.len();
+        for (offset, _, width) in graphemes(text) {
+            if width > 0 && column >= columns.end {
+                end = offset;
+                break;
+            }
+            if start.is_none() && column + width > columns.start {
+                start = Some(offset);
+            }
+            column += width;
+        }
+
+        if let Some(start) = start {
+            self.add_overlay(layer, line, start..end, style);
+        }
+    }
+
+    /// Removes the overlays of one layer.
+    pub fn clear_overlays(&mut self, layer: OverlayLayer) {
+        self.version += 1;
+        for overlay in self
+            .overlays
+            .iter()
+            .filter(|overlay| overlay.layer == layer)
+        {
+            self.damage.add_line(overlay.line);
+        }
+        self.overlays.retain(|overlay| overlay.layer != layer);
+    }
+
+    /// Removes all overlays.
+    pub fn clear_all_overlays(&mut self) {
+        self.version += 1;
+        for overlay in &self.overlays {
+            self.damage.add_line(overlay.line);
+        }
+        self.overlays.clear();
+    }
+
+    /// Returns the overlays in the order they composite.
+    #[must_use]
+    pub fn overlays(&self) -> &[AnsiOverlay] {
+        &self.overlays
+    }
+
+    /// Returns the matches of `pattern` in the visible text of the retained
+    /// lines, in document order.
+    ///
+    /// Each line is searched without its escape sequences, so a match may
+    /// span several style runs; matches do not cross line breaks.
+    ///
+    /// ```rust
+    /// use egui_sgr::AnsiDocument;
+    ///
+    /// let mut document = AnsiDocument::<()>::new();
+    /// document.append_str("\x1b[1mwarn\x1b[0ming: 全 disk\n");
+    /// let found = document.find("disk");
+    /// assert_eq!(found[0].byte_range, 13..17);
+    /// assert_eq!(found[0].col_range, 12..16);
+    /// ```
+    #[must_use]
+    pub fn find<P: SearchPattern>(&self, pattern: P) -> Vec<AnsiMatch> {
+        profile_scope!("egui_sgr::find");
+        let first = self.evicted;
+        self.lines
+            .iter()
+            .enumerate()
+            .flat_map(|(index, line)| {
+                let text = &line.text.text;
+                search::with_columns(text, pattern.find_ranges(text))
+                    .into_iter()
+                    .map(move |(byte_range, col_range)| AnsiMatch {
+                        line: first + index as u64,
+                        byte_range,
+                        col_range,
+                    })
+            })
+            .collect()
+    }
+
+    /// Returns the indices of the retained lines that start a page, in
+    /// order.
+    ///
+    /// A form feed at the end of a line, as in `"\x0c\n"`, leaves an empty
+    /// line at the top of the new page.
+    #[must_use]
+    pub fn page_starts(&self) -> Vec<usize> {
+        self.lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.page_break)
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Joins the retained lines into one styled text, with the
+    /// [page separator](Self::with_page_separator) before each page and long
+    /// lines [folded](Self::with_fold_columns).
+    #[must_use]
+    pub fn to_text(&self) -> AnsiText {
+        self.render().0
+    }
+
+    /// Builds [`Self::to_text`] and records where each line landed in it.
+    fn render(&self) -> (AnsiText, Vec<ShownLine>) {
+        let faint = AnsiStyle {
+            intensity: AnsiIntensity::Faint,
+            ..AnsiStyle::default()
+        };
+        let mut text = AnsiText::new();
+        let mut shown = Vec::with_capacity(self.lines.len());
+        for (index, line) in self.lines.iter().enumerate() {
+            if index > 0 {
+                let style = text.runs.last().map(|run| run.style).unwrap_or_default();
+                text.push_str("\n", style);
+            }
+            if let Some(separator) = self.separator_before(line) {
+                text.push_str(separator, faint);
+                text.push_str("\n", AnsiStyle::default());
+            }
+            let start = text.text.len();
+            let Some(folded) = self.folded(index, line) else {
+                for (part, style) in line.text.iter() {
+                    text.push_str(part, style);
+                }
+                shown.push(ShownLine {
+                    start,
+                    marker: None,
+                });
+                continue;
+            };
+            for (part, style) in folded.preview.iter() {
+                text.push_str(part, style);
+            }
+            text.push_str(" ", AnsiStyle::default());
+            let marker_start = text.text.len();
+            text.push_str(&folded.marker(), faint);
+            shown.push(ShownLine {
+                start,
+                marker: Some(marker_start..text.text.len()),
+            });
+        }
+        (text, shown)
+    }
+
+    /// Computes the [`ShownLine`]s of [`Self::render`] from line lengths,
+    /// without building the text.
+    fn shown_lines(&self) -> impl Iterator<Item = ShownLine> + '_ {
+        let mut offset = 0;
+        self.lines.iter().enumerate().map(move |(index, line)| {
+            if index > 0 {
+                offset += 1;
+            }
+            if let Some(separator) = self.separator_before(line) {
+                offset += separator.len() + 1;
+            }
+            let start = offset;
+            let Some(folded) = self.folded(index, line) else {
+                offset += line.text.text.len();
+                return ShownLine {
+                    start,
+                    marker: None,
+                };
+            };
+            let marker_start = start + folded.preview.text.len() + 1;
+            offset = marker_start + folded.marker().len();
+            ShownLine {
+                start,
+                marker: Some(marker_start..offset),
+            }
+        })
+    }
+
+    /// Folds the retained line at `index` if folding is on and the line is
+    /// not expanded.
+    fn folded(&self, index: usize, line: &AnsiLine<M>) -> Option<FoldedText> {
+        self.fold_columns
+            .filter(|_| !self.expanded.contains(&(self.evicted + index as u64)))
+            .and_then(|max_cols| line.text.fold(max_cols))
+    }
+
+    /// Converts the retained lines to an egui layout job, with overlays
+    /// composited over the parsed styles.
+    #[cfg(feature = "egui")]
+    #[must_use]
+    pub fn to_layout_job(&self, theme: &EguiAnsiTheme) -> LayoutJob {
+        let (text, shown) = self.render();
+        let mut job = text.to_layout_job(theme);
+        if self.overlays.is_empty() {
+            return job;
+        }
+
+        for overlay in &self.overlays {
+            let Some(index) = overlay
+                .line
+                .checked_sub(self.evicted)
+                .and_then(|index| usize::try_from(index).ok())
+                .filter(|&index| index < self.lines.len())
+            else {
+                continue;
+            };
+            // Overlays on the folded part of a line are clipped to the
+            // preview.
+            let text = self.lines[index].text.text.as_str();
+            let text = match &shown[index].marker {
+                Some(marker) => &text[..marker.start - 1 - shown[index].start],
+                None => text,
+            };
+            let end = overlay.range.end.min(text.len());
+            let start = overlay.range.start.min(end);
+            if text.is_char_boundary(start) && text.is_char_boundary(end) {
+                let base = shown[index].start;
+                crate::egui_render::apply_overlay(
+                    &mut job,
+                    base + start..base + end,
+                    &overlay.style,
+                    theme,
+                );
+            }
+        }
+        job
+    }
+
+    fn separator_before(&self, line: &AnsiLine<M>) -> Option<&str> {
+        self.page_separator.as_deref().filter(|_| line.page_break)
+    }
+
+    /// Appends `spans` to the open line, without line breaks, and tags them.
+    fn push_spans(&mut self, spans: Vec<AnsiSpan>) {
+        let line = self.lines.back_mut().expect("an open line exists");
+        for span in spans {
+            let text = span.text.trim_end_matches('\n');
+            let start = line.text.text.len();
+            line.text.push_str(text, span.style);
+            let Some(hook) = self.segment_hook.as_mut().filter(|_| !text.is_empty()) else {
+                continue;
+            };
+            let Some(tag) = hook(&span.style, text) else {
+                continue;
+            };
+            let range = start..line.text.text.len();
+            match line.tags.last_mut() {
+                Some(last) if last.range.end == start && last.tag == tag => {
+                    last.range.end = range.end;
+                }
+                _ => line.tags.push(SegmentTag { range, tag }),
+            }
+        }
+    }
+
+    /// Damages the open line, or the next line if none is open, and every
+    /// line after it.
+    fn damage_tail(&mut self) {
+        let next = self.evicted + self.lines.len() as u64;
+        self.damage.add_tail(next - u64::from(self.open));
+    }
+
+    fn open_line(&mut self) -> &mut AnsiLine<M> {
+        if !self.open {
+            self.lines.push_back(AnsiLine {
+                page_break: std::mem::take(&mut self.page_pending),
+                ..AnsiLine::default()
+            });
+            self.open = true;
+        }
+        self.lines.back_mut().expect("an open line exists")
+    }
+
+    fn evict(&mut self) {
+        let Some(max_lines) = self.max_lines else {
+            return;
+        };
+        while self.lines.len() > max_lines {
+            self.lines.pop_front();
+            self.evicted += 1;
+        }
+        let evicted = self.evicted;
+        self.overlays.retain(|overlay| overlay.line >= evicted);
+        self.expanded.retain(|&line| line >= evicted);
+    }
+}
+
+/// Appends each item as one line of raw output, followed by a newline.
+///
+/// ```rust
+/// use egui_sgr::AnsiDocument;
+///
+/// let log = "ok\n\x1b[31merror: disk\x1b[0m\nok\n";
+/// let errors: AnsiDocument = log.lines().filter(|line| line.contains("error")).collect();
+/// assert_eq!(errors.line_count(), 1);
+/// ```
+impl<'a, M: Default, S: IntoAnsiSource<'a>> Extend<S> for AnsiDocument<M> {
+    fn extend<I: IntoIterator<Item = S>>(&mut self, lines: I) {
+        for line in lines {
+            self.append(line);
+            self.append_bytes(b"\n");
+        }
+    }
+}
+
+impl<'a, M: Default, S: IntoAnsiSource<'a>> FromIterator<S> for AnsiDocument<M> {
+    fn from_iter<I: IntoIterator<Item = S>>(lines: I) -> Self {
+        let mut document = Self::new();
+        document.extend(lines);
+        document
+    }
+}
+
+/// Adds already parsed lines, such as lines taken from another document.
+///
+/// An open last line is closed before the first added line; the stream
+/// state is unchanged.
+impl<M: Default> Extend<AnsiLine<M>> for AnsiDocument<M> {
+    fn extend<I: IntoIterator<Item = AnsiLine<M>>>(&mut self, lines: I) {
+        let mut lines = lines.into_iter().peekable();
+        if lines.peek().is_some() {
+            self.version += 1;
+            self.open = false;
+            self.damage_tail();
+        }
+        self.lines.extend(lines);
+        self.evict();
+    }
+}
+
+impl<M: Default> FromIterator<AnsiLine<M>> for AnsiDocument<M> {
+    fn from_iter<I: IntoIterator<Item = AnsiLine<M>>>(lines: I) -> Self {
+        let mut document = Self::new();
+        document.extend(lines);
+        document
+    }
+}
@@ -1,36 +1,104 @@
-use crate::{AnsiColor, AnsiIntensity, AnsiSpan, AnsiStyle, EguiAnsiTheme, UnderlineStyle, sgr};
+use crate::parser::PrintRuns;
+use crate::resolve::resolve_style;
+use crate::{
+    AnsiColor, AnsiSpan, AnsiStyle, AnsiText, EguiAnsiTheme, InternedSpan, IntoAnsiSource,
+    OverlayStyle, parser, sgr,
+};
 use egui::text::{LayoutJob, LayoutSection};
-use egui::{Color32, Stroke, TextFormat};
+use egui::{Stroke, TextFormat};
+use std::ops::Range;
 use vte::{Params, Perform};
 
 /// Converts ANSI spans to an egui layout job.
 #[must_use]
 pub fn spans_to_layout_job(spans: &[AnsiSpan], theme: &EguiAnsiTheme) -> LayoutJob {
+    styled_runs_to_layout_job(
+        spans.iter().map(|span| (span.text.as_str(), span.style)),
+        theme,
+    )
+}
+
+/// Lazily maps ANSI spans to their text and egui text format.
+///
+/// Useful for callers that feed sections into their own [`LayoutJob`] or
+/// widgets without building an intermediate collection.
+pub fn spans_to_text_formats<'a>(
+    spans: &'a [AnsiSpan],
+    theme: &'a EguiAnsiTheme,
+) -> impl ExactSizeIterator<Item = (&'a str, TextFormat)> + 'a {
+    spans.iter().map(|span| {
+        (
+            span.text.as_str(),
+            text_format_for_style(&span.style, theme),
+        )
+    })
+}
+
+/// Converts interned spans to an egui layout job.
+#[must_use]
+pub fn interned_spans_to_layout_job(spans: &[InternedSpan], theme: &EguiAnsiTheme) -> LayoutJob {
+    styled_runs_to_layout_job(spans.iter().map(|span| (&*span.text, span.style)), theme)
+}
+
+impl EguiAnsiTheme {
+    /// Returns the egui text format used to render `style` with this theme.
+    #[must_use]
+    pub fn text_format(&self, style: &AnsiStyle) -> TextFormat {
+        text_format_for_style(style, self)
+    }
+}
+
+impl AnsiText {
+    /// Converts the styled text to an egui layout job.
+    #[must_use]
+    pub fn to_layout_job(&self, theme: &EguiAnsiTheme) -> LayoutJob {
+        styled_runs_to_layout_job(self.iter(), theme)
+    }
+}
+
+fn styled_runs_to_layout_job<'a>(
+    runs: impl ExactSizeIterator<Item = (&'a str, AnsiStyle)> + Clone,
+    theme: &EguiAnsiTheme,
+) -> LayoutJob {
+    profile_scope!("egui_sgr::convert");
     let mut job = LayoutJob::default();
     job.text
-        .reserve(spans.iter().map(|span| span.text.len()).sum());
-    job.sections.reserve(spans.len());
+        .reserve(runs.clone().map(|(text, _)| text.len()).sum());
+    job.sections.reserve(runs.len());
     let mut last_style = None;
 
-    for span in spans {
-        append_styled_text(&mut job, &span.text, span.style, theme, &mut last_style);
+    for (text, style) in runs {
+        append_styled_text(&mut job, text, style, theme, &mut last_style);
     }
 
     job
 }
 
-/// Converts a UTF-8 string with ANSI escapes directly to an egui layout job.
+/// Converts text or bytes with ANSI escapes directly to an egui layout job.
 #[must_use]
-pub fn ansi_to_layout_job(input: &str, theme: &EguiAnsiTheme) -> LayoutJob {
-    ansi_bytes_to_layout_job(input.as_bytes(), theme)
+pub fn ansi_to_layout_job<'a>(input: impl IntoAnsiSource<'a>, theme: &EguiAnsiTheme) -> LayoutJob {
+    let input = input.into_ansi_str();
+    let (bytes, plain) = parser::source_parts(&input);
+    layout_job_from(bytes, plain, theme)
 }
 
 /// Converts bytes with ANSI escapes directly to an egui layout job.
 #[must_use]
 pub fn ansi_bytes_to_layout_job(input: &[u8], theme: &EguiAnsiTheme) -> LayoutJob {
-    let mut parser = vte::Parser::new();
+    layout_job_from(input, parser::plain_text(input), theme)
+}
+
+fn layout_job_from(input: &[u8], plain: Option<&str>, theme: &EguiAnsiTheme) -> LayoutJob {
+    profile_scope!("egui_sgr::layout_job");
+    if let Some(text) = plain {
+        let mut job = LayoutJob::default();
+        let mut last_style = None;
+        append_styled_text(&mut job, text, AnsiStyle::default(), theme, &mut last_style);
+        return job;
+    }
+
     let mut performer = LayoutJobPerformer::new(theme, input.len());
-    parser.advance(&mut performer, input);
+    parser::Parser::new().advance(&mut performer, input);
     performer.finish()
 }
 
@@ -61,14 +129,14 @@ impl<'a> LayoutJobPerformer<'a> {
             return;
         }
 
-        let text = std::mem::take(&mut self.text);
         append_styled_text(
             &mut self.job,
-            &text,
+            &self.text,
             self.current_style,
             self.theme,
             &mut self.last_style,
         );
+        self.text.clear();
     }
 
     fn finish(mut self) -> LayoutJob {
@@ -77,6 +145,12 @@ impl<'a> LayoutJobPerformer<'a> {
     }
 }
 
+impl PrintRuns for LayoutJobPerformer<'_> {
+    fn print_str(&mut self, text: &str) {
+        self.text.push_str(text);
+    }
+}
+
 impl Perform for LayoutJobPerformer<'_> {
     fn print(&mut self, c: char) {
         self.text.push(c);
@@ -130,97 +204,76 @@ fn append_styled_text(
     *last_style = Some(style);
 }
 
-fn text_format_for_style(style: &AnsiStyle, theme: &EguiAnsiTheme) -> TextFormat {
-    let colors = effective_colors(style, theme);
-    let mut format = theme.default_format.clone();
-
-    format.color = colors.foreground;
-    format.background = colors.background.unwrap_or(theme.default_format.background);
-    format.italics = style.italic;
-    format.underline = if style.underline == UnderlineStyle::None {
-        Stroke::NONE
-    } else {
-        let underline_color = style
-            .underline_color
-            .map(|color| resolve_color(color, theme))
-            .unwrap_or(colors.foreground);
-        Stroke::new(theme.underline_width, underline_color)
-    };
-    format.strikethrough = if style.strikethrough {
-        Stroke::new(theme.strikethrough_width, colors.foreground)
-    } else {
-        Stroke::NONE
-    };
-
-    format
-}
-
-#[derive(Debug, Clone, Copy)]
-struct EffectiveColors {
-    foreground: Color32,
-    background: Option<Color32>,
-}
-
-fn effective_colors(style: &AnsiStyle, theme: &EguiAnsiTheme) -> EffectiveColors {
-    let mut foreground = foreground_color(style, theme);
-    let mut background = background_color(style, theme);
-
-    if style.reverse {
-        let original_foreground = foreground;
-        foreground = background.unwrap_or(theme.default_background);
-        background = Some(original_foreground);
-    }
-
-    if style.hidden {
-        foreground = Color32::TRANSPARENT;
-    } else if style.intensity == AnsiIntensity::Faint {
-        foreground = with_scaled_alpha(foreground, theme.faint_opacity);
-    }
-
-    EffectiveColors {
-        foreground,
-        background,
+/// Composites an overlay over the sections covering `range` of `job.text`,
+/// splitting sections at the range boundaries.
+pub(crate) fn apply_overlay(
+    job: &mut LayoutJob,
+    range: Range<usize>,
+    style: &OverlayStyle,
+    theme: &EguiAnsiTheme,
+) {
+    if range.is_empty() {
+        return;
     }
-}
+    split_section_at(job, range.start);
+    split_section_at(job, range.end);
 
-fn foreground_color(style: &AnsiStyle, theme: &EguiAnsiTheme) -> Color32 {
-    match style.foreground {
-        AnsiColor::Indexed(index)
-            if theme.bold_is_bright && style.intensity == AnsiIntensity::Bold && index < 8 =>
-        {
-            theme.palette[(index + 8) as usize]
+    for section in &mut job.sections {
+        if section.byte_range.start < range.start || section.byte_range.end > range.end {
+            continue;
+        }
+        if let Some(color) = style.foreground {
+            section.format.color = color;
+        }
+        if let Some(color) = style.background {
+            section.format.background = color;
+        }
+        if let Some(color) = style.underline {
+            section.format.underline = Stroke::new(theme.underline_width, color);
         }
-        color => resolve_color_or_default(color, theme.default_foreground, theme),
     }
 }
 
-fn background_color(style: &AnsiStyle, theme: &EguiAnsiTheme) -> Option<Color32> {
-    match style.background {
-        AnsiColor::Default => None,
-        color => Some(resolve_color(color, theme)),
-    }
-}
+fn split_section_at(job: &mut LayoutJob, offset: usize) {
+    let Some(index) = job
+        .sections
+        .iter()
+        .position(|section| section.byte_range.start < offset && offset < section.byte_range.end)
+    else {
+        return;
+    };
 
-fn resolve_color_or_default(
-    color: AnsiColor,
-    default_color: Color32,
-    theme: &EguiAnsiTheme,
-) -> Color32 {
-    match color {
-        AnsiColor::Default => default_color,
-        color => resolve_color(color, theme),
-    }
+    let mut tail = job.sections[index].clone();
+    tail.byte_range.start = offset;
+    job.sections[index].byte_range.end = offset;
+    job.sections.insert(index + 1, tail);
 }
 
-fn resolve_color(color: AnsiColor, theme: &EguiAnsiTheme) -> Color32 {
-    match color {
-        AnsiColor::Default => theme.default_foreground,
-        AnsiColor::Indexed(index) => theme.palette[index as usize],
-        AnsiColor::Rgb(r, g, b) => Color32::from_rgb(r, g, b),
+fn text_format_for_style(style: &AnsiStyle, theme: &EguiAnsiTheme) -> TextFormat {
+    let resolved = resolve_style(style, theme);
+    let mut format = theme.default_format.clone();
+
+    format.color = resolved.foreground;
+    if resolved.bold
+        && style.foreground == AnsiColor::Default
+        && !style.reverse
+        && !style.hidden
+        && let Some(color) = theme.bold_foreground
+    {
+        format.color = color;
     }
-}
+    format.background = resolved
+        .background
+        .unwrap_or(theme.default_format.background);
+    format.italics = resolved.italic;
+    format.underline = resolved.underline.map_or(Stroke::NONE, |color| {
+        Stroke::new(theme.underline_width, color)
+    });
+    format.strikethrough = if resolved.strikethrough {
+        Stroke::new(theme.strikethrough_width, resolved.foreground)
+    } else {
+        Stroke::NONE
+    };
 
-fn with_scaled_alpha(color: Color32, opacity: f32) -> Color32 {
-    let alpha = ((color.a() as f32) * opacity.clamp(0.0, 1.0)).round() as u8;
-    Color32::from_rgba_unmultiplied(color.r(), color.g(), color.b(), alpha)
+    format
 }
@@ -0,0 +1,159 @@
+use crate::{AnsiColor, AnsiIntensity, AnsiSpan, AnsiStyle, AnsiText, UnderlineStyle};
+use alloc::string::String;
+use core::fmt::Write;
+
+/// Re-emits ANSI text that reproduces the styles of `spans`.
+///
+/// Parsing the result with [`ansi_to_spans`](crate::ansi_to_spans) yields the
+/// same spans. A final reset is appended when the last span is styled.
+#[must_use]
+pub fn spans_to_ansi(spans: &[AnsiSpan]) -> String {
+    emit_runs(spans.iter().map(|span| (span.text.as_str(), span.style)))
+}
+
+impl AnsiText {
+    /// Re-emits ANSI text that reproduces these style runs.
+    #[must_use]
+    pub fn to_ansi(&self) -> String {
+        emit_runs(self.iter())
+    }
+}
+
+impl AnsiStyle {
+    /// Returns the shortest SGR sequence that switches a terminal from `from`
+    /// to `to`.
+    ///
+    /// Only the attributes that differ are changed, unless starting over from
+    /// a reset is shorter. Equal styles produce an empty string.
+    ///
+    /// ```rust
+    /// use egui_sgr::{AnsiColor, AnsiStyle};
+    ///
+    /// let red = AnsiStyle::new().fg(AnsiColor::Indexed(1));
+    /// assert_eq!(AnsiStyle::sgr_transition(&red, &red.bold()), "\x1b[1m");
+    /// assert_eq!(AnsiStyle::sgr_transition(&red.bold(), &AnsiStyle::new()), "\x1b[0m");
+    /// ```
+    #[must_use]
+    pub fn sgr_transition(from: &Self, to: &Self) -> String {
+        let mut output = String::new();
+        write_transition(&mut output, from, to);
+        output
+    }
+}
+
+pub(crate) fn emit_runs<'a>(runs: impl Iterator<Item = (&'a str, AnsiStyle)>) -> String {
+    let mut output = String::new();
+    let mut current = AnsiStyle::default();
+
+    for (text, style) in runs {
+        if text.is_empty() {
+            continue;
+        }
+        write_transition(&mut output, &current, &style);
+        current = style;
+        output.push_str(text);
+    }
+
+    if current != AnsiStyle::default() {
+        output.push_str("\x1b[0m");
+    }
+
+    output
+}
+
+fn write_transition(output: &mut String, from: &AnsiStyle, to: &AnsiStyle) {
+    if from == to {
+        return;
+    }
+
+    let mut changes = String::new();
+    write_changes(&mut changes, from, to);
+    let mut reset = String::from(";0");
+    write_changes(&mut reset, &AnsiStyle::default(), to);
+
+    let params = if changes.len() <= reset.len() {
+        changes
+    } else {
+        reset
+    };
+    output.push_str("\x1b[");
+    output.push_str(&params[1..]);
+    output.push('m');
+}
+
+/// Writes `;`-prefixed parameters that change each attribute of `from` that
+/// differs in `to`.
+fn write_changes(output: &mut String, from: &AnsiStyle, to: &AnsiStyle) {
+    if from.intensity != to.intensity {
+        if from.intensity != AnsiIntensity::Normal {
+            output.push_str(";22");
+        }
+        match to.intensity {
+            AnsiIntensity::Normal => {}
+            AnsiIntensity::Bold => output.push_str(";1"),
+            AnsiIntensity::Faint => output.push_str(";2"),
+        }
+    }
+    if from.italic != to.italic {
+        output.push_str(if to.italic { ";3" } else { ";23" });
+    }
+    if from.underline != to.underline {
+        output.push_str(match to.underline {
+            UnderlineStyle::None => ";24",
+            UnderlineStyle::Single => ";4",
+            UnderlineStyle::Double => ";4:2",
+            UnderlineStyle::Curly => ";4:3",
+            UnderlineStyle::Dotted => ";4:4",
+            UnderlineStyle::Dashed => ";4:5",
+        });
+    }
+    if from.reverse != to.reverse {
+        output.push_str(if to.reverse { ";7" } else { ";27" });
+    }
+    if from.hidden != to.hidden {
+        output.push_str(if to.hidden { ";8" } else { ";28" });
+    }
+    if from.strikethrough != to.strikethrough {
+        output.push_str(if to.strikethrough { ";9" } else { ";29" });
+    }
+
+    if from.foreground != to.foreground {
+        write_color(output, to.foreground, 30, 90, 38);
+    }
+    if from.background != to.background {
+        write_color(output, to.background, 40, 100, 48);
+    }
+    if from.underline_color != to.underline_color {
+        match to.underline_color {
+            Some(color) => write_extended_color(output, color, 58),
+            None => output.push_str(";59"),
+        }
+    }
+}
+
+fn write_color(output: &mut String, color: AnsiColor, base: u16, bright_base: u16, extended: u16) {
+    match color {
+        AnsiColor::Default => {
+            let _ = write!(output, ";{}", base + 9);
+        }
+        AnsiColor::Indexed(index @ 0..=7) => {
+            let _ = write!(output, ";{}", base + u16::from(index));
+        }
+        AnsiColor::Indexed(index @ 8..=15) => {
+            let _ = write!(output, ";{}", bright_base + u16::from(index - 8));
+        }
+        color => write_extended_color(output, color, extended),
+    }
+}
+
+fn write_extended_color(output: &mut String, color: AnsiColor, target: u16) {
+    match color {
+        AnsiColor::Default => {}
+        AnsiColor::Indexed(index) => {
+            let _ = write!(output, ";{target};5;{index}");
+        }
+        AnsiColor::Rgb(r, g, b) => {
+            let _ = write!(output, ";{target};2;{r};{g};{b}");
+        }
+    }
+}
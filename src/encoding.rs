@@ -0,0 +1,84 @@
+use crate::ansi_art::CP437_HIGH;
+use std::borrow::Cow;
+
+/// Text encoding of loaded bytes.
+///
+/// All three encodings agree on ASCII, so escape sequences decode the same
+/// way in each; they differ only in how bytes `0x80..=0xFF` become text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TextEncoding {
+    /// UTF-8; invalid sequences become U+FFFD.
+    #[default]
+    Utf8,
+    /// ISO 8859-1, where every byte is the code point of the same value.
+    Latin1,
+    /// IBM code page 437, the DOS character set of ANSI art and old logs.
+    Cp437,
+}
+
+impl TextEncoding {
+    /// Guesses the encoding of `bytes`.
+    ///
+    /// Valid UTF-8 is always taken as UTF-8. Otherwise the high bytes decide:
+    /// CP437 art is dominated by the block and line-drawing range
+    /// `0xB0..=0xDF` and uses `0x80..=0x9F` for accented letters, while
+    /// Latin-1 text mostly has lowercase accented letters in `0xE0..=0xFF`
+    /// and no C1 controls. Pass an explicit encoding instead when the user
+    /// knows better.
+    #[must_use]
+    pub fn detect(bytes: &[u8]) -> Self {
+        if std::str::from_utf8(bytes).is_ok() {
+            return Self::Utf8;
+        }
+
+        let (mut cp437, mut latin1) = (0usize, 0usize);
+        for &byte in bytes {
+            match byte {
+                0x80..=0x9f | 0xb0..=0xdf => cp437 += 1,
+                0xe0..=0xff => latin1 += 1,
+                _ => {}
+            }
+        }
+        if cp437 > latin1 {
+            Self::Cp437
+        } else {
+            Self::Latin1
+        }
+    }
+
+    /// Decodes `bytes`, borrowing them when they are already valid UTF-8 in
+    /// this encoding.
+    ///
+    /// Control bytes stay control characters in every encoding, so tabs and
+    /// escape sequences keep working; use
+    /// [`cp437_to_string`](crate::cp437_to_string) for the glyph mapping of
+    /// ANSI art. The single-byte encodings can be decoded chunk by chunk
+    /// before feeding a streaming parser.
+    #[must_use]
+    pub fn decode(self, bytes: &[u8]) -> Cow<'_, str> {
+        match self {
+            Self::Utf8 => String::from_utf8_lossy(bytes),
+            _ if bytes.is_ascii() => Cow::Borrowed(std::str::from_utf8(bytes).unwrap_or_default()),
+            Self::Latin1 => Cow::Owned(bytes.iter().map(|&byte| char::from(byte)).collect()),
+            Self::Cp437 => Cow::Owned(
+                bytes
+                    .iter()
+                    .map(|&byte| match byte {
+                        0x80..=0xff => CP437_HIGH[usize::from(byte - 0x80)],
+                        _ => char::from(byte),
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Returns a short display name, such as `"UTF-8"`.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Utf8 => "UTF-8",
+            Self::Latin1 => "Latin-1",
+            Self::Cp437 => "CP437",
+        }
+    }
+}
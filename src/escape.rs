@@ -0,0 +1,89 @@
+use std::fmt::Write as _;
+
+/// Makes escape sequences and other control characters visible.
+///
+/// `ESC` becomes `\x1b`, newline, carriage return, and tab become `\n`, `\r`,
+/// and `\t`, and the remaining C0 and C1 controls and `DEL` become `\xNN`.
+/// Backslashes are doubled, so [`unescape_from_display`] restores the exact
+/// input. This is the form used for editable inputs and debugging output.
+#[must_use]
+pub fn escape_for_display(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(output, "\\x{:02x}", u32::from(c));
+            }
+            c => output.push(c),
+        }
+    }
+    output
+}
+
+/// Reverses [`escape_for_display`], also accepting common hand-typed forms.
+///
+/// Besides `\xNN` (either case), `\n`, `\r`, `\t`, and `\\`, this accepts `\e`
+/// and three-digit octal such as `\033` for `ESC`. Any other backslash is
+/// kept as typed.
+#[must_use]
+pub fn unescape_from_display(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(index) = rest.find('\\') {
+        output.push_str(&rest[..index]);
+        rest = &rest[index..];
+        let (c, len) = match rest.as_bytes().get(1) {
+            Some(b'\\') => (Some('\\'), 2),
+            Some(b'n') => (Some('\n'), 2),
+            Some(b'r') => (Some('\r'), 2),
+            Some(b't') => (Some('\t'), 2),
+            Some(b'e') => (Some('\x1b'), 2),
+            Some(b'x' | b'X') => (parse_code(rest.get(2..4), 16), 4),
+            Some(b'0'..=b'3') => (parse_code(rest.get(1..4), 8), 4),
+            _ => (None, 1),
+        };
+        match c {
+            Some(c) => {
+                output.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                output.push('\\');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Replaces C0 controls and `DEL` with their Unicode control pictures, such
+/// as `␛` for `ESC` and `␊` for newline.
+///
+/// Unlike [`escape_for_display`] this keeps one glyph per character, which
+/// suits compact inspectors and tooltips, but it cannot be reversed.
+#[must_use]
+pub fn to_control_pictures(input: &str) -> String {
+    input
+        .chars()
+        .map(|c| match u32::from(c) {
+            code @ 0..=0x1f => char::from_u32(0x2400 + code).unwrap_or(c),
+            0x7f => '\u{2421}',
+            _ => c,
+        })
+        .collect()
+}
+
+fn parse_code(digits: Option<&str>, radix: u32) -> Option<char> {
+    let digits = digits?;
+    if !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    u8::from_str_radix(digits, radix).ok().map(char::from)
+}
@@ -0,0 +1,291 @@
+use crate::{AnsiArt, AnsiDocument, SauceRecord, TextEncoding};
+#[cfg(feature = "asciicast")]
+use crate::{Asciicast, AsciicastError};
+use std::fmt;
+use std::path::Path;
+#[cfg(feature = "egui")]
+use std::{borrow::Cow, path::PathBuf, sync::Arc};
+
+/// Format of an ANSI file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnsiFileKind {
+    /// Terminal output such as a build log, loaded into an [`AnsiDocument`].
+    Log,
+    /// CP437 ANSI art, loaded as [`AnsiArt`].
+    Art,
+    /// asciicast v2 recording.
+    Cast,
+}
+
+impl AnsiFileKind {
+    /// Detects the format of a file from its name and, when available, its
+    /// contents.
+    ///
+    /// `.ans` and files with a SAUCE record are art, and `.cast` files and
+    /// files whose first line is a JSON object with a `"version"` key are
+    /// recordings. Everything else is treated as a log.
+    #[must_use]
+    pub fn detect(name: &str, bytes: Option<&[u8]>) -> Self {
+        let extension = Path::new(name)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("ans") => return Self::Art,
+            Some("cast") => return Self::Cast,
+            _ => {}
+        }
+
+        let Some(bytes) = bytes else {
+            return Self::Log;
+        };
+        if SauceRecord::parse(bytes).is_some() {
+            return Self::Art;
+        }
+        let first_line = bytes
+            .split(|&byte| byte == b'\n')
+            .find(|line| !line.trim_ascii().is_empty())
+            .unwrap_or_default()
+            .trim_ascii();
+        if first_line.starts_with(b"{")
+            && first_line.ends_with(b"}")
+            && memchr::memmem::find(first_line, b"\"version\"").is_some()
+        {
+            Self::Cast
+        } else {
+            Self::Log
+        }
+    }
+
+    /// Returns the encoding files of this kind conventionally use: CP437 for
+    /// art and UTF-8 otherwise.
+    #[must_use]
+    pub fn default_encoding(self) -> TextEncoding {
+        match self {
+            Self::Art => TextEncoding::Cp437,
+            Self::Log | Self::Cast => TextEncoding::Utf8,
+        }
+    }
+}
+
+/// File loaded through the pipeline matching its [`AnsiFileKind`].
+pub enum AnsiFile {
+    /// A log, split into lines.
+    Log(Box<AnsiDocument>),
+    /// Decoded ANSI art.
+    Art(Box<AnsiArt>),
+    /// A parsed recording.
+    #[cfg(feature = "asciicast")]
+    Cast(Box<Asciicast>),
+}
+
+impl AnsiFile {
+    /// Loads `bytes` as the format detected from `name` and the contents.
+    pub fn load(name: &str, bytes: &[u8]) -> Result<Self, AnsiFileError> {
+        Self::load_as(AnsiFileKind::detect(name, Some(bytes)), bytes)
+    }
+
+    /// Loads `bytes` as `kind`, in the kind's
+    /// [default encoding](AnsiFileKind::default_encoding).
+    ///
+    /// Recordings need the `asciicast` feature; without it they are
+    /// rejected with [`AnsiFileError::Unsupported`].
+    pub fn load_as(kind: AnsiFileKind, bytes: &[u8]) -> Result<Self, AnsiFileError> {
+        Self::load_with_encoding(kind, bytes, kind.default_encoding())
+    }
+
+    /// Loads `bytes` as `kind`, decoding text as `encoding`.
+    ///
+    /// Pass [`TextEncoding::detect`] for automatic detection, or the user's
+    /// choice to override it.
+    pub fn load_with_encoding(
+        kind: AnsiFileKind,
+        bytes: &[u8],
+        encoding: TextEncoding,
+    ) -> Result<Self, AnsiFileError> {
+        profile_scope!("egui_sgr::load_file");
+        match kind {
+            AnsiFileKind::Log => {
+                let mut document = AnsiDocument::new();
+                match encoding {
+                    TextEncoding::Utf8 => document.append(bytes),
+                    _ => document.append_str(&encoding.decode(bytes)),
+                }
+                Ok(Self::Log(Box::new(document)))
+            }
+            AnsiFileKind::Art => Ok(Self::Art(Box::new(AnsiArt::from_bytes_with_encoding(
+                bytes, encoding,
+            )))),
+            #[cfg(feature = "asciicast")]
+            AnsiFileKind::Cast => {
+                let cast = Asciicast::parse(&encoding.decode(bytes))?;
+                Ok(Self::Cast(Box::new(cast)))
+            }
+            #[cfg(not(feature = "asciicast"))]
+            AnsiFileKind::Cast => Err(AnsiFileError::Unsupported(kind)),
+        }
+    }
+
+    /// Reads and loads the file at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, AnsiFileError> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)?;
+        Self::load(&path.to_string_lossy(), &bytes)
+    }
+
+    /// Returns the format the file was loaded as.
+    #[must_use]
+    pub fn kind(&self) -> AnsiFileKind {
+        match self {
+            Self::Log(_) => AnsiFileKind::Log,
+            Self::Art(_) => AnsiFileKind::Art,
+            #[cfg(feature = "asciicast")]
+            Self::Cast(_) => AnsiFileKind::Cast,
+        }
+    }
+}
+
+impl fmt::Debug for AnsiFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Log(document) => f
+                .debug_struct("Log")
+                .field("lines", &document.line_count())
+                .finish(),
+            Self::Art(art) => f.debug_tuple("Art").field(art).finish(),
+            #[cfg(feature = "asciicast")]
+            Self::Cast(cast) => f.debug_tuple("Cast").field(cast).finish(),
+        }
+    }
+}
+
+/// Error returned when a file cannot be loaded.
+#[derive(Debug)]
+pub enum AnsiFileError {
+    /// The file could not be read.
+    Io(std::io::Error),
+    /// The format needs a feature that is not enabled.
+    Unsupported(AnsiFileKind),
+    /// The recording could not be parsed.
+    #[cfg(feature = "asciicast")]
+    Cast(AsciicastError),
+}
+
+impl fmt::Display for AnsiFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "cannot read file: {error}"),
+            Self::Unsupported(kind) => write!(f, "{kind:?} files are not supported"),
+            #[cfg(feature = "asciicast")]
+            Self::Cast(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for AnsiFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Unsupported(_) => None,
+            #[cfg(feature = "asciicast")]
+            Self::Cast(error) => Some(error),
+        }
+    }
+}
+
+impl From<std::io::Error> for AnsiFileError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+#[cfg(feature = "asciicast")]
+impl From<AsciicastError> for AnsiFileError {
+    fn from(error: AsciicastError) -> Self {
+        Self::Cast(error)
+    }
+}
+
+/// File dropped onto the egui window, not yet loaded.
+///
+/// Inspect [`Self::kind`], [`Self::name`], or [`Self::byte_len`] to decide
+/// whether to accept it, then call [`Self::load`]; dropping the value
+/// without loading it rejects the file.
+#[cfg(feature = "egui")]
+#[derive(Debug, Clone)]
+pub struct DroppedAnsiFile {
+    /// File name, or the full path on native platforms.
+    pub name: String,
+    /// Path of the file, when the platform provides one.
+    pub path: Option<PathBuf>,
+    /// Detected format. Drops that only carry a path are detected from the
+    /// name here and from the contents again by [`Self::load`].
+    pub kind: AnsiFileKind,
+    bytes: Option<Arc<[u8]>>,
+}
+
+#[cfg(feature = "egui")]
+impl DroppedAnsiFile {
+    /// Returns the files dropped onto the window this frame.
+    ///
+    /// Dropping is opt-in: nothing happens unless the app calls this, and the
+    /// files stay in egui's input for other consumers.
+    #[must_use]
+    pub fn from_input(ctx: &egui::Context) -> Vec<Self> {
+        ctx.input(|input| {
+            input
+                .raw
+                .dropped_files
+                .iter()
+                .map(|file| {
+                    let name = match &file.path {
+                        Some(path) if file.name.is_empty() => path.to_string_lossy().into_owned(),
+                        _ => file.name.clone(),
+                    };
+                    Self {
+                        kind: AnsiFileKind::detect(&name, file.bytes.as_deref()),
+                        name,
+                        path: file.path.clone(),
+                        bytes: file.bytes.clone(),
+                    }
+                })
+                .collect()
+        })
+    }
+
+    /// Returns the size of the contents, if they came with the drop.
+    ///
+    /// Native platforms usually only provide a path, which [`Self::load`]
+    /// reads.
+    #[must_use]
+    pub fn byte_len(&self) -> Option<usize> {
+        self.bytes.as_ref().map(|bytes| bytes.len())
+    }
+
+    /// Loads the file, reading it from [`Self::path`] if the drop did not
+    /// carry its contents.
+    pub fn load(&self) -> Result<AnsiFile, AnsiFileError> {
+        self.load_with(|kind, _| kind.default_encoding())
+    }
+
+    /// Loads the file like [`Self::load`], decoding text in the encoding
+    /// `choose` returns for the detected kind and the contents, such as
+    /// `|_, bytes| TextEncoding::detect(bytes)` or a user override.
+    pub fn load_with(
+        &self,
+        choose: impl FnOnce(AnsiFileKind, &[u8]) -> TextEncoding,
+    ) -> Result<AnsiFile, AnsiFileError> {
+        let (kind, bytes) = match (&self.bytes, &self.path) {
+            (Some(bytes), _) => (self.kind, Cow::Borrowed(&bytes[..])),
+            (None, Some(path)) => {
+                let bytes = std::fs::read(path)?;
+                (
+                    AnsiFileKind::detect(&self.name, Some(&bytes)),
+                    Cow::Owned(bytes),
+                )
+            }
+            (None, None) => return Err(std::io::Error::from(std::io::ErrorKind::NotFound).into()),
+        };
+        AnsiFile::load_with_encoding(kind, &bytes, choose(kind, &bytes))
+    }
+}
@@ -0,0 +1,187 @@
+use crate::{AnsiLine, LineStructure, LogLevel};
+
+/// Line filter for a log view: a text filter, a search, and a minimum
+/// level.
+///
+/// [`Self::keeps`] applies the text filter and the level; the search is
+/// stored for highlighting, for example with
+/// [`AnsiDocument::find`](crate::AnsiDocument::find). With the `persistence`
+/// feature, filters can be saved by name in [`FilterPresets`].
+///
+/// ```rust
+/// use egui_sgr::{AnsiDocument, CommonLogFormat, LogFilter, LogLevel};
+///
+/// let mut document = AnsiDocument::<()>::new();
+/// document.append("INFO db up\nWARN db slow\nERROR net down\n    at frame 1\n");
+/// let filter = LogFilter {
+///     text: "DB".into(),
+///     min_level: Some(LogLevel::Warn),
+///     ..LogFilter::default()
+/// };
+/// let kept: Vec<_> = document
+///     .lines()
+///     .filter(|line| filter.keeps(line, &CommonLogFormat))
+///     .map(|line| line.text.text.as_str())
+///     .collect();
+/// assert_eq!(kept, ["WARN db slow"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LogFilter {
+    /// Text a line must contain, ignoring case. Empty keeps every line.
+    pub text: String,
+    /// Search to highlight, such as a regex typed by the user.
+    pub search: String,
+    /// Lowest level to keep. Lines without a recognized level, such as
+    /// stack trace continuations, are kept unless [`Self::text`] rules them
+    /// out.
+    pub min_level: Option<LogLevel>,
+}
+
+impl LogFilter {
+    /// Returns `true` if the filter keeps every line.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty() && self.min_level.is_none()
+    }
+
+    /// Returns `true` if `line` passes the text filter and, with levels
+    /// found by `structure`, the minimum level.
+    #[must_use]
+    pub fn keeps<M>(&self, line: &AnsiLine<M>, structure: &impl LineStructure) -> bool {
+        let text = &line.text.text;
+        if !self.text.is_empty() && !contains_ignore_case(text, &self.text) {
+            return false;
+        }
+        let Some(min_level) = self.min_level else {
+            return true;
+        };
+        structure
+            .parse(text)
+            .and_then(|fields| fields.log_level(text))
+            .is_none_or(|level| level >= min_level)
+    }
+}
+
+/// Returns `true` if `haystack` contains `needle`, ignoring case, without
+/// allocating lowercase copies of either.
+fn contains_ignore_case(haystack: &str, needle: &str) -> bool {
+    if haystack.is_ascii() && needle.is_ascii() {
+        return haystack
+            .as_bytes()
+            .windows(needle.len())
+            .any(|window| window.eq_ignore_ascii_case(needle.as_bytes()));
+    }
+    haystack.char_indices().any(|(start, _)| {
+        let mut rest = haystack[start..].chars().flat_map(char::to_lowercase);
+        needle
+            .chars()
+            .flat_map(char::to_lowercase)
+            .all(|c| rest.next() == Some(c))
+    })
+}
+
+/// Named [`LogFilter`]s, stored in egui's persisted memory.
+///
+/// Load the presets with [`Self::load`] when showing a log view, and
+/// [`Self::store`] them after a change. egui saves persisted memory with the
+/// app state, so with eframe's `persistence` feature the presets come back in
+/// the next session.
+///
+/// ```rust
+/// use egui_sgr::{FilterPresets, LogFilter, LogLevel};
+///
+/// fn presets_menu(ui: &mut egui::Ui, filter: &mut LogFilter) {
+///     let id = ui.id().with("log_presets");
+///     let mut presets = FilterPresets::load(ui.ctx(), id);
+///     for (name, preset) in presets.iter() {
+///         if ui.button(name).clicked() {
+///             *filter = preset.clone();
+///         }
+///     }
+///     if ui.button("Save as \"errors\"").clicked() {
+///         presets.save("errors", filter.clone());
+///         presets.store(ui.ctx(), id);
+///     }
+/// }
+/// ```
+#[cfg(feature = "persistence")]
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FilterPresets {
+    presets: Vec<(String, LogFilter)>,
+}
+
+#[cfg(feature = "persistence")]
+impl FilterPresets {
+    /// Returns the presets stored under `id`, or none if nothing was stored.
+    #[must_use]
+    pub fn load(ctx: &egui::Context, id: egui::Id) -> Self {
+        ctx.data_mut(|data| data.get_persisted(id))
+            .unwrap_or_default()
+    }
+
+    /// Stores the presets under `id`, replacing what was stored there.
+    pub fn store(&self, ctx: &egui::Context, id: egui::Id) {
+        ctx.data_mut(|data| data.insert_persisted(id, self.clone()));
+    }
+
+    /// Saves `filter` as `name`, replacing a preset with the same name or
+    /// adding it at the end.
+    pub fn save(&mut self, name: impl Into<String>, filter: LogFilter) {
+        let name = name.into();
+        match self.presets.iter_mut().find(|(known, _)| *known == name) {
+            Some((_, preset)) => *preset = filter,
+            None => self.presets.push((name, filter)),
+        }
+    }
+
+    /// Removes the preset called `name` and returns it.
+    pub fn remove(&mut self, name: &str) -> Option<LogFilter> {
+        let index = self.presets.iter().position(|(known, _)| known == name)?;
+        Some(self.presets.remove(index).1)
+    }
+
+    /// Renames a preset, keeping its position. Returns `false` if there is no
+    /// preset called `from` or one called `to` already exists.
+    pub fn rename(&mut self, from: &str, to: impl Into<String>) -> bool {
+        let to = to.into();
+        if self.get(&to).is_some() {
+            return false;
+        }
+        match self.presets.iter_mut().find(|(known, _)| known == from) {
+            Some((name, _)) => {
+                *name = to;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the preset called `name`.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&LogFilter> {
+        self.presets
+            .iter()
+            .find(|(known, _)| known == name)
+            .map(|(_, filter)| filter)
+    }
+
+    /// Iterates over the presets in the order they were added.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (&str, &LogFilter)> {
+        self.presets
+            .iter()
+            .map(|(name, filter)| (name.as_str(), filter))
+    }
+
+    /// Returns the number of presets.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.presets.len()
+    }
+
+    /// Returns `true` if there are no presets.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.presets.is_empty()
+    }
+}
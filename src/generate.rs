@@ -0,0 +1,229 @@
+use crate::{AnsiColor, AnsiStyle, AnsiText, UnderlineStyle};
+use proptest::prelude::*;
+
+/// Generated ANSI input together with the styled text it must parse to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedAnsi {
+    /// Raw ANSI input.
+    pub input: String,
+    /// Expected result of parsing [`Self::input`], for example with
+    /// [`ansi_to_text`](crate::ansi_to_text).
+    pub expected: AnsiText,
+}
+
+/// Returns a proptest strategy producing ANSI streams with a known style
+/// model.
+///
+/// Streams mix text (including newlines, tabs, and non-ASCII characters)
+/// with SGR sequences that combine several attribute changes, each written
+/// in one of its equivalent encodings: `31` or `38;5;1` or `38:5:1`, `4:3`,
+/// `\x1b[m` for a reset, and so on. Near-valid noise that must not affect
+/// styles is interleaved: cursor and private-mode CSI sequences, OSC titles
+/// terminated by `BEL` or `ST`, and unassigned SGR codes. The expected text
+/// is computed from the attribute changes directly, not by a parser, so it
+/// can validate any parsing pipeline.
+///
+/// ```rust
+/// use egui_sgr::{ansi_stream_strategy, ansi_to_text};
+/// use proptest::prelude::*;
+///
+/// proptest!(|(generated in ansi_stream_strategy())| {
+///     prop_assert_eq!(ansi_to_text(&generated.input), generated.expected);
+/// });
+/// ```
+pub fn ansi_stream_strategy() -> impl Strategy<Value = GeneratedAnsi> {
+    prop::collection::vec(token(), 0..24).prop_map(|tokens| {
+        let mut input = String::new();
+        let mut expected = AnsiText::new();
+        let mut style = AnsiStyle::default();
+
+        for token in tokens {
+            match token {
+                Token::Text(text) => {
+                    input.push_str(&text);
+                    expected.push_str(&text, style);
+                }
+                Token::Sgr(changes) => {
+                    let params: Vec<_> = changes.iter().map(|(_, param)| param.as_str()).collect();
+                    input.push_str("\x1b[");
+                    input.push_str(&params.join(";"));
+                    input.push('m');
+                    for (change, _) in changes {
+                        change.apply(&mut style);
+                    }
+                }
+                Token::Noise(noise) => input.push_str(&noise),
+            }
+        }
+
+        GeneratedAnsi { input, expected }
+    })
+}
+
+#[derive(Debug, Clone)]
+enum Token {
+    Text(String),
+    Sgr(Vec<(Change, String)>),
+    Noise(String),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Change {
+    Reset,
+    Bold,
+    Faint,
+    Normal,
+    Italic(bool),
+    Underline(UnderlineStyle),
+    Reverse(bool),
+    Hidden(bool),
+    Strikethrough(bool),
+    Foreground(AnsiColor),
+    Background(AnsiColor),
+    UnderlineColor(Option<AnsiColor>),
+}
+
+impl Change {
+    fn apply(self, style: &mut AnsiStyle) {
+        match self {
+            Self::Reset => *style = AnsiStyle::default(),
+            Self::Bold => *style = style.bold(),
+            Self::Faint => *style = style.faint(),
+            Self::Normal => style.intensity = AnsiStyle::default().intensity,
+            Self::Italic(on) => style.italic = on,
+            Self::Underline(underline) => style.underline = underline,
+            Self::Reverse(on) => style.reverse = on,
+            Self::Hidden(on) => style.hidden = on,
+            Self::Strikethrough(on) => style.strikethrough = on,
+            Self::Foreground(color) => style.foreground = color,
+            Self::Background(color) => style.background = color,
+            Self::UnderlineColor(color) => style.underline_color = color,
+        }
+    }
+}
+
+fn token() -> impl Strategy<Value = Token> {
+    prop_oneof![
+        4 => "[a-zA-Z0-9 .,:;!?é界\n\t]{1,12}".prop_map(Token::Text),
+        3 => prop::collection::vec(change(), 1..4).prop_map(|changes| {
+            // An empty parameter only means reset on its own.
+            let changes = if changes.len() > 1 {
+                changes
+                    .into_iter()
+                    .map(|(change, param)| {
+                        let param = if param.is_empty() { "0".to_owned() } else { param };
+                        (change, param)
+                    })
+                    .collect()
+            } else {
+                changes
+            };
+            Token::Sgr(changes)
+        }),
+        1 => noise().prop_map(Token::Noise),
+    ]
+}
+
+fn change() -> impl Strategy<Value = (Change, String)> {
+    prop_oneof![
+        prop_oneof![Just(""), Just("0")].prop_map(|param| (Change::Reset, param.to_owned())),
+        Just((Change::Bold, "1".to_owned())),
+        Just((Change::Faint, "2".to_owned())),
+        Just((Change::Normal, "22".to_owned())),
+        any::<bool>().prop_map(|on| (Change::Italic(on), if on { "3" } else { "23" }.to_owned())),
+        underline(),
+        any::<bool>().prop_map(|on| (Change::Reverse(on), if on { "7" } else { "27" }.to_owned())),
+        any::<bool>().prop_map(|on| (Change::Hidden(on), if on { "8" } else { "28" }.to_owned())),
+        any::<bool>().prop_map(|on| {
+            (
+                Change::Strikethrough(on),
+                if on { "9" } else { "29" }.to_owned(),
+            )
+        }),
+        (color(), any::<bool>()).prop_map(|((color, param), short)| {
+            (
+                Change::Foreground(color),
+                encode_color(color, &param, 30, 90, 38, short),
+            )
+        }),
+        (color(), any::<bool>()).prop_map(|((color, param), short)| {
+            (
+                Change::Background(color),
+                encode_color(color, &param, 40, 100, 48, short),
+            )
+        }),
+        prop::option::of(color()).prop_map(|color| match color {
+            Some((AnsiColor::Default, _)) | None => (Change::UnderlineColor(None), "59".to_owned()),
+            Some((color, param)) => (Change::UnderlineColor(Some(color)), format!("58{param}")),
+        }),
+    ]
+}
+
+fn underline() -> impl Strategy<Value = (Change, String)> {
+    prop_oneof![
+        prop_oneof![Just("24"), Just("4:0")].prop_map(|param| (UnderlineStyle::None, param)),
+        prop_oneof![Just("4"), Just("4:1")].prop_map(|param| (UnderlineStyle::Single, param)),
+        prop_oneof![Just("21"), Just("4:2")].prop_map(|param| (UnderlineStyle::Double, param)),
+        Just((UnderlineStyle::Curly, "4:3")),
+        Just((UnderlineStyle::Dotted, "4:4")),
+        Just((UnderlineStyle::Dashed, "4:5")),
+    ]
+    .prop_map(|(underline, param)| (Change::Underline(underline), param.to_owned()))
+}
+
+/// Returns a color with its extended-color suffix, such as `;5;196` or
+/// `:2::1:2:3`, in a random encoding.
+fn color() -> impl Strategy<Value = (AnsiColor, String)> {
+    prop_oneof![
+        Just((AnsiColor::Default, String::new())),
+        (any::<u8>(), any::<bool>()).prop_map(|(index, colon)| {
+            let param = if colon {
+                format!(":5:{index}")
+            } else {
+                format!(";5;{index}")
+            };
+            (AnsiColor::Indexed(index), param)
+        }),
+        (any::<(u8, u8, u8)>(), any::<bool>()).prop_map(|((r, g, b), colon)| {
+            let param = if colon {
+                format!(":2::{r}:{g}:{b}")
+            } else {
+                format!(";2;{r};{g};{b}")
+            };
+            (AnsiColor::Rgb(r, g, b), param)
+        }),
+    ]
+}
+
+fn encode_color(
+    color: AnsiColor,
+    extended: &str,
+    base: u16,
+    bright_base: u16,
+    target: u16,
+    short: bool,
+) -> String {
+    match color {
+        AnsiColor::Default => (base + 9).to_string(),
+        AnsiColor::Indexed(index @ 0..=7) if short => (base + u16::from(index)).to_string(),
+        AnsiColor::Indexed(index @ 8..=15) if short => {
+            (bright_base + u16::from(index - 8)).to_string()
+        }
+        _ => format!("{target}{extended}"),
+    }
+}
+
+fn noise() -> impl Strategy<Value = String> {
+    prop_oneof![
+        (
+            1..100u16,
+            prop::sample::select(vec!['A', 'B', 'C', 'D', 'G', 'J', 'K'])
+        )
+            .prop_map(|(count, action)| format!("\x1b[{count}{action}")),
+        Just("\x1b[?25l".to_owned()),
+        Just("\x1b[2 q".to_owned()),
+        "[a-z ]{0,8}".prop_map(|title| format!("\x1b]0;{title}\x07")),
+        "[a-z ]{0,8}".prop_map(|title| format!("\x1b]2;{title}\x1b\\")),
+        prop_oneof![Just("50"), Just("63"), Just("75")].prop_map(|code| format!("\x1b[{code}m")),
+    ]
+}
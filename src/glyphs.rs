@@ -0,0 +1,183 @@
+use egui::epaint::text::{FontInsert, InsertFontFamily};
+use egui::text::{LayoutJob, LayoutSection};
+use egui::{Color32, FontData, FontFamily, Galley, Painter, Pos2, Rect, Shape, Stroke, pos2, vec2};
+use std::f32::consts::PI;
+
+/// Registers a font, such as a Nerd Font or a symbols-only font, as the
+/// last fallback of egui's monospace and proportional families.
+///
+/// Characters missing from the default fonts, such as the Powerline and
+/// devicon glyphs that starship or oh-my-zsh prompts print, are then drawn
+/// from `data` instead of as boxes, while all other text keeps its font.
+/// Call it once at startup, for example from the `eframe` creation closure.
+pub fn add_symbol_font(ctx: &egui::Context, name: &str, data: Vec<u8>) {
+    let families = [FontFamily::Monospace, FontFamily::Proportional]
+        .into_iter()
+        .map(|family| InsertFontFamily {
+            family,
+            priority: egui::epaint::text::FontPriority::Lowest,
+        })
+        .collect();
+    ctx.add_font(FontInsert::new(name, FontData::from_owned(data), families));
+}
+
+/// Returns `true` for the Powerline separators that
+/// [`paint_powerline_glyph`] can draw: the solid and thin triangles
+/// `U+E0B0`..`U+E0B3` and the rounded caps `U+E0B4`..`U+E0B7`.
+#[must_use]
+pub fn is_powerline_glyph(c: char) -> bool {
+    ('\u{e0b0}'..='\u{e0b7}').contains(&c)
+}
+
+/// Draws a Powerline separator filling `rect` with `color`.
+///
+/// Separators are drawn as shapes rather than from a font, so they join
+/// seamlessly with the neighbouring cell backgrounds at any font size.
+/// Returns `false`, drawing nothing, for other characters.
+pub fn paint_powerline_glyph(painter: &Painter, rect: Rect, c: char, color: Color32) -> bool {
+    if !is_powerline_glyph(c) {
+        return false;
+    }
+    let offset = u32::from(c) - 0xe0b0;
+    // Even glyphs are solid, odd ones thin; the first four are triangles
+    // and the last four rounded; pairs alternate pointing right and left.
+    let solid = offset % 2 == 0;
+    let rounded = offset >= 4;
+    let right = offset % 4 < 2;
+    let (base, tip) = if right {
+        (rect.left(), rect.right())
+    } else {
+        (rect.right(), rect.left())
+    };
+
+    let points: Vec<Pos2> = if rounded {
+        const STEPS: usize = 16;
+        (0..=STEPS)
+            .map(|step| {
+                let angle = PI * step as f32 / STEPS as f32;
+                let x = base + (tip - base) * angle.sin();
+                let y = rect.top() + rect.height() * (1.0 - angle.cos()) / 2.0;
+                pos2(x, y)
+            })
+            .collect()
+    } else {
+        vec![
+            pos2(base, rect.top()),
+            pos2(tip, rect.center().y),
+            pos2(base, rect.bottom()),
+        ]
+    };
+
+    if solid {
+        painter.add(Shape::convex_polygon(points, color, Stroke::NONE));
+    } else {
+        painter.add(Shape::line(points, Stroke::new(1.0, color)));
+    }
+    true
+}
+
+/// Powerline separators taken out of a [`LayoutJob`] so the painter can
+/// draw them.
+///
+/// Without a font providing them, Powerline separators come out as boxes.
+/// [`Self::hide`] makes them transparent in the job while keeping their
+/// advance, and [`Self::paint`] draws each one over its place in the laid
+/// out galley, in its original color.
+///
+/// ```rust
+/// use egui_sgr::{EguiAnsiTheme, PowerlineGlyphs, ansi_to_layout_job};
+///
+/// fn prompt(ui: &mut egui::Ui, line: &str) {
+///     let mut job = ansi_to_layout_job(line, &EguiAnsiTheme::default());
+///     let glyphs = PowerlineGlyphs::hide(&mut job);
+///     let galley = ui.fonts_mut(|fonts| fonts.layout_job(job));
+///     let (rect, _) = ui.allocate_exact_size(galley.size(), egui::Sense::hover());
+///     ui.painter().galley(rect.min, galley.clone(), egui::Color32::WHITE);
+///     glyphs.paint(ui.painter(), rect.min, &galley);
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PowerlineGlyphs {
+    /// Color of each hidden separator, in text order.
+    colors: Vec<Color32>,
+}
+
+impl PowerlineGlyphs {
+    /// Makes the Powerline separators in `job` transparent and remembers
+    /// their colors.
+    pub fn hide(job: &mut LayoutJob) -> Self {
+        let mut colors = Vec::new();
+        if !job.text.chars().any(is_powerline_glyph) {
+            return Self { colors };
+        }
+
+        let mut sections = Vec::with_capacity(job.sections.len());
+        for mut section in job.sections.drain(..) {
+            let range = section.byte_range.clone();
+            let mut start = range.start;
+            for (offset, c) in job.text[range.clone()].char_indices() {
+                if !is_powerline_glyph(c) {
+                    continue;
+                }
+                let glyph = range.start + offset;
+                if start < glyph {
+                    sections.push(LayoutSection {
+                        byte_range: start..glyph,
+                        ..section.clone()
+                    });
+                    section.leading_space = 0.0;
+                }
+                colors.push(section.format.color);
+                let mut hidden = LayoutSection {
+                    byte_range: glyph..glyph + c.len_utf8(),
+                    ..section.clone()
+                };
+                hidden.format.color = Color32::TRANSPARENT;
+                sections.push(hidden);
+                section.leading_space = 0.0;
+                start = glyph + c.len_utf8();
+            }
+            if start < range.end {
+                section.byte_range = start..range.end;
+                sections.push(section);
+            }
+        }
+        job.sections = sections;
+        Self { colors }
+    }
+
+    /// Returns `true` if the job had no Powerline separators.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+
+    /// Draws the hidden separators over `galley`, laid out from the job
+    /// passed to [`Self::hide`] and painted at `pos`.
+    ///
+    /// Each separator fills its glyph's advance and the full row height.
+    /// Separators whose color was the placeholder use the style's text
+    /// color.
+    pub fn paint(&self, painter: &Painter, pos: Pos2, galley: &Galley) {
+        if self.is_empty() {
+            return;
+        }
+        let text_color = painter.ctx().global_style().visuals.text_color();
+        let glyphs = galley.rows.iter().flat_map(|row| {
+            row.glyphs
+                .iter()
+                .filter(|glyph| is_powerline_glyph(glyph.chr))
+                .map(move |glyph| (row, glyph))
+        });
+        for ((row, glyph), &color) in glyphs.zip(&self.colors) {
+            let min = pos + row.pos.to_vec2() + vec2(glyph.pos.x, 0.0);
+            let rect = Rect::from_min_size(min, vec2(glyph.advance_width, row.rect().height()));
+            let color = if color == Color32::PLACEHOLDER {
+                text_color
+            } else {
+                color
+            };
+            paint_powerline_glyph(painter, rect, glyph.chr, color);
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use crate::resolve::{ResolvedStyle, resolve_style};
+use crate::{AnsiSpan, AnsiStyle, AnsiText, EguiAnsiTheme, UnderlineStyle, ansi_to_text};
+use ecolor::Color32;
+use std::fmt::Write;
+
+/// Options for HTML export.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HtmlOptions {
+    /// Theme used to resolve ANSI colors.
+    pub theme: EguiAnsiTheme,
+    /// Whether to wrap the output in a `<pre>` element carrying the theme's
+    /// default foreground and background colors.
+    pub wrap_in_pre: bool,
+}
+
+impl Default for HtmlOptions {
+    fn default() -> Self {
+        Self {
+            theme: EguiAnsiTheme::default(),
+            wrap_in_pre: true,
+        }
+    }
+}
+
+/// Converts ANSI text to HTML with inline-styled `<span>` elements.
+///
+/// Colors go through the same theme pipeline as `LayoutJob` rendering, so
+/// reverse video, faint, hidden, and bold-as-bright text look the same in a
+/// browser as in egui.
+#[must_use]
+pub fn ansi_to_html(input: &str, options: &HtmlOptions) -> String {
+    let text = ansi_to_text(input);
+    runs_to_html(text.iter(), options)
+}
+
+/// Converts parsed spans to HTML with inline-styled `<span>` elements.
+#[must_use]
+pub fn spans_to_html(spans: &[AnsiSpan], options: &HtmlOptions) -> String {
+    runs_to_html(
+        spans.iter().map(|span| (span.text.as_str(), span.style)),
+        options,
+    )
+}
+
+impl AnsiText {
+    /// Converts the style runs to HTML with inline-styled `<span>` elements.
+    ///
+    /// Combined with [`AnsiText::slice`], this exports just a selection, for
+    /// example to paste styled output into a rich-text editor.
+    #[must_use]
+    pub fn to_html(&self, options: &HtmlOptions) -> String {
+        runs_to_html(self.iter(), options)
+    }
+}
+
+fn runs_to_html<'a>(
+    runs: impl Iterator<Item = (&'a str, AnsiStyle)>,
+    options: &HtmlOptions,
+) -> String {
+    let theme = &options.theme;
+    let mut html = String::new();
+
+    if options.wrap_in_pre {
+        let _ = write!(
+            html,
+            "<pre style=\"color:{};background-color:{}\">",
+            css_color(theme.default_foreground),
+            css_color(theme.default_background)
+        );
+    }
+
+    for (text, style) in runs {
+        if text.is_empty() {
+            continue;
+        }
+
+        if style == AnsiStyle::default() {
+            push_escaped(&mut html, text);
+            continue;
+        }
+
+        html.push_str("<span style=\"");
+        write_css(&mut html, &resolve_style(&style, theme));
+        html.push_str("\">");
+        push_escaped(&mut html, text);
+        html.push_str("</span>");
+    }
+
+    if options.wrap_in_pre {
+        html.push_str("</pre>");
+    }
+
+    html
+}
+
+fn write_css(html: &mut String, style: &ResolvedStyle) {
+    let _ = write!(html, "color:{}", css_color(style.foreground));
+    if let Some(background) = style.background {
+        let _ = write!(html, ";background-color:{}", css_color(background));
+    }
+    if style.bold {
+        html.push_str(";font-weight:bold");
+    }
+    if style.italic {
+        html.push_str(";font-style:italic");
+    }
+
+    match (style.underline, style.strikethrough) {
+        (Some(_), true) => html.push_str(";text-decoration:underline line-through"),
+        (Some(_), false) => html.push_str(";text-decoration:underline"),
+        (None, true) => html.push_str(";text-decoration:line-through"),
+        (None, false) => {}
+    }
+    if let Some(color) = style.underline {
+        let line_style = match style.underline_style {
+            UnderlineStyle::Double => Some("double"),
+            UnderlineStyle::Curly => Some("wavy"),
+            UnderlineStyle::Dotted => Some("dotted"),
+            UnderlineStyle::Dashed => Some("dashed"),
+            UnderlineStyle::None | UnderlineStyle::Single => None,
+        };
+        if let Some(line_style) = line_style {
+            let _ = write!(html, ";text-decoration-style:{line_style}");
+        }
+        if color != style.foreground {
+            let _ = write!(html, ";text-decoration-color:{}", css_color(color));
+        }
+    }
+}
+
+fn css_color(color: Color32) -> String {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    if a == u8::MAX {
+        format!("#{r:02x}{g:02x}{b:02x}")
+    } else {
+        format!("rgba({r},{g},{b},{:.3})", f32::from(a) / 255.0)
+    }
+}
+
+fn push_escaped(html: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            c => html.push(c),
+        }
+    }
+}
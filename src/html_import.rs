@@ -0,0 +1,365 @@
+use crate::{AnsiColor, AnsiIntensity, AnsiSpan, AnsiStyle, AnsiText, UnderlineStyle};
+
+/// Converts simple styled HTML into ANSI spans.
+///
+/// See [`html_to_text`] for the supported subset.
+#[must_use]
+pub fn html_to_spans(html: &str) -> Vec<AnsiSpan> {
+    html_to_text(html).to_spans()
+}
+
+/// Converts simple styled HTML into styled text.
+///
+/// This targets HTML produced by terminal-to-HTML converters and web APIs,
+/// not arbitrary documents. Supported markup:
+///
+/// - `<span style="...">` and `<font color="...">` with `color`,
+///   `background-color`/`background`, `font-weight`, `font-style`, and
+///   `text-decoration` declarations. Colors may be `#rgb`, `#rrggbb`,
+///   `rgb(r, g, b)`, or one of the 16 basic CSS color names.
+/// - `<b>`/`<strong>`, `<i>`/`<em>`, `<u>`/`<ins>`, and `<s>`/`<strike>`/`<del>`.
+/// - `<br>` as a line break, and common character entities.
+///
+/// Whitespace is kept as written, as inside `<pre>`. Unknown tags are
+/// dropped while their content is kept; comments and the content of
+/// `<script>` and `<style>` are dropped.
+#[must_use]
+pub fn html_to_text(html: &str) -> AnsiText {
+    let mut output = AnsiText::new();
+    let mut stack: Vec<(String, AnsiStyle)> = Vec::new();
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        let style = stack.last().map(|(_, style)| *style).unwrap_or_default();
+
+        let Some(tag_start) = rest.find('<') else {
+            push_decoded(&mut output, rest, style);
+            break;
+        };
+        push_decoded(&mut output, &rest[..tag_start], style);
+        rest = &rest[tag_start..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+
+        let Some(tag_end) = find_tag_end(rest) else {
+            push_decoded(&mut output, rest, style);
+            break;
+        };
+        let tag = Tag::parse(&rest[1..tag_end]);
+        rest = &rest[tag_end + 1..];
+
+        let Some(tag) = tag else {
+            continue;
+        };
+
+        if tag.closing {
+            if let Some(index) = stack.iter().rposition(|(name, _)| *name == tag.name) {
+                stack.truncate(index);
+            }
+            continue;
+        }
+
+        match tag.name.as_str() {
+            "br" => output.push_str("\n", style),
+            "script" | "style" => {
+                let close = format!("</{}", tag.name);
+                rest = find_ignore_ascii_case(rest, &close)
+                    .and_then(|start| rest[start..].find('>').map(|end| &rest[start + end + 1..]))
+                    .unwrap_or("");
+            }
+            _ if tag.self_closing => {}
+            _ => {
+                let mut style = style;
+                tag.apply(&mut style);
+                stack.push((tag.name, style));
+            }
+        }
+    }
+
+    output
+}
+
+struct Tag {
+    name: String,
+    closing: bool,
+    self_closing: bool,
+    attributes: Vec<(String, String)>,
+}
+
+impl Tag {
+    fn parse(source: &str) -> Option<Self> {
+        let (closing, source) = match source.strip_prefix('/') {
+            Some(source) => (true, source),
+            None => (false, source),
+        };
+        let (self_closing, source) = match source.trim_end().strip_suffix('/') {
+            Some(source) => (true, source),
+            None => (false, source),
+        };
+
+        let name_end = source
+            .find(|c: char| c.is_ascii_whitespace())
+            .unwrap_or(source.len());
+        let name = source[..name_end].to_ascii_lowercase();
+        if name.is_empty() || name.starts_with('!') || name.starts_with('?') {
+            return None;
+        }
+
+        Some(Self {
+            name,
+            closing,
+            self_closing,
+            attributes: parse_attributes(&source[name_end..]),
+        })
+    }
+
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn apply(&self, style: &mut AnsiStyle) {
+        match self.name.as_str() {
+            "b" | "strong" => style.intensity = AnsiIntensity::Bold,
+            "i" | "em" => style.italic = true,
+            "u" | "ins" => style.underline = UnderlineStyle::Single,
+            "s" | "strike" | "del" => style.strikethrough = true,
+            "font" => {
+                if let Some(color) = self.attribute("color").and_then(parse_css_color) {
+                    style.foreground = color;
+                }
+            }
+            _ => {}
+        }
+
+        if let Some(css) = self.attribute("style") {
+            apply_css(css, style);
+        }
+    }
+}
+
+fn find_tag_end(source: &str) -> Option<usize> {
+    let mut quote = None;
+    for (index, c) in source.char_indices().skip(1) {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return Some(index),
+            (None, _) => {}
+        }
+    }
+    None
+}
+
+fn find_ignore_ascii_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+fn parse_attributes(mut source: &str) -> Vec<(String, String)> {
+    let mut attributes = Vec::new();
+
+    loop {
+        source = source.trim_start();
+        if source.is_empty() {
+            return attributes;
+        }
+
+        let name_end = source
+            .find(|c: char| c.is_ascii_whitespace() || c == '=')
+            .unwrap_or(source.len());
+        let name = source[..name_end].to_ascii_lowercase();
+        source = source[name_end..].trim_start();
+
+        let value = if let Some(after_equals) = source.strip_prefix('=') {
+            let after_equals = after_equals.trim_start();
+            match after_equals.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let body = &after_equals[1..];
+                    let end = body.find(quote).unwrap_or(body.len());
+                    source = body.get(end + 1..).unwrap_or("");
+                    &body[..end]
+                }
+                _ => {
+                    let end = after_equals
+                        .find(|c: char| c.is_ascii_whitespace())
+                        .unwrap_or(after_equals.len());
+                    source = &after_equals[end..];
+                    &after_equals[..end]
+                }
+            }
+        } else {
+            ""
+        };
+
+        attributes.push((name, decode_entities(value)));
+    }
+}
+
+fn apply_css(css: &str, style: &mut AnsiStyle) {
+    for declaration in css.split(';') {
+        let Some((property, value)) = declaration.split_once(':') else {
+            continue;
+        };
+        let property = property.trim().to_ascii_lowercase();
+        let value = value.trim().to_ascii_lowercase();
+
+        match property.as_str() {
+            "color" => {
+                if let Some(color) = parse_css_color(&value) {
+                    style.foreground = color;
+                }
+            }
+            "background-color" | "background" => {
+                if let Some(color) = parse_css_color(&value) {
+                    style.background = color;
+                }
+            }
+            "font-weight" => {
+                style.intensity = match value.as_str() {
+                    "bold" | "bolder" => AnsiIntensity::Bold,
+                    "lighter" => AnsiIntensity::Faint,
+                    "normal" => AnsiIntensity::Normal,
+                    weight => match weight.parse::<u16>() {
+                        Ok(600..) => AnsiIntensity::Bold,
+                        Ok(..=300) => AnsiIntensity::Faint,
+                        _ => AnsiIntensity::Normal,
+                    },
+                };
+            }
+            "font-style" => style.italic = value == "italic" || value == "oblique",
+            "text-decoration" | "text-decoration-line" => {
+                if value.contains("none") {
+                    style.underline = UnderlineStyle::None;
+                    style.strikethrough = false;
+                }
+                if value.contains("underline") {
+                    style.underline = UnderlineStyle::Single;
+                }
+                if value.contains("line-through") {
+                    style.strikethrough = true;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn parse_css_color(value: &str) -> Option<AnsiColor> {
+    let value = value.trim().to_ascii_lowercase();
+
+    if let Some(hex) = value.strip_prefix('#') {
+        let digit =
+            |index: usize, len: usize| u8::from_str_radix(hex.get(index..index + len)?, 16).ok();
+        return match hex.len() {
+            3 => Some(AnsiColor::Rgb(
+                digit(0, 1)? * 17,
+                digit(1, 1)? * 17,
+                digit(2, 1)? * 17,
+            )),
+            6 => Some(AnsiColor::Rgb(digit(0, 2)?, digit(2, 2)?, digit(4, 2)?)),
+            _ => None,
+        };
+    }
+
+    if let Some(args) = value
+        .strip_prefix("rgb(")
+        .or_else(|| value.strip_prefix("rgba("))
+        .and_then(|args| args.strip_suffix(')'))
+    {
+        let mut channels = args
+            .split(|c: char| c == ',' || c.is_ascii_whitespace())
+            .filter(|channel| !channel.is_empty())
+            .map(|channel| channel.parse::<u8>().ok());
+        return Some(AnsiColor::Rgb(
+            channels.next()??,
+            channels.next()??,
+            channels.next()??,
+        ));
+    }
+
+    let (r, g, b) = match value.as_str() {
+        "black" => (0, 0, 0),
+        "silver" => (192, 192, 192),
+        "gray" | "grey" => (128, 128, 128),
+        "white" => (255, 255, 255),
+        "maroon" => (128, 0, 0),
+        "red" => (255, 0, 0),
+        "purple" => (128, 0, 128),
+        "fuchsia" | "magenta" => (255, 0, 255),
+        "green" => (0, 128, 0),
+        "lime" => (0, 255, 0),
+        "olive" => (128, 128, 0),
+        "yellow" => (255, 255, 0),
+        "navy" => (0, 0, 128),
+        "blue" => (0, 0, 255),
+        "teal" => (0, 128, 128),
+        "aqua" | "cyan" => (0, 255, 255),
+        _ => return None,
+    };
+    Some(AnsiColor::Rgb(r, g, b))
+}
+
+fn push_decoded(output: &mut AnsiText, text: &str, style: AnsiStyle) {
+    if text.contains('&') {
+        output.push_str(&decode_entities(text), style);
+    } else {
+        output.push_str(text, style);
+    }
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .map(|end| &rest[1..end + 1]);
+        match entity.and_then(decode_entity) {
+            Some(c) => {
+                decoded.push(c);
+                rest = &rest[entity.map_or(0, str::len) + 2..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+    decoded
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    if let Some(number) = entity.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+
+    match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some('\u{a0}'),
+        _ => None,
+    }
+}
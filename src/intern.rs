@@ -0,0 +1,83 @@
+use crate::{AnsiSpan, AnsiStyle};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// A span whose text is shared through an [`AnsiSpanInterner`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InternedSpan {
+    /// Shared visible text for this span.
+    pub text: Arc<str>,
+    /// ANSI style for this span.
+    pub style: AnsiStyle,
+}
+
+impl From<&InternedSpan> for AnsiSpan {
+    fn from(span: &InternedSpan) -> Self {
+        Self::new(&*span.text, span.style)
+    }
+}
+
+/// Pool of shared span texts for long-lived logs.
+///
+/// Logs repeat many identical segments: level tags, separators, masked
+/// timestamps. Interning stores each distinct text once and hands out
+/// [`Arc<str>`] clones, which cuts memory for long sessions.
+#[derive(Debug, Default)]
+pub struct AnsiSpanInterner {
+    pool: HashSet<Arc<str>>,
+}
+
+impl AnsiSpanInterner {
+    /// Creates an empty interner.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the shared copy of `text`, inserting it on first use.
+    pub fn intern_str(&mut self, text: &str) -> Arc<str> {
+        if let Some(shared) = self.pool.get(text) {
+            return Arc::clone(shared);
+        }
+
+        let shared: Arc<str> = Arc::from(text);
+        self.pool.insert(Arc::clone(&shared));
+        shared
+    }
+
+    /// Interns the text of one span.
+    pub fn intern(&mut self, span: &AnsiSpan) -> InternedSpan {
+        InternedSpan {
+            text: self.intern_str(&span.text),
+            style: span.style,
+        }
+    }
+
+    /// Interns the text of every span.
+    pub fn intern_spans(&mut self, spans: &[AnsiSpan]) -> Vec<InternedSpan> {
+        spans.iter().map(|span| self.intern(span)).collect()
+    }
+
+    /// Returns the number of distinct texts in the pool.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.pool.len()
+    }
+
+    /// Returns `true` if the pool is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.pool.is_empty()
+    }
+
+    /// Drops texts that are no longer referenced outside the pool.
+    pub fn shrink(&mut self) {
+        self.pool.retain(|text| Arc::strong_count(text) > 1);
+    }
+
+    /// Removes every text from the pool.
+    pub fn clear(&mut self) {
+        self.pool.clear();
+    }
+}
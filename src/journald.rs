@@ -0,0 +1,222 @@
+use crate::SharedAnsiDocument;
+use crate::repaint::Repaint;
+use serde_json::Value;
+use std::fmt::Write;
+use std::io::{self, BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::thread::{self, JoinHandle};
+
+/// Tails the systemd journal into a [`SharedAnsiDocument`].
+///
+/// Entries are read from `journalctl --follow --output=json` and formatted
+/// like [`AnsiLogger`](crate::AnsiLogger) records, with the journal priority
+/// mapped to a colored severity. Messages are pushed through the ANSI parser,
+/// so escape codes logged by services keep their colors. The document keeps
+/// the last 10,000 lines unless [`Self::with_max_lines`] says otherwise.
+pub struct AnsiJournal {
+    unit: Option<String>,
+    current_boot: bool,
+    lines: Option<usize>,
+    document: SharedAnsiDocument,
+    repaint: Repaint,
+}
+
+impl Default for AnsiJournal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnsiJournal {
+    /// Creates a source for the whole journal.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            unit: None,
+            current_boot: false,
+            lines: None,
+            document: SharedAnsiDocument::for_sink(),
+            repaint: Repaint::default(),
+        }
+    }
+
+    /// Only follows entries of a systemd unit, such as `"nginx.service"`.
+    #[must_use]
+    pub fn with_unit(mut self, unit: impl Into<String>) -> Self {
+        self.unit = Some(unit.into());
+        self
+    }
+
+    /// Only follows entries from the current boot.
+    #[must_use]
+    pub fn with_current_boot(mut self) -> Self {
+        self.current_boot = true;
+        self
+    }
+
+    /// Sets how many past entries are shown before following new ones.
+    ///
+    /// Defaults to journalctl's own default of 10.
+    #[must_use]
+    pub fn with_lines(mut self, lines: usize) -> Self {
+        self.lines = Some(lines);
+        self
+    }
+
+    /// Keeps at most `max_lines` entry lines, evicting the oldest.
+    #[must_use]
+    pub fn with_max_lines(self, max_lines: usize) -> Self {
+        self.document.lock().set_max_lines(Some(max_lines));
+        self
+    }
+
+    /// Appends to `document` instead of a new one, keeping its retention
+    /// cap.
+    #[must_use]
+    pub fn with_document(mut self, document: SharedAnsiDocument) -> Self {
+        self.document = document;
+        self
+    }
+
+    /// Requests an egui repaint after each entry.
+    #[cfg(feature = "egui")]
+    #[must_use]
+    pub fn with_repaint(mut self, ctx: egui::Context) -> Self {
+        self.repaint = Repaint::new(ctx);
+        self
+    }
+
+    /// Returns the shared document that entries are appended to.
+    #[must_use]
+    pub fn document(&self) -> SharedAnsiDocument {
+        self.document.clone()
+    }
+
+    /// Spawns `journalctl` and appends its entries on a background thread.
+    pub fn follow(self) -> io::Result<AnsiJournalFollower> {
+        let mut command = Command::new("journalctl");
+        command.args(["--follow", "--output=json"]);
+        if let Some(unit) = &self.unit {
+            command.arg(format!("--unit={unit}"));
+        }
+        if self.current_boot {
+            command.arg("--boot");
+        }
+        if let Some(lines) = self.lines {
+            command.arg(format!("--lines={lines}"));
+        }
+
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdout = child.stdout.take().expect("stdout is piped");
+
+        let document = self.document();
+        let repaint = self.repaint;
+        let reader = thread::Builder::new()
+            .name("egui_sgr journald".to_owned())
+            .spawn(move || {
+                for line in BufReader::new(stdout).lines() {
+                    let Ok(line) = line else {
+                        break;
+                    };
+                    let Some(entry) = format_entry(&line) else {
+                        continue;
+                    };
+                    document.lock().append_str(&entry);
+                    repaint.request();
+                }
+            });
+        let reader = match reader {
+            Ok(reader) => reader,
+            Err(error) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(error);
+            }
+        };
+
+        Ok(AnsiJournalFollower {
+            child,
+            reader: Some(reader),
+            document: self.document,
+        })
+    }
+}
+
+/// Running `journalctl` process started by [`AnsiJournal::follow`].
+///
+/// Dropping the follower stops the process.
+pub struct AnsiJournalFollower {
+    child: Child,
+    reader: Option<JoinHandle<()>>,
+    document: SharedAnsiDocument,
+}
+
+impl AnsiJournalFollower {
+    /// Returns the shared document that entries are appended to.
+    #[must_use]
+    pub fn document(&self) -> SharedAnsiDocument {
+        self.document.clone()
+    }
+}
+
+impl Drop for AnsiJournalFollower {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        if let Some(reader) = self.reader.take() {
+            let _ = reader.join();
+        }
+    }
+}
+
+/// Formats one `journalctl --output=json` line, or returns `None` for lines
+/// without a message.
+pub(crate) fn format_entry(json: &str) -> Option<String> {
+    let entry: Value = serde_json::from_str(json).ok()?;
+    let message = field(&entry, "MESSAGE")?;
+    let source = field(&entry, "SYSLOG_IDENTIFIER")
+        .or_else(|| field(&entry, "_COMM"))
+        .or_else(|| field(&entry, "_SYSTEMD_UNIT"))
+        .unwrap_or_default();
+
+    let (label, style) = match field(&entry, "PRIORITY").and_then(|p| p.parse::<u8>().ok()) {
+        Some(0) => ("EMERG", "1;31"),
+        Some(1) => ("ALERT", "1;31"),
+        Some(2) => ("CRIT", "1;31"),
+        Some(3) => ("ERROR", "31"),
+        Some(4) => ("WARN", "33"),
+        Some(5) => ("NOTE", "36"),
+        Some(7) => ("DEBUG", "34"),
+        _ => ("INFO", "32"),
+    };
+
+    let mut line = String::new();
+    // The trailing reset keeps a message's unterminated style from bleeding
+    // into the next entry.
+    let _ = writeln!(
+        line,
+        "\x1b[2m[\x1b[0m\x1b[{style}m{label:<5}\x1b[0m {source}\x1b[2m]\x1b[0m {}\x1b[0m",
+        message.trim_end_matches('\n')
+    );
+    Some(line)
+}
+
+/// Reads a journal field. journalctl encodes values with control characters,
+/// such as ANSI escapes, or invalid UTF-8 as arrays of bytes.
+fn field(entry: &Value, name: &str) -> Option<String> {
+    match entry.get(name)? {
+        Value::String(value) => Some(value.clone()),
+        Value::Array(bytes) => {
+            let bytes = bytes
+                .iter()
+                .map(|byte| byte.as_u64().and_then(|byte| u8::try_from(byte).ok()))
+                .collect::<Option<Vec<u8>>>()?;
+            Some(String::from_utf8_lossy(&bytes).into_owned())
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,280 @@
+use crate::{AnsiColor, AnsiStyle, AnsiText, LineStructure, LogFields};
+use std::ops::Range;
+
+/// Keys holding the fields of a JSON log record, in order of preference.
+const TIMESTAMP_KEYS: [&str; 5] = ["timestamp", "@timestamp", "time", "ts", "datetime"];
+const LEVEL_KEYS: [&str; 5] = ["level", "levelname", "severity", "lvl", "log.level"];
+const SOURCE_KEYS: [&str; 5] = ["target", "logger", "logger_name", "module", "caller"];
+const MESSAGE_KEYS: [&str; 3] = ["message", "msg", "@message"];
+
+/// JSON token kinds, which decide the token's color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Open,
+    Close,
+    Colon,
+    Comma,
+    Key,
+    String,
+    Number,
+    Literal,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    range: Range<usize>,
+}
+
+/// Splits a one-line JSON object into tokens.
+///
+/// Returns `None` unless `line` is exactly one object with balanced
+/// brackets; values are not validated beyond what coloring needs.
+fn tokenize(line: &str) -> Option<Vec<Token>> {
+    let bytes = line.as_bytes();
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut depth = 0usize;
+    let mut position = 0;
+
+    while position < bytes.len() {
+        let start = position;
+        let kind = match bytes[position] {
+            b' ' | b'\t' | b'\r' => {
+                position += 1;
+                continue;
+            }
+            b'{' | b'[' => {
+                depth += 1;
+                position += 1;
+                TokenKind::Open
+            }
+            b'}' | b']' => {
+                depth = depth.checked_sub(1)?;
+                position += 1;
+                TokenKind::Close
+            }
+            b':' => {
+                // The string before a colon is a key.
+                let key = tokens
+                    .last_mut()
+                    .filter(|token| token.kind == TokenKind::String)?;
+                key.kind = TokenKind::Key;
+                position += 1;
+                TokenKind::Colon
+            }
+            b',' => {
+                position += 1;
+                TokenKind::Comma
+            }
+            b'"' => {
+                position += 1;
+                loop {
+                    match bytes.get(position)? {
+                        b'"' => break,
+                        b'\\' => position += 2,
+                        _ => position += 1,
+                    }
+                }
+                position += 1;
+                TokenKind::String
+            }
+            b'-' | b'0'..=b'9' => {
+                position += bytes[position..]
+                    .iter()
+                    .take_while(|byte| {
+                        matches!(byte, b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')
+                    })
+                    .count();
+                TokenKind::Number
+            }
+            _ => {
+                let literal = ["true", "false", "null"]
+                    .into_iter()
+                    .find(|literal| line[position..].starts_with(literal))?;
+                position += literal.len();
+                TokenKind::Literal
+            }
+        };
+        if depth == 0 && kind != TokenKind::Close {
+            return None;
+        }
+        tokens.push(Token {
+            kind,
+            range: start..position,
+        });
+        if depth == 0 {
+            break;
+        }
+    }
+
+    let first = tokens.first()?;
+    let trailing = line[position..].trim_ascii();
+    (depth == 0 && &line[first.range.clone()] == "{" && trailing.is_empty()).then_some(tokens)
+}
+
+/// Returns the style of a token kind.
+fn token_style(kind: TokenKind) -> AnsiStyle {
+    let color = match kind {
+        TokenKind::Key => AnsiColor::Indexed(6),
+        TokenKind::String => AnsiColor::Indexed(2),
+        TokenKind::Number => AnsiColor::Indexed(3),
+        TokenKind::Literal => AnsiColor::Indexed(5),
+        TokenKind::Open | TokenKind::Close | TokenKind::Colon | TokenKind::Comma => {
+            AnsiColor::Default
+        }
+    };
+    AnsiStyle {
+        foreground: color,
+        ..AnsiStyle::default()
+    }
+}
+
+/// Colors a line holding a JSON object, such as a structured log record.
+///
+/// Keys are cyan, strings green, numbers yellow, and `true`, `false`, and
+/// `null` magenta. With `pretty`, the object is spread over indented lines
+/// for an expanded view; otherwise spacing is kept as written. Returns `None`
+/// if the line is not a single JSON object, so callers can fall back to ANSI
+/// parsing.
+///
+/// ```rust
+/// use egui_sgr::json_to_text;
+///
+/// let text = json_to_text(r#"{"level":"info","n":[1,2]}"#, true).unwrap();
+/// assert_eq!(text.text, "{\n  \"level\": \"info\",\n  \"n\": [\n    1,\n    2\n  ]\n}");
+/// assert!(json_to_text("not json", false).is_none());
+/// ```
+#[must_use]
+pub fn json_to_text(line: &str, pretty: bool) -> Option<AnsiText> {
+    profile_scope!("egui_sgr::json_to_text");
+    let tokens = tokenize(line)?;
+    let mut text = AnsiText::new();
+    if !pretty {
+        let mut end = tokens[0].range.start;
+        for token in &tokens {
+            text.push_str(&line[end..token.range.start], AnsiStyle::default());
+            text.push_str(&line[token.range.clone()], token_style(token.kind));
+            end = token.range.end;
+        }
+        return Some(text);
+    }
+
+    let mut depth = 0;
+    let newline = |text: &mut AnsiText, depth: usize| {
+        text.push_str("\n", AnsiStyle::default());
+        text.push_str(&"  ".repeat(depth), AnsiStyle::default());
+    };
+    for (index, token) in tokens.iter().enumerate() {
+        let next_closes = tokens
+            .get(index + 1)
+            .is_some_and(|next| next.kind == TokenKind::Close);
+        match token.kind {
+            TokenKind::Close => {
+                let empty = index > 0 && tokens[index - 1].kind == TokenKind::Open;
+                depth -= 1;
+                if !empty {
+                    newline(&mut text, depth);
+                }
+                text.push_str(&line[token.range.clone()], token_style(token.kind));
+            }
+            TokenKind::Open => {
+                text.push_str(&line[token.range.clone()], token_style(token.kind));
+                depth += 1;
+                if !next_closes {
+                    newline(&mut text, depth);
+                }
+            }
+            TokenKind::Comma => {
+                text.push_str(",", token_style(token.kind));
+                newline(&mut text, depth);
+            }
+            TokenKind::Colon => text.push_str(": ", token_style(token.kind)),
+            _ => text.push_str(&line[token.range.clone()], token_style(token.kind)),
+        }
+    }
+    Some(text)
+}
+
+/// [`LineStructure`] for JSON log records, one object per line.
+///
+/// Fields are taken from well-known keys of the top-level object: the
+/// timestamp from `timestamp`, `@timestamp`, `time`, `ts`, or `datetime`, the
+/// level from `level`, `levelname`, `severity`, `lvl`, or `log.level`, the
+/// source from `target`, `logger`, `logger_name`, `module`, or `caller`, and
+/// the message from `message`, `msg`, or `@message`, also inside a `fields`
+/// object as written by `tracing-subscriber`. Ranges cover string values
+/// without their quotes. Records without a message key use the whole line
+/// as the message; lines that are not JSON objects are not recognized.
+///
+/// ```rust
+/// use egui_sgr::{JsonLogFormat, LineStructure, LogLevel};
+///
+/// let line = r#"{"ts":"2024-05-01T12:00:00Z","level":"warn","msg":"slow query"}"#;
+/// let fields = JsonLogFormat.parse(line).unwrap();
+/// assert_eq!(&line[fields.timestamp.clone().unwrap()], "2024-05-01T12:00:00Z");
+/// assert_eq!(fields.log_level(line), Some(LogLevel::Warn));
+/// assert_eq!(&line[fields.message], "slow query");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JsonLogFormat;
+
+impl LineStructure for JsonLogFormat {
+    fn parse(&self, line: &str) -> Option<LogFields> {
+        let tokens = tokenize(line)?;
+        // The best key found so far for each field, as an index into its key
+        // list, with the value's range.
+        let mut found: [Option<(usize, Range<usize>)>; 4] = Default::default();
+        let mut depth = 0;
+        let mut in_fields = false;
+
+        for (index, token) in tokens.iter().enumerate() {
+            match token.kind {
+                TokenKind::Open => depth += 1,
+                TokenKind::Close => {
+                    depth -= 1;
+                    in_fields &= depth > 1;
+                }
+                TokenKind::Key => {
+                    let key = &line[token.range.start + 1..token.range.end - 1];
+                    let Some(value) = tokens.get(index + 2) else {
+                        continue;
+                    };
+                    if depth == 1 && key == "fields" && value.kind == TokenKind::Open {
+                        in_fields = true;
+                        continue;
+                    }
+                    let nested = depth == 2 && in_fields;
+                    if depth != 1 && !nested {
+                        continue;
+                    }
+                    let range = match value.kind {
+                        TokenKind::String => value.range.start + 1..value.range.end - 1,
+                        TokenKind::Number => value.range.clone(),
+                        _ => continue,
+                    };
+                    let lists: [&[&str]; 4] =
+                        [&TIMESTAMP_KEYS, &LEVEL_KEYS, &SOURCE_KEYS, &MESSAGE_KEYS];
+                    for (field, keys) in lists.into_iter().enumerate() {
+                        if nested && field != 3 {
+                            continue;
+                        }
+                        if let Some(rank) = keys.iter().position(|known| *known == key)
+                            && found[field].as_ref().is_none_or(|(best, _)| rank < *best)
+                        {
+                            found[field] = Some((rank, range.clone()));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let [timestamp, level, source, message] = found.map(|field| field.map(|(_, range)| range));
+        Some(LogFields {
+            timestamp,
+            level,
+            source,
+            message: message.unwrap_or(0..line.len()),
+        })
+    }
+}
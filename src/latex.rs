@@ -0,0 +1,131 @@
+use crate::resolve::resolve_style;
+use crate::{AnsiSpan, AnsiStyle, EguiAnsiTheme, ansi_to_text};
+use std::fmt::Write;
+
+/// Options for LaTeX export.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatexOptions {
+    /// Theme used to resolve ANSI colors.
+    pub theme: EguiAnsiTheme,
+    /// Whether to wrap the output in a fancyvrb `Verbatim` environment with
+    /// `commandchars=\\\{\}`, which keeps spacing and line breaks as written.
+    pub fancyvrb: bool,
+}
+
+impl Default for LatexOptions {
+    fn default() -> Self {
+        Self {
+            theme: EguiAnsiTheme::default(),
+            fancyvrb: true,
+        }
+    }
+}
+
+/// Converts ANSI text to LaTeX using `xcolor`.
+///
+/// Colored runs become `\textcolor[HTML]{RRGGBB}{...}`; text in the theme's
+/// default foreground is left uncolored so it follows the document. Bold,
+/// italic, underline, and strikethrough map to `\textbf`, `\textit`,
+/// `\underline`, and `\sout` (from `ulem`). Backgrounds are dropped.
+#[must_use]
+pub fn ansi_to_latex(input: &str, options: &LatexOptions) -> String {
+    let text = ansi_to_text(input);
+    runs_to_latex(text.iter(), options)
+}
+
+/// Converts parsed spans to LaTeX using `xcolor`.
+#[must_use]
+pub fn spans_to_latex(spans: &[AnsiSpan], options: &LatexOptions) -> String {
+    runs_to_latex(
+        spans.iter().map(|span| (span.text.as_str(), span.style)),
+        options,
+    )
+}
+
+fn runs_to_latex<'a>(
+    runs: impl Iterator<Item = (&'a str, AnsiStyle)>,
+    options: &LatexOptions,
+) -> String {
+    let theme = &options.theme;
+    let mut latex = String::new();
+
+    if options.fancyvrb {
+        latex.push_str("\\begin{Verbatim}[commandchars=\\\\\\{\\}]\n");
+    }
+
+    for (text, style) in runs {
+        if text.is_empty() {
+            continue;
+        }
+
+        let resolved = resolve_style(&style, theme).opaque(theme.default_background);
+        let mut groups = 0;
+
+        if resolved.foreground != theme.default_foreground {
+            let [r, g, b, _] = resolved.foreground.to_array();
+            let _ = write!(latex, "\\textcolor[HTML]{{{r:02X}{g:02X}{b:02X}}}{{");
+            groups += 1;
+        }
+        for (enabled, command) in [
+            (resolved.bold, "\\textbf{"),
+            (resolved.italic, "\\textit{"),
+            (resolved.underline.is_some(), "\\underline{"),
+            (resolved.strikethrough, "\\sout{"),
+        ] {
+            if enabled {
+                latex.push_str(command);
+                groups += 1;
+            }
+        }
+
+        if options.fancyvrb {
+            push_verbatim_escaped(&mut latex, text);
+        } else {
+            push_escaped(&mut latex, text);
+        }
+        for _ in 0..groups {
+            latex.push('}');
+        }
+    }
+
+    if options.fancyvrb {
+        if !latex.ends_with('\n') {
+            latex.push('\n');
+        }
+        latex.push_str("\\end{Verbatim}\n");
+    }
+
+    latex
+}
+
+/// Escapes the command characters of a `commandchars=\\\{\}` environment.
+fn push_verbatim_escaped(latex: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '\\' => latex.push_str("\\textbackslash{}"),
+            '{' => latex.push_str("\\{"),
+            '}' => latex.push_str("\\}"),
+            '\r' => {}
+            c => latex.push(c),
+        }
+    }
+}
+
+/// Escapes LaTeX special characters for running text.
+fn push_escaped(latex: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '\\' => latex.push_str("\\textbackslash{}"),
+            '~' => latex.push_str("\\textasciitilde{}"),
+            '^' => latex.push_str("\\textasciicircum{}"),
+            '{' | '}' | '$' | '&' | '#' | '_' | '%' => {
+                latex.push('\\');
+                latex.push(c);
+            }
+            // `\leavevmode` keeps empty lines from ending a missing line.
+            '\n' => latex.push_str("\\leavevmode\\\\\n"),
+            '\r' => {}
+            c => latex.push(c),
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use crate::{AnsiColor, AnsiDocument, AnsiStyle, AnsiText, OverlayLayer, OverlayStyle};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+/// Which side of the text a color is painted on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorRole {
+    /// Text color.
+    Foreground,
+    /// Cell background color.
+    Background,
+}
+
+/// How often one color appears in a document, for building a legend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ColorUsage {
+    /// The color as written in the input.
+    pub color: AnsiColor,
+    /// Whether the color is painted on the text or behind it.
+    pub role: ColorRole,
+    /// Number of retained lines using the color.
+    pub lines: usize,
+    /// Number of characters painted with the color.
+    pub chars: usize,
+}
+
+/// Returns the colors used by the retained lines of `document`, most widely
+/// used first.
+///
+/// Colors are counted as they appear on screen: reverse video swaps the
+/// roles, hidden text is skipped, and foreground colors of whitespace-only
+/// runs are left out because nothing shows them. Default colors are not
+/// listed.
+#[must_use]
+pub fn color_usage<M: Default>(document: &AnsiDocument<M>) -> Vec<ColorUsage> {
+    profile_scope!("egui_sgr::color_usage");
+    let mut usage: HashMap<(AnsiColor, ColorRole), ColorUsage> = HashMap::new();
+    let mut on_line = HashSet::new();
+
+    for line in document.lines() {
+        on_line.clear();
+        for (text, style) in line.text.iter() {
+            for (color, role) in painted_colors(text, &style) {
+                let entry = usage.entry((color, role)).or_insert(ColorUsage {
+                    color,
+                    role,
+                    lines: 0,
+                    chars: 0,
+                });
+                entry.chars += text.chars().count();
+                if on_line.insert((color, role)) {
+                    entry.lines += 1;
+                }
+            }
+        }
+    }
+
+    let mut usage: Vec<_> = usage.into_values().collect();
+    usage.sort_by(|a, b| {
+        (b.lines, b.chars)
+            .cmp(&(a.lines, a.chars))
+            .then_with(|| color_order(a).cmp(&color_order(b)))
+    });
+    usage
+}
+
+/// Returns the indices of the retained lines that show `color` in `role`,
+/// for filtering a view down to them.
+#[must_use]
+pub fn lines_with_color<M: Default>(
+    document: &AnsiDocument<M>,
+    color: AnsiColor,
+    role: ColorRole,
+) -> Vec<usize> {
+    document
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !color_ranges(&line.text, color, role).is_empty())
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Highlights the text shown in `color` in `role` and returns the number of
+/// lines it appears on.
+///
+/// Like [`highlight_matches`](crate::highlight_matches), previous overlays on
+/// [`OverlayLayer::SEARCH`] are replaced, so picking a legend entry and
+/// searching take turns.
+pub fn highlight_color<M: Default>(
+    document: &mut AnsiDocument<M>,
+    color: AnsiColor,
+    role: ColorRole,
+    style: OverlayStyle,
+) -> usize {
+    profile_scope!("egui_sgr::highlight_color");
+    document.clear_overlays(OverlayLayer::SEARCH);
+
+    let first = document.first_line_number();
+    let ranges: Vec<_> = document
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (first + index as u64, color_ranges(&line.text, color, role)))
+        .filter(|(_, ranges)| !ranges.is_empty())
+        .collect();
+
+    let count = ranges.len();
+    for (line, ranges) in ranges {
+        for range in ranges {
+            document.add_overlay(OverlayLayer::SEARCH, line, range, style);
+        }
+    }
+    count
+}
+
+/// Returns the merged byte ranges of `text` shown in `color` in `role`.
+fn color_ranges(text: &AnsiText, color: AnsiColor, role: ColorRole) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for run in &text.runs {
+        let run_text = &text.text[run.range.clone()];
+        if !painted_colors(run_text, &run.style).any(|painted| painted == (color, role)) {
+            continue;
+        }
+        match ranges.last_mut() {
+            Some(last) if last.end == run.range.start => last.end = run.range.end,
+            _ => ranges.push(run.range.clone()),
+        }
+    }
+    ranges
+}
+
+/// Returns the non-default colors a run shows on screen.
+fn painted_colors(text: &str, style: &AnsiStyle) -> impl Iterator<Item = (AnsiColor, ColorRole)> {
+    let (foreground, background) = if style.reverse {
+        (style.background, style.foreground)
+    } else {
+        (style.foreground, style.background)
+    };
+    let visible = !style.hidden;
+    let shows_foreground = visible && !text.trim().is_empty();
+    [
+        (foreground, ColorRole::Foreground, shows_foreground),
+        (background, ColorRole::Background, visible),
+    ]
+    .into_iter()
+    .filter(|&(color, _, shown)| shown && color != AnsiColor::Default)
+    .map(|(color, role, _)| (color, role))
+}
+
+/// Orders ties by role, then palette index, then RGB value.
+fn color_order(usage: &ColorUsage) -> (bool, u32) {
+    let key = match usage.color {
+        AnsiColor::Default => 0,
+        AnsiColor::Indexed(index) => u32::from(index),
+        AnsiColor::Rgb(r, g, b) => 256 + u32::from_be_bytes([0, r, g, b]),
+    };
+    (usage.role == ColorRole::Background, key)
+}
@@ -1,5 +1,7 @@
-#![forbid(unsafe_code)]
+#![cfg_attr(not(feature = "mmap"), forbid(unsafe_code))]
+#![cfg_attr(feature = "mmap", deny(unsafe_code))]
 #![warn(missing_docs, rustdoc::broken_intra_doc_links)]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
 //! Convert ANSI/SGR text into egui text representations.
 //!
@@ -7,34 +9,315 @@
 //! single logical string with style changes inside it.
 //!
 //! ```rust
+//! # #[cfg(feature = "egui")] {
 //! use egui_sgr::{ansi_to_layout_job, EguiAnsiTheme};
 //!
 //! let theme = EguiAnsiTheme::default();
 //! let job = ansi_to_layout_job("\x1b[31mred\x1b[0m default", &theme);
 //! assert_eq!(job.text, "red default");
+//! # }
 //! ```
+//!
+//! Parsing, the style model, themes, and the text exporters do not need
+//! egui. Disabling the default `egui` feature drops the dependency; theme
+//! colors are then plain RGBA [`Color32`] values from `ecolor`.
+//!
+//! Without the default `std` feature the crate is `no_std` and needs only
+//! `alloc`: the style model, SGR handling, [`AnsiStreamParser`], and
+//! [`spans_to_ansi`] are available, everything else needs `std`.
+
+extern crate alloc;
+
+/// Opens a puffin profiling scope when the `profiling` feature is enabled.
+macro_rules! profile_scope {
+    ($name:expr) => {
+        #[cfg(feature = "profiling")]
+        puffin::profile_scope!($name);
+    };
+}
 
+#[cfg(feature = "std")]
+mod accessibility;
+#[cfg(feature = "std")]
+mod ansi_art;
+#[cfg(feature = "asciicast")]
+mod asciicast;
+mod backend;
+#[cfg(feature = "std")]
+mod bbcode;
+#[cfg(feature = "bidi")]
+mod bidi;
+#[cfg(feature = "egui")]
+mod cache;
+#[cfg(any(feature = "termwiz", feature = "alacritty_terminal"))]
+mod cells;
+#[cfg(feature = "std")]
+mod color;
+#[cfg(feature = "regex")]
+mod colorizer;
+#[cfg(feature = "std")]
+mod columns;
+#[cfg(feature = "std")]
+mod compare;
+#[cfg(feature = "std")]
+mod damage;
+#[cfg(feature = "std")]
+mod diff;
+#[cfg(feature = "std")]
+mod document;
+#[cfg(feature = "egui")]
 mod egui_render;
+mod emit;
+#[cfg(feature = "std")]
+mod encoding;
+#[cfg(feature = "std")]
+mod escape;
+#[cfg(feature = "std")]
+mod files;
+#[cfg(feature = "std")]
+mod filter;
+#[cfg(feature = "proptest")]
+mod generate;
+#[cfg(feature = "egui")]
+mod glyphs;
+#[cfg(feature = "std")]
+mod html;
+#[cfg(feature = "html-import")]
+mod html_import;
+#[cfg(feature = "std")]
+mod intern;
+#[cfg(all(feature = "journald", target_os = "linux"))]
+mod journald;
+#[cfg(feature = "std")]
+mod json;
+#[cfg(feature = "std")]
+mod latex;
+#[cfg(feature = "std")]
+mod legend;
+#[cfg(feature = "std")]
+mod links;
+#[cfg(feature = "std")]
+mod logfmt;
+#[cfg(feature = "log")]
+mod logger;
+#[cfg(feature = "std")]
+mod minecraft;
+#[cfg(feature = "std")]
+mod mirc;
+#[cfg(all(feature = "mmap", not(target_arch = "wasm32")))]
+mod mmap;
 mod model;
+#[cfg(all(feature = "network", not(target_arch = "wasm32")))]
+mod network;
+#[cfg(feature = "std")]
+mod overlay;
+#[cfg(feature = "std")]
+mod packed;
 mod parser;
+#[cfg(feature = "std")]
+mod profile;
+#[cfg(feature = "std")]
+mod quantize;
+#[cfg(feature = "egui")]
+mod raster;
+#[cfg(feature = "regex")]
+mod redact;
+#[cfg(feature = "std")]
+mod repaint;
+#[cfg(feature = "std")]
+mod resolve;
+#[cfg(feature = "std")]
+mod rtf;
+#[cfg(feature = "std")]
+mod search;
+#[cfg(all(feature = "serialport", not(target_arch = "wasm32")))]
+mod serial;
 mod sgr;
+#[cfg(feature = "std")]
+mod shared;
+#[cfg(feature = "test-utils")]
+mod snapshot;
+mod source;
+mod stats;
+#[cfg(feature = "std")]
+mod strict;
+#[cfg(feature = "std")]
+mod structure;
+#[cfg(feature = "syntect")]
+mod syntect_bridge;
+#[cfg(any(feature = "nu-ansi-term", feature = "ansi_term"))]
+mod term_style;
+#[cfg(feature = "std")]
 mod theme;
-
-pub use egui_render::{ansi_bytes_to_layout_job, ansi_to_layout_job, spans_to_layout_job};
-pub use model::{AnsiColor, AnsiIntensity, AnsiSpan, AnsiStyle, UnderlineStyle};
-pub use parser::{AnsiSpanBuffer, AnsiStreamParser, ansi_bytes_to_spans, ansi_to_spans};
+#[cfg(feature = "tracing")]
+mod tracing_layer;
+#[cfg(feature = "egui")]
+mod ui_ext;
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+mod worker;
+#[cfg(feature = "std")]
+mod workspace;
+#[cfg(feature = "std")]
+mod writer;
+
+#[cfg(feature = "std")]
+pub use accessibility::{accessible_description, color_name};
+#[cfg(feature = "std")]
+pub use ansi_art::{AnsiArt, SauceRecord, cp437_to_string};
+#[cfg(feature = "asciicast")]
+pub use asciicast::{
+    Asciicast, AsciicastError, AsciicastEvent, AsciicastHeader, AsciicastPlayer, AsciicastRecorder,
+};
+pub use backend::AnsiBackend;
+#[cfg(feature = "std")]
+pub use bbcode::{ansi_to_bbcode, spans_to_bbcode};
+#[cfg(feature = "bidi")]
+pub use bidi::BidiText;
+#[cfg(feature = "egui")]
+pub use cache::LayoutJobCache;
+#[cfg(feature = "alacritty_terminal")]
+pub use cells::alacritty_cells_to_spans;
+#[cfg(feature = "termwiz")]
+pub use cells::termwiz_cells_to_spans;
+#[cfg(feature = "std")]
+pub use color::{ColorMetric, Lab, Oklab, nearest_color};
+#[cfg(feature = "regex")]
+pub use colorizer::Colorizer;
+#[cfg(feature = "std")]
+pub use columns::{FoldedText, align_columns, slice_ansi, truncate_ansi, visible_width, wrap_ansi};
+#[cfg(feature = "std")]
+pub use compare::{AlignKind, AlignedRow, align_documents, highlight_changes};
+#[cfg(feature = "std")]
+pub use damage::DocumentDamage;
+#[cfg(feature = "std")]
+pub use diff::DiffHighlighter;
+#[cfg(feature = "std")]
+pub use document::{AnsiDocument, AnsiLine, SegmentTag};
+#[cfg(feature = "std")]
+pub use ecolor::Color32;
+#[cfg(feature = "egui")]
+pub use egui_render::{
+    ansi_bytes_to_layout_job, ansi_to_layout_job, interned_spans_to_layout_job,
+    spans_to_layout_job, spans_to_text_formats,
+};
+pub use emit::spans_to_ansi;
+#[cfg(feature = "std")]
+pub use encoding::TextEncoding;
+#[cfg(feature = "std")]
+pub use escape::{escape_for_display, to_control_pictures, unescape_from_display};
+#[cfg(feature = "egui")]
+pub use files::DroppedAnsiFile;
+#[cfg(feature = "std")]
+pub use files::{AnsiFile, AnsiFileError, AnsiFileKind};
+#[cfg(feature = "persistence")]
+pub use filter::FilterPresets;
+#[cfg(feature = "std")]
+pub use filter::LogFilter;
+#[cfg(feature = "proptest")]
+pub use generate::{GeneratedAnsi, ansi_stream_strategy};
+#[cfg(feature = "egui")]
+pub use glyphs::{PowerlineGlyphs, add_symbol_font, is_powerline_glyph, paint_powerline_glyph};
+#[cfg(feature = "std")]
+pub use html::{HtmlOptions, ansi_to_html, spans_to_html};
+#[cfg(feature = "html-import")]
+pub use html_import::{html_to_spans, html_to_text};
+#[cfg(feature = "std")]
+pub use intern::{AnsiSpanInterner, InternedSpan};
+#[cfg(all(feature = "journald", target_os = "linux"))]
+pub use journald::{AnsiJournal, AnsiJournalFollower};
+#[cfg(feature = "std")]
+pub use json::{JsonLogFormat, json_to_text};
+#[cfg(feature = "std")]
+pub use latex::{LatexOptions, ansi_to_latex, spans_to_latex};
+#[cfg(feature = "std")]
+pub use legend::{ColorRole, ColorUsage, color_usage, highlight_color, lines_with_color};
+#[cfg(feature = "std")]
+pub use links::{AnsiHyperlink, LinkPolicy, ansi_hyperlinks, find_urls};
+#[cfg(feature = "std")]
+pub use logfmt::{LogfmtFormat, logfmt_to_text};
+#[cfg(feature = "log")]
+pub use logger::AnsiLogger;
+#[cfg(feature = "std")]
+pub use minecraft::{MinecraftOptions, Obfuscation, minecraft_to_spans};
+#[cfg(feature = "std")]
+pub use mirc::{mirc_to_spans, mirc_to_text};
+#[cfg(all(feature = "mmap", not(target_arch = "wasm32")))]
+pub use mmap::AnsiMappedFile;
+pub use model::{
+    AnsiColor, AnsiIntensity, AnsiSpan, AnsiStyle, AnsiStyleRun, AnsiText, UnderlineStyle,
+};
+#[cfg(all(feature = "network", not(target_arch = "wasm32")))]
+pub use network::{AnsiNetworkConnection, AnsiNetworkSource};
+#[cfg(feature = "std")]
+pub use overlay::{AnsiOverlay, OverlayLayer, OverlayStyle};
+#[cfg(feature = "std")]
+pub use packed::{PackedCell, PackedCells};
+#[cfg(feature = "std")]
+pub use parser::AnsiSpanBuffer;
+pub use parser::{
+    AnsiStreamParser, ParseBudget, ansi_bytes_to_spans, ansi_bytes_to_text, ansi_lines_to_spans,
+    ansi_style_at, ansi_to_spans, ansi_to_text, strip_ansi,
+};
+#[cfg(feature = "std")]
+pub use profile::{CursorShape, TerminalProfile};
+#[cfg(feature = "std")]
+pub use quantize::{ColorDepth, nearest_16, nearest_256, spans_to_ansi_with_depth};
+#[cfg(feature = "egui")]
+pub use raster::render_ansi_to_image;
+#[cfg(feature = "png")]
+pub use raster::render_ansi_to_png;
+#[cfg(feature = "regex")]
+pub use redact::Redactor;
+#[cfg(feature = "std")]
+pub use rtf::{ansi_to_rtf, spans_to_rtf};
+#[cfg(feature = "regex")]
+pub use search::highlight_matches;
+#[cfg(feature = "std")]
+pub use search::{AnsiMatch, SearchPattern};
+#[cfg(all(feature = "serialport", not(target_arch = "wasm32")))]
+pub use serial::{AnsiSerialConnection, AnsiSerialMonitor};
+#[cfg(feature = "std")]
+pub use shared::SharedAnsiDocument;
+#[cfg(feature = "test-utils")]
+pub use snapshot::{snapshot_ansi, snapshot_document, snapshot_spans, snapshot_text};
+pub use source::IntoAnsiSource;
+pub use stats::ParseStats;
+#[cfg(feature = "std")]
+pub use strict::{AnsiError, AnsiLimits, try_ansi_bytes_to_spans, try_ansi_to_spans};
+#[cfg(feature = "std")]
+pub use structure::{CommonLogFormat, LineStructure, LogFields, LogLevel, LogRow};
+#[cfg(feature = "syntect")]
+pub use syntect_bridge::syntect_ranges_to_spans;
+#[cfg(feature = "ansi_term")]
+pub use term_style::ansi_term_strings_to_spans;
+#[cfg(feature = "nu-ansi-term")]
+pub use term_style::nu_ansi_strings_to_spans;
+#[cfg(feature = "std")]
 pub use theme::EguiAnsiTheme;
+#[cfg(feature = "tracing")]
+pub use tracing_layer::AnsiTracingLayer;
+#[cfg(feature = "egui")]
+pub use ui_ext::AnsiUiExt;
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+pub use worker::AnsiParserWorker;
+#[cfg(feature = "std")]
+pub use workspace::{AnsiSession, AnsiWorkspace};
+#[cfg(feature = "std")]
+pub use writer::AnsiWriter;
 
 /// Small compile-checked usage sample used by examples and documentation.
+#[cfg(feature = "egui")]
 pub fn example_usage() {
     let theme = EguiAnsiTheme::default();
     let _job = ansi_to_layout_job("\x1b[38;5;208morange\x1b[0m", &theme);
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
+    #[cfg(feature = "egui")]
     use egui::Stroke;
+    use std::borrow::Cow;
 
     fn text_of(spans: &[AnsiSpan]) -> String {
         spans.iter().map(|span| span.text.as_str()).collect()
@@ -146,6 +429,7 @@ mod tests {
         assert_eq!(buffer.spans()[0].style.foreground, AnsiColor::Indexed(2));
     }
 
+    #[cfg(feature = "egui")]
     #[test]
     fn layout_job_contains_expected_sections() {
         let theme = EguiAnsiTheme::default();
@@ -166,6 +450,7 @@ mod tests {
         assert_eq!(spans[0].style.foreground, AnsiColor::Indexed(1));
     }
 
+    #[cfg(feature = "egui")]
     #[test]
     fn layout_job_merges_redundant_same_style_sections() {
         let theme = EguiAnsiTheme::default();
@@ -177,6 +462,7 @@ mod tests {
         assert_eq!(job.sections[0].format.color, theme.palette[1]);
     }
 
+    #[cfg(feature = "egui")]
     #[test]
     fn direct_layout_job_matches_span_rendering() {
         let theme = EguiAnsiTheme::default();
@@ -190,6 +476,7 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "egui")]
     #[test]
     fn layout_job_maps_background_underline_and_strikethrough() {
         let theme = EguiAnsiTheme::default();
@@ -207,6 +494,7 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "egui")]
     #[test]
     fn reverse_video_is_render_time_style() {
         let spans = ansi_to_spans("\x1b[31;42;7mSwap");
@@ -307,6 +595,7 @@ mod tests {
         assert_eq!(spans[2].style.underline, UnderlineStyle::None);
     }
 
+    #[cfg(feature = "egui")]
     #[test]
     fn ansi_bytes_to_layout_job_matches_string_api() {
         let theme = EguiAnsiTheme::default();
@@ -363,6 +652,7 @@ mod tests {
         assert_eq!(spans[1].style.underline_color, None);
     }
 
+    #[cfg(feature = "egui")]
     #[test]
     fn reverse_reset_restores_normal_rendering() {
         let spans = ansi_to_spans("\x1b[31;42;7mA\x1b[27mB");
@@ -375,6 +665,7 @@ mod tests {
         assert_eq!(job.sections[1].format.background, theme.palette[2]);
     }
 
+    #[cfg(feature = "egui")]
     #[test]
     fn default_theme_renders_bold_low_colors_as_bright() {
         let theme = EguiAnsiTheme::default();
@@ -395,4 +686,3006 @@ mod tests {
         assert_eq!(buffer.spans()[0].text, "Plain");
         assert_eq!(buffer.spans()[0].style.foreground, AnsiColor::Default);
     }
+
+    #[cfg(feature = "egui")]
+    #[test]
+    fn layout_job_cache_reuses_jobs_until_epoch_changes() {
+        let theme = EguiAnsiTheme::default();
+        let mut cache = LayoutJobCache::new();
+
+        let first = cache.layout_job("\x1b[31mred", 200.0, &theme);
+        let second = cache.layout_job("\x1b[31mred", 200.0, &theme);
+        let wider = cache.layout_job("\x1b[31mred", 300.0, &theme);
+
+        assert!(std::sync::Arc::ptr_eq(&first, &second));
+        assert!(!std::sync::Arc::ptr_eq(&first, &wider));
+        assert_eq!(first.wrap.max_width, 200.0);
+        assert_eq!(cache.len(), 2);
+
+        cache.set_epoch(1);
+        let refreshed = cache.layout_job("\x1b[31mred", 200.0, &theme);
+        assert!(!std::sync::Arc::ptr_eq(&first, &refreshed));
+    }
+
+    #[cfg(feature = "egui")]
+    #[test]
+    fn layout_job_cache_evicts_unused_lines() {
+        let theme = EguiAnsiTheme::default();
+        let mut cache = LayoutJobCache::new();
+
+        let _ = cache.layout_job("a", 100.0, &theme);
+        let _ = cache.layout_job("b", 100.0, &theme);
+        cache.evict_unused();
+        let _ = cache.layout_job("a", 100.0, &theme);
+        cache.evict_unused();
+
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[cfg(feature = "egui")]
+    #[test]
+    fn escape_free_input_matches_stream_parsing() {
+        let theme = EguiAnsiTheme::default();
+        let input = "plain 你好\tline\r\n";
+        let mut parser = AnsiStreamParser::new();
+        let streamed = parser.push_str(input);
+
+        assert_eq!(ansi_to_spans(input), streamed);
+        assert_eq!(
+            ansi_to_layout_job(input, &theme),
+            spans_to_layout_job(&streamed, &theme)
+        );
+        assert!(ansi_to_spans("").is_empty());
+    }
+
+    #[test]
+    fn escape_free_input_still_drops_other_controls() {
+        let spans = ansi_to_spans("bell\x07 \u{85}done");
+
+        assert_eq!(text_of(&spans), "bell done");
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn parser_worker_parses_chunks_off_thread() {
+        let worker = AnsiParserWorker::new();
+
+        worker.push_bytes(b"\x1b[3".to_vec());
+        worker.push_str("1mRe");
+        worker.sender().send(b"d\x1b[0m done".to_vec()).unwrap();
+        let spans = worker.finish();
+
+        assert_eq!(text_of(&spans), "Red done");
+        assert_eq!(spans[0].style.foreground, AnsiColor::Indexed(1));
+        assert_eq!(spans[1].style.foreground, AnsiColor::Default);
+    }
+
+    #[test]
+    fn span_buffer_extend_merges_worker_batches() {
+        let mut buffer = AnsiSpanBuffer::new();
+
+        buffer.extend(ansi_to_spans("\x1b[32mHel"));
+        buffer.extend(ansi_to_spans("\x1b[32mlo"));
+
+        assert_eq!(buffer.spans().len(), 1);
+        assert_eq!(buffer.spans()[0].text, "Hello");
+    }
+
+    #[cfg(all(feature = "mmap", not(target_arch = "wasm32")))]
+    #[allow(unsafe_code)]
+    #[test]
+    fn mapped_file_indexes_and_parses_lines_lazily() {
+        let path = std::env::temp_dir().join(format!("egui_sgr_mmap_{}.log", std::process::id()));
+        std::fs::write(&path, b"\x1b[31mred\r\nplain\n\nlast").unwrap();
+        // SAFETY: the test owns the file and does not change it while mapped.
+        let mut file = unsafe { AnsiDocument::<()>::open_mmap(&path) }.unwrap();
+
+        assert_eq!(file.line(0), Some(&b"\x1b[31mred"[..]));
+        assert_eq!(file.indexed_line_count(), 1);
+        assert_eq!(file.line_spans(1).unwrap()[0].text, "plain");
+        assert_eq!(file.line(2), Some(&b""[..]));
+        assert_eq!(file.line_count(), 4);
+        assert_eq!(file.line(4), None);
+        assert_eq!(
+            file.line_spans(0).unwrap()[0].style.foreground,
+            AnsiColor::Indexed(1)
+        );
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(feature = "egui")]
+    #[test]
+    fn interner_shares_repeated_span_text() {
+        let theme = EguiAnsiTheme::default();
+        let mut interner = AnsiSpanInterner::new();
+        let first = interner.intern_spans(&ansi_to_spans("\x1b[31mERROR\x1b[0m one"));
+        let second = interner.intern_spans(&ansi_to_spans("\x1b[31mERROR\x1b[0m two"));
+
+        assert!(std::sync::Arc::ptr_eq(&first[0].text, &second[0].text));
+        assert_eq!(interner.len(), 3);
+        assert_eq!(
+            interned_spans_to_layout_job(&first, &theme),
+            ansi_to_layout_job("\x1b[31mERROR\x1b[0m one", &theme)
+        );
+
+        drop(second);
+        interner.shrink();
+        assert_eq!(interner.len(), 2);
+    }
+
+    #[test]
+    fn ansi_lines_to_spans_threads_style_across_lines() {
+        let lines = ansi_lines_to_spans(["\x1b[31mred", "still red\x1b[0m", "", "plain"]);
+
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[1][0].text, "still red");
+        assert_eq!(lines[1][0].style.foreground, AnsiColor::Indexed(1));
+        assert!(lines[2].is_empty());
+        assert_eq!(lines[3][0].style.foreground, AnsiColor::Default);
+    }
+
+    #[cfg(feature = "egui")]
+    #[test]
+    fn ansi_to_text_matches_span_output() {
+        let theme = EguiAnsiTheme::default();
+        let input = "A\x1b[31mRed\x1b[31m More\x1b[0m\x1b[1mZ";
+        let text = ansi_to_text(input);
+
+        assert_eq!(text.text, "ARed MoreZ");
+        assert_eq!(text.runs.len(), 3);
+        assert_eq!(text.runs[1].range, 1..9);
+        assert_eq!(text.to_spans(), ansi_to_spans(input));
+        assert_eq!(
+            text.to_layout_job(&theme),
+            ansi_to_layout_job(input, &theme)
+        );
+        assert_eq!(AnsiText::from(ansi_to_spans(input).as_slice()), text);
+    }
+
+    #[test]
+    fn span_buffer_processes_queued_bytes_within_budget() {
+        let mut buffer = AnsiSpanBuffer::new();
+
+        buffer.queue_str("\x1b[31mred ");
+        buffer.queue_str("more");
+        assert_eq!(buffer.pending_bytes(), 13);
+
+        assert!(buffer.process_pending(ParseBudget::Bytes(5)));
+        assert!(buffer.spans().is_empty());
+        assert!((buffer.progress() - 5.0 / 13.0).abs() < f32::EPSILON);
+
+        assert!(!buffer.process_pending(ParseBudget::Bytes(100)));
+        assert_eq!(buffer.progress(), 1.0);
+        assert_eq!(buffer.spans()[0].text, "red more");
+        assert_eq!(buffer.spans()[0].style.foreground, AnsiColor::Indexed(1));
+    }
+
+    #[test]
+    fn span_buffer_push_parses_queued_bytes_first() {
+        let mut buffer = AnsiSpanBuffer::new();
+
+        buffer.queue_str("\x1b[32mqueued ");
+        buffer.push_str("pushed");
+
+        assert_eq!(buffer.pending_bytes(), 0);
+        assert_eq!(text_of(buffer.spans()), "queued pushed");
+        assert!(!buffer.process_pending(ParseBudget::Time(std::time::Duration::ZERO)));
+    }
+
+    #[test]
+    fn span_buffer_keeps_order_while_queue_refills() {
+        let mut buffer = AnsiSpanBuffer::new();
+        let mut expected = String::new();
+        for frame in 0..50 {
+            let line = format!("\x1b[3{}mline {frame}\x1b[0m\n", frame % 8);
+            expected.push_str(&strip_ansi(&line));
+            buffer.queue_str(&line);
+            let _ = buffer.process_pending(ParseBudget::Bytes(7));
+        }
+        assert!(buffer.pending_bytes() > 0);
+        assert!(!buffer.process_pending(ParseBudget::Bytes(usize::MAX)));
+        assert_eq!(text_of(buffer.spans()), expected);
+    }
+
+    #[cfg(feature = "egui")]
+    #[test]
+    fn text_format_iterator_matches_layout_job_sections() {
+        let theme = EguiAnsiTheme::default();
+        let spans = ansi_to_spans("plain \x1b[4;38;5;208morange");
+        let job = spans_to_layout_job(&spans, &theme);
+        let formats: Vec<_> = spans_to_text_formats(&spans, &theme).collect();
+
+        assert_eq!(formats.len(), job.sections.len());
+        assert_eq!(formats[1].0, "orange");
+        assert_eq!(formats[1].1, job.sections[1].format);
+        assert_eq!(theme.text_format(&spans[1].style), formats[1].1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trips_spans_text_and_theme() {
+        let spans = ansi_to_spans("\x1b[1;38;2;1;2;3;4:3mStyled\x1b[0m plain");
+        let json = serde_json::to_string(&spans).unwrap();
+        assert_eq!(serde_json::from_str::<Vec<AnsiSpan>>(&json).unwrap(), spans);
+
+        let text = ansi_to_text("\x1b[31mred");
+        let json = serde_json::to_string(&text).unwrap();
+        assert_eq!(serde_json::from_str::<AnsiText>(&json).unwrap(), text);
+
+        let theme = EguiAnsiTheme::default();
+        let json = serde_json::to_value(&theme).unwrap();
+        assert_eq!(
+            json["default_background"],
+            serde_json::json!([0, 0, 0, 255])
+        );
+        assert_eq!(
+            serde_json::from_value::<EguiAnsiTheme>(json).unwrap(),
+            theme
+        );
+    }
+
+    #[test]
+    fn spans_to_ansi_round_trips_styles() {
+        let input = "plain \x1b[1;3;4:3;31;48;5;21;58;2;1;2;3mall\x1b[22;24;9;97mmore\x1b[0m \x1b[2;7;8;38;2;4;5;6mrgb";
+        let spans = ansi_to_spans(input);
+        let emitted = spans_to_ansi(&spans);
+
+        assert_eq!(ansi_to_spans(&emitted), spans);
+        assert!(emitted.starts_with(
+            "plain \x1b[1;3;4:3;31;48;5;21;58;2;1;2;3mall\x1b[22;24;9;97mmore\x1b[0m "
+        ));
+        assert!(emitted.ends_with("\x1b[0m"));
+        assert_eq!(ansi_to_text(input).to_ansi(), emitted);
+        assert_eq!(spans_to_ansi(&ansi_to_spans("plain")), "plain");
+    }
+
+    #[test]
+    fn html_export_uses_resolved_theme_colors() {
+        let options = HtmlOptions::default();
+        let html = ansi_to_html("a<b \x1b[1;31mred\x1b[0m \x1b[4:3;58;5;21;7mrev", &options);
+
+        assert_eq!(
+            html,
+            "<pre style=\"color:#e5e5e5;background-color:#000000\">a&lt;b \
+             <span style=\"color:#ff0000;font-weight:bold\">red</span> \
+             <span style=\"color:#000000;background-color:#e5e5e5;text-decoration:underline;\
+             text-decoration-style:wavy;text-decoration-color:#0000ff\">rev</span></pre>"
+        );
+    }
+
+    #[test]
+    fn html_export_writes_faint_alpha_without_pre() {
+        let options = HtmlOptions {
+            wrap_in_pre: false,
+            ..HtmlOptions::default()
+        };
+        let spans = ansi_to_spans("\x1b[2;9mdim");
+
+        assert_eq!(
+            spans_to_html(&spans, &options),
+            "<span style=\"color:rgba(228,228,228,0.600);text-decoration:line-through\">dim</span>"
+        );
+    }
+
+    #[cfg(feature = "html-import")]
+    #[test]
+    fn html_import_maps_tags_and_inline_styles() {
+        let spans = html_to_spans(
+            "<pre>a &amp; <span style=\"color: #f00; background-color: rgb(0, 0, 255)\">\
+             red <b>bold</b></span><br/><font color=lime>x</font><!-- skip --><i>&#x41;</i></pre>",
+        );
+
+        assert_eq!(text_of(&spans), "a & red bold\nxA");
+        assert_eq!(spans[1].text, "red ");
+        assert_eq!(spans[1].style.foreground, AnsiColor::Rgb(255, 0, 0));
+        assert_eq!(spans[1].style.background, AnsiColor::Rgb(0, 0, 255));
+        assert_eq!(spans[2].style.intensity, AnsiIntensity::Bold);
+        assert_eq!(spans[2].style.foreground, AnsiColor::Rgb(255, 0, 0));
+        assert_eq!(spans[3].style, AnsiStyle::default());
+        assert_eq!(spans[4].style.foreground, AnsiColor::Rgb(0, 255, 0));
+        assert!(spans[5].style.italic);
+    }
+
+    #[cfg(feature = "html-import")]
+    #[test]
+    fn html_import_round_trips_html_export() {
+        let input = "\x1b[1;3;4;9;38;2;1;2;3;48;2;4;5;6mstyled\x1b[0m <plain>";
+        let options = HtmlOptions {
+            theme: EguiAnsiTheme {
+                bold_is_bright: false,
+                ..EguiAnsiTheme::default()
+            },
+            wrap_in_pre: false,
+        };
+
+        assert_eq!(
+            html_to_spans(&ansi_to_html(input, &options)),
+            ansi_to_spans(input)
+        );
+    }
+
+    #[cfg(feature = "nu-ansi-term")]
+    #[test]
+    fn nu_ansi_strings_convert_without_reparsing() {
+        use nu_ansi_term::Color;
+
+        let strings = [
+            Color::Red.bold().paint("error"),
+            Color::Red.bold().paint(":"),
+            Color::Rgb(1, 2, 3)
+                .on(Color::LightGray)
+                .italic()
+                .paint(" detail"),
+        ];
+        let spans = nu_ansi_strings_to_spans(&strings);
+        let rendered: String = strings.iter().map(ToString::to_string).collect();
+
+        assert_eq!(spans, ansi_to_spans(&rendered));
+        assert_eq!(spans[0].text, "error:");
+        assert_eq!(spans[1].style.background, AnsiColor::Indexed(15));
+    }
+
+    #[cfg(feature = "ansi_term")]
+    #[test]
+    fn ansi_term_strings_convert_without_reparsing() {
+        use ansi_term::Colour;
+
+        let strings = [
+            Colour::Fixed(208).underline().paint("warn"),
+            Colour::Blue.dimmed().strikethrough().paint(" old"),
+        ];
+        let spans = ansi_term_strings_to_spans(&strings);
+        let rendered: String = strings.iter().map(ToString::to_string).collect();
+
+        assert_eq!(spans, ansi_to_spans(&rendered));
+    }
+
+    #[cfg(feature = "syntect")]
+    #[test]
+    fn syntect_ranges_map_foreground_and_font_style() {
+        use syntect::highlighting::{Color, FontStyle, Style};
+
+        let keyword = Style {
+            foreground: Color {
+                r: 200,
+                g: 100,
+                b: 50,
+                a: 255,
+            },
+            background: Color::BLACK,
+            font_style: FontStyle::BOLD | FontStyle::ITALIC,
+        };
+        let plain = Style {
+            font_style: FontStyle::empty(),
+            ..keyword
+        };
+        let spans = syntect_ranges_to_spans(&[(keyword, "fn"), (keyword, " "), (plain, "main")]);
+
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0].text, "fn ");
+        assert_eq!(spans[0].style.foreground, AnsiColor::Rgb(200, 100, 50));
+        assert_eq!(spans[0].style.background, AnsiColor::Default);
+        assert_eq!(spans[0].style.intensity, AnsiIntensity::Bold);
+        assert!(spans[0].style.italic);
+        assert!(!spans[1].style.italic);
+    }
+
+    #[test]
+    fn rtf_export_builds_color_table_and_escapes_text() {
+        let theme = EguiAnsiTheme::default();
+        let rtf = ansi_to_rtf("\x1b[1;31m{err}\x1b[0m\\ ok\n\u{e9}", &theme);
+
+        assert!(rtf.starts_with("{\\rtf1\\ansi"));
+        assert!(rtf.contains(
+            "{\\colortbl ;\\red255\\green0\\blue0;\\red0\\green0\\blue0;\\red229\\green229\\blue229;}"
+        ));
+        assert!(rtf.contains("{\\cf1\\highlight2\\b \\{err\\}}"));
+        assert!(rtf.contains("{\\cf3\\highlight2 \\\\ ok\\line \\u233?}"));
+        assert!(rtf.ends_with('}'));
+    }
+
+    #[test]
+    fn bbcode_export_nests_tags_and_skips_default_color() {
+        let theme = EguiAnsiTheme::default();
+        let bbcode = ansi_to_bbcode(
+            "plain \x1b[1;4;38;2;1;2;3mloud\x1b[0m \x1b[3;9mgone",
+            &theme,
+        );
+
+        assert_eq!(
+            bbcode,
+            "plain [color=#010203][b][u]loud[/u][/b][/color] [i][s]gone[/s][/i]"
+        );
+    }
+
+    #[test]
+    fn bbcode_export_keeps_brackets_in_text_literal() {
+        let theme = EguiAnsiTheme::default();
+        assert_eq!(
+            ansi_to_bbcode("\x1b[31m[b]x[/color]\x1b[0m ok", &theme),
+            "[color=#cd0000][noparse][b]x[/color][/noparse][/color] ok"
+        );
+        assert_eq!(
+            ansi_to_bbcode("a[/NOPARSE]b", &theme),
+            "[noparse]a[/noparse][noparse][[/noparse]/NOPARSE][noparse]b[/noparse]"
+        );
+    }
+
+    #[test]
+    fn latex_export_wraps_colored_runs() {
+        let input = "\x1b[1;38;2;255;128;0m{x}\x1b[0m 50% _done_\n";
+        let verbatim = ansi_to_latex(input, &LatexOptions::default());
+        let inline = ansi_to_latex(
+            input,
+            &LatexOptions {
+                fancyvrb: false,
+                ..LatexOptions::default()
+            },
+        );
+
+        assert_eq!(
+            verbatim,
+            "\\begin{Verbatim}[commandchars=\\\\\\{\\}]\n\
+             \\textcolor[HTML]{FF8000}{\\textbf{\\{x\\}}} 50% _done_\n\
+             \\end{Verbatim}\n"
+        );
+        assert_eq!(
+            inline,
+            "\\textcolor[HTML]{FF8000}{\\textbf{\\{x\\}}} 50\\% \\_done\\_\\leavevmode\\\\\n"
+        );
+    }
+
+    #[cfg(feature = "egui")]
+    #[test]
+    fn render_to_image_rasterizes_backgrounds_and_glyphs() {
+        let theme = EguiAnsiTheme::default();
+        let image = render_ansi_to_image("\x1b[41m    \x1b[0m\n\x1b[97mWWWW", 64.0, &theme);
+        let red = theme.palette[1];
+
+        assert_eq!(image.size[0], 64);
+        assert!(image.size[1] > 0);
+        assert_eq!(image.pixels[image.size[0] + 2], red);
+        assert!(
+            image.pixels[image.size[0] * image.size[1] / 2..]
+                .iter()
+                .any(|pixel| *pixel != theme.default_background && *pixel != red)
+        );
+    }
+
+    #[cfg(feature = "png")]
+    #[cfg(feature = "egui")]
+    #[test]
+    fn render_to_png_writes_png_signature() {
+        let png = render_ansi_to_png("\x1b[32mok", 32.0, &EguiAnsiTheme::default()).unwrap();
+
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+    }
+
+    #[cfg(feature = "termwiz")]
+    #[test]
+    fn termwiz_cells_convert_attributes() {
+        use termwiz::cell::{Cell, CellAttributes, Intensity, Underline};
+        use termwiz::color::{AnsiColor as TermwizColor, ColorAttribute};
+
+        let mut attrs = CellAttributes::default();
+        attrs
+            .set_foreground(TermwizColor::Maroon)
+            .set_intensity(Intensity::Bold)
+            .set_underline(Underline::Curly);
+        let cells = [
+            Cell::new('o', attrs.clone()),
+            Cell::new('k', attrs),
+            Cell::new('!', CellAttributes::default()),
+        ];
+        let spans = termwiz_cells_to_spans(&cells);
+
+        assert_eq!(text_of(&spans), "ok!");
+        assert_eq!(spans[0].style.foreground, AnsiColor::Indexed(1));
+        assert_eq!(spans[0].style.intensity, AnsiIntensity::Bold);
+        assert_eq!(spans[0].style.underline, UnderlineStyle::Curly);
+        assert_eq!(spans[1].style, AnsiStyle::default());
+        assert_eq!(
+            AnsiColor::from(ColorAttribute::PaletteIndex(200)),
+            AnsiColor::Indexed(200)
+        );
+    }
+
+    #[cfg(feature = "alacritty_terminal")]
+    #[test]
+    fn alacritty_cells_skip_wide_spacers() {
+        use alacritty_terminal::term::cell::{Cell, Flags};
+        use alacritty_terminal::vte::ansi::{Color, NamedColor, Rgb};
+
+        let wide = Cell {
+            c: '\u{4e2d}',
+            fg: Color::Spec(Rgb { r: 1, g: 2, b: 3 }),
+            flags: Flags::WIDE_CHAR | Flags::ITALIC,
+            ..Cell::default()
+        };
+        let spacer = Cell {
+            c: ' ',
+            flags: Flags::WIDE_CHAR_SPACER,
+            ..Cell::default()
+        };
+        let named = Cell {
+            c: 'x',
+            fg: Color::Named(NamedColor::BrightGreen),
+            ..Cell::default()
+        };
+        let spans = alacritty_cells_to_spans(&[wide, spacer, named]);
+
+        assert_eq!(text_of(&spans), "\u{4e2d}x");
+        assert_eq!(spans[0].style.foreground, AnsiColor::Rgb(1, 2, 3));
+        assert!(spans[0].style.italic);
+        assert_eq!(spans[1].style.foreground, AnsiColor::Indexed(10));
+    }
+
+    #[test]
+    fn strip_ansi_borrows_plain_input() {
+        assert!(matches!(
+            strip_ansi("plain\ttext\n"),
+            Cow::Borrowed("plain\ttext\n")
+        ));
+        assert_eq!(
+            strip_ansi("\x1b]0;title\x07\x1b[1;31merr\x1b[0m\x1b[2K: \x1b[5Cdone\x07"),
+            "err: done"
+        );
+    }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn logger_appends_colored_records_to_shared_document() {
+        use log::{Level, Log, Record};
+
+        let logger = AnsiLogger::new().with_max_lines(2);
+        let document = logger.document();
+        for (level, message) in [
+            (Level::Error, format_args!("disk \x1b[1mfull")),
+            (Level::Debug, format_args!("hidden")),
+            (Level::Info, format_args!("ready")),
+        ] {
+            logger.log(
+                &Record::builder()
+                    .level(level)
+                    .target("app")
+                    .args(message)
+                    .build(),
+            );
+        }
+
+        let text = document.lock().to_text();
+        assert_eq!(text.text, "[ERROR app] disk full\n[INFO  app] ready");
+        assert_eq!(text.style_at(1).unwrap().foreground, AnsiColor::Indexed(1));
+        assert_eq!(text.style_at(17).unwrap().intensity, AnsiIntensity::Bold);
+        assert_eq!(text.style_at(34), Some(AnsiStyle::default()));
+
+        logger.log(
+            &Record::builder()
+                .level(Level::Warn)
+                .args(format_args!("third"))
+                .build(),
+        );
+        assert_eq!(document.lock().line_count(), 2);
+        assert_eq!(document.lock().first_line_number(), 1);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn tracing_layer_appends_events_with_span_context() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let layer = AnsiTracingLayer::new().with_max_lines(1);
+        let document = layer.document();
+        let subscriber = tracing_subscriber::registry().with(layer);
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("evicted");
+            let _request = tracing::info_span!("request").entered();
+            tracing::warn!(target: "app", attempt = 2, "\x1b[31mretrying\x1b[0m");
+        });
+
+        let text = document.lock().to_text();
+        assert_eq!(text.text, "[WARN  app] request: retrying attempt=2");
+        assert_eq!(text.style_at(1).unwrap().foreground, AnsiColor::Indexed(3));
+        assert_eq!(text.style_at(21).unwrap().foreground, AnsiColor::Indexed(1));
+    }
+
+    #[test]
+    fn writer_streams_split_sequences_into_shared_document() {
+        use std::io::Write;
+
+        let mut writer = AnsiWriter::new().with_max_lines(1);
+        let mut clone = writer.clone();
+        writer.write_all(b"evicted\n\x1b[3").unwrap();
+        clone.write_all(b"2mok\xe2\x9c").unwrap();
+        writer.write_all(b"\x93\x1b[0m done\n").unwrap();
+
+        let text = writer.document().lock().to_text();
+        assert_eq!(text.text, "ok\u{2713} done");
+        assert_eq!(text.style_at(0).unwrap().foreground, AnsiColor::Indexed(2));
+    }
+
+    #[test]
+    fn span_buffer_accepts_formatted_writes() {
+        use std::fmt::Write;
+
+        let mut buffer = AnsiSpanBuffer::new();
+        let value = 42;
+        write!(buffer, "value: \x1b[32m{value}\x1b[0m").unwrap();
+        writeln!(buffer, " ({:>4})", "ok").unwrap();
+
+        assert_eq!(text_of(buffer.spans()), "value: 42 (  ok)\n");
+        assert_eq!(buffer.spans()[1].text, "42");
+        assert_eq!(buffer.spans()[1].style.foreground, AnsiColor::Indexed(2));
+    }
+
+    #[test]
+    fn mirc_codes_map_to_spans() {
+        let spans = mirc_to_spans(
+            "\x02bold\x02 \x0304,12red on blue\x03 \x1dit\x0f, \x0399x\x0452FF00,y\x0fz",
+        );
+
+        assert_eq!(text_of(&spans), "bold red on blue it, x,yz");
+        assert_eq!(spans[0].style.intensity, AnsiIntensity::Bold);
+        assert_eq!(spans[2].style.foreground, AnsiColor::Rgb(255, 0, 0));
+        assert_eq!(spans[2].style.background, AnsiColor::Rgb(0, 0, 252));
+        assert_eq!(spans[3].text, " ");
+        assert_eq!(spans[3].style, AnsiStyle::default());
+        assert!(spans[4].style.italic);
+        assert_eq!(spans[5].text, ", x");
+        assert_eq!(spans[6].text, ",y");
+        assert_eq!(spans[6].style.foreground, AnsiColor::Rgb(0x52, 0xff, 0));
+        assert_eq!(spans[7].style, AnsiStyle::default());
+    }
+
+    #[test]
+    fn minecraft_codes_map_to_spans() {
+        let options = MinecraftOptions::default();
+        let spans = minecraft_to_spans("§4§lAlert§r: §x§1§2§3§4§5§6hex §oit§amore §kxyz", &options);
+
+        assert_eq!(text_of(&spans), "Alert: hex itmore xyz");
+        assert_eq!(spans[0].style.foreground, AnsiColor::Rgb(0xaa, 0, 0));
+        assert_eq!(spans[0].style.intensity, AnsiIntensity::Bold);
+        assert_eq!(spans[1].style, AnsiStyle::default());
+        assert_eq!(spans[2].style.foreground, AnsiColor::Rgb(0x12, 0x34, 0x56));
+        assert!(spans[3].style.italic);
+        assert_eq!(spans[4].style.foreground, AnsiColor::Rgb(0x55, 0xff, 0x55));
+        assert!(!spans[4].style.italic);
+
+        let hidden = minecraft_to_spans(
+            "a&kbc",
+            &MinecraftOptions {
+                code_char: '&',
+                obfuscation: Obfuscation::Hidden,
+            },
+        );
+        assert!(hidden[1].style.hidden);
+
+        let scramble = |seed| {
+            text_of(&minecraft_to_spans(
+                "§kabc def",
+                &MinecraftOptions {
+                    obfuscation: Obfuscation::Scrambled(seed),
+                    ..options
+                },
+            ))
+        };
+        assert_eq!(scramble(7), scramble(7));
+        assert_ne!(scramble(7), scramble(8));
+        assert_eq!(scramble(7).chars().nth(3), Some(' '));
+        let mixed_to_zero = scramble(0x9e37_79b9_7f4a_7c15);
+        let first = mixed_to_zero.chars().next();
+        assert!(
+            mixed_to_zero
+                .chars()
+                .filter(|c| *c != ' ')
+                .any(|c| Some(c) != first)
+        );
+    }
+
+    #[test]
+    fn ansi_art_decodes_cp437_and_reads_sauce_width() {
+        let mut bytes = b"\x1b[1;34m\xdb\xdb\x1b[2C\xb0\xb1\r\n\x1b[0mok".to_vec();
+        bytes.push(0x1a);
+        bytes.extend_from_slice(b"COMNT");
+        bytes.extend_from_slice(&[b' '; 64]);
+        let mut sauce = [0_u8; 128];
+        sauce[..7].copy_from_slice(b"SAUCE00");
+        sauce[7..12].copy_from_slice(b"Title");
+        sauce[42..44].copy_from_slice(b"me");
+        sauce[94] = 1;
+        sauce[95] = 1;
+        sauce[96..98].copy_from_slice(&4_u16.to_le_bytes());
+        sauce[104] = 1;
+        sauce[105] = 1;
+        bytes.extend_from_slice(&sauce);
+
+        let art = AnsiArt::from_bytes(&bytes);
+        let record = art.sauce.as_ref().unwrap();
+
+        assert_eq!(record.title, "Title");
+        assert_eq!(record.author, "me");
+        assert_eq!(record.width, Some(4));
+        assert!(record.ice_colors);
+        assert_eq!(record.comments, [""]);
+        assert_eq!(art.width(), 4);
+        assert_eq!(art.text.text, "\u{2588}\u{2588}  \n\u{2591}\u{2592}\nok");
+        assert_eq!(art.to_spans()[0].style.foreground, AnsiColor::Indexed(4));
+        assert_eq!(cp437_to_string(b"\x03\x7f\xe1"), "\u{2665}\u{2302}\u{df}");
+    }
+
+    #[test]
+    fn ansi_art_applies_cursor_movement_within_the_canvas() {
+        let art = AnsiArt::from_bytes(b"abc\r\ndef\x1b[Ax\x1b[3;2Hy\x1b[sz\x1b[1;1Hq\x1b[uw");
+        assert_eq!(art.text.text, "qbcx\ndef\n yw");
+
+        let art = AnsiArt::from_bytes(b"\x1b[4294967295Cx\x1b[99999999999999999999;5Hy");
+        let lines: Vec<&str> = art.text.text.split('\n').collect();
+        assert_eq!(lines.len(), 25);
+        assert_eq!(lines[0], " ".repeat(80));
+        assert_eq!(lines[1], "x");
+        assert_eq!(lines[24], "    y");
+    }
+
+    #[test]
+    fn ansi_art_grows_past_the_default_height() {
+        let input: String = (0..30).map(|index| format!("line{index}\r\n")).collect();
+        let art = AnsiArt::from_bytes(input.as_bytes());
+        let lines: Vec<&str> = art.text.text.lines().collect();
+        assert_eq!(lines.len(), 30);
+        assert_eq!(lines[24], "line24");
+        assert_eq!(lines[29], "line29");
+
+        // Explicit moves are still clamped to the rows drawn so far.
+        let art = AnsiArt::from_bytes(format!("{input}\x1b[99Bend").as_bytes());
+        assert!(art.text.text.ends_with("\nend"));
+        assert_eq!(art.text.text.lines().count(), 31);
+    }
+
+    #[test]
+    fn ansi_art_ice_colors_brighten_blinking_backgrounds() {
+        let mut bytes = b"\x1b[5;41mx".to_vec();
+        let mut sauce = [0_u8; 128];
+        sauce[..7].copy_from_slice(b"SAUCE00");
+        sauce[94] = 1;
+        sauce[105] = 1;
+        bytes.push(0x1a);
+        bytes.extend_from_slice(&sauce);
+        let art = AnsiArt::from_bytes(&bytes);
+        assert!(art.sauce.as_ref().is_some_and(|sauce| sauce.ice_colors));
+        assert_eq!(art.text.runs[0].style.background, AnsiColor::Indexed(9));
+    }
+
+    #[cfg(feature = "asciicast")]
+    #[test]
+    fn asciicast_player_replays_seeks_and_scales_speed() {
+        let cast = Asciicast::parse(
+            "{\"version\": 2, \"width\": 80, \"height\": 24, \"title\": \"demo\"}\n\
+             [0.5, \"o\", \"\\u001b[32mone\"]\n\
+             [1.0, \"i\", \"typed\"]\n\
+             [2.0, \"o\", \" two\\u001b[0m\"]\n",
+        )
+        .unwrap();
+        assert_eq!(cast.header.title.as_deref(), Some("demo"));
+        assert_eq!(cast.duration(), 2.0);
+
+        let mut player = AsciicastPlayer::new(cast);
+        assert!(!player.advance(1.0));
+        player.play();
+        player.set_speed(2.0);
+        assert!(player.advance(0.5));
+        assert_eq!(text_of(player.buffer().spans()), "one");
+        assert!(player.advance(0.5));
+        assert!(player.is_finished());
+        assert!(!player.is_playing());
+        assert_eq!(text_of(player.buffer().spans()), "one two");
+
+        player.seek(0.75);
+        assert_eq!(text_of(player.buffer().spans()), "one");
+        assert_eq!(
+            player.buffer().spans()[0].style.foreground,
+            AnsiColor::Indexed(2)
+        );
+
+        assert!(matches!(
+            Asciicast::parse("{\"version\": 1, \"width\": 1, \"height\": 1}"),
+            Err(AsciicastError::UnsupportedVersion(1))
+        ));
+        assert!(matches!(
+            Asciicast::parse("{\"version\": 2, \"width\": 1, \"height\": 1}\n[1, \"o\"]"),
+            Err(AsciicastError::InvalidLine { line: 2, .. })
+        ));
+        assert!(matches!(
+            Asciicast::parse("{\"version\": 2, \"width\": 1, \"height\": 1}\n[-1, \"o\", \"x\"]"),
+            Err(AsciicastError::InvalidTime { line: 2 })
+        ));
+        assert!(matches!(
+            Asciicast::parse("\n\n{\"version\": 2,"),
+            Err(AsciicastError::InvalidLine { line: 3, .. })
+        ));
+
+        let mut player = AsciicastPlayer::new(Asciicast {
+            header: AsciicastHeader {
+                version: 2,
+                width: 1,
+                height: 1,
+                timestamp: None,
+                title: None,
+            },
+            events: vec![AsciicastEvent {
+                time: -1.0,
+                code: "o".to_owned(),
+                data: "x".to_owned(),
+            }],
+        });
+        player.seek(f64::NAN);
+        player.seek(5.0);
+
+        let mut player = AsciicastPlayer::new(
+            Asciicast::parse("{\"version\": 2, \"width\": 1, \"height\": 1}\n[1.0, \"o\", \"x\"]")
+                .unwrap(),
+        );
+        player.play();
+        for dt in [f64::NAN, f64::INFINITY, -1.0] {
+            assert!(!player.advance(dt));
+            assert_eq!(player.position(), 0.0);
+        }
+        assert!(player.advance(1.0));
+    }
+
+    #[cfg(feature = "asciicast")]
+    #[test]
+    fn asciicast_recorder_output_round_trips() {
+        let header = AsciicastHeader {
+            version: 2,
+            width: 80,
+            height: 24,
+            timestamp: None,
+            title: Some("session".to_owned()),
+        };
+        let mut recorder = AsciicastRecorder::new(Vec::new(), &header).unwrap();
+        recorder.record_bytes(b"\x1b[31mcaf\xc3").unwrap();
+        recorder.record_bytes(b"\xa9\x1b[0m\n").unwrap();
+        recorder.record_event(5.0, "m", "marker").unwrap();
+        let file = String::from_utf8(recorder.finish().unwrap()).unwrap();
+
+        let cast = Asciicast::parse(&file).unwrap();
+        assert_eq!(cast.header, header);
+        let output: String = cast
+            .events
+            .iter()
+            .filter(|event| event.code == "o")
+            .map(|event| event.data.as_str())
+            .collect();
+        assert_eq!(output, "\x1b[31mcaf\u{e9}\x1b[0m\n");
+        assert_eq!(cast.events.last().unwrap().code, "m");
+    }
+
+    #[cfg(all(feature = "journald", target_os = "linux"))]
+    #[test]
+    fn journald_entries_map_priority_and_keep_embedded_ansi() {
+        let entry = journald::format_entry(
+            r#"{"PRIORITY":"4","SYSLOG_IDENTIFIER":"app","MESSAGE":[27,91,51,50,109,111,107,10]}"#,
+        )
+        .unwrap();
+        let spans = ansi_to_spans(&entry);
+
+        assert_eq!(text_of(&spans), "[WARN  app] ok\n");
+        assert_eq!(spans[1].text, "WARN ");
+        assert_eq!(spans[1].style.foreground, AnsiColor::Indexed(3));
+        let ok = spans.iter().find(|span| span.text == "ok").unwrap();
+        assert_eq!(ok.style.foreground, AnsiColor::Indexed(2));
+
+        let entry = journald::format_entry(r#"{"PRIORITY":"3","_COMM":"sshd","MESSAGE":"denied"}"#)
+            .unwrap();
+        assert!(entry.contains("\x1b[31mERROR"));
+        assert!(entry.contains("sshd"));
+        assert_eq!(journald::format_entry(r#"{"PRIORITY":"6"}"#), None);
+    }
+
+    #[cfg(all(feature = "serialport", unix))]
+    #[test]
+    fn serial_monitor_parses_split_device_output() {
+        use serialport::SerialPort;
+        use std::io::Write;
+
+        let (mut device, host) = serialport::TTYPort::pair().unwrap();
+        let path = host.name().unwrap();
+        let connection = AnsiSerialMonitor::new(path, 115_200).open().unwrap();
+
+        device.write_all(b"\x1b[3").unwrap();
+        device.flush().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        device.write_all(b"2mok \xe2\x9c").unwrap();
+        device.write_all(b"\x93\x1b[0m").unwrap();
+        device.flush().unwrap();
+
+        let document = connection.document();
+        for _ in 0..100 {
+            if document.lock().to_text().text == "ok \u{2713}" {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        let text = document.lock().to_text();
+        assert_eq!(text.text, "ok \u{2713}");
+        assert_eq!(text.style_at(0).unwrap().foreground, AnsiColor::Indexed(2));
+        drop(host);
+    }
+
+    #[cfg(all(feature = "network", not(target_arch = "wasm32")))]
+    #[test]
+    fn network_source_reconnects_and_drops_partial_escapes() {
+        use std::io::Write;
+        use std::net::TcpListener;
+        use std::time::Duration;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let connection = AnsiNetworkSource::tcp(address)
+            .with_backoff(Duration::from_millis(10), Duration::from_millis(20))
+            .connect()
+            .unwrap();
+
+        let (mut first, _) = listener.accept().unwrap();
+        first.write_all(b"\x1b[32mup\n\x1b[3").unwrap();
+        drop(first);
+        let (mut second, _) = listener.accept().unwrap();
+        second.write_all(b"1mback").unwrap();
+        second.flush().unwrap();
+
+        let document = connection.document();
+        for _ in 0..200 {
+            if document.lock().to_text().text.ends_with("back") {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let text = document.lock().to_text();
+        assert_eq!(text.text, "up\n1mback");
+        assert_eq!(text.style_at(0).unwrap().foreground, AnsiColor::Indexed(2));
+        assert_eq!(text.style_at(3), Some(AnsiStyle::default()));
+        assert!(connection.is_connected());
+    }
+
+    #[test]
+    fn style_builders_compose_generated_and_parsed_text() {
+        let style = AnsiStyle::new()
+            .fg(AnsiColor::Indexed(1))
+            .bold()
+            .faint()
+            .underline_color(AnsiColor::Rgb(1, 2, 3));
+
+        assert_eq!(style.intensity, AnsiIntensity::Faint);
+        assert_eq!(style.underline, UnderlineStyle::Single);
+        assert_eq!(
+            AnsiStyle::new()
+                .underline(UnderlineStyle::Curly)
+                .underline_color(AnsiColor::Indexed(4))
+                .underline,
+            UnderlineStyle::Curly
+        );
+        assert_eq!(AnsiStyle::new(), AnsiStyle::default());
+
+        let mut text = AnsiText::new().with_str("label: ", AnsiStyle::new().bold());
+        text.push_ansi("\x1b[32mok");
+        let spans = text.to_spans();
+
+        assert_eq!(text_of(&spans), "label: ok");
+        assert_eq!(spans[0].style.intensity, AnsiIntensity::Bold);
+        assert_eq!(spans[1].style, AnsiStyle::new().fg(AnsiColor::Indexed(2)));
+    }
+
+    #[test]
+    fn strict_parsing_reports_error_offsets() {
+        let limits = AnsiLimits::default();
+
+        assert_eq!(
+            try_ansi_to_spans("\x1b[31mred\x1b]8;;x\x1b\\ ok", &limits).unwrap(),
+            ansi_to_spans("\x1b[31mred\x1b]8;;x\x1b\\ ok")
+        );
+        assert_eq!(
+            try_ansi_bytes_to_spans(b"ok\xff", &limits),
+            Err(AnsiError::InvalidUtf8 { offset: 2 })
+        );
+        assert_eq!(
+            try_ansi_to_spans("ab\x1b[31", &limits),
+            Err(AnsiError::UnterminatedSequence { offset: 2 })
+        );
+        assert_eq!(
+            try_ansi_to_spans("\x1b]0;title", &limits),
+            Err(AnsiError::UnterminatedSequence { offset: 0 })
+        );
+
+        let tight = AnsiLimits {
+            max_input_len: 16,
+            max_sequence_len: 8,
+        };
+        assert_eq!(
+            try_ansi_to_spans("x\x1b[38;2;1;2;3m", &tight),
+            Err(AnsiError::SequenceTooLong {
+                offset: 1,
+                limit: 8
+            })
+        );
+        assert_eq!(
+            try_ansi_to_spans(&"a".repeat(17), &tight),
+            Err(AnsiError::InputTooLong { len: 17, limit: 16 })
+        );
+    }
+
+    #[test]
+    fn style_at_queries_raw_and_visible_offsets() {
+        let input = "a\x1b[1;31mbc\x1b[0md";
+
+        assert_eq!(ansi_style_at(input, 0), AnsiStyle::default());
+        assert_eq!(
+            ansi_style_at(input, 3),
+            AnsiStyle::default(),
+            "inside an unfinished sequence"
+        );
+        assert_eq!(
+            ansi_style_at(input, 8),
+            AnsiStyle::new().fg(AnsiColor::Indexed(1)).bold()
+        );
+        assert_eq!(ansi_style_at(input, usize::MAX), AnsiStyle::default());
+
+        let text = ansi_to_text(input);
+        assert_eq!(text.style_at(0), Some(AnsiStyle::default()));
+        assert_eq!(
+            text.style_at(2),
+            Some(AnsiStyle::new().fg(AnsiColor::Indexed(1)).bold())
+        );
+        assert_eq!(text.style_at(3), Some(AnsiStyle::default()));
+        assert_eq!(text.style_at(4), None);
+    }
+
+    #[test]
+    fn visible_width_ignores_escapes_and_counts_wide_glyphs() {
+        assert_eq!(visible_width("\x1b[31mred\x1b[0m"), 3);
+        assert_eq!(
+            visible_width("\x1b]8;;https://x\x1b\\link\x1b]8;;\x1b\\"),
+            4
+        );
+        assert_eq!(visible_width("\x1b[1m\u{4e2d}\u{6587}\x1b[0m"), 4);
+        assert_eq!(visible_width("e\u{301}"), 1);
+        assert_eq!(
+            visible_width("\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}"),
+            2
+        );
+        assert_eq!(visible_width("\u{1f44d}\u{1f3fd}"), 2);
+        assert_eq!(visible_width("a\tb\n"), 2);
+    }
+
+    #[test]
+    fn truncate_ansi_cuts_on_columns_and_styles_ellipsis() {
+        let line = "\x1b[32mok\x1b[0m \x1b[31mfailed: \u{4e2d}\u{6587}\x1b[0m";
+
+        assert!(matches!(
+            truncate_ansi(line, 40, "\u{2026}"),
+            Cow::Borrowed(_)
+        ));
+
+        let short = truncate_ansi(line, 6, "\u{2026}");
+        let spans = ansi_to_spans(&short);
+        assert_eq!(text_of(&spans), "ok fa\u{2026}");
+        assert_eq!(spans[2].text, "fa\u{2026}");
+        assert_eq!(spans[2].style.foreground, AnsiColor::Indexed(1));
+        assert!(short.ends_with("\x1b[0m"));
+
+        let wide = truncate_ansi("\u{4e2d}\u{6587}e\u{301}", 3, "");
+        assert_eq!(strip_ansi(&wide), "\u{4e2d}");
+        assert_eq!(truncate_ansi("abcde", 4, "..."), "a...");
+        assert_eq!(truncate_ansi("abcde", 2, "..."), "ab");
+    }
+
+    #[test]
+    fn wrap_ansi_reopens_styles_on_continuation_lines() {
+        let lines = wrap_ansi("\x1b[1;34mabcdef\x1b[0mg\nh\u{4e2d}\u{6587}", 4);
+
+        assert_eq!(
+            lines
+                .iter()
+                .map(|line| strip_ansi(line))
+                .collect::<Vec<_>>(),
+            ["abcd", "efg", "h\u{4e2d}", "\u{6587}"]
+        );
+        let continuation = ansi_to_spans(&lines[1]);
+        assert_eq!(continuation[0].text, "ef");
+        assert_eq!(continuation[0].style.foreground, AnsiColor::Indexed(4));
+        assert_eq!(continuation[0].style.intensity, AnsiIntensity::Bold);
+        assert_eq!(continuation[1].style, AnsiStyle::default());
+        assert!(lines[0].ends_with("\x1b[0m"));
+
+        assert_eq!(wrap_ansi("ab\n\ncd\n", 10), ["ab", "", "cd"]);
+        assert!(wrap_ansi("", 10).is_empty());
+    }
+
+    #[test]
+    fn slice_ansi_extracts_columns_with_style_prefix() {
+        let line = "ab\x1b[31mcd\u{4e2d}e\x1b[0mf";
+
+        let slice = slice_ansi(line, 3..7);
+        let spans = ansi_to_spans(&slice);
+        assert_eq!(text_of(&spans), "d\u{4e2d}e");
+        assert_eq!(spans[0].style.foreground, AnsiColor::Indexed(1));
+        assert!(slice.ends_with("\x1b[0m"));
+
+        assert_eq!(strip_ansi(&slice_ansi(line, 5..8)), " ef");
+        assert_eq!(strip_ansi(&slice_ansi(line, 0..5)), "abcd ");
+        assert_eq!(slice_ansi(line, 20..30), "");
+        assert_eq!(strip_ansi(&slice_ansi("e\u{301}x", 0..1)), "e\u{301}");
+    }
+
+    #[test]
+    fn document_splits_chunks_into_retained_lines() {
+        let mut document: AnsiDocument<u32> = AnsiDocument::new().with_max_lines(3);
+        document.append_str("\x1b[3");
+        document.append_str("2mone\ntw");
+        assert_eq!(document.line_count(), 2);
+        assert!(document.has_open_line());
+
+        document.append(b"o\n\nfour\n");
+        assert_eq!(document.line_count(), 3);
+        assert_eq!(document.first_line_number(), 1);
+        assert!(!document.has_open_line());
+
+        let lines: Vec<_> = document
+            .lines()
+            .map(|line| line.text.text.as_str())
+            .collect();
+        assert_eq!(lines, ["two", "", "four"]);
+        let two = document.line(0).unwrap();
+        assert_eq!(two.raw, b"two");
+        assert_eq!(two.text.runs[0].style.foreground, AnsiColor::Indexed(2));
+
+        document.line_mut(2).unwrap().meta = 7;
+        assert_eq!(document.line(2).unwrap().meta, 7);
+        assert_eq!(document.to_text().text, "two\n\nfour");
+
+        document.clear();
+        assert!(document.is_empty());
+        assert_eq!(document.first_line_number(), 4);
+    }
+
+    #[test]
+    fn documents_concatenate_and_splice_with_independent_styles() {
+        let mut build = AnsiDocument::<()>::new();
+        build.append_str("\x1b[31mcompile\nerr");
+
+        let mut test = AnsiDocument::new();
+        test.append_str("test ok\n\x1b[32mpass");
+        build.append_document(test);
+
+        let lines: Vec<_> = build.lines().map(|line| line.text.text.as_str()).collect();
+        assert_eq!(lines, ["compile", "err", "test ok", "pass"]);
+        assert_eq!(
+            build.line(2).unwrap().text.runs[0].style,
+            AnsiStyle::default()
+        );
+        build.append_str("ed");
+        assert_eq!(build.line(3).unwrap().text.text, "passed");
+        assert_eq!(
+            build.line(3).unwrap().text.runs[0].style.foreground,
+            AnsiColor::Indexed(2)
+        );
+
+        let mut header = AnsiDocument::new();
+        header.append_str("\x1b[1m== log ==");
+        build.insert_document(0, header);
+        assert_eq!(build.line_count(), 5);
+        assert_eq!(build.line(0).unwrap().text.text, "== log ==");
+        assert_eq!(
+            build.line(1).unwrap().text.runs[0].style.foreground,
+            AnsiColor::Indexed(1)
+        );
+        build.append_str("!");
+        assert_eq!(build.line(4).unwrap().text.text, "passed!");
+    }
+
+    #[cfg(feature = "egui")]
+    #[test]
+    fn document_overlays_composite_over_ansi_styles() {
+        let mut document = AnsiDocument::<()>::new().with_max_lines(2);
+        document.append_str("\x1b[31mfirst\nerror: bad\n");
+        document.add_overlay(
+            OverlayLayer::SEARCH,
+            1,
+            7..10,
+            OverlayStyle::highlight(Color32::YELLOW),
+        );
+        document.add_overlay_columns(
+            OverlayLayer::DIAGNOSTICS,
+            1,
+            0..5,
+            OverlayStyle::underline(Color32::RED),
+        );
+
+        let theme = EguiAnsiTheme::default();
+        let job = document.to_layout_job(&theme);
+        let section = |text: &str| {
+            let start = job.text.find(text).unwrap();
+            job.sections
+                .iter()
+                .find(|section| section.byte_range.contains(&start))
+                .unwrap()
+        };
+        assert_eq!(section("bad").byte_range.len(), 3);
+        assert_eq!(section("bad").format.background, Color32::YELLOW);
+        assert_eq!(section("bad").format.color, section("first").format.color);
+        assert_eq!(section("error").format.underline.color, Color32::RED);
+        assert_eq!(section(": ").format.underline, Stroke::NONE);
+
+        document.clear_overlays(OverlayLayer::SEARCH);
+        assert_eq!(document.overlays().len(), 1);
+        document.append_str("next\nmore\n");
+        assert!(document.overlays().is_empty());
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn highlight_matches_spans_style_runs_and_replaces_previous_hits() {
+        let mut document = AnsiDocument::<()>::new();
+        document.append_str("GET /a \x1b[32m200\x1b[0m\nGET /b \x1b[31m50\x1b[1m0\x1b[0m\n");
+        let style = OverlayStyle::highlight(Color32::YELLOW);
+
+        let regex = regex::Regex::new("[0-9]{3}").unwrap();
+        assert_eq!(highlight_matches(&mut document, &regex, style), 2);
+        let ranges: Vec<_> = document
+            .overlays()
+            .iter()
+            .map(|overlay| (overlay.line, overlay.range.clone()))
+            .collect();
+        assert_eq!(ranges, [(0, 7..10), (1, 7..10)]);
+
+        let regex = regex::Regex::new("/b").unwrap();
+        assert_eq!(highlight_matches(&mut document, &regex, style), 1);
+        assert_eq!(document.overlays()[0].line, 1);
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn colorizer_styles_capture_groups_on_uncolored_lines_only() {
+        let level = AnsiStyle::new().fg(AnsiColor::Indexed(3));
+        let address = AnsiStyle::new()
+            .fg(AnsiColor::Indexed(6))
+            .underline(UnderlineStyle::Single);
+        let colorizer = Colorizer::new()
+            .with_group_rule(regex::Regex::new(r"level=(\w+)").unwrap(), 1, level)
+            .with_rule(regex::Regex::new(r"\d+\.\d+\.\d+\.\d+").unwrap(), address);
+
+        let line = colorizer.colorize_ansi("\x1b[1mlevel=warn\x1b[0m from 10.0.0.1");
+        let runs: Vec<_> = line.iter().collect();
+        assert_eq!(
+            runs,
+            [
+                ("level=", AnsiStyle::new().bold()),
+                ("warn", level),
+                (" from ", AnsiStyle::default()),
+                ("10.0.0.1", address),
+            ]
+        );
+
+        let colored = colorizer.colorize_ansi("\x1b[32mlevel=info\x1b[0m from 10.0.0.1");
+        assert_eq!(colored.runs.len(), 2);
+        assert_eq!(colored.runs[0].style.foreground, AnsiColor::Indexed(2));
+    }
+
+    #[test]
+    fn diff_highlighter_styles_hunks_and_keeps_git_colors() {
+        let input = "diff --git a/x b/x\n--- a/x\n+++ b/x\n@@ -1,2 +1,2 @@\n ctx\n\x1b[31m--old\x1b[m\n+new";
+        let text = DiffHighlighter::new()
+            .with_backgrounds(AnsiColor::Indexed(22), AnsiColor::Indexed(52))
+            .highlight(input);
+        let style_of = |line: &str| {
+            let start = text.text.find(line).unwrap();
+            text.style_at(start).unwrap()
+        };
+
+        assert_eq!(
+            text.text,
+            input.replace("\x1b[31m", "").replace("\x1b[m", "")
+        );
+        assert_eq!(style_of("diff --git").intensity, AnsiIntensity::Bold);
+        assert_eq!(style_of("--- a/x").intensity, AnsiIntensity::Bold);
+        assert_eq!(style_of("@@").foreground, AnsiColor::Indexed(6));
+        assert_eq!(style_of(" ctx"), AnsiStyle::default());
+
+        let removed = style_of("--old");
+        assert_eq!(removed.foreground, AnsiColor::Indexed(1));
+        assert_eq!(removed.background, AnsiColor::Indexed(52));
+        assert_eq!(removed.intensity, AnsiIntensity::Normal);
+        assert_eq!(style_of("+new").background, AnsiColor::Indexed(22));
+    }
+
+    #[test]
+    fn align_columns_shares_widths_within_blocks() {
+        let lines: Vec<_> = [
+            "NAME\tREADY  STATUS",
+            "\x1b[32mweb-1\x1b[0m\t1/1  Running",
+            "no columns here",
+            "a  b",
+        ]
+        .iter()
+        .map(ansi_to_text)
+        .collect();
+
+        let aligned = align_columns(&lines, 2);
+        let texts: Vec<_> = aligned.iter().map(|line| line.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            [
+                "NAME   READY  STATUS",
+                "web-1  1/1    Running",
+                "no columns here",
+                "a  b",
+            ]
+        );
+        assert_eq!(aligned[1].runs[0].range, 0..5);
+        assert_eq!(aligned[1].runs[0].style.foreground, AnsiColor::Indexed(2));
+    }
+
+    #[test]
+    fn display_escapes_round_trip_and_accept_typed_forms() {
+        let input = "\x1b[31mred\x1b[0m\tC:\\dir\n\u{9b}1m\x07";
+        let escaped = escape_for_display(input);
+        assert_eq!(escaped, "\\x1b[31mred\\x1b[0m\\tC:\\\\dir\\n\\x9b1m\\x07");
+        assert_eq!(unescape_from_display(&escaped), input);
+
+        assert_eq!(
+            unescape_from_display("\\033[1m\\e[0m\\X1B \\q \\x9"),
+            "\x1b[1m\x1b[0m\x1b \\q \\x9"
+        );
+        assert_eq!(to_control_pictures("\x1b[0m\n"), "\u{241b}[0m\u{240a}");
+    }
+
+    #[test]
+    fn sgr_transition_emits_only_changed_attributes() {
+        let red = AnsiStyle::new().fg(AnsiColor::Indexed(1)).bold();
+        let blue_faint = AnsiStyle::new().fg(AnsiColor::Indexed(4)).faint();
+        let underlined = red
+            .bg(AnsiColor::Rgb(1, 2, 3))
+            .underline(UnderlineStyle::Curly);
+
+        assert_eq!(AnsiStyle::sgr_transition(&red, &red), "");
+        assert_eq!(
+            AnsiStyle::sgr_transition(&AnsiStyle::new(), &red),
+            "\x1b[1;31m"
+        );
+        assert_eq!(AnsiStyle::sgr_transition(&red, &blue_faint), "\x1b[0;2;34m");
+        assert_eq!(
+            AnsiStyle::sgr_transition(&red.italic(), &blue_faint.italic()),
+            "\x1b[22;2;34m"
+        );
+        assert_eq!(
+            AnsiStyle::sgr_transition(&red, &underlined),
+            "\x1b[4:3;48;2;1;2;3m"
+        );
+        assert_eq!(
+            AnsiStyle::sgr_transition(&underlined, &red.italic()),
+            "\x1b[3;24;49m"
+        );
+        assert_eq!(
+            AnsiStyle::sgr_transition(&underlined, &AnsiStyle::new().italic()),
+            "\x1b[0;3m"
+        );
+    }
+
+    #[cfg(feature = "test-utils")]
+    #[test]
+    fn snapshots_annotate_style_changes() {
+        let mut document = AnsiDocument::<()>::new();
+        document.append_str("\x1b[4:3;58;5;1;38;2;255;0;0mtypo\x1b[0m here\n\x1b[7;9m{x}");
+
+        assert_eq!(
+            snapshot_document(&document),
+            "{fg=#ff0000,underline=curly,ul=1}typo{} here\n{reverse,strike}{{x}}"
+        );
+        assert_eq!(
+            snapshot_spans(&ansi_to_spans("\x1b[2;3;44mdim")),
+            "{bg=4,faint,italic}dim"
+        );
+    }
+
+    #[cfg(feature = "proptest")]
+    proptest::proptest! {
+        #[test]
+        fn generated_streams_parse_to_their_expected_styles(
+            generated in ansi_stream_strategy(),
+        ) {
+            proptest::prop_assert_eq!(ansi_to_text(&generated.input), generated.expected.clone());
+            proptest::prop_assert_eq!(
+                ansi_to_spans(&generated.input),
+                generated.expected.to_spans()
+            );
+        }
+    }
+
+    #[test]
+    fn split_utf8_parses_like_whole_input() {
+        let input = "\x1b[31m\u{684}A\u{8b}\u{9b}x\u{1f600}!".as_bytes();
+        let whole = ansi_bytes_to_spans(input);
+        assert_eq!(text_of(&whole), "\u{684}Ax\u{1f600}!");
+
+        for size in 1..input.len() {
+            let mut parser = AnsiStreamParser::new();
+            let mut spans = Vec::new();
+            for chunk in input.chunks(size) {
+                spans.extend(parser.push_bytes(chunk));
+            }
+            spans.extend(parser.finish());
+            assert_eq!(text_of(&spans), text_of(&whole), "chunk size {size}");
+        }
+
+        // Escapes take the input off the plain-text fast path and through the
+        // block-wise parser; the wide character straddles the first block.
+        let mut long = format!("\x1b[1m{}", "a".repeat(4091));
+        long.push_str("\u{754c}b");
+        assert_eq!(text_of(&ansi_to_spans(&long)), strip_ansi(&long));
+    }
+
+    #[test]
+    fn text_fast_path_matches_vte() {
+        use vte::{Params, Perform};
+
+        #[derive(Debug, Default, PartialEq)]
+        struct Recorder(Vec<String>);
+
+        impl Perform for Recorder {
+            fn print(&mut self, c: char) {
+                match self.0.last_mut() {
+                    Some(last) if last.starts_with('"') => last.push(c),
+                    _ => self.0.push(format!("\"{c}")),
+                }
+            }
+            fn execute(&mut self, byte: u8) {
+                self.0.push(format!("execute {byte}"));
+            }
+            fn hook(&mut self, params: &Params, intermediates: &[u8], ignore: bool, action: char) {
+                self.0.push(format!(
+                    "hook {params:?} {intermediates:?} {ignore} {action}"
+                ));
+            }
+            fn put(&mut self, byte: u8) {
+                self.0.push(format!("put {byte}"));
+            }
+            fn unhook(&mut self) {
+                self.0.push("unhook".to_owned());
+            }
+            fn osc_dispatch(&mut self, params: &[&[u8]], bell_terminated: bool) {
+                self.0.push(format!("osc {params:?} {bell_terminated}"));
+            }
+            fn csi_dispatch(
+                &mut self,
+                params: &Params,
+                intermediates: &[u8],
+                ignore: bool,
+                action: char,
+            ) {
+                self.0.push(format!(
+                    "csi {params:?} {intermediates:?} {ignore} {action}"
+                ));
+            }
+            fn esc_dispatch(&mut self, intermediates: &[u8], ignore: bool, byte: u8) {
+                self.0
+                    .push(format!("esc {intermediates:?} {ignore} {byte}"));
+            }
+        }
+
+        impl parser::PrintRuns for Recorder {
+            fn print_str(&mut self, text: &str) {
+                text.chars().for_each(|c| self.print(c));
+            }
+        }
+
+        // Short and long text, C0 and C1 controls, invalid and split UTF-8,
+        // and pieces of CSI, OSC, DCS, and SOS sequences, in random order
+        // and chunking.
+        const PIECES: &[&[u8]] = &[
+            b"ab",
+            b"a text run long enough for the fast path",
+            "\u{e9}".as_bytes(),
+            "\u{85}".as_bytes(),
+            "\u{1f600}".as_bytes(),
+            b"\n",
+            b"\t",
+            b"\x07",
+            b"\x18",
+            b"\x1b",
+            b"[",
+            b"31",
+            b";",
+            b"m",
+            b"]8;;",
+            b"\\",
+            b"P",
+            b"X",
+            b"\xff",
+            b"\xe2\x9c",
+            b"\x93",
+            b"\x9b",
+            b"\x7f",
+        ];
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut next = |bound: usize| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % bound as u64) as usize
+        };
+        for _ in 0..5_000 {
+            let input: Vec<u8> = (0..next(40))
+                .flat_map(|_| PIECES[next(PIECES.len())].iter().copied())
+                .collect();
+            let chunk = 1 + next(input.len().max(1));
+
+            let mut expected = Recorder::default();
+            let mut vte = vte::Parser::new();
+            let mut actual = Recorder::default();
+            let mut fast = parser::Parser::new();
+            for part in input.chunks(chunk) {
+                vte.advance(&mut expected, part);
+                fast.advance(&mut actual, part);
+            }
+            assert_eq!(actual, expected, "{input:?} in chunks of {chunk}");
+        }
+    }
+
+    #[test]
+    fn chunked_sinks_decode_utf8_split_across_chunks() {
+        let text = "\x1b[32m日本\x1b[0m語\n\u{1f600}\x1b[1mé".as_bytes();
+        // A lead byte cut off by a newline, and an unfinished final character.
+        let input = [text, b"\xe4\nok\xf0\x9f"].concat();
+
+        let mut whole = AnsiDocument::<()>::new();
+        whole.append(&input);
+        let expected: Vec<_> = whole.lines().map(|line| line.text.clone()).collect();
+        assert_eq!(expected[0].text, "日本語");
+        assert_eq!(expected[1].text, "\u{1f600}é\u{fffd}");
+
+        for size in 1..input.len() {
+            let mut document = AnsiDocument::<()>::new();
+            let mut buffer = AnsiSpanBuffer::new();
+            for chunk in input.chunks(size) {
+                document.append(chunk);
+                buffer.push_bytes(chunk);
+            }
+            let lines: Vec<_> = document.lines().map(|line| line.text.clone()).collect();
+            assert_eq!(lines, expected, "chunk size {size}");
+            assert_eq!(
+                text_of(buffer.spans()),
+                text_of(&ansi_bytes_to_spans(&input[..input.len() - 2])),
+                "chunk size {size}"
+            );
+        }
+    }
+
+    #[test]
+    fn colors_downconvert_to_limited_depths() {
+        let palette = EguiAnsiTheme::xterm_palette();
+
+        assert_eq!(nearest_256(Color32::from_rgb(255, 135, 0)), 208);
+        assert_eq!(nearest_256(Color32::from_rgb(128, 128, 128)), 244);
+        assert_eq!(nearest_256(Color32::from_rgb(0, 0, 0)), 16);
+        assert_eq!(nearest_16(Color32::from_rgb(250, 10, 10), &palette), 9);
+        assert_eq!(nearest_16(Color32::from_rgb(0, 180, 190), &palette), 6);
+
+        let style = AnsiStyle::new()
+            .fg(AnsiColor::Rgb(240, 20, 250))
+            .bg(AnsiColor::Indexed(21))
+            .underline_color(AnsiColor::Rgb(0, 0, 0));
+        let reduced = style.to_depth(ColorDepth::Ansi16, &palette);
+        assert_eq!(reduced.foreground, AnsiColor::Indexed(13));
+        assert_eq!(reduced.background, AnsiColor::Indexed(4));
+        assert_eq!(reduced.underline_color, None);
+        assert_eq!(style.to_depth(ColorDepth::TrueColor, &palette), style);
+
+        let text = ansi_to_text("\x1b[38;2;255;135;0mwarn\x1b[0m ok");
+        assert_eq!(
+            text.to_ansi_with_depth(ColorDepth::Indexed256, &palette),
+            "\x1b[38;5;208mwarn\x1b[0m ok"
+        );
+        assert_eq!(
+            spans_to_ansi_with_depth(&text.to_spans(), ColorDepth::Ansi16, &palette),
+            "\x1b[91mwarn\x1b[0m ok"
+        );
+    }
+
+    #[test]
+    fn color_metrics_rank_perceptual_differences() {
+        let white = Color32::WHITE;
+        let black = Color32::BLACK;
+
+        for metric in [ColorMetric::Redmean, ColorMetric::Cie76, ColorMetric::Oklab] {
+            assert_eq!(metric.distance(white, white), 0.0);
+            assert!(metric.distance(white, black) > metric.distance(white, Color32::GRAY));
+        }
+        assert!((ColorMetric::Cie76.distance(white, black) - 100.0).abs() < 0.01);
+        assert!((Oklab::from_color(white).l - 1.0).abs() < 0.001);
+        assert!(Lab::from_color(Color32::from_rgb(255, 0, 0)).a > 70.0);
+
+        let palette = EguiAnsiTheme::xterm_palette();
+        let teal = Color32::from_rgb(20, 190, 200);
+        assert_eq!(
+            nearest_color(teal, &palette[..16], ColorMetric::Oklab),
+            Some(6)
+        );
+        assert_eq!(nearest_color(teal, &[], ColorMetric::Oklab), None);
+    }
+
+    #[test]
+    fn accessible_description_announces_colors() {
+        let theme = EguiAnsiTheme::default();
+        let text = ansi_to_text(
+            "\x1b[1;31merror\x1b[0m: \x1b[38;5;214mslow\x1b[0m \x1b[97mplain\x1b[0m \x1b[8msecret\x1b[0m\n\x1b[32mok \x1b[1mdone\x1b[0m",
+        );
+
+        assert_eq!(
+            accessible_description(&text, &theme),
+            "error (red): slow (yellow) plain \nok done (green)"
+        );
+        assert_eq!(
+            color_name(AnsiColor::Rgb(10, 10, 230), &theme),
+            Some("blue")
+        );
+        assert_eq!(color_name(AnsiColor::Default, &theme), None);
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn redactor_masks_secrets_and_keeps_styles() {
+        let redactor = Redactor::new().with_default_rules();
+        let line = redactor.redact_ansi(
+            "\x1b[31mkey AKIA\x1b[1mABCDEFGHIJKLMNOP\x1b[0m Authorization: Bearer abc.def",
+        );
+
+        assert_eq!(
+            line.text,
+            "key ******************** Authorization: Bearer *******"
+        );
+        assert_eq!(line.runs.len(), 3);
+        assert_eq!(line.runs[1].style.intensity, AnsiIntensity::Bold);
+        assert!(redactor.redact(&ansi_to_text("nothing here")).is_none());
+
+        let mut document = AnsiDocument::<()>::new();
+        document.append_str("db=postgres://app:s3cret@db/main\nplain\n");
+        let redactor = redactor.with_mask('#');
+        assert_eq!(redactor.redact_document(&mut document), 1);
+        let line = document.line(0).unwrap();
+        assert_eq!(line.text.text, "db=postgres://app:######@db/main");
+        assert_eq!(line.raw, line.text.text.as_bytes());
+    }
+
+    #[test]
+    fn hyperlinks_and_urls_are_found_in_visible_text() {
+        let input = "see \x1b]8;id=1;https://example.com/a;b\x07\x1b[34mdocs\x1b[0m\x1b]8;;\x1b\\ \
+                     or \x1b]8;;javascript:alert(1)\x07here\x1b]8;;\x07\x1b]8;;https://x\x07\x1b]8;;\x07";
+        let text = ansi_to_text(input);
+        let links = ansi_hyperlinks(input);
+
+        assert_eq!(links.len(), 2);
+        assert_eq!(&text.text[links[0].range.clone()], "docs");
+        assert_eq!(links[0].url, "https://example.com/a;b");
+        assert_eq!(&text.text[links[1].range.clone()], "here");
+
+        let long = format!(
+            "{}\t\u{85}\x1b[1m\x1b]8;;https://b\x1b\\bold\x1b]8;;\x07 tail",
+            "é".repeat(40)
+        );
+        let long_text = ansi_to_text(&long);
+        let long_links = ansi_hyperlinks(&long);
+        assert_eq!(long_links.len(), 1);
+        assert_eq!(&long_text.text[long_links[0].range.clone()], "bold");
+
+        let policy = LinkPolicy::new().with_max_len(32);
+        assert!(policy.allows(&links[0].url));
+        assert!(!policy.allows(&links[1].url));
+        assert!(!policy.allows("https://example.com/a-very-long-path-indeed"));
+        assert!(!policy.allows("https://exa mple.com"));
+        assert!(
+            LinkPolicy::new()
+                .with_allowed_schemes(["FILE"])
+                .allows("file:///tmp/x")
+        );
+
+        let plain = "Docs (https://example.com/x_(y)), mail mailto:a@b.c. Not a:b or http://";
+        let urls: Vec<_> = find_urls(plain)
+            .into_iter()
+            .map(|range| &plain[range])
+            .collect();
+        assert_eq!(urls, ["https://example.com/x_(y)", "mailto:a@b.c"]);
+    }
+
+    #[cfg(feature = "egui")]
+    #[test]
+    fn link_policy_routes_allowed_links_to_handler() {
+        let opened = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = std::sync::Arc::clone(&opened);
+        let policy =
+            LinkPolicy::new().with_on_open(move |url| sink.lock().unwrap().push(url.to_owned()));
+        let ctx = egui::Context::default();
+
+        assert!(policy.open(&ctx, "https://example.com"));
+        assert!(!policy.open(&ctx, "ftp://example.com"));
+        assert_eq!(*opened.lock().unwrap(), ["https://example.com"]);
+        assert!(ctx.output(|output| output.commands.is_empty()));
+    }
+
+    #[test]
+    fn parse_stats_count_sequences_and_colors() {
+        let mut parser = AnsiStreamParser::new().with_color_stats(true);
+        let _ = parser.push_str("\x1b[31;5mred\x1b[");
+        let _ = parser.push_str("0m\n\x1b[2J\x1b]0;title\x07\x1b7\x07done\n");
+        let _ = parser.finish();
+
+        let stats = parser.stats();
+        assert_eq!(stats.bytes, 37);
+        assert_eq!(stats.lines, 2);
+        assert_eq!(stats.sgr_sequences, 2);
+        assert_eq!(stats.other_csi_sequences, 1);
+        assert_eq!(stats.osc_sequences, 1);
+        assert_eq!(stats.esc_sequences, 1);
+        assert_eq!(stats.control_chars, 1);
+        assert_eq!(stats.unsupported_sgr_codes, 1);
+        assert_eq!(stats.escape_sequences(), 5);
+        assert_eq!(stats.colors.len(), 1);
+        assert!(stats.colors.contains(&AnsiColor::Indexed(1)));
+
+        parser.reset_stats();
+        assert_eq!(parser.stats(), &ParseStats::default());
+
+        let mut parser = AnsiStreamParser::new();
+        let _ = parser.push_str("\x1b[31mred\x1b[38;2;1;2;3mrgb");
+        assert_eq!(parser.stats().sgr_sequences, 2);
+        assert!(parser.stats().colors.is_empty());
+
+        let mut document = AnsiDocument::<()>::new();
+        document.append_str("\x1b[32mok\x1b[0m\n");
+        assert_eq!(document.stats().lines, 1);
+        assert_eq!(document.stats().sgr_sequences, 2);
+    }
+
+    #[test]
+    fn color_usage_counts_lines_and_highlights_a_color() {
+        let mut document = AnsiDocument::<()>::new();
+        document.append_str("\x1b[31merror\x1b[0m: a \x1b[31mb\x1b[0m\n");
+        document.append_str("plain\n");
+        document.append_str("\x1b[7;31m err \x1b[0m \x1b[32m  \x1b[0m\n");
+        document.append_str("\x1b[8;34mhidden\x1b[0m \x1b[31mred\x1b[0m\n");
+
+        let usage = color_usage(&document);
+        assert_eq!(
+            usage,
+            [
+                ColorUsage {
+                    color: AnsiColor::Indexed(1),
+                    role: ColorRole::Foreground,
+                    lines: 2,
+                    chars: 9,
+                },
+                ColorUsage {
+                    color: AnsiColor::Indexed(1),
+                    role: ColorRole::Background,
+                    lines: 1,
+                    chars: 5,
+                },
+            ]
+        );
+
+        let red = AnsiColor::Indexed(1);
+        assert_eq!(
+            lines_with_color(&document, red, ColorRole::Foreground),
+            [0, 3]
+        );
+
+        let style = OverlayStyle::highlight(Color32::YELLOW);
+        assert_eq!(
+            highlight_color(&mut document, red, ColorRole::Foreground, style),
+            2
+        );
+        let ranges: Vec<_> = document
+            .overlays()
+            .iter()
+            .map(|overlay| (overlay.line, overlay.range.clone()))
+            .collect();
+        assert_eq!(ranges, [(0, 0..5), (0, 9..10), (3, 7..10)]);
+    }
+
+    #[test]
+    fn document_find_reports_bytes_and_columns() {
+        let mut document = AnsiDocument::<()>::new().with_max_lines(2);
+        document.append_str("dropped error\n");
+        document.append_str("\x1b[31mer\x1b[1mror\x1b[0m \u{4e2d}error\n");
+        document.append_str("no match here\n");
+
+        let found = document.find("error");
+        assert_eq!(
+            found,
+            [
+                AnsiMatch {
+                    line: 1,
+                    byte_range: 0..5,
+                    col_range: 0..5,
+                },
+                AnsiMatch {
+                    line: 1,
+                    byte_range: 9..14,
+                    col_range: 8..13,
+                },
+            ]
+        );
+        assert!(document.find("").is_empty());
+        assert_eq!(document.find(String::from("match")).len(), 1);
+
+        #[cfg(feature = "regex")]
+        {
+            let regex = regex::Regex::new(r"e\w+").unwrap();
+            let lines: Vec<_> = document.find(&regex).iter().map(|m| m.line).collect();
+            assert_eq!(lines, [1, 1, 2]);
+        }
+    }
+
+    #[test]
+    fn align_documents_pairs_changes_and_keeps_sides_in_step() {
+        let mut left = AnsiDocument::<()>::new();
+        left.append_str("build\n\x1b[32mok\x1b[0m a\nfail b\nextra\nsame\ntail\n");
+        let mut right = AnsiDocument::<()>::new();
+        right.append_str("build\nok a\nok b\nsame\nnew\ntail\n");
+
+        let rows = align_documents(&left, &right);
+        let summary: Vec<_> = rows
+            .iter()
+            .map(|row| (row.left, row.right, row.kind))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (Some(0), Some(0), AlignKind::Equal),
+                (Some(1), Some(1), AlignKind::Equal),
+                (Some(2), Some(2), AlignKind::Changed),
+                (Some(3), None, AlignKind::LeftOnly),
+                (Some(4), Some(3), AlignKind::Equal),
+                (None, Some(4), AlignKind::RightOnly),
+                (Some(5), Some(5), AlignKind::Equal),
+            ]
+        );
+
+        let style = OverlayStyle::highlight(Color32::DARK_RED);
+        assert_eq!(highlight_changes(&mut left, &mut right, &rows, style), 3);
+        let lines = |document: &AnsiDocument| -> Vec<_> {
+            document
+                .overlays()
+                .iter()
+                .map(|overlay| overlay.line)
+                .collect()
+        };
+        assert_eq!(lines(&left), [2, 3]);
+        assert_eq!(lines(&right), [2, 4]);
+
+        let empty = AnsiDocument::<()>::new();
+        let rows = align_documents(&empty, &right);
+        assert_eq!(rows.len(), 6);
+        assert!(rows.iter().all(|row| row.kind == AlignKind::RightOnly));
+
+        // Past the edit cost cap the middle is paired in order.
+        let mut left = AnsiDocument::<()>::new();
+        let mut right = AnsiDocument::<()>::new();
+        for index in 0..1000 {
+            left.append_str(&format!("left {index}\n"));
+            right.append_str(&format!("right {index}\n"));
+        }
+        let rows = align_documents(&left, &right);
+        assert_eq!(rows.len(), 1000);
+        assert!(rows.iter().all(|row| row.kind == AlignKind::Changed));
+    }
+
+    #[test]
+    fn text_slice_exports_a_selection_as_html() {
+        let text = ansi_to_text("plain \x1b[31mred\x1b[1m bold\x1b[0m \u{e9}nd");
+
+        let selection = text.slice(8..12);
+        assert_eq!(selection.text, "d bo");
+        assert_eq!(selection.runs.len(), 2);
+        assert_eq!(text.slice(15..17).text, "\u{e9}");
+        assert_eq!(text.slice(10..100).text, "bold \u{e9}nd");
+        assert!(text.slice(40..50).is_empty());
+
+        let options = HtmlOptions {
+            theme: EguiAnsiTheme::default(),
+            wrap_in_pre: false,
+        };
+        let html = text.slice(0..9).to_html(&options);
+        assert!(html.starts_with("plain <span style=\"color:#"));
+        assert!(html.ends_with(">red</span>"));
+        assert_eq!(html, spans_to_html(&text.slice(0..9).to_spans(), &options));
+    }
+
+    #[test]
+    fn files_are_detected_by_name_and_contents() {
+        let cast = b"{\"version\": 2, \"width\": 80, \"height\": 24}\n[0.5, \"o\", \"hi\"]\n";
+        assert_eq!(AnsiFileKind::detect("x.ANS", None), AnsiFileKind::Art);
+        assert_eq!(AnsiFileKind::detect("run.cast", None), AnsiFileKind::Cast);
+        assert_eq!(
+            AnsiFileKind::detect("upload", Some(cast)),
+            AnsiFileKind::Cast
+        );
+        assert_eq!(AnsiFileKind::detect("build.log", None), AnsiFileKind::Log);
+        assert_eq!(
+            AnsiFileKind::detect("build.log", Some(b"{ not json\n")),
+            AnsiFileKind::Log
+        );
+
+        let file = AnsiFile::load("build.log", b"\x1b[32mok\x1b[0m\nnext\n").unwrap();
+        let AnsiFile::Log(document) = &file else {
+            panic!("expected a log, got {file:?}");
+        };
+        assert_eq!(document.line_count(), 2);
+        assert_eq!(file.kind(), AnsiFileKind::Log);
+
+        let art = AnsiFile::load_as(AnsiFileKind::Art, b"\xdb\xdb\x1a").unwrap();
+        assert_eq!(art.kind(), AnsiFileKind::Art);
+
+        #[cfg(feature = "asciicast")]
+        assert_eq!(
+            AnsiFile::load("upload", cast).unwrap().kind(),
+            AnsiFileKind::Cast
+        );
+        #[cfg(not(feature = "asciicast"))]
+        assert!(matches!(
+            AnsiFile::load("upload", cast),
+            Err(AnsiFileError::Unsupported(AnsiFileKind::Cast))
+        ));
+        assert!(matches!(
+            AnsiFile::open("/nonexistent/egui_sgr.log"),
+            Err(AnsiFileError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn text_encodings_are_detected_and_decoded() {
+        let utf8 = "gr\u{fc}n \u{2588}".as_bytes();
+        let latin1 = b"\x1b[32mgr\xfcn\x1b[0m f\xfcr M\xfcller";
+        let cp437 = b"\x1b[31m\xdb\xdb\xb2\xb1\xb0\x1b[0m\t\x81";
+
+        assert_eq!(TextEncoding::detect(b"plain"), TextEncoding::Utf8);
+        assert_eq!(TextEncoding::detect(utf8), TextEncoding::Utf8);
+        assert_eq!(TextEncoding::detect(latin1), TextEncoding::Latin1);
+        assert_eq!(TextEncoding::detect(cp437), TextEncoding::Cp437);
+
+        assert_eq!(
+            TextEncoding::Latin1.decode(latin1),
+            "\x1b[32mgr\u{fc}n\x1b[0m f\u{fc}r M\u{fc}ller"
+        );
+        assert_eq!(
+            TextEncoding::Cp437.decode(cp437),
+            "\x1b[31m\u{2588}\u{2588}\u{2593}\u{2592}\u{2591}\x1b[0m\t\u{fc}"
+        );
+        assert!(matches!(
+            TextEncoding::Cp437.decode(b"ascii"),
+            std::borrow::Cow::Borrowed("ascii")
+        ));
+
+        let file = AnsiFile::load_with_encoding(AnsiFileKind::Log, latin1, TextEncoding::Latin1);
+        let Ok(AnsiFile::Log(document)) = file else {
+            panic!("expected a log");
+        };
+        assert_eq!(document.to_text().text, "gr\u{fc}n f\u{fc}r M\u{fc}ller");
+
+        let art =
+            AnsiArt::from_bytes_with_encoding("\u{2588}\u{2580}".as_bytes(), TextEncoding::Utf8);
+        assert!(art.text.text.starts_with("\u{2588}\u{2580}"));
+    }
+
+    #[test]
+    fn document_finish_discards_partial_escapes_and_ends_the_line() {
+        let mut document = AnsiDocument::<()>::new();
+        document.append_str("\x1b[32mup\n\x1b[3");
+        document.finish();
+        document.append_str("1mback");
+        assert_eq!(document.line_count(), 2);
+        assert_eq!(document.line(1).unwrap().raw, b"1mback");
+        assert_eq!(
+            document.line(1).unwrap().text.style_at(0),
+            Some(AnsiStyle::default())
+        );
+
+        document.finish();
+        document.append_str("next");
+        assert_eq!(document.line_count(), 3);
+        assert!(document.has_open_line());
+    }
+
+    #[test]
+    fn document_form_feeds_start_pages() {
+        let mut document = AnsiDocument::<()>::new();
+        document.append_str("one\x0ctwo\n\x0c\x1b[31mthree\nfour\x0c\n");
+        let texts: Vec<_> = document
+            .lines()
+            .map(|line| line.text.text.as_str())
+            .collect();
+        assert_eq!(texts, ["one", "two", "three", "four", ""]);
+        assert_eq!(document.page_starts(), [1, 2, 4]);
+        assert_eq!(document.to_text().text, "one\ntwo\nthree\nfour\n");
+        assert_eq!(document.stats().control_chars, 3);
+
+        document.set_page_separator(Some("--".to_owned()));
+        let text = document.to_text();
+        assert_eq!(text.text, "one\n--\ntwo\n--\nthree\nfour\n--\n");
+        let separator = text.runs.iter().find(|run| run.range.start == 4).unwrap();
+        assert_eq!(separator.style.intensity, AnsiIntensity::Faint);
+        assert_eq!(document.find("three")[0].byte_range, 0..5);
+
+        #[cfg(feature = "egui")]
+        {
+            document.add_overlay(
+                OverlayLayer::SEARCH,
+                2,
+                0..5,
+                OverlayStyle::highlight(Color32::YELLOW),
+            );
+            let job = document.to_layout_job(&EguiAnsiTheme::default());
+            let start = job.text.find("three").unwrap();
+            assert!(job.sections.iter().any(|section| {
+                section.byte_range == (start..start + 5)
+                    && section.format.background == Color32::YELLOW
+            }));
+        }
+
+        document.clear();
+        document.append_str("\x0c");
+        document.clear();
+        document.append_str("a\n");
+        assert!(document.page_starts().is_empty());
+    }
+
+    #[cfg(feature = "egui")]
+    #[test]
+    fn ui_ext_labels_follow_the_ui_style() {
+        let ctx = egui::Context::default();
+        ctx.set_visuals(egui::Visuals::light());
+        let _ = ctx.run_ui(egui::RawInput::default(), |ui| {
+            let label = ui.ansi_label("\x1b[31mred\x1b[0m plain");
+            let code = ui.ansi_monospace("\x1b[31mred\x1b[0m plain");
+            assert!(code.rect.height() > 0.0);
+            assert_ne!(label.rect.width(), code.rect.width());
+            assert!(!ui.ansi_selectable_label(true, "\x1b[1mtab").clicked());
+
+            let job =
+                ui_ext::ui_layout_job(ui, b"\x1b[31mred\x1b[0m plain", egui::TextStyle::Monospace);
+            assert_eq!(job.text, "red plain");
+            assert_eq!(job.sections[1].format.color, ui.visuals().text_color());
+            assert_eq!(
+                job.sections[1].format.font_id,
+                egui::TextStyle::Monospace.resolve(ui.style())
+            );
+        });
+    }
+
+    #[cfg(feature = "egui")]
+    #[test]
+    fn ui_ext_cached_label_reparses_only_on_change() {
+        let ctx = egui::Context::default();
+        let mut jobs = Vec::new();
+        for (text, dark) in [
+            ("\x1b[31mred", true),
+            ("\x1b[31mred", true),
+            ("\x1b[32mgreen", true),
+            ("\x1b[32mgreen", false),
+        ] {
+            ctx.set_visuals(if dark {
+                egui::Visuals::dark()
+            } else {
+                egui::Visuals::light()
+            });
+            let _ = ctx.run_ui(egui::RawInput::default(), |ui| {
+                let id = egui::Id::new("status");
+                assert!(ui.ansi_label_cached(id, text).rect.width() > 0.0);
+                jobs.push(ui_ext::cached_layout_job(
+                    ui,
+                    id,
+                    text.as_bytes(),
+                    egui::TextStyle::Body,
+                ));
+            });
+        }
+
+        assert!(std::sync::Arc::ptr_eq(&jobs[0], &jobs[1]));
+        assert!(!std::sync::Arc::ptr_eq(&jobs[1], &jobs[2]));
+        assert_eq!(jobs[2].text, "green");
+        assert!(!std::sync::Arc::ptr_eq(&jobs[2], &jobs[3]));
+    }
+
+    #[cfg(feature = "egui")]
+    #[test]
+    fn ui_ext_state_persists_parser_across_frames() {
+        let ctx = egui::Context::default();
+        let mut texts = Vec::new();
+        for chunk in ["\x1b[3", "1mred\n", "more\n"] {
+            let _ = ctx.run_ui(egui::RawInput::default(), |ui| {
+                let id = ui.id().with("console");
+                let text = ui.ansi_state(id, |document: &mut AnsiDocument| {
+                    document.append_str(chunk);
+                    document.to_text()
+                });
+                let other = ui
+                    .ansi_state(ui.id().with("other"), |parser: &mut AnsiStreamParser| {
+                        parser.push_str(chunk).len()
+                    });
+                assert!(other <= 1);
+                texts.push(text);
+            });
+        }
+
+        assert_eq!(texts[2].text, "red\nmore");
+        assert_eq!(texts[2].runs[0].style.foreground, AnsiColor::Indexed(1));
+        assert_eq!(
+            texts[2].runs.last().unwrap().style.foreground,
+            AnsiColor::Indexed(1)
+        );
+    }
+
+    #[test]
+    fn documents_parse_through_custom_backends() {
+        /// Shows escape bytes as text instead of interpreting them.
+        #[derive(Default)]
+        struct Literal;
+
+        impl AnsiBackend for Literal {
+            fn push_bytes(&mut self, chunk: &[u8]) -> Vec<AnsiSpan> {
+                vec![AnsiSpan {
+                    text: to_control_pictures(&String::from_utf8_lossy(chunk)),
+                    style: AnsiStyle::default(),
+                }]
+            }
+
+            fn finish(&mut self) -> Vec<AnsiSpan> {
+                Vec::new()
+            }
+
+            fn reset(&mut self) {}
+        }
+
+        let mut document = AnsiDocument::<()>::new().with_backend(Literal);
+        document.append_str("\x1b[31mred\nnext\n");
+        let texts: Vec<_> = document
+            .lines()
+            .map(|line| line.text.text.as_str())
+            .collect();
+        assert_eq!(texts, ["\u{241b}[31mred\u{240a}", "next\u{240a}"]);
+        assert_eq!(document.stats().bytes, 0);
+
+        let mut buffer = AnsiSpanBuffer::new().with_backend(Literal);
+        buffer.push_str("\x1b[1m");
+        assert_eq!(buffer.spans()[0].text, "\u{241b}[1m");
+
+        let mut builtin: Box<dyn AnsiBackend> = Box::new(AnsiStreamParser::new());
+        let spans = builtin.push_bytes(b"\x1b[32mok");
+        assert_eq!(spans[0].style.foreground, AnsiColor::Indexed(2));
+        assert_eq!(builtin.stats().sgr_sequences, 1);
+    }
+
+    #[test]
+    fn segment_hook_tags_split_spans_and_travels_with_lines() {
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = std::sync::Arc::clone(&seen);
+        let mut document = AnsiDocument::<()>::new().with_segment_hook(move |style, text| {
+            sink.lock().unwrap().push(text.to_owned());
+            (style.underline == UnderlineStyle::Single).then(|| "request".into())
+        });
+        document.append_str("GET \x1b[4mreq-");
+        document.append_str("42\x1b[0m ok\n\x1b[4mnext\n");
+
+        assert_eq!(*seen.lock().unwrap(), ["GET ", "req-", "42", " ok", "next"]);
+        let first = document.line(0).unwrap();
+        assert_eq!(
+            first.tags,
+            [SegmentTag {
+                range: 4..10,
+                tag: "request".into(),
+            }]
+        );
+        assert_eq!(first.tag_at(9).map(|tag| &**tag), Some("request"));
+        assert_eq!(first.tag_at(10), None);
+
+        let mut host = AnsiDocument::<()>::new();
+        host.append_str("start\n");
+        host.append_document(document);
+        assert_eq!(host.line(2).unwrap().tags[0].range, 0..4);
+    }
+
+    #[test]
+    fn entry_points_accept_any_ansi_source() {
+        let input = "\x1b[31mred\x1b[0m plain\n";
+        let expected = ansi_to_spans(input);
+        let shared: std::sync::Arc<str> = input.into();
+        let owned = input.to_owned();
+        let cow: Cow<'_, str> = Cow::Borrowed(input);
+
+        assert_eq!(ansi_to_spans(&owned), expected);
+        assert_eq!(ansi_to_spans(&shared), expected);
+        assert!(matches!((&shared).into_ansi_bytes(), Cow::Borrowed(_)));
+        assert_eq!(ansi_to_spans(&cow), expected);
+        assert_eq!(ansi_to_spans(input.as_bytes().to_vec()), expected);
+        assert_eq!(ansi_to_text(shared).text, "red plain\n");
+        assert!(matches!(
+            b"bytes".into_ansi_bytes(),
+            Cow::Borrowed(b"bytes")
+        ));
+        let shared_bytes: std::sync::Arc<[u8]> = input.as_bytes().into();
+        assert!(matches!(
+            (&shared_bytes).into_ansi_bytes(),
+            Cow::Borrowed(_)
+        ));
+        assert_eq!(ansi_to_spans(&shared_bytes), expected);
+        assert!(matches!(
+            (&shared_bytes).into_ansi_str(),
+            Err(Cow::Borrowed(_))
+        ));
+        assert!(matches!(
+            "plain".into_ansi_str(),
+            Ok(Cow::Borrowed("plain"))
+        ));
+        assert!(matches!(
+            Cow::Borrowed("plain").into_ansi_str(),
+            Ok(Cow::Borrowed("plain"))
+        ));
+        assert_eq!(ansi_to_spans("plain")[0].text, "plain");
+        assert_eq!(ansi_to_spans("a\x07b"), ansi_to_spans(b"a\x07b"));
+        assert_eq!(ansi_to_spans(shared_bytes), expected);
+
+        let mut document = AnsiDocument::<()>::new();
+        document.append(owned);
+        document.append(b"\x1b[32mgreen\n");
+        let read = document.append_reader(&b"one\ntwo\n"[..]).unwrap();
+        assert_eq!(read, 8);
+        let texts: Vec<_> = document
+            .lines()
+            .map(|line| line.text.text.as_str())
+            .collect();
+        assert_eq!(texts, ["red plain", "green", "one", "two"]);
+        assert_eq!(
+            document.line(3).unwrap().text.runs[0].style.foreground,
+            AnsiColor::Indexed(2)
+        );
+    }
+
+    #[test]
+    fn documents_collect_from_line_iterators() {
+        let log = "ok\n\x1b[31merror: disk\nok\n\x1b[0merror: net\n";
+        let errors: AnsiDocument = log.lines().filter(|line| line.contains("error")).collect();
+        let texts: Vec<_> = errors.lines().map(|line| line.text.text.as_str()).collect();
+        assert_eq!(texts, ["error: disk", "error: net"]);
+        assert!(!errors.has_open_line());
+
+        let mut document: AnsiDocument = vec![String::from("\x1b[32mfirst")].into_iter().collect();
+        document.append_str("open");
+        document.extend(errors.lines().cloned());
+        document.extend(std::iter::empty::<AnsiLine>());
+        document.extend([b"last".as_slice()]);
+        let texts: Vec<_> = document
+            .lines()
+            .map(|line| line.text.text.as_str())
+            .collect();
+        assert_eq!(
+            texts,
+            ["first", "open", "error: disk", "error: net", "last"]
+        );
+        assert_eq!(
+            document.line(2).unwrap().text.runs[0].style.foreground,
+            AnsiColor::Indexed(1)
+        );
+        assert_eq!(
+            document.line(4).unwrap().text.runs[0].style.foreground,
+            AnsiColor::Indexed(2)
+        );
+    }
+
+    #[test]
+    fn common_log_format_splits_fields() {
+        type Fields<'a> = (Option<&'a str>, Option<&'a str>, Option<&'a str>, &'a str);
+        fn fields(line: &str) -> Option<Fields<'_>> {
+            let fields = CommonLogFormat.parse(line)?;
+            let text = |range: Option<std::ops::Range<usize>>| range.map(|range| &line[range]);
+            Some((
+                text(fields.timestamp),
+                text(fields.level),
+                text(fields.source),
+                &line[fields.message],
+            ))
+        }
+
+        assert_eq!(
+            fields("[2024-05-01T12:00:00Z INFO  app::db] connected"),
+            Some((
+                Some("2024-05-01T12:00:00Z"),
+                Some("INFO"),
+                Some("app::db"),
+                "connected"
+            ))
+        );
+        assert_eq!(
+            fields("2024-05-01 12:00:00,123 - worker - ERROR - job failed"),
+            Some((
+                Some("2024-05-01 12:00:00,123"),
+                Some("ERROR"),
+                Some("worker"),
+                "job failed"
+            ))
+        );
+        assert_eq!(
+            fields("[12:00:00] [WARN] low disk"),
+            Some((Some("12:00:00"), Some("WARN"), None, "low disk"))
+        );
+        assert_eq!(
+            fields("May  1 12:00:00 host sshd[42]: accepted"),
+            Some((
+                Some("May  1 12:00:00"),
+                None,
+                None,
+                "host sshd[42]: accepted"
+            ))
+        );
+        assert_eq!(
+            fields("error: could not compile"),
+            Some((None, Some("error"), None, "could not compile"))
+        );
+        assert_eq!(fields("Error opening file"), None);
+        assert_eq!(fields("plain output"), None);
+    }
+
+    #[test]
+    fn log_row_keeps_message_styles() {
+        let mut document = AnsiDocument::<()>::new();
+        document.append("2024-05-01T12:00:00Z \x1b[33mWARN\x1b[0m cache: \x1b[1mmiss\x1b[0m\n");
+        document.append("    at frame 1\n");
+
+        let row = document.line(0).unwrap().log_row(&CommonLogFormat).unwrap();
+        assert_eq!(row.timestamp.text, "2024-05-01T12:00:00Z");
+        assert_eq!(row.level.runs[0].style.foreground, AnsiColor::Indexed(3));
+        assert_eq!(row.log_level, Some(LogLevel::Warn));
+        assert_eq!(row.source.text, "cache");
+        assert_eq!(row.message.text, "miss");
+        assert_eq!(row.message.runs[0].style.intensity, AnsiIntensity::Bold);
+        assert!(
+            document
+                .line(1)
+                .unwrap()
+                .log_row(&CommonLogFormat)
+                .is_none()
+        );
+
+        let custom = |line: &str| {
+            let (source, _) = line.split_once('>')?;
+            Some(LogFields {
+                source: Some(0..source.len()),
+                message: source.len() + 1..line.len(),
+                ..LogFields::default()
+            })
+        };
+        assert_eq!(custom.parse("db>ready").unwrap().message, 3..8);
+    }
+
+    #[test]
+    fn json_log_lines_are_highlighted_and_split() {
+        let line = r#"{"timestamp":"2024-05-01T12:00:00Z","level":"ERROR","fields":{"message":"disk \"full\""},"target":"app::io","ok":false,"n":1.5e3}"#;
+        let text = json_to_text(line, false).unwrap();
+        assert_eq!(text.text, line);
+        let style_of = |needle: &str| {
+            text.style_at(line.find(needle).unwrap())
+                .unwrap()
+                .foreground
+        };
+        assert_eq!(style_of("\"level\""), AnsiColor::Indexed(6));
+        assert_eq!(style_of("\"ERROR\""), AnsiColor::Indexed(2));
+        assert_eq!(style_of("false"), AnsiColor::Indexed(5));
+        assert_eq!(style_of("1.5e3"), AnsiColor::Indexed(3));
+
+        let fields = JsonLogFormat.parse(line).unwrap();
+        assert_eq!(
+            &line[fields.timestamp.clone().unwrap()],
+            "2024-05-01T12:00:00Z"
+        );
+        assert_eq!(fields.log_level(line), Some(LogLevel::Error));
+        assert_eq!(&line[fields.source.clone().unwrap()], "app::io");
+        assert_eq!(&line[fields.message], r#"disk \"full\""#);
+
+        let pretty = json_to_text(r#"{ "a": {}, "b": [ ] }"#, true).unwrap();
+        assert_eq!(pretty.text, "{\n  \"a\": {},\n  \"b\": []\n}");
+
+        for not_object in [r#"["a"]"#, r#"{"a":1} tail"#, r#"{"a":"open}"#, "{1:2}", ""] {
+            assert!(json_to_text(not_object, false).is_none(), "{not_object}");
+        }
+        let mixed = (JsonLogFormat, CommonLogFormat);
+        assert!(mixed.parse("[12:00:00] INFO up").unwrap().level.is_some());
+        assert_eq!(JsonLogFormat.parse(r#"{"a":1}"#).unwrap().message, 0..7);
+    }
+
+    #[test]
+    fn logfmt_lines_are_highlighted_and_split() {
+        let line = r#"time=12:00:00 lvl=WARN component=db msg="slow \"query\"" took=1.5 cached"#;
+        let text = logfmt_to_text(line).unwrap();
+        assert_eq!(text.text, line);
+        let style_at = |needle: &str| text.style_at(line.find(needle).unwrap()).unwrap();
+        assert_eq!(style_at("component").foreground, AnsiColor::Indexed(6));
+        assert_eq!(style_at("WARN"), LogLevel::Warn.style());
+        assert_eq!(style_at("\"slow").foreground, AnsiColor::Indexed(2));
+        assert_eq!(style_at("1.5").foreground, AnsiColor::Indexed(3));
+        assert_eq!(style_at("=db").foreground, AnsiColor::Default);
+
+        let fields = LogfmtFormat.parse(line).unwrap();
+        assert_eq!(&line[fields.timestamp.clone().unwrap()], "12:00:00");
+        assert_eq!(fields.log_level(line), Some(LogLevel::Warn));
+        assert_eq!(&line[fields.source.clone().unwrap()], "db");
+        assert_eq!(&line[fields.message], r#"slow \"query\""#);
+
+        for prose in [
+            "set a=1 while b is unset",
+            "a=1",
+            r#"a=1 b="open"#,
+            "x=1 y=2 words here too",
+        ] {
+            assert!(logfmt_to_text(prose).is_none(), "{prose}");
+        }
+        assert_eq!(LogfmtFormat.parse("a=1 b=2").unwrap().message, 0..7);
+    }
+
+    #[test]
+    fn long_lines_fold_to_styled_previews() {
+        let text = ansi_to_text("\x1b[31mab全cd\x1b[0m");
+        let folded = text.fold(3).unwrap();
+        assert_eq!(folded.preview.text, "ab");
+        assert_eq!(
+            folded.preview.runs[0].style.foreground,
+            AnsiColor::Indexed(1)
+        );
+        assert_eq!(folded.hidden_chars, 3);
+        assert_eq!(folded.marker(), "… +3 chars");
+        assert!(text.fold(6).is_none());
+        let marker = |hidden_chars| {
+            FoldedText {
+                preview: AnsiText::new(),
+                hidden_chars,
+            }
+            .marker()
+        };
+        assert_eq!(marker(999), "… +999 chars");
+        assert_eq!(marker(12_345), "… +12,345 chars");
+        assert_eq!(marker(1_234_567), "… +1,234,567 chars");
+
+        let mut document = AnsiDocument::<()>::new()
+            .with_fold_columns(5)
+            .with_max_lines(2);
+        document.append_str("one\n0123456789\nlonger line\n");
+        document.add_overlay(OverlayLayer(0), 2, 3..9, OverlayStyle::default());
+        assert_eq!(
+            document.to_text().text,
+            "01234 … +5 chars\nlonge … +6 chars"
+        );
+        assert_eq!(document.line_offsets(), vec![0, 19]);
+        assert_eq!(document.fold_marker_at(3), None);
+        assert_eq!(document.fold_marker_at(19 + 6), Some(2));
+
+        document.set_line_expanded(2, true);
+        assert!(document.is_line_expanded(2));
+        assert_eq!(document.to_text().text, "01234 … +5 chars\nlonger line");
+        assert_eq!(document.fold_marker_at(19 + 6), None);
+
+        let mut paged = AnsiDocument::<()>::new()
+            .with_fold_columns(2)
+            .with_page_separator("--");
+        paged.append_str("abc\x0c\nde\n");
+        let text = paged.to_text().text;
+        assert_eq!(text, "ab … +1 chars\n--\n\nde");
+        assert_eq!(paged.line_offsets(), vec![0, 19, 20]);
+        assert_eq!(paged.fold_marker_at(3), Some(0));
+        document.append_str("x\ny\n");
+        assert!(!document.is_line_expanded(2));
+        document.set_fold_columns(None);
+        assert_eq!(document.to_text().text, "x\ny");
+    }
+
+    #[test]
+    fn log_filter_matches_text_ignoring_case() {
+        let mut document = AnsiDocument::<()>::new();
+        document.append_str("DB up\n\x1b[31mÉchec\x1b[0m du disque\nnet\n");
+        let kept = |text: &str| -> Vec<String> {
+            let filter = LogFilter {
+                text: text.into(),
+                ..LogFilter::default()
+            };
+            document
+                .lines()
+                .filter(|line| filter.keeps(line, &CommonLogFormat))
+                .map(|line| line.text.text.clone())
+                .collect()
+        };
+        assert_eq!(kept("db"), ["DB up"]);
+        assert_eq!(kept("éCHEC"), ["Échec du disque"]);
+        assert_eq!(kept("DISQUE"), ["Échec du disque"]);
+        assert!(kept("network").is_empty());
+        assert_eq!(kept("").len(), 3);
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn filter_presets_round_trip_through_egui_memory() {
+        let errors = LogFilter {
+            text: "db".into(),
+            search: "time(out)?".into(),
+            min_level: Some(LogLevel::Error),
+        };
+        let mut presets = FilterPresets::default();
+        presets.save("errors", errors.clone());
+        presets.save("all", LogFilter::default());
+        presets.save(
+            "errors",
+            LogFilter {
+                min_level: Some(LogLevel::Warn),
+                ..errors.clone()
+            },
+        );
+        assert_eq!(presets.len(), 2);
+        assert_eq!(
+            presets.get("errors").unwrap().min_level,
+            Some(LogLevel::Warn)
+        );
+        assert!(!presets.rename("all", "errors"));
+        assert!(presets.rename("all", "everything"));
+        assert!(presets.get("everything").unwrap().is_empty());
+        assert_eq!(presets.remove("missing"), None);
+
+        let ctx = egui::Context::default();
+        let id = egui::Id::new("presets");
+        assert!(FilterPresets::load(&ctx, id).is_empty());
+        presets.store(&ctx, id);
+        // Restore the memory into a new context, like the next session.
+        let saved = ctx.memory(serde_json::to_string).unwrap();
+        let ctx = egui::Context::default();
+        ctx.memory_mut(|memory| *memory = serde_json::from_str(&saved).unwrap());
+        let loaded = FilterPresets::load(&ctx, id);
+        let names: Vec<_> = loaded.iter().map(|(name, _)| name).collect();
+        assert_eq!(names, ["errors", "everything"]);
+        assert_eq!(loaded, presets);
+    }
+
+    #[test]
+    fn shared_document_versions_track_changes() {
+        let shared = SharedAnsiDocument::<()>::default();
+        let view = shared.clone();
+        assert!(view.ptr_eq(&shared));
+        assert!(!view.ptr_eq(&SharedAnsiDocument::default()));
+
+        let mut seen = view.version();
+        let mut changed = |document: &SharedAnsiDocument| {
+            let version = document.version();
+            std::mem::replace(&mut seen, version) != version
+        };
+        shared.lock().append("\x1b[31merror\x1b[0m\n");
+        assert!(changed(&view));
+        assert!(!changed(&view));
+        assert_eq!(view.lock().line(0).unwrap().text.text, "error");
+
+        shared
+            .lock()
+            .add_overlay(OverlayLayer(0), 0, 0..1, OverlayStyle::default());
+        assert!(changed(&view));
+        shared.lock().set_line_expanded(0, true);
+        assert!(changed(&view));
+        let _ = shared.lock().line_mut(0);
+        assert!(changed(&view));
+        let _ = shared.lock().to_text();
+        assert!(!changed(&view));
+        shared.lock().extend([AnsiLine::default()]);
+        assert!(changed(&view));
+        shared.lock().extend(std::iter::empty::<AnsiLine>());
+        assert!(!changed(&view));
+
+        let producer = {
+            let shared = shared.clone();
+            std::thread::spawn(move || shared.lock().append("from thread\n"))
+        };
+        producer.join().unwrap();
+        assert_eq!(view.lock().line_count(), 3);
+        assert!(changed(&view));
+    }
+
+    #[cfg(feature = "bidi")]
+    #[test]
+    fn bidi_reorders_rtl_runs_per_line() {
+        let text = ansi_to_text("user \x1b[1mמשה\x1b[0m [ok]\nسلام (1)\nplain\n");
+        let visual = text.to_visual();
+        assert!(visual.is_reordered());
+        let lines: Vec<&str> = visual.text().text.lines().collect();
+        // LTR line: the Hebrew word is reversed in place.
+        assert_eq!(lines[0], "user השמ [ok]");
+        // RTL line: the whole line reads from the right, brackets mirrored.
+        assert_eq!(lines[1], "(1) مالس");
+        assert_eq!(lines[2], "plain");
+        assert_eq!(visual.text().text.len(), text.text.len());
+
+        // Styles follow their characters, and offsets map both ways.
+        let bold = text.text.find("מ").unwrap();
+        let shown = visual.visual_offset(bold).unwrap();
+        assert_eq!(&visual.text().text[shown..shown + 2], "מ");
+        assert_eq!(visual.logical_offset(shown), Some(bold));
+        assert_eq!(
+            visual.text().style_at(shown).unwrap().intensity,
+            AnsiIntensity::Bold
+        );
+        for (offset, _) in visual.text().text.char_indices() {
+            let logical = visual.logical_offset(offset).unwrap();
+            assert_eq!(visual.visual_offset(logical), Some(offset));
+        }
+
+        // Empty lines around right-to-left text pass through.
+        for input in ["\n\u{5e9}", "\u{5e9}\n\nx", "\u{5e9}\n\n"] {
+            let visual = ansi_to_text(input).to_visual();
+            assert_eq!(visual.text().text, input);
+            for (offset, _) in input.char_indices() {
+                assert_eq!(visual.logical_offset(offset), Some(offset));
+            }
+        }
+
+        // Combining marks stay after their base character.
+        let pointed = ansi_to_text("\u{5e9}\u{5b8}\u{5dc}").to_visual();
+        assert_eq!(pointed.text().text, "\u{5dc}\u{5e9}\u{5b8}");
+        assert_eq!(pointed.visual_offset(2), Some(4));
+        assert_eq!(pointed.logical_offset(4), Some(2));
+
+        let plain = ansi_to_text("\x1b[32mok\x1b[0m");
+        let unchanged = plain.to_visual();
+        assert!(!unchanged.is_reordered());
+        assert_eq!(unchanged.text(), &plain);
+        assert_eq!(unchanged.logical_offset(1), Some(1));
+        assert_eq!(unchanged.logical_offset(2), None);
+    }
+
+    #[test]
+    fn width_utilities_keep_grapheme_clusters_together() {
+        let heart = "\u{2764}\u{fe0f}";
+        let family = "\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}";
+        assert_eq!(visible_width(heart), 2);
+        assert_eq!(visible_width("\u{1f1fa}\u{1f1f8}"), 2);
+        assert_eq!(visible_width(&format!("a{heart}b")), 4);
+
+        // An emoji sequence either fits whole or is cut whole.
+        let line = format!("ok {family}\x1b[31m{heart}\x1b[0m done");
+        assert_eq!(strip_ansi(&truncate_ansi(&line, 4, "")), "ok ");
+        assert_eq!(
+            strip_ansi(&truncate_ansi(&line, 6, "")),
+            format!("ok {family}")
+        );
+        let with_heart = truncate_ansi(&line, 7, "");
+        assert_eq!(strip_ansi(&with_heart), format!("ok {family}{heart}"));
+        assert_eq!(
+            ansi_to_spans(&with_heart)[1].style.foreground,
+            AnsiColor::Indexed(1)
+        );
+
+        assert_eq!(
+            wrap_ansi(&format!("ab{heart}{family}"), 3)
+                .iter()
+                .map(|line| strip_ansi(line).into_owned())
+                .collect::<Vec<_>>(),
+            ["ab", heart, family]
+        );
+        assert_eq!(strip_ansi(&slice_ansi(&line, 3..5)), family);
+        assert_eq!(strip_ansi(&slice_ansi(&line, 4..7)), format!(" {heart}"));
+
+        let text = ansi_to_text(format!("{family}{family}x"));
+        let folded = text.fold(3).unwrap();
+        assert_eq!(folded.preview.text, family);
+        assert_eq!(folded.hidden_chars, 6);
+
+        let aligned = align_columns(
+            &[ansi_to_text(format!("{heart}\tok")), ansi_to_text("ab\tok")],
+            1,
+        );
+        assert_eq!(aligned[0].text, format!("{heart} ok"));
+        assert_eq!(aligned[1].text, "ab ok");
+
+        let mut document: AnsiDocument = AnsiDocument::new();
+        document.append(format!("{heart}{family}x\n"));
+        document.add_overlay_columns(OverlayLayer(0), 0, 2..4, OverlayStyle::default());
+        let start = heart.len();
+        assert_eq!(document.overlays()[0].range, start..start + family.len());
+    }
+
+    #[cfg(feature = "egui")]
+    #[test]
+    fn powerline_glyphs_are_hidden_and_painted() {
+        let theme = EguiAnsiTheme::default();
+        let line = "\x1b[44m ~/src \x1b[34;42m\u{e0b0}\x1b[30m main \x1b[0;32m\u{e0b4}\x1b[0m $";
+        let mut job = ansi_to_layout_job(line, &theme);
+        let original = job.clone();
+        let glyphs = PowerlineGlyphs::hide(&mut job);
+        assert!(!glyphs.is_empty());
+        assert_eq!(job.text, original.text);
+
+        let hidden: Vec<_> = job
+            .sections
+            .iter()
+            .filter(|section| section.format.color == egui::Color32::TRANSPARENT)
+            .map(|section| &job.text[section.byte_range.clone()])
+            .collect();
+        assert_eq!(hidden, ["\u{e0b0}", "\u{e0b4}"]);
+        let mut end = 0;
+        for section in &job.sections {
+            assert_eq!(section.byte_range.start, end);
+            end = section.byte_range.end;
+        }
+        assert_eq!(end, job.text.len());
+
+        let mut plain = ansi_to_layout_job("no separators", &theme);
+        let unchanged = plain.clone();
+        assert!(PowerlineGlyphs::hide(&mut plain).is_empty());
+        assert_eq!(plain, unchanged);
+
+        let ctx = egui::Context::default();
+        let output = ctx.run_ui(egui::RawInput::default(), |ui| {
+            let galley = ui.fonts_mut(|fonts| fonts.layout_job(job.clone()));
+            glyphs.paint(ui.painter(), egui::Pos2::ZERO, &galley);
+            assert!(!paint_powerline_glyph(
+                ui.painter(),
+                egui::Rect::ZERO,
+                'x',
+                egui::Color32::RED
+            ));
+        });
+        let painted: Vec<_> = output
+            .shapes
+            .iter()
+            .filter_map(|clipped| match &clipped.shape {
+                egui::Shape::Path(path) => Some(path.fill),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(painted, [theme.palette[4], theme.palette[2]]);
+    }
+
+    #[test]
+    fn workspace_tracks_tabs_and_unread_lines() {
+        let build = SharedAnsiDocument::default();
+        build.lock().append("old output\n");
+        let mut workspace = AnsiWorkspace::new();
+        assert_eq!(workspace.add(AnsiSession::new("build", build.clone())), 0);
+        let mut profile = TerminalProfile::default();
+        profile.theme.palette[1] = ecolor::Color32::from_rgb(255, 0, 0);
+        let serial =
+            AnsiSession::new("serial", SharedAnsiDocument::default()).with_profile(&profile);
+        assert_eq!(workspace.add(serial), 1);
+        assert_eq!(workspace.active_index(), 1);
+        assert_eq!(
+            workspace.active().unwrap().profile.theme.palette[1],
+            profile.theme.palette[1]
+        );
+
+        // Output in a background tab is unread; a partial line is not counted.
+        assert_eq!(workspace.unread_lines(0), 0);
+        build.lock().append("one\ntwo\nthr");
+        assert_eq!(workspace.unread_lines(0), 2);
+        build.lock().set_max_lines(Some(1));
+        assert_eq!(workspace.unread_lines(0), 2);
+
+        // Output shown while a tab is active stays read after switching away.
+        workspace.set_active(0);
+        assert_eq!(workspace.unread_lines(0), 0);
+        assert_eq!(workspace.sessions()[0].unread_lines(), 0);
+        build.lock().append("ee\n");
+        workspace.set_active(1);
+        assert_eq!(workspace.unread_lines(0), 0);
+        workspace
+            .active_mut()
+            .unwrap()
+            .document
+            .lock()
+            .append("boot\n");
+        assert_eq!(workspace.unread_lines(1), 0);
+
+        workspace.rename(1, "ttyUSB0");
+        assert_eq!(workspace.sessions()[1].title, "ttyUSB0");
+        if let Some(session) = workspace.session_mut(1) {
+            session.profile = TerminalProfile::default();
+        }
+        let closed = workspace.close(0).unwrap();
+        assert!(closed.document.ptr_eq(&build));
+        assert_eq!(workspace.len(), 1);
+        assert_eq!(workspace.active().unwrap().title, "ttyUSB0");
+        assert!(workspace.close(5).is_none());
+        let _ = workspace.close(0);
+        assert!(workspace.is_empty());
+        assert!(workspace.active().is_none());
+        assert_eq!(workspace.unread_lines(0), 0);
+    }
+
+    #[test]
+    fn terminal_profile_configures_documents() {
+        let mut profile = TerminalProfile {
+            scrollback_lines: Some(2),
+            fold_columns: Some(3),
+            page_separator: Some("--".into()),
+            ..TerminalProfile::default()
+        };
+        profile.theme.palette[1] = ecolor::Color32::from_rgb(200, 0, 0);
+        let mut document: AnsiDocument = AnsiDocument::new();
+        document.append("one\ntwo\nthree long\n");
+        let version = document.version();
+        profile.apply_to_document(&mut document);
+        assert!(document.version() > version);
+        assert_eq!(document.line_count(), 2);
+        assert!(document.to_text().text.starts_with("two\nthr"));
+
+        profile.scrollback_lines = None;
+        profile.fold_columns = None;
+        profile.apply_to_document(&mut document);
+        document.append("four\nfive\n");
+        assert_eq!(document.line_count(), 4);
+        assert_eq!(TerminalProfile::default().cursor, CursorShape::Block);
+
+        #[cfg(feature = "egui")]
+        {
+            let font = egui::FontId::monospace(18.0);
+            let profile = profile.clone().with_font(font.clone());
+            assert_eq!(profile.font_id(), &font);
+        }
+
+        let mut session = AnsiSession::new("log", SharedAnsiDocument::default());
+        session.document.lock().append("a\nb\nc\n");
+        profile.scrollback_lines = Some(1);
+        profile.apply_to_session(&mut session);
+        assert_eq!(session.profile, profile);
+        assert_eq!(session.document.lock().line_count(), 1);
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&profile).unwrap();
+            let loaded: TerminalProfile = serde_json::from_str(&json).unwrap();
+            assert_eq!(loaded, profile);
+            let partial: TerminalProfile =
+                serde_json::from_str(r#"{"name":"Mono","cursor":"Bar"}"#).unwrap();
+            assert_eq!(partial.cursor, CursorShape::Bar);
+            assert_eq!(partial.scrollback_lines, Some(10_000));
+        }
+    }
+
+    #[test]
+    fn document_damage_tracks_changed_lines() {
+        let mut document = AnsiDocument::<()>::new().with_max_lines(3);
+        document.append("a\nb\nc\n");
+        assert_eq!(document.take_damage().first_line(), Some(0));
+
+        let _ = document.line_mut(1);
+        document.add_overlay(OverlayLayer::SEARCH, 2, 0..1, OverlayStyle::default());
+        let damage = document.take_damage();
+        assert!(!damage.contains(0) && damage.contains(1) && damage.contains(2));
+        assert!(!damage.contains(3));
+
+        document.append("d\n");
+        let damage = document.take_damage();
+        assert_eq!(document.first_line_number(), 1);
+        assert_eq!(damage.first_line(), Some(3));
+        assert!(!damage.contains(2));
+
+        document.clear_overlays(OverlayLayer::SEARCH);
+        assert!(document.damage().contains(2));
+        document.set_fold_columns(Some(10));
+        assert!(document.take_damage().is_full());
+        assert!(document.damage().is_empty());
+    }
+
+    #[test]
+    fn packed_cells_round_trip_styles() {
+        let text = ansi_to_text(
+            "\x1b[1;3;4:3;9;7;8;38;5;200;48;5;0mA\x1b[0m \x1b[2;38;2;1;2;3mB\x1b[58;5;1;4mC",
+        );
+        let mut cells = PackedCells::from_text(&text);
+        assert_eq!(cells.len(), 4);
+        assert_eq!(cells.extended_styles(), 2);
+        assert_eq!(cells.to_text(), text);
+        assert_eq!(std::mem::size_of::<PackedCell>(), 8);
+
+        let copy = cells.clone();
+        assert_eq!(cells, copy);
+        let (_, style) = cells.get(2).unwrap();
+        assert!(cells.set(0, 'A', style));
+        assert!(!cells.set(4, 'x', style));
+        assert_eq!(cells.changed_columns(&copy).collect::<Vec<_>>(), [0]);
+        assert_eq!(cells.get(0), Some(('A', style)));
+    }
+
+    #[cfg(feature = "egui")]
+    #[test]
+    fn bold_default_foreground_uses_bold_color() {
+        let mut theme = EguiAnsiTheme::default();
+        let job = ansi_to_layout_job("\x1b[1mbold", &theme);
+        assert_eq!(job.sections[0].format.color, theme.default_foreground);
+
+        theme.bold_foreground = Some(Color32::WHITE);
+        let job = ansi_to_layout_job(
+            "\x1b[1mbold\x1b[0m \x1b[1;31mred\x1b[0m \x1b[1;7mrev",
+            &theme,
+        );
+        assert_eq!(job.sections[0].format.color, Color32::WHITE);
+        assert_eq!(job.sections[2].format.color, theme.palette[9]);
+        assert_eq!(job.sections[4].format.color, theme.default_background);
+    }
+
+    #[cfg(feature = "egui")]
+    #[test]
+    fn layout_job_italics_follow_sgr_3_and_23() {
+        let theme = EguiAnsiTheme::default();
+        let job = ansi_to_layout_job("\x1b[3;31msrc/lib.rs\x1b[23m:12 \x1b[3mnote", &theme);
+        let italics: Vec<_> = job
+            .sections
+            .iter()
+            .map(|section| {
+                (
+                    &job.text[section.byte_range.clone()],
+                    section.format.italics,
+                )
+            })
+            .collect();
+        assert_eq!(
+            italics,
+            [("src/lib.rs", true), (":12 ", false), ("note", true)]
+        );
+        assert_eq!(job.sections[1].format.color, theme.palette[1]);
+    }
+
+    #[cfg(feature = "egui")]
+    #[test]
+    fn layout_job_underline_persists_until_sgr_24() {
+        let theme = EguiAnsiTheme::default();
+        let mut parser = AnsiStreamParser::new();
+        let mut spans = parser.push_bytes(b"\x1b[4mlink \x1b[3");
+        spans.extend(parser.push_bytes(b"4mtext\x1b[24m plain"));
+        let job = spans_to_layout_job(&spans, &theme);
+
+        let underline = |index: usize| job.sections[index].format.underline;
+        assert_eq!(
+            underline(0),
+            Stroke::new(theme.underline_width, theme.default_foreground)
+        );
+        assert_eq!(
+            underline(1),
+            Stroke::new(theme.underline_width, theme.palette[4])
+        );
+        assert_eq!(underline(2), Stroke::NONE);
+        assert_eq!(&job.text[job.sections[2].byte_range.clone()], " plain");
+    }
+
+    #[cfg(feature = "egui")]
+    #[test]
+    fn layout_job_strikethrough_ends_at_sgr_29() {
+        let theme = EguiAnsiTheme::default();
+        let job = ansi_to_layout_job("\x1b[33;9mold_api\x1b[29m() is deprecated", &theme);
+
+        assert_eq!(
+            job.sections[0].format.strikethrough,
+            Stroke::new(theme.strikethrough_width, theme.palette[3])
+        );
+        assert_eq!(job.sections[1].format.strikethrough, Stroke::NONE);
+        assert_eq!(job.sections[1].format.color, theme.palette[3]);
+    }
+
+    #[cfg(feature = "egui")]
+    #[test]
+    fn faint_text_fades_by_theme_opacity() {
+        let mut theme = EguiAnsiTheme {
+            faint_opacity: 0.5,
+            ..EguiAnsiTheme::default()
+        };
+        let input = "\x1b[2;31mdim\x1b[22m normal";
+        let job = ansi_to_layout_job(input, &theme);
+        let [r, g, b, _] = theme.palette[1].to_array();
+        assert_eq!(
+            job.sections[0].format.color,
+            Color32::from_rgba_unmultiplied(r, g, b, 128)
+        );
+        assert_eq!(job.sections[1].format.color, theme.palette[1]);
+
+        theme.faint_opacity = 1.0;
+        let job = ansi_to_layout_job(input, &theme);
+        assert_eq!(job.sections[0].format.color, theme.palette[1]);
+    }
 }
@@ -0,0 +1,193 @@
+use crate::parser;
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Hyperlink over a byte range of the visible text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AnsiHyperlink {
+    /// Byte range into the visible text, as produced by
+    /// [`ansi_to_text`](crate::ansi_to_text) for the same input.
+    pub range: Range<usize>,
+    /// Target URL, unvalidated; check it with [`LinkPolicy::allows`].
+    pub url: String,
+}
+
+/// Returns the OSC 8 hyperlinks of ANSI input.
+///
+/// Both BEL- and ST-terminated sequences are recognized, and the `id=`
+/// parameter is ignored. Links that cover no visible text are dropped.
+#[must_use]
+pub fn ansi_hyperlinks(input: &str) -> Vec<AnsiHyperlink> {
+    parser::hyperlink_ranges(input.as_bytes())
+        .into_iter()
+        .map(|(range, url)| AnsiHyperlink { range, url })
+        .collect()
+}
+
+/// Returns the byte ranges of URLs written out in plain text.
+///
+/// A URL starts with a `scheme://` prefix or `mailto:` and runs to the next
+/// whitespace. Trailing punctuation such as a sentence's final period or a
+/// closing parenthesis without a matching opening one is left out.
+#[must_use]
+pub fn find_urls(text: &str) -> Vec<Range<usize>> {
+    let bytes = text.as_bytes();
+    let mut urls = Vec::new();
+    let mut search = 0;
+
+    while let Some(found) = text[search..].find(':').map(|offset| search + offset) {
+        let start = scheme_start(bytes, found);
+        let after = &text[found..];
+        let url_like = start < found
+            && (after.starts_with("://") || text[start..found].eq_ignore_ascii_case("mailto"));
+        if !url_like {
+            search = found + 1;
+            continue;
+        }
+
+        let end = found
+            + after
+                .find(|c: char| c.is_whitespace() || c.is_control())
+                .unwrap_or(after.len());
+        let end = trim_url_end(&text[start..end]) + start;
+        let separator_len = if after.starts_with("://") { 3 } else { 1 };
+        if end > found + separator_len {
+            urls.push(start..end);
+        }
+        search = end.max(found + 1);
+    }
+    urls
+}
+
+/// Callback receiving approved link targets.
+type LinkHandler = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// Rules for which link targets may be opened, and how.
+///
+/// Links come from untrusted output, so the default only allows `http`,
+/// `https`, and `mailto` targets of at most 2048 bytes. Embedders can route
+/// approved links to their own handler, for example to ask for confirmation
+/// first, instead of opening them directly.
+#[derive(Clone)]
+pub struct LinkPolicy {
+    allowed_schemes: Vec<String>,
+    max_len: usize,
+    on_open: Option<LinkHandler>,
+}
+
+impl fmt::Debug for LinkPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LinkPolicy")
+            .field("allowed_schemes", &self.allowed_schemes)
+            .field("max_len", &self.max_len)
+            .field("on_open", &self.on_open.is_some())
+            .finish()
+    }
+}
+
+impl Default for LinkPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LinkPolicy {
+    /// Creates the default policy.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            allowed_schemes: vec!["http".to_owned(), "https".to_owned(), "mailto".to_owned()],
+            max_len: 2048,
+            on_open: None,
+        }
+    }
+
+    /// Replaces the allowed URL schemes; matching ignores ASCII case.
+    #[must_use]
+    pub fn with_allowed_schemes<S: Into<String>>(
+        mut self,
+        schemes: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.allowed_schemes = schemes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the maximum URL length in bytes.
+    #[must_use]
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Routes allowed links to `on_open` instead of opening them.
+    #[must_use]
+    pub fn with_on_open(mut self, on_open: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        self.on_open = Some(Arc::new(on_open));
+        self
+    }
+
+    /// Returns `true` if `url` has an allowed scheme, fits the length limit,
+    /// and contains no whitespace or control characters.
+    #[must_use]
+    pub fn allows(&self, url: &str) -> bool {
+        let Some((scheme, rest)) = url.split_once(':') else {
+            return false;
+        };
+        url.len() <= self.max_len
+            && !rest.is_empty()
+            && !url.chars().any(|c| c.is_whitespace() || c.is_control())
+            && self
+                .allowed_schemes
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(scheme))
+    }
+
+    /// Opens `url` if the policy allows it and returns whether it did.
+    ///
+    /// The `on_open` handler receives the URL when one is set; otherwise it
+    /// is opened in a new tab through [`egui::Context::open_url`].
+    #[cfg(feature = "egui")]
+    pub fn open(&self, ctx: &egui::Context, url: &str) -> bool {
+        if !self.allows(url) {
+            return false;
+        }
+        match &self.on_open {
+            Some(on_open) => on_open(url),
+            None => ctx.open_url(egui::OpenUrl::new_tab(url)),
+        }
+        true
+    }
+}
+
+fn scheme_start(bytes: &[u8], colon: usize) -> usize {
+    let mut start = colon;
+    while start > 0
+        && (bytes[start - 1].is_ascii_alphanumeric()
+            || matches!(bytes[start - 1], b'+' | b'.' | b'-'))
+    {
+        start -= 1;
+    }
+    while start < colon && !bytes[start].is_ascii_alphabetic() {
+        start += 1;
+    }
+    start
+}
+
+/// Returns the length of `url` without trailing punctuation.
+fn trim_url_end(url: &str) -> usize {
+    let mut end = url.len();
+    while let Some(last) = url[..end].chars().next_back() {
+        let unmatched_close = match last {
+            ')' => url[..end].matches('(').count() < url[..end].matches(')').count(),
+            ']' => url[..end].matches('[').count() < url[..end].matches(']').count(),
+            _ => false,
+        };
+        if unmatched_close || matches!(last, '.' | ',' | ';' | ':' | '!' | '?' | '\'' | '"' | '>') {
+            end -= last.len_utf8();
+        } else {
+            break;
+        }
+    }
+    end
+}
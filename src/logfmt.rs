@@ -0,0 +1,180 @@
+use crate::{AnsiColor, AnsiStyle, AnsiText, LineStructure, LogFields, LogLevel};
+use std::ops::Range;
+
+/// Keys holding the fields of a logfmt record, in order of preference.
+const TIMESTAMP_KEYS: [&str; 4] = ["ts", "time", "timestamp", "t"];
+const LEVEL_KEYS: [&str; 4] = ["level", "lvl", "severity", "at"];
+const SOURCE_KEYS: [&str; 5] = ["logger", "component", "caller", "module", "target"];
+const MESSAGE_KEYS: [&str; 3] = ["msg", "message", "err"];
+
+/// One `key=value` pair, or a bare key.
+#[derive(Debug, Clone)]
+struct Pair {
+    key: Range<usize>,
+    /// Value including quotes, if the pair has an `=`.
+    value: Option<Range<usize>>,
+}
+
+impl Pair {
+    /// Returns the value without its quotes.
+    fn unquoted(&self, line: &str) -> Option<Range<usize>> {
+        let value = self.value.clone()?;
+        if line[value.clone()].starts_with('"') {
+            Some(value.start + 1..value.end - 1)
+        } else {
+            Some(value)
+        }
+    }
+}
+
+/// Splits a logfmt line into pairs.
+///
+/// Returns `None` unless the line is made of pairs only, with at least two
+/// values and no more bare keys than values, so that prose with an `a=b` in
+/// it is not taken for a record.
+fn pairs(line: &str) -> Option<Vec<Pair>> {
+    let bytes = line.as_bytes();
+    let mut pairs = Vec::new();
+    let mut position = 0;
+    while position < bytes.len() {
+        if bytes[position] == b' ' || bytes[position] == b'\t' {
+            position += 1;
+            continue;
+        }
+        let key_start = position;
+        position += bytes[position..]
+            .iter()
+            .take_while(|&&byte| byte.is_ascii_alphanumeric() || b"_.-/@".contains(&byte))
+            .count();
+        if position == key_start {
+            return None;
+        }
+        let key = key_start..position;
+
+        let value = match bytes.get(position) {
+            Some(b'=') => {
+                position += 1;
+                let value_start = position;
+                if bytes.get(position) == Some(&b'"') {
+                    position += 1;
+                    loop {
+                        match bytes.get(position)? {
+                            b'"' => break,
+                            b'\\' => position += 2,
+                            _ => position += 1,
+                        }
+                    }
+                    position += 1;
+                } else {
+                    position += bytes[position..]
+                        .iter()
+                        .take_while(|&&byte| byte != b' ' && byte != b'\t' && byte != b'"')
+                        .count();
+                }
+                Some(value_start..position)
+            }
+            None | Some(b' ' | b'\t') => None,
+            Some(_) => return None,
+        };
+        if !matches!(bytes.get(position), None | Some(b' ' | b'\t')) {
+            return None;
+        }
+        pairs.push(Pair { key, value });
+    }
+    let values = pairs.iter().filter(|pair| pair.value.is_some()).count();
+    (values >= 2 && values * 2 >= pairs.len()).then_some(pairs)
+}
+
+/// Colors a logfmt line such as `level=info msg="server started" port=80`.
+///
+/// Keys are cyan, quoted values green, numeric values yellow, and other
+/// values keep the default color, except the value of a `level` key, which
+/// is colored like its [`LogLevel`]. Returns `None` if the line is not
+/// logfmt, so callers can fall back to ANSI parsing.
+///
+/// ```rust
+/// use egui_sgr::{AnsiColor, logfmt_to_text};
+///
+/// let text = logfmt_to_text(r#"level=warn msg="disk full" free=0"#).unwrap();
+/// assert_eq!(text.text, r#"level=warn msg="disk full" free=0"#);
+/// assert_eq!(text.style_at(0).unwrap().foreground, AnsiColor::Indexed(6));
+/// assert!(logfmt_to_text("just some text").is_none());
+/// ```
+#[must_use]
+pub fn logfmt_to_text(line: &str) -> Option<AnsiText> {
+    profile_scope!("egui_sgr::logfmt_to_text");
+    let pairs = pairs(line)?;
+    let colored = |color| AnsiStyle {
+        foreground: color,
+        ..AnsiStyle::default()
+    };
+    let mut text = AnsiText::new();
+    let mut end = 0;
+    for pair in &pairs {
+        text.push_str(&line[end..pair.key.start], AnsiStyle::default());
+        text.push_str(&line[pair.key.clone()], colored(AnsiColor::Indexed(6)));
+        end = pair.key.end;
+        let Some(value) = pair.value.clone() else {
+            continue;
+        };
+        text.push_str("=", AnsiStyle::default());
+        let value_text = &line[value.clone()];
+        let level = LEVEL_KEYS
+            .contains(&&line[pair.key.clone()])
+            .then(|| LogLevel::parse(value_text.trim_matches('"')))
+            .flatten();
+        let style = if let Some(level) = level {
+            level.style()
+        } else if value_text.starts_with('"') {
+            colored(AnsiColor::Indexed(2))
+        } else if value_text.parse::<f64>().is_ok() {
+            colored(AnsiColor::Indexed(3))
+        } else {
+            AnsiStyle::default()
+        };
+        text.push_str(value_text, style);
+        end = value.end;
+    }
+    text.push_str(&line[end..], AnsiStyle::default());
+    Some(text)
+}
+
+/// [`LineStructure`] for logfmt records.
+///
+/// The timestamp comes from `ts`, `time`, `timestamp`, or `t`, the level from
+/// `level`, `lvl`, `severity`, or `at`, the source from `logger`,
+/// `component`, `caller`, `module`, or `target`, and the message from `msg`,
+/// `message`, or `err`. Ranges cover values without their quotes. Records
+/// without a message key use the whole line as the message.
+///
+/// ```rust
+/// use egui_sgr::{LineStructure, LogLevel, LogfmtFormat};
+///
+/// let line = r#"ts=2024-05-01T12:00:00Z level=error msg="db down" retry=3"#;
+/// let fields = LogfmtFormat.parse(line).unwrap();
+/// assert_eq!(&line[fields.timestamp.clone().unwrap()], "2024-05-01T12:00:00Z");
+/// assert_eq!(fields.log_level(line), Some(LogLevel::Error));
+/// assert_eq!(&line[fields.message], "db down");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LogfmtFormat;
+
+impl LineStructure for LogfmtFormat {
+    fn parse(&self, line: &str) -> Option<LogFields> {
+        let pairs = pairs(line)?;
+        let field = |keys: &[&str]| {
+            keys.iter().find_map(|key| {
+                pairs
+                    .iter()
+                    .find(|pair| &line[pair.key.clone()] == *key)
+                    .and_then(|pair| pair.unquoted(line))
+            })
+        };
+        Some(LogFields {
+            timestamp: field(&TIMESTAMP_KEYS),
+            level: field(&LEVEL_KEYS),
+            source: field(&SOURCE_KEYS),
+            message: field(&MESSAGE_KEYS).unwrap_or(0..line.len()),
+        })
+    }
+}
@@ -0,0 +1,125 @@
+use crate::SharedAnsiDocument;
+use crate::repaint::Repaint;
+use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+use std::fmt::Write;
+
+/// `log` logger that appends colored records to a [`SharedAnsiDocument`].
+///
+/// Records are formatted like env_logger's default output, with a colored
+/// level and the target, and pushed through the ANSI parser, so messages that
+/// carry their own escape codes keep their colors. The document keeps the
+/// last 10,000 lines unless [`Self::with_max_lines`] says otherwise. Two
+/// lines give an in-app log console:
+///
+/// ```no_run
+/// # fn show(ui: &mut egui::Ui) -> Result<(), log::SetLoggerError> {
+/// let logs = egui_sgr::AnsiLogger::new().init()?;
+/// ui.label(logs.lock().to_layout_job(&egui_sgr::EguiAnsiTheme::default()));
+/// # Ok(())
+/// # }
+/// ```
+pub struct AnsiLogger {
+    document: SharedAnsiDocument,
+    max_level: LevelFilter,
+    repaint: Repaint,
+}
+
+impl Default for AnsiLogger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnsiLogger {
+    /// Creates a logger for `Info` and more severe records.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            document: SharedAnsiDocument::for_sink(),
+            max_level: LevelFilter::Info,
+            repaint: Repaint::default(),
+        }
+    }
+
+    /// Sets the most verbose level that is recorded.
+    #[must_use]
+    pub fn with_max_level(mut self, max_level: LevelFilter) -> Self {
+        self.max_level = max_level;
+        self
+    }
+
+    /// Keeps at most `max_lines` records' lines, evicting the oldest.
+    #[must_use]
+    pub fn with_max_lines(self, max_lines: usize) -> Self {
+        self.document.lock().set_max_lines(Some(max_lines));
+        self
+    }
+
+    /// Appends to `document` instead of a new one, for example one that an
+    /// [`AnsiWriter`](crate::AnsiWriter) also writes to. Its retention cap
+    /// is kept.
+    #[must_use]
+    pub fn with_document(mut self, document: SharedAnsiDocument) -> Self {
+        self.document = document;
+        self
+    }
+
+    /// Requests an egui repaint after each record.
+    #[cfg(feature = "egui")]
+    #[must_use]
+    pub fn with_repaint(mut self, ctx: egui::Context) -> Self {
+        self.repaint = Repaint::new(ctx);
+        self
+    }
+
+    /// Returns the shared document that records are appended to.
+    #[must_use]
+    pub fn document(&self) -> SharedAnsiDocument {
+        self.document.clone()
+    }
+
+    /// Installs this logger as the global `log` logger and returns its
+    /// document.
+    pub fn init(self) -> Result<SharedAnsiDocument, SetLoggerError> {
+        let document = self.document();
+        let max_level = self.max_level;
+        log::set_boxed_logger(Box::new(self))?;
+        log::set_max_level(max_level);
+        Ok(document)
+    }
+}
+
+impl Log for AnsiLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.level() <= self.max_level
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let level_style = match record.level() {
+            Level::Error => "1;31",
+            Level::Warn => "33",
+            Level::Info => "32",
+            Level::Debug => "34",
+            Level::Trace => "36",
+        };
+        let mut line = String::new();
+        // The trailing reset keeps a message's unterminated style from
+        // bleeding into the next record.
+        let _ = writeln!(
+            line,
+            "\x1b[2m[\x1b[0m\x1b[{level_style}m{:<5}\x1b[0m {}\x1b[2m]\x1b[0m {}\x1b[0m",
+            record.level(),
+            record.target(),
+            record.args()
+        );
+
+        self.document.lock().append_str(&line);
+        self.repaint.request();
+    }
+
+    fn flush(&self) {}
+}
@@ -0,0 +1,192 @@
+use crate::{AnsiColor, AnsiIntensity, AnsiSpan, AnsiStyle, AnsiText, UnderlineStyle};
+
+/// Java edition colors for the codes `0` through `f`.
+const MINECRAFT_PALETTE: [u32; 16] = [
+    0x000000, 0x0000aa, 0x00aa00, 0x00aaaa, 0xaa0000, 0xaa00aa, 0xffaa00, 0xaaaaaa, 0x555555,
+    0x5555ff, 0x55ff55, 0x55ffff, 0xff5555, 0xff55ff, 0xffff55, 0xffffff,
+];
+
+/// Characters drawn in place of obfuscated (`§k`) text.
+const SCRAMBLE_CHARS: &[u8] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789#$%&?@";
+
+/// How obfuscated (`§k`) text is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Obfuscation {
+    /// Show the original characters.
+    #[default]
+    Plain,
+    /// Hide the characters while keeping their space.
+    Hidden,
+    /// Replace each visible character with one picked from this seed.
+    ///
+    /// Changing the seed every few frames, and requesting a repaint,
+    /// animates the text like the game does. Prefer [`Self::Hidden`] for
+    /// users who asked for reduced motion.
+    Scrambled(u64),
+}
+
+/// Options for Minecraft formatting code conversion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MinecraftOptions {
+    /// Character that starts a formatting code, usually `§`; some server
+    /// configurations use `&`.
+    pub code_char: char,
+    /// How obfuscated text is shown.
+    pub obfuscation: Obfuscation,
+}
+
+impl Default for MinecraftOptions {
+    fn default() -> Self {
+        Self {
+            code_char: '§',
+            obfuscation: Obfuscation::Plain,
+        }
+    }
+}
+
+/// Converts text with Minecraft legacy formatting codes into spans.
+///
+/// Supports `§0`–`§f` colors, `§k` obfuscated, `§l` bold, `§m`
+/// strikethrough, `§n` underline, `§o` italic, `§r` reset, and the
+/// `§x§r§r§g§g§b§b` hex colors used by server consoles. As in the game, a
+/// color code also clears the formatting codes before it. Colors become
+/// [`AnsiColor::Rgb`] with the game's values.
+#[must_use]
+pub fn minecraft_to_spans(input: &str, options: &MinecraftOptions) -> Vec<AnsiSpan> {
+    let mut output = AnsiText::new();
+    let mut style = AnsiStyle::default();
+    let mut obfuscated = false;
+    let mut scramble_state = match options.obfuscation {
+        Obfuscation::Scrambled(seed) => scramble_seed(seed),
+        Obfuscation::Plain | Obfuscation::Hidden => 0,
+    };
+    let mut chars = input.char_indices().peekable();
+    let mut text_start = 0;
+
+    while let Some((index, c)) = chars.next() {
+        if c != options.code_char {
+            continue;
+        }
+        let Some(&(_, code)) = chars.peek() else {
+            break;
+        };
+
+        push_text(
+            &mut output,
+            &input[text_start..index],
+            style,
+            obfuscated.then_some(options.obfuscation),
+            &mut scramble_state,
+        );
+        chars.next();
+
+        match code.to_ascii_lowercase() {
+            'x' => {
+                if let Some(color) = parse_hex_color(&input[index..], options.code_char) {
+                    style = AnsiStyle {
+                        foreground: color,
+                        ..AnsiStyle::default()
+                    };
+                    obfuscated = false;
+                    for _ in 0..12 {
+                        chars.next();
+                    }
+                }
+            }
+            digit @ ('0'..='9' | 'a'..='f') => {
+                let index = digit.to_digit(16).unwrap_or_default() as usize;
+                style = AnsiStyle {
+                    foreground: rgb_color(MINECRAFT_PALETTE[index]),
+                    ..AnsiStyle::default()
+                };
+                obfuscated = false;
+            }
+            'k' => obfuscated = true,
+            'l' => style.intensity = AnsiIntensity::Bold,
+            'm' => style.strikethrough = true,
+            'n' => style.underline = UnderlineStyle::Single,
+            'o' => style.italic = true,
+            'r' => {
+                style = AnsiStyle::default();
+                obfuscated = false;
+            }
+            _ => {}
+        }
+
+        text_start = chars.peek().map_or(input.len(), |(index, _)| *index);
+    }
+
+    push_text(
+        &mut output,
+        &input[text_start..],
+        style,
+        obfuscated.then_some(options.obfuscation),
+        &mut scramble_state,
+    );
+    output.to_spans()
+}
+
+/// Turns a seed into a xorshift state, which must not be zero since
+/// xorshift never leaves it.
+fn scramble_seed(seed: u64) -> u64 {
+    const MIX: u64 = 0x9e37_79b9_7f4a_7c15;
+    match seed ^ MIX {
+        0 => MIX,
+        state => state,
+    }
+}
+
+fn push_text(
+    output: &mut AnsiText,
+    text: &str,
+    mut style: AnsiStyle,
+    obfuscation: Option<Obfuscation>,
+    scramble_state: &mut u64,
+) {
+    match obfuscation {
+        None | Some(Obfuscation::Plain) => output.push_str(text, style),
+        Some(Obfuscation::Hidden) => {
+            style.hidden = true;
+            output.push_str(text, style);
+        }
+        Some(Obfuscation::Scrambled(_)) => {
+            let scrambled: String = text
+                .chars()
+                .map(|c| {
+                    if c.is_whitespace() {
+                        return c;
+                    }
+                    // xorshift64: cheap, deterministic per seed.
+                    *scramble_state ^= *scramble_state << 13;
+                    *scramble_state ^= *scramble_state >> 7;
+                    *scramble_state ^= *scramble_state << 17;
+                    let pick = (*scramble_state % SCRAMBLE_CHARS.len() as u64) as usize;
+                    char::from(SCRAMBLE_CHARS[pick])
+                })
+                .collect();
+            output.push_str(&scrambled, style);
+        }
+    }
+}
+
+/// Parses `§x§r§r§g§g§b§b` at the start of `input`.
+fn parse_hex_color(input: &str, code_char: char) -> Option<AnsiColor> {
+    let mut chars = input.chars();
+    chars.next();
+    chars.next();
+
+    let mut rgb = 0;
+    for _ in 0..6 {
+        if chars.next()? != code_char {
+            return None;
+        }
+        rgb = rgb << 4 | chars.next()?.to_digit(16)?;
+    }
+    Some(rgb_color(rgb))
+}
+
+fn rgb_color(rgb: u32) -> AnsiColor {
+    let [_, r, g, b] = rgb.to_be_bytes();
+    AnsiColor::Rgb(r, g, b)
+}
@@ -0,0 +1,151 @@
+use crate::{AnsiColor, AnsiIntensity, AnsiSpan, AnsiStyle, AnsiText, UnderlineStyle};
+
+const BOLD: char = '\x02';
+const COLOR: char = '\x03';
+const HEX_COLOR: char = '\x04';
+const RESET: char = '\x0f';
+const MONOSPACE: char = '\x11';
+const REVERSE: char = '\x16';
+const ITALIC: char = '\x1d';
+const STRIKETHROUGH: char = '\x1e';
+const UNDERLINE: char = '\x1f';
+
+/// mIRC colors 0 through 98 as defined by modern IRC clients.
+const MIRC_PALETTE: [u32; 99] = [
+    0xffffff, 0x000000, 0x00007f, 0x009300, 0xff0000, 0x7f0000, 0x9c009c, 0xfc7f00, 0xffff00,
+    0x00fc00, 0x009393, 0x00ffff, 0x0000fc, 0xff00ff, 0x7f7f7f, 0xd2d2d2, 0x470000, 0x472100,
+    0x474700, 0x324700, 0x004700, 0x00472c, 0x004747, 0x002747, 0x000047, 0x2e0047, 0x470047,
+    0x47002a, 0x740000, 0x743a00, 0x747400, 0x517400, 0x007400, 0x007449, 0x007474, 0x004074,
+    0x000074, 0x4b0074, 0x740074, 0x740045, 0xb50000, 0xb56300, 0xb5b500, 0x7db500, 0x00b500,
+    0x00b571, 0x00b5b5, 0x0063b5, 0x0000b5, 0x7500b5, 0xb500b5, 0xb5006b, 0xff0000, 0xff8c00,
+    0xffff00, 0xb2ff00, 0x00ff00, 0x00ffa0, 0x00ffff, 0x008cff, 0x0000ff, 0xa500ff, 0xff00ff,
+    0xff0098, 0xff5959, 0xffb459, 0xffff71, 0xcfff60, 0x6fff6f, 0x65ffc9, 0x6dffff, 0x59b4ff,
+    0x5959ff, 0xc459ff, 0xff66ff, 0xff59bc, 0xff9c9c, 0xffd39c, 0xffff9c, 0xe2ff9c, 0x9cff9c,
+    0x9cffdb, 0x9cffff, 0x9cd3ff, 0x9c9cff, 0xdc9cff, 0xff9cff, 0xff94d3, 0x000000, 0x131313,
+    0x282828, 0x363636, 0x4d4d4d, 0x656565, 0x818181, 0x9f9f9f, 0xbcbcbc, 0xe2e2e2, 0xffffff,
+];
+
+/// Converts text with mIRC formatting codes into spans.
+///
+/// Supports `^B` bold, `^]` italic, `^_` underline, `^^` strikethrough,
+/// `^V` reverse, `^O` reset, `^C` colors (`^Cfg` / `^Cfg,bg`, 0–98, with 99
+/// and a bare `^C` meaning the default), and `^D` hex colors (`^DRRGGBB` /
+/// `^DRRGGBB,RRGGBB`). Colors become [`AnsiColor::Rgb`] with the standard
+/// mIRC values, so they look the same under every theme.
+#[must_use]
+pub fn mirc_to_spans(input: &str) -> Vec<AnsiSpan> {
+    mirc_to_text(input).to_spans()
+}
+
+/// Converts text with mIRC formatting codes into styled text.
+///
+/// See [`mirc_to_spans`] for the supported codes.
+#[must_use]
+pub fn mirc_to_text(input: &str) -> AnsiText {
+    let mut output = AnsiText::new();
+    let mut style = AnsiStyle::default();
+    let mut rest = input;
+
+    while let Some(start) = rest.find(is_format_code) {
+        output.push_str(&rest[..start], style);
+        let code = rest[start..].chars().next().unwrap_or_default();
+        rest = &rest[start + code.len_utf8()..];
+
+        match code {
+            BOLD => {
+                style.intensity = if style.intensity == AnsiIntensity::Bold {
+                    AnsiIntensity::Normal
+                } else {
+                    AnsiIntensity::Bold
+                };
+            }
+            ITALIC => style.italic = !style.italic,
+            UNDERLINE => {
+                style.underline = if style.underline == UnderlineStyle::None {
+                    UnderlineStyle::Single
+                } else {
+                    UnderlineStyle::None
+                };
+            }
+            STRIKETHROUGH => style.strikethrough = !style.strikethrough,
+            REVERSE => style.reverse = !style.reverse,
+            RESET => style = AnsiStyle::default(),
+            COLOR => rest = parse_colors(rest, &mut style, 2, palette_color),
+            HEX_COLOR => rest = parse_colors(rest, &mut style, 6, hex_color),
+            // Monospace has no meaning for already monospaced terminal text.
+            _ => {}
+        }
+    }
+
+    output.push_str(rest, style);
+    output
+}
+
+fn is_format_code(c: char) -> bool {
+    matches!(
+        c,
+        BOLD | COLOR | HEX_COLOR | RESET | MONOSPACE | REVERSE | ITALIC | STRIKETHROUGH | UNDERLINE
+    )
+}
+
+/// Parses `fg[,bg]` after a color code. A code without a foreground resets
+/// both colors; a comma not followed by a color stays in the text.
+fn parse_colors<'a>(
+    rest: &'a str,
+    style: &mut AnsiStyle,
+    max_len: usize,
+    parse: fn(&str) -> Option<(AnsiColor, usize)>,
+) -> &'a str {
+    let Some((foreground, len)) = parse_prefix(rest, max_len, parse) else {
+        style.foreground = AnsiColor::Default;
+        style.background = AnsiColor::Default;
+        return rest;
+    };
+    style.foreground = foreground;
+    let rest = &rest[len..];
+
+    if let Some(after_comma) = rest.strip_prefix(',')
+        && let Some((background, len)) = parse_prefix(after_comma, max_len, parse)
+    {
+        style.background = background;
+        return &after_comma[len..];
+    }
+
+    rest
+}
+
+fn parse_prefix(
+    rest: &str,
+    max_len: usize,
+    parse: fn(&str) -> Option<(AnsiColor, usize)>,
+) -> Option<(AnsiColor, usize)> {
+    let len = rest
+        .bytes()
+        .take(max_len)
+        .take_while(u8::is_ascii_hexdigit)
+        .count();
+    parse(&rest[..len])
+}
+
+fn palette_color(digits: &str) -> Option<(AnsiColor, usize)> {
+    let len = digits.bytes().take_while(u8::is_ascii_digit).count();
+    let index: usize = digits[..len].parse().ok()?;
+    let color = match MIRC_PALETTE.get(index) {
+        Some(rgb) => rgb_color(*rgb),
+        None => AnsiColor::Default,
+    };
+    Some((color, len))
+}
+
+fn hex_color(digits: &str) -> Option<(AnsiColor, usize)> {
+    if digits.len() != 6 {
+        return None;
+    }
+    let rgb = u32::from_str_radix(digits, 16).ok()?;
+    Some((rgb_color(rgb), 6))
+}
+
+fn rgb_color(rgb: u32) -> AnsiColor {
+    let [_, r, g, b] = rgb.to_be_bytes();
+    AnsiColor::Rgb(r, g, b)
+}
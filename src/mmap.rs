@@ -0,0 +1,147 @@
+use crate::{AnsiDocument, AnsiSpan, ansi_bytes_to_spans};
+#[cfg(feature = "egui")]
+use crate::{EguiAnsiTheme, ansi_bytes_to_layout_job};
+#[cfg(feature = "egui")]
+use egui::text::LayoutJob;
+use memmap2::Mmap;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+/// Memory-mapped ANSI log file with a lazily built line index.
+///
+/// The file is never read into memory as a whole. Line offsets are indexed
+/// only as far as the requested line, and lines are parsed when asked for, so
+/// multi-gigabyte logs can be viewed a screen at a time.
+///
+/// Each line is parsed independently: SGR state is not carried from one line
+/// to the next, which keeps random access cheap.
+pub struct AnsiMappedFile {
+    map: Mmap,
+    line_ends: Vec<usize>,
+    scan_pos: usize,
+    complete: bool,
+}
+
+impl AnsiMappedFile {
+    /// Maps the file at `path`.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be truncated or modified in place, by this or any
+    /// other process, while the returned value is alive. Reading a page
+    /// past the new end of a truncated file raises `SIGBUS`, and logs
+    /// rotated with `copytruncate` are truncated exactly this way; map only
+    /// files that are no longer written to, such as rotated archives.
+    #[allow(unsafe_code)]
+    pub unsafe fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: the mapping is read-only and owned by this value; the
+        // caller guarantees the file keeps its length and contents.
+        let map = unsafe { Mmap::map(&file)? };
+
+        Ok(Self {
+            map,
+            line_ends: Vec::new(),
+            scan_pos: 0,
+            complete: false,
+        })
+    }
+
+    /// Returns the mapped file size in bytes.
+    #[must_use]
+    pub fn len_bytes(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns the raw mapped bytes.
+    #[must_use]
+    pub fn bytes(&self) -> &[u8] {
+        &self.map
+    }
+
+    /// Returns the number of lines, indexing the rest of the file if needed.
+    pub fn line_count(&mut self) -> usize {
+        while self.index_next_line() {}
+        self.line_ends.len()
+    }
+
+    /// Returns the number of lines indexed so far.
+    #[must_use]
+    pub fn indexed_line_count(&self) -> usize {
+        self.line_ends.len()
+    }
+
+    /// Returns the raw bytes of a line, without its `\n` or `\r\n` ending.
+    pub fn line(&mut self, index: usize) -> Option<&[u8]> {
+        while self.line_ends.len() <= index {
+            if !self.index_next_line() {
+                return None;
+            }
+        }
+
+        let start = match index {
+            0 => 0,
+            _ => self.line_ends[index - 1] + 1,
+        };
+        let line = &self.map[start..self.line_ends[index]];
+        Some(line.strip_suffix(b"\r").unwrap_or(line))
+    }
+
+    /// Parses a line into ANSI spans.
+    pub fn line_spans(&mut self, index: usize) -> Option<Vec<AnsiSpan>> {
+        self.line(index).map(ansi_bytes_to_spans)
+    }
+
+    /// Parses and renders a line into an egui layout job.
+    #[cfg(feature = "egui")]
+    pub fn line_layout_job(&mut self, index: usize, theme: &EguiAnsiTheme) -> Option<LayoutJob> {
+        self.line(index)
+            .map(|line| ansi_bytes_to_layout_job(line, theme))
+    }
+
+    fn index_next_line(&mut self) -> bool {
+        if self.complete {
+            return false;
+        }
+
+        let rest = &self.map[self.scan_pos..];
+        match memchr::memchr(b'\n', rest) {
+            Some(offset) => {
+                let end = self.scan_pos + offset;
+                self.line_ends.push(end);
+                self.scan_pos = end + 1;
+                true
+            }
+            None => {
+                self.complete = true;
+                if rest.is_empty() {
+                    return false;
+                }
+                self.line_ends.push(self.map.len());
+                self.scan_pos = self.map.len();
+                true
+            }
+        }
+    }
+}
+
+impl<M: Default> AnsiDocument<M> {
+    /// Maps the log file at `path` for viewing without reading it into
+    /// memory; see [`AnsiMappedFile`].
+    ///
+    /// A document keeps every retained line parsed, which is what makes it
+    /// editable and searchable; a mapped file indexes and parses lines only
+    /// when they are shown, so multi-gigabyte logs can be viewed a screen at
+    /// a time.
+    ///
+    /// # Safety
+    ///
+    /// The same as [`AnsiMappedFile::open`]: the file must not be truncated
+    /// or modified in place while the mapping is alive.
+    #[allow(unsafe_code)]
+    pub unsafe fn open_mmap(path: impl AsRef<Path>) -> io::Result<AnsiMappedFile> {
+        // SAFETY: forwarded to the caller.
+        unsafe { AnsiMappedFile::open(path) }
+    }
+}
@@ -1,5 +1,10 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Range;
+
 /// ANSI color representation before it is mapped into an egui color.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AnsiColor {
     /// Use the theme's default color.
     #[default]
@@ -12,6 +17,7 @@ pub enum AnsiColor {
 
 /// ANSI text intensity.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AnsiIntensity {
     /// Normal intensity.
     #[default]
@@ -24,6 +30,7 @@ pub enum AnsiIntensity {
 
 /// ANSI underline style.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum UnderlineStyle {
     /// No underline.
     #[default]
@@ -42,6 +49,7 @@ pub enum UnderlineStyle {
 
 /// Complete style state for an ANSI span.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AnsiStyle {
     /// Foreground color.
     pub foreground: AnsiColor,
@@ -64,6 +72,102 @@ pub struct AnsiStyle {
 }
 
 impl AnsiStyle {
+    /// Creates the default style, for use as a starting point of the builder
+    /// methods below.
+    ///
+    /// ```rust
+    /// use egui_sgr::{AnsiColor, AnsiStyle};
+    ///
+    /// let warning = AnsiStyle::new().fg(AnsiColor::Indexed(3)).bold();
+    /// ```
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            foreground: AnsiColor::Default,
+            background: AnsiColor::Default,
+            underline_color: None,
+            intensity: AnsiIntensity::Normal,
+            italic: false,
+            underline: UnderlineStyle::None,
+            strikethrough: false,
+            reverse: false,
+            hidden: false,
+        }
+    }
+
+    /// Sets the foreground color.
+    #[must_use]
+    pub const fn fg(mut self, color: AnsiColor) -> Self {
+        self.foreground = color;
+        self
+    }
+
+    /// Sets the background color.
+    #[must_use]
+    pub const fn bg(mut self, color: AnsiColor) -> Self {
+        self.background = color;
+        self
+    }
+
+    /// Makes the text bold, replacing faint.
+    #[must_use]
+    pub const fn bold(mut self) -> Self {
+        self.intensity = AnsiIntensity::Bold;
+        self
+    }
+
+    /// Makes the text faint, replacing bold.
+    #[must_use]
+    pub const fn faint(mut self) -> Self {
+        self.intensity = AnsiIntensity::Faint;
+        self
+    }
+
+    /// Makes the text italic.
+    #[must_use]
+    pub const fn italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+
+    /// Sets the underline style.
+    #[must_use]
+    pub const fn underline(mut self, style: UnderlineStyle) -> Self {
+        self.underline = style;
+        self
+    }
+
+    /// Sets the underline color, enabling a single underline if none is set.
+    #[must_use]
+    pub const fn underline_color(mut self, color: AnsiColor) -> Self {
+        self.underline_color = Some(color);
+        if matches!(self.underline, UnderlineStyle::None) {
+            self.underline = UnderlineStyle::Single;
+        }
+        self
+    }
+
+    /// Strikes the text through.
+    #[must_use]
+    pub const fn strikethrough(mut self) -> Self {
+        self.strikethrough = true;
+        self
+    }
+
+    /// Swaps the foreground and background colors.
+    #[must_use]
+    pub const fn reverse(mut self) -> Self {
+        self.reverse = true;
+        self
+    }
+
+    /// Hides the text.
+    #[must_use]
+    pub const fn hidden(mut self) -> Self {
+        self.hidden = true;
+        self
+    }
+
     pub(crate) fn reset(&mut self) {
         *self = Self::default();
     }
@@ -71,6 +175,7 @@ impl AnsiStyle {
 
 /// A visible run of text with one ANSI style.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AnsiSpan {
     /// Visible text for this span.
     pub text: String,
@@ -88,3 +193,153 @@ impl AnsiSpan {
         }
     }
 }
+
+/// A style applied to a byte range of an [`AnsiText`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnsiStyleRun {
+    /// Byte range into [`AnsiText::text`].
+    pub range: Range<usize>,
+    /// ANSI style for this range.
+    pub style: AnsiStyle,
+}
+
+/// Visible text stored in one contiguous buffer with style runs over it.
+///
+/// This mirrors the structure of [`egui::text::LayoutJob`]: one `String` plus
+/// byte-range sections. It avoids the per-span `String` allocations of
+/// [`AnsiSpan`] lists and makes range-based tooling straightforward.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnsiText {
+    /// Visible text without escape sequences.
+    pub text: String,
+    /// Non-empty, contiguous runs covering all of [`Self::text`] in order.
+    pub runs: Vec<AnsiStyleRun>,
+}
+
+impl AnsiText {
+    /// Creates an empty styled text.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends text with a style, extending the last run if the style matches.
+    pub fn push_str(&mut self, text: &str, style: AnsiStyle) {
+        if text.is_empty() {
+            return;
+        }
+
+        let start = self.text.len();
+        self.text.push_str(text);
+        self.extend_last_run(start, style);
+    }
+
+    /// Appends text with a style and returns `self`, for chained construction.
+    #[must_use]
+    pub fn with_str(mut self, text: &str, style: AnsiStyle) -> Self {
+        self.push_str(text, style);
+        self
+    }
+
+    /// Parses `input` as ANSI text and appends the result.
+    ///
+    /// Each call starts from the default style, so parsed content does not
+    /// inherit styles from text pushed before it.
+    pub fn push_ansi(&mut self, input: &str) {
+        for (text, style) in crate::ansi_to_text(input).iter() {
+            self.push_str(text, style);
+        }
+    }
+
+    /// Styles the text appended since `start`, merging with the last run.
+    pub(crate) fn extend_last_run(&mut self, start: usize, style: AnsiStyle) {
+        let end = self.text.len();
+        if start == end {
+            return;
+        }
+
+        if let Some(last) = self.runs.last_mut()
+            && last.style == style
+            && last.range.end == start
+        {
+            last.range.end = end;
+            return;
+        }
+
+        self.runs.push(AnsiStyleRun {
+            range: start..end,
+            style,
+        });
+    }
+
+    /// Iterates over `(text, style)` pairs for every run.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (&str, AnsiStyle)> + Clone + '_ {
+        self.runs
+            .iter()
+            .map(|run| (&self.text[run.range.clone()], run.style))
+    }
+
+    /// Converts the runs into owned spans.
+    #[must_use]
+    pub fn to_spans(&self) -> Vec<AnsiSpan> {
+        self.iter()
+            .map(|(text, style)| AnsiSpan::new(text, style))
+            .collect()
+    }
+
+    /// Returns the style of the run containing `byte_offset` of the visible
+    /// text, such as a position hit-tested in a galley.
+    ///
+    /// Returns `None` past the end of the text.
+    #[must_use]
+    pub fn style_at(&self, byte_offset: usize) -> Option<AnsiStyle> {
+        let index = self
+            .runs
+            .partition_point(|run| run.range.end <= byte_offset);
+        self.runs
+            .get(index)
+            .filter(|run| run.range.contains(&byte_offset))
+            .map(|run| run.style)
+    }
+
+    /// Returns the styled text within `range`, a byte range of
+    /// [`Self::text`], such as a selection in a label showing it.
+    ///
+    /// The range is clamped to the text and widened to whole characters.
+    #[must_use]
+    pub fn slice(&self, range: Range<usize>) -> Self {
+        let end = self.text.ceil_char_boundary(range.end.min(self.text.len()));
+        let start = self.text.floor_char_boundary(range.start.min(end));
+        let mut slice = Self::new();
+        let first = self.runs.partition_point(|run| run.range.end <= start);
+        for run in self.runs[first..]
+            .iter()
+            .take_while(|run| run.range.start < end)
+        {
+            let run_start = run.range.start.max(start);
+            let run_end = run.range.end.min(end);
+            slice.push_str(&self.text[run_start..run_end], run.style);
+        }
+        slice
+    }
+
+    /// Returns `true` if there is no visible text.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+}
+
+impl From<&[AnsiSpan]> for AnsiText {
+    fn from(spans: &[AnsiSpan]) -> Self {
+        let mut text = Self::new();
+        text.text
+            .reserve(spans.iter().map(|span| span.text.len()).sum());
+        for span in spans {
+            text.push_str(&span.text, span.style);
+        }
+        text
+    }
+}
@@ -0,0 +1,258 @@
+use crate::SharedAnsiDocument;
+use crate::repaint::Repaint;
+use std::io::{self, Read};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// How long blocking reads wait before checking whether to stop.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Remote log source that streams a TCP or WebSocket endpoint into a
+/// [`SharedAnsiDocument`].
+///
+/// Received bytes go through the streaming parser. When the connection drops,
+/// the source reconnects with exponential backoff; a partial escape sequence
+/// from the lost connection is discarded, so it cannot style the next one.
+/// The document keeps the last 10,000 lines unless [`Self::with_max_lines`]
+/// says otherwise.
+pub struct AnsiNetworkSource {
+    endpoint: Endpoint,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    document: SharedAnsiDocument,
+    repaint: Repaint,
+}
+
+enum Endpoint {
+    Tcp(String),
+    #[cfg(feature = "websocket")]
+    WebSocket(String),
+}
+
+impl AnsiNetworkSource {
+    /// Creates a source reading raw bytes from a TCP address such as
+    /// `"logs.local:5140"`.
+    #[must_use]
+    pub fn tcp(address: impl Into<String>) -> Self {
+        Self::with_endpoint(Endpoint::Tcp(address.into()))
+    }
+
+    /// Creates a source reading text and binary messages from a `ws://` URL.
+    #[cfg(feature = "websocket")]
+    #[must_use]
+    pub fn websocket(url: impl Into<String>) -> Self {
+        Self::with_endpoint(Endpoint::WebSocket(url.into()))
+    }
+
+    fn with_endpoint(endpoint: Endpoint) -> Self {
+        Self {
+            endpoint,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            document: SharedAnsiDocument::for_sink(),
+            repaint: Repaint::default(),
+        }
+    }
+
+    /// Sets the first reconnect delay and the cap it doubles up to.
+    ///
+    /// Defaults to 500 ms and 30 s. The delay resets after a successful
+    /// connection.
+    #[must_use]
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    /// Keeps at most `max_lines` received lines, evicting the oldest.
+    #[must_use]
+    pub fn with_max_lines(self, max_lines: usize) -> Self {
+        self.document.lock().set_max_lines(Some(max_lines));
+        self
+    }
+
+    /// Appends to `document` instead of a new one, keeping its retention
+    /// cap.
+    #[must_use]
+    pub fn with_document(mut self, document: SharedAnsiDocument) -> Self {
+        self.document = document;
+        self
+    }
+
+    /// Requests an egui repaint after each received chunk.
+    #[cfg(feature = "egui")]
+    #[must_use]
+    pub fn with_repaint(mut self, ctx: egui::Context) -> Self {
+        self.repaint = Repaint::new(ctx);
+        self
+    }
+
+    /// Returns the shared document that received output is parsed into.
+    #[must_use]
+    pub fn document(&self) -> SharedAnsiDocument {
+        self.document.clone()
+    }
+
+    /// Starts connecting and reading on a background thread.
+    pub fn connect(self) -> io::Result<AnsiNetworkConnection> {
+        let stop = Arc::new(AtomicBool::new(false));
+        let connected = Arc::new(AtomicBool::new(false));
+        let document = self.document();
+        let state = Arc::clone(&stop);
+        let status = Arc::clone(&connected);
+
+        let handle = thread::Builder::new()
+            .name("egui_sgr network".to_owned())
+            .spawn(move || self.run(&state, &status))?;
+
+        Ok(AnsiNetworkConnection {
+            stop,
+            connected,
+            handle: Some(handle),
+            document,
+        })
+    }
+
+    fn run(self, stop: &AtomicBool, connected: &AtomicBool) {
+        let mut backoff = self.initial_backoff;
+
+        while !stop.load(Ordering::Relaxed) {
+            let mut established = false;
+            let result = self.read_endpoint(stop, connected, &mut established);
+            connected.store(false, Ordering::Relaxed);
+            self.document.lock().finish();
+
+            if result.is_ok() && stop.load(Ordering::Relaxed) {
+                break;
+            }
+            if established {
+                backoff = self.initial_backoff;
+            }
+            sleep_unless_stopped(backoff, stop);
+            backoff = backoff.saturating_mul(2).min(self.max_backoff);
+        }
+    }
+
+    fn read_endpoint(
+        &self,
+        stop: &AtomicBool,
+        connected: &AtomicBool,
+        established: &mut bool,
+    ) -> io::Result<()> {
+        match &self.endpoint {
+            Endpoint::Tcp(address) => {
+                let mut stream = connect_tcp(address)?;
+                stream.set_read_timeout(Some(POLL_INTERVAL))?;
+                connected.store(true, Ordering::Relaxed);
+                *established = true;
+
+                let mut chunk = [0; 8192];
+                while !stop.load(Ordering::Relaxed) {
+                    match stream.read(&mut chunk) {
+                        Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                        Ok(len) => self.push(&chunk[..len]),
+                        Err(error) if is_timeout(&error) => {}
+                        Err(error) => return Err(error),
+                    }
+                }
+                Ok(())
+            }
+            #[cfg(feature = "websocket")]
+            Endpoint::WebSocket(url) => {
+                use tungstenite::{Error, Message, stream::MaybeTlsStream};
+
+                let (mut socket, _) =
+                    tungstenite::connect(url.as_str()).map_err(io::Error::other)?;
+                if let MaybeTlsStream::Plain(stream) = socket.get_ref() {
+                    stream.set_read_timeout(Some(POLL_INTERVAL))?;
+                }
+                connected.store(true, Ordering::Relaxed);
+                *established = true;
+
+                while !stop.load(Ordering::Relaxed) {
+                    match socket.read() {
+                        Ok(Message::Text(text)) => self.push(text.as_bytes()),
+                        Ok(Message::Binary(bytes)) => self.push(&bytes),
+                        Ok(Message::Close(_)) => {
+                            return Err(io::ErrorKind::ConnectionAborted.into());
+                        }
+                        Ok(_) => {}
+                        Err(Error::Io(error)) if is_timeout(&error) => {}
+                        Err(error) => return Err(io::Error::other(error)),
+                    }
+                }
+                let _ = socket.close(None);
+                Ok(())
+            }
+        }
+    }
+
+    fn push(&self, chunk: &[u8]) {
+        self.document.lock().append(chunk);
+        self.repaint.request();
+    }
+}
+
+/// Background connection started by [`AnsiNetworkSource::connect`].
+///
+/// Dropping the connection stops reconnecting and closes the socket.
+pub struct AnsiNetworkConnection {
+    stop: Arc<AtomicBool>,
+    connected: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+    document: SharedAnsiDocument,
+}
+
+impl AnsiNetworkConnection {
+    /// Returns the shared document that received output is parsed into.
+    #[must_use]
+    pub fn document(&self) -> SharedAnsiDocument {
+        self.document.clone()
+    }
+
+    /// Returns whether the endpoint is currently connected.
+    #[must_use]
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for AnsiNetworkConnection {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn connect_tcp(address: &str) -> io::Result<TcpStream> {
+    let mut last_error = io::Error::from(io::ErrorKind::AddrNotAvailable);
+    for address in address.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&address, Duration::from_secs(5)) {
+            Ok(stream) => return Ok(stream),
+            Err(error) => last_error = error,
+        }
+    }
+    Err(last_error)
+}
+
+fn is_timeout(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut | io::ErrorKind::Interrupted
+    )
+}
+
+fn sleep_unless_stopped(duration: Duration, stop: &AtomicBool) {
+    let mut remaining = duration;
+    while !remaining.is_zero() && !stop.load(Ordering::Relaxed) {
+        let step = remaining.min(POLL_INTERVAL);
+        thread::sleep(step);
+        remaining -= step;
+    }
+}
@@ -0,0 +1,69 @@
+use ecolor::Color32;
+use std::ops::Range;
+
+/// Group of overlays that is added and cleared together.
+///
+/// The associated constants cover the common uses; any other value works as
+/// a custom layer. Overlays composite in the order they were added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OverlayLayer(pub u32);
+
+impl OverlayLayer {
+    /// Search hits.
+    pub const SEARCH: Self = Self(0);
+    /// Text selection.
+    pub const SELECTION: Self = Self(1);
+    /// Diagnostics such as error markers.
+    pub const DIAGNOSTICS: Self = Self(2);
+    /// Lines that differ from a compared document.
+    pub const COMPARE: Self = Self(3);
+}
+
+/// Extra styling composited over parsed ANSI styles at render time.
+///
+/// Unset fields keep the color resolved from the ANSI style.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OverlayStyle {
+    /// Replacement text color.
+    pub foreground: Option<Color32>,
+    /// Replacement background color.
+    pub background: Option<Color32>,
+    /// Underline color; adds an underline.
+    pub underline: Option<Color32>,
+}
+
+impl OverlayStyle {
+    /// Returns a style that only paints a background highlight.
+    #[must_use]
+    pub const fn highlight(background: Color32) -> Self {
+        Self {
+            foreground: None,
+            background: Some(background),
+            underline: None,
+        }
+    }
+
+    /// Returns a style that only adds an underline.
+    #[must_use]
+    pub const fn underline(color: Color32) -> Self {
+        Self {
+            foreground: None,
+            background: None,
+            underline: Some(color),
+        }
+    }
+}
+
+/// Styled range on one line of an [`AnsiDocument`](crate::AnsiDocument).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnsiOverlay {
+    /// Layer the overlay belongs to.
+    pub layer: OverlayLayer,
+    /// Absolute line number, as counted by
+    /// [`AnsiDocument::first_line_number`](crate::AnsiDocument::first_line_number).
+    pub line: u64,
+    /// Byte range into the line's visible text.
+    pub range: Range<usize>,
+    /// Styling to apply.
+    pub style: OverlayStyle,
+}
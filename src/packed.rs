@@ -0,0 +1,255 @@
+use crate::{AnsiColor, AnsiIntensity, AnsiStyle, AnsiText, UnderlineStyle};
+use std::collections::HashMap;
+
+/// Set in a style word that indexes the side table instead of packing the
+/// style.
+const EXTENDED: u32 = 1 << 31;
+
+/// One character cell of a [`PackedCells`] row: a `char` and a 32-bit style
+/// word, eight bytes in total.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PackedCell {
+    ch: char,
+    style: u32,
+}
+
+impl PackedCell {
+    /// Returns the cell's character.
+    #[must_use]
+    pub fn ch(self) -> char {
+        self.ch
+    }
+}
+
+/// Styled characters stored one compact cell each, for cell grids such as
+/// rows converted from a terminal emulator.
+///
+/// Per-character [`AnsiStyle`] values take 20 bytes or more; here each cell
+/// is a `char` plus a style word. Palette colors and all attributes are
+/// packed into the word itself. Styles with RGB colors or an underline
+/// color, which are rare in most output, are stored once in a side table
+/// that the word indexes. Comparing two rows cell by cell, for example to
+/// find the columns that changed between frames, mostly compares integers.
+///
+/// Runs of one style in [`AnsiText`] are already cheaper for long lines of
+/// uniform color; packed cells pay off where every cell may differ and
+/// single cells are read and replaced.
+///
+/// ```rust
+/// use egui_sgr::{PackedCells, ansi_to_text};
+///
+/// let before = PackedCells::from_text(&ansi_to_text("\x1b[31mCPU\x1b[0m 12%"));
+/// let after = PackedCells::from_text(&ansi_to_text("\x1b[31mCPU\x1b[0m 47%"));
+/// assert_eq!(before.changed_columns(&after).collect::<Vec<_>>(), [4, 5]);
+/// assert_eq!(after.to_text(), ansi_to_text("\x1b[31mCPU\x1b[0m 47%"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PackedCells {
+    cells: Vec<PackedCell>,
+    /// Styles that do not fit a style word.
+    extended: Vec<AnsiStyle>,
+    extended_index: HashMap<AnsiStyle, u32>,
+}
+
+impl PackedCells {
+    /// Creates an empty row.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Packs every character of `text` into one cell.
+    #[must_use]
+    pub fn from_text(text: &AnsiText) -> Self {
+        let mut cells = Self::new();
+        cells.cells.reserve(text.text.len());
+        for (segment, style) in text.iter() {
+            for ch in segment.chars() {
+                cells.push(ch, style);
+            }
+        }
+        cells
+    }
+
+    /// Appends a cell.
+    pub fn push(&mut self, ch: char, style: AnsiStyle) {
+        let style = self.pack(style);
+        self.cells.push(PackedCell { ch, style });
+    }
+
+    /// Replaces the cell at `index`, returning `false` if it does not exist.
+    pub fn set(&mut self, index: usize, ch: char, style: AnsiStyle) -> bool {
+        if index >= self.cells.len() {
+            return false;
+        }
+        let style = self.pack(style);
+        self.cells[index] = PackedCell { ch, style };
+        true
+    }
+
+    /// Returns the character and style of the cell at `index`.
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<(char, AnsiStyle)> {
+        let cell = self.cells.get(index)?;
+        Some((cell.ch, self.unpack(cell.style)))
+    }
+
+    /// Returns the cells, whose characters can be read without unpacking
+    /// styles.
+    #[must_use]
+    pub fn cells(&self) -> &[PackedCell] {
+        &self.cells
+    }
+
+    /// Returns the number of cells.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Returns `true` if there are no cells.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Returns the number of distinct styles kept in the side table.
+    #[must_use]
+    pub fn extended_styles(&self) -> usize {
+        self.extended.len()
+    }
+
+    /// Removes all cells and side-table styles.
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.extended.clear();
+        self.extended_index.clear();
+    }
+
+    /// Iterates over the indices of cells that differ between `self` and
+    /// `other` in character or style, including cells present in only one of
+    /// them.
+    pub fn changed_columns<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = usize> + 'a {
+        let shared = self.len().min(other.len());
+        let changed = self.cells[..shared]
+            .iter()
+            .zip(&other.cells[..shared])
+            .enumerate()
+            .filter(|(_, (a, b))| {
+                a.ch != b.ch
+                    || if a.style & b.style & EXTENDED == 0 {
+                        a.style != b.style
+                    } else {
+                        self.unpack(a.style) != other.unpack(b.style)
+                    }
+            })
+            .map(|(index, _)| index);
+        changed.chain(shared..self.len().max(other.len()))
+    }
+
+    /// Converts the cells back to styled text.
+    #[must_use]
+    pub fn to_text(&self) -> AnsiText {
+        let mut text = AnsiText::new();
+        let mut buffer = [0; 4];
+        for cell in &self.cells {
+            text.push_str(cell.ch.encode_utf8(&mut buffer), self.unpack(cell.style));
+        }
+        text
+    }
+
+    fn pack(&mut self, style: AnsiStyle) -> u32 {
+        if let Some(word) = pack_style(style) {
+            return word;
+        }
+        if let Some(&index) = self.extended_index.get(&style) {
+            return EXTENDED | index;
+        }
+        let index = u32::try_from(self.extended.len()).expect("side table fits a style word");
+        self.extended.push(style);
+        self.extended_index.insert(style, index);
+        EXTENDED | index
+    }
+
+    fn unpack(&self, word: u32) -> AnsiStyle {
+        if word & EXTENDED != 0 {
+            return self.extended[(word & !EXTENDED) as usize];
+        }
+        unpack_style(word)
+    }
+}
+
+impl PartialEq for PackedCells {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.changed_columns(other).next().is_none()
+    }
+}
+
+impl Eq for PackedCells {}
+
+/// Packs a style without RGB or underline colors into a word: nine bits
+/// each for the foreground and background (zero for the default, otherwise
+/// the palette index plus one), then intensity, italic, underline,
+/// strikethrough, reverse, and hidden.
+fn pack_style(style: AnsiStyle) -> Option<u32> {
+    if style.underline_color.is_some() {
+        return None;
+    }
+    let color = |color| match color {
+        AnsiColor::Default => Some(0),
+        AnsiColor::Indexed(index) => Some(u32::from(index) + 1),
+        AnsiColor::Rgb(..) => None,
+    };
+    let intensity = match style.intensity {
+        AnsiIntensity::Normal => 0,
+        AnsiIntensity::Bold => 1,
+        AnsiIntensity::Faint => 2,
+    };
+    let underline = match style.underline {
+        UnderlineStyle::None => 0,
+        UnderlineStyle::Single => 1,
+        UnderlineStyle::Double => 2,
+        UnderlineStyle::Curly => 3,
+        UnderlineStyle::Dotted => 4,
+        UnderlineStyle::Dashed => 5,
+    };
+    Some(
+        color(style.foreground)?
+            | color(style.background)? << 9
+            | intensity << 18
+            | u32::from(style.italic) << 20
+            | underline << 21
+            | u32::from(style.strikethrough) << 24
+            | u32::from(style.reverse) << 25
+            | u32::from(style.hidden) << 26,
+    )
+}
+
+fn unpack_style(word: u32) -> AnsiStyle {
+    let color = |bits: u32| match bits & 0x1ff {
+        0 => AnsiColor::Default,
+        index => AnsiColor::Indexed((index - 1) as u8),
+    };
+    AnsiStyle {
+        foreground: color(word),
+        background: color(word >> 9),
+        underline_color: None,
+        intensity: match word >> 18 & 0b11 {
+            1 => AnsiIntensity::Bold,
+            2 => AnsiIntensity::Faint,
+            _ => AnsiIntensity::Normal,
+        },
+        italic: word & 1 << 20 != 0,
+        underline: match word >> 21 & 0b111 {
+            1 => UnderlineStyle::Single,
+            2 => UnderlineStyle::Double,
+            3 => UnderlineStyle::Curly,
+            4 => UnderlineStyle::Dotted,
+            5 => UnderlineStyle::Dashed,
+            _ => UnderlineStyle::None,
+        },
+        strikethrough: word & 1 << 24 != 0,
+        reverse: word & 1 << 25 != 0,
+        hidden: word & 1 << 26 != 0,
+    }
+}
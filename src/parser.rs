@@ -1,6 +1,27 @@
-use crate::{AnsiSpan, AnsiStyle, EguiAnsiTheme, sgr};
+#[cfg(feature = "std")]
+use crate::AnsiBackend;
+#[cfg(feature = "egui")]
+use crate::EguiAnsiTheme;
+use crate::{AnsiSpan, AnsiStyle, AnsiText, IntoAnsiSource, ParseStats, sgr};
+use alloc::borrow::Cow;
+#[cfg(feature = "std")]
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use core::fmt;
+use core::ops::Range;
+use core::time::Duration;
+#[cfg(feature = "egui")]
 use egui::text::LayoutJob;
+#[cfg(feature = "std")]
+use std::collections::VecDeque;
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+use std::time::Instant;
 use vte::{Params, Perform};
+#[cfg(all(feature = "std", target_arch = "wasm32"))]
+use web_time::Instant;
 
 /// Stateful streaming ANSI parser.
 ///
@@ -8,8 +29,9 @@ use vte::{Params, Perform};
 /// with [`Self::push_bytes`]. Style state and incomplete ANSI/UTF-8 sequences
 /// are preserved between calls.
 pub struct AnsiStreamParser {
-    parser: vte::Parser,
+    parser: Parser,
     performer: SgrPerformer,
+    utf8_tail: Vec<u8>,
 }
 
 impl Default for AnsiStreamParser {
@@ -23,15 +45,36 @@ impl AnsiStreamParser {
     #[must_use]
     pub fn new() -> Self {
         Self {
-            parser: vte::Parser::new(),
+            parser: Parser::new(),
             performer: SgrPerformer::new(),
+            utf8_tail: Vec::new(),
         }
     }
 
     /// Pushes a byte chunk and returns visible spans produced by this chunk.
     #[must_use]
     pub fn push_bytes(&mut self, chunk: &[u8]) -> Vec<AnsiSpan> {
-        self.parser.advance(&mut self.performer, chunk);
+        profile_scope!("egui_sgr::parse");
+        self.performer.stats.bytes += chunk.len() as u64;
+        let mut chunk = chunk;
+        if !self.utf8_tail.is_empty() {
+            // Complete the held code point from the front of the chunk, so
+            // the chunk itself never has to be copied.
+            let mut taken = 0;
+            while taken < chunk.len() && incomplete_utf8_len(&self.utf8_tail) > 0 {
+                self.utf8_tail.push(chunk[taken]);
+                taken += 1;
+            }
+            chunk = &chunk[taken..];
+            if incomplete_utf8_len(&self.utf8_tail) == 0 {
+                self.parser.advance(&mut self.performer, &self.utf8_tail);
+                self.utf8_tail.clear();
+            }
+        }
+
+        let (complete, tail) = chunk.split_at(chunk.len() - incomplete_utf8_len(chunk));
+        self.parser.advance(&mut self.performer, complete);
+        self.utf8_tail.extend_from_slice(tail);
         self.performer.flush_text();
         self.performer.take_output()
     }
@@ -47,6 +90,8 @@ impl AnsiStreamParser {
     /// Unfinished escape, OSC, DCS, or UTF-8 sequences are discarded.
     #[must_use]
     pub fn finish(&mut self) -> Vec<AnsiSpan> {
+        let tail = core::mem::take(&mut self.utf8_tail);
+        self.parser.advance(&mut self.performer, &tail);
         self.performer.flush_text();
         let output = self.performer.take_output();
         self.reset();
@@ -54,9 +99,16 @@ impl AnsiStreamParser {
     }
 
     /// Clears all parser and style state.
+    ///
+    /// [`Self::stats`] are kept; see [`Self::reset_stats`].
     pub fn reset(&mut self) {
-        self.parser = vte::Parser::new();
+        let stats = core::mem::take(&mut self.performer.stats);
+        let color_stats = self.performer.color_stats;
+        self.parser = Parser::new();
         self.performer = SgrPerformer::new();
+        self.performer.stats = stats;
+        self.performer.color_stats = color_stats;
+        self.utf8_tail.clear();
     }
 
     /// Returns the currently active ANSI style.
@@ -64,43 +116,175 @@ impl AnsiStreamParser {
     pub fn current_style(&self) -> &AnsiStyle {
         &self.performer.current_style
     }
+
+    /// Returns counters for the input pushed so far.
+    #[must_use]
+    pub fn stats(&self) -> &ParseStats {
+        &self.performer.stats
+    }
+
+    /// Clears [`Self::stats`].
+    pub fn reset_stats(&mut self) {
+        self.performer.stats = ParseStats::default();
+    }
+
+    /// Collects the colors set by SGR sequences into
+    /// [`ParseStats::colors`]. Off by default.
+    #[must_use]
+    pub fn with_color_stats(mut self, enabled: bool) -> Self {
+        self.set_color_stats(enabled);
+        self
+    }
+
+    /// Sets whether [`ParseStats::colors`] is collected. Colors already
+    /// collected are kept.
+    pub fn set_color_stats(&mut self, enabled: bool) {
+        self.performer.color_stats = enabled;
+    }
+}
+
+/// Limit for one call to [`AnsiSpanBuffer::process_pending`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ParseBudget {
+    /// Parse at most this many queued bytes.
+    Bytes(usize),
+    /// Parse queued bytes until this much time has passed.
+    ///
+    /// Work is done in blocks of a few KiB, so the budget can be exceeded by
+    /// the time needed for one block.
+    Time(Duration),
 }
 
 /// Accumulates streamed ANSI spans and can render the full buffer to egui.
+///
+/// Chunks can be parsed immediately with [`Self::push_bytes`], or queued
+/// with [`Self::queue_bytes`] and parsed a slice per frame with
+/// [`Self::process_pending`] to keep the UI responsive during large bursts.
+#[cfg(feature = "std")]
 pub struct AnsiSpanBuffer {
-    parser: AnsiStreamParser,
+    parser: Box<dyn AnsiBackend>,
     spans: Vec<AnsiSpan>,
+    /// Queued bytes; parsed bytes are drained from the front, so a producer
+    /// that outpaces the budget only keeps the unparsed backlog alive.
+    pending: VecDeque<u8>,
+    queued_total: usize,
 }
 
+#[cfg(feature = "std")]
 impl Default for AnsiSpanBuffer {
     fn default() -> Self {
         Self::new()
     }
 }
 
+#[cfg(feature = "std")]
 impl AnsiSpanBuffer {
     /// Creates an empty span buffer.
     #[must_use]
     pub fn new() -> Self {
         Self {
-            parser: AnsiStreamParser::new(),
+            parser: Box::new(AnsiStreamParser::new()),
             spans: Vec::new(),
+            pending: VecDeque::new(),
+            queued_total: 0,
         }
     }
 
+    /// Parses pushed output with `backend` instead of the built-in
+    /// [`AnsiStreamParser`].
+    #[must_use]
+    pub fn with_backend(mut self, backend: impl AnsiBackend + 'static) -> Self {
+        self.parser = Box::new(backend);
+        self
+    }
+
     /// Pushes a byte chunk into the buffer.
+    ///
+    /// Any queued bytes are parsed first, so output order is preserved.
     pub fn push_bytes(&mut self, chunk: &[u8]) {
+        self.process_all_pending();
         let spans = self.parser.push_bytes(chunk);
         extend_and_merge(&mut self.spans, spans);
     }
 
+    /// Queues a byte chunk to be parsed by [`Self::process_pending`].
+    pub fn queue_bytes(&mut self, chunk: &[u8]) {
+        self.pending.extend(chunk);
+        self.queued_total += chunk.len();
+    }
+
+    /// Queues a UTF-8 string chunk to be parsed by [`Self::process_pending`].
+    pub fn queue_str(&mut self, chunk: &str) {
+        self.queue_bytes(chunk.as_bytes());
+    }
+
+    /// Parses queued bytes within `budget`.
+    ///
+    /// Returns `true` while queued bytes remain, so callers can request
+    /// another repaint and resume on the next frame.
+    pub fn process_pending(&mut self, budget: ParseBudget) -> bool {
+        let started = Instant::now();
+        let mut remaining_bytes = match budget {
+            ParseBudget::Bytes(bytes) => bytes,
+            ParseBudget::Time(_) => usize::MAX,
+        };
+
+        while !self.pending.is_empty() && remaining_bytes > 0 {
+            let (front, _) = self.pending.as_slices();
+            let len = front.len().min(remaining_bytes).min(PARSE_BLOCK_SIZE);
+            let spans = self.parser.push_bytes(&front[..len]);
+            extend_and_merge(&mut self.spans, spans);
+            self.pending.drain(..len);
+            remaining_bytes -= len;
+
+            if let ParseBudget::Time(limit) = budget
+                && started.elapsed() >= limit
+            {
+                break;
+            }
+        }
+
+        if self.pending.is_empty() {
+            self.queued_total = 0;
+        }
+
+        self.pending_bytes() > 0
+    }
+
+    fn process_all_pending(&mut self) {
+        if self.pending_bytes() > 0 {
+            self.process_pending(ParseBudget::Bytes(usize::MAX));
+        }
+    }
+
+    /// Returns the number of queued bytes that are not parsed yet.
+    #[must_use]
+    pub fn pending_bytes(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns the parsed fraction of the bytes queued since the queue was
+    /// last empty, from `0.0` to `1.0`.
+    #[must_use]
+    pub fn progress(&self) -> f32 {
+        if self.queued_total == 0 {
+            1.0
+        } else {
+            1.0 - self.pending_bytes() as f32 / self.queued_total as f32
+        }
+    }
+
     /// Pushes a UTF-8 string chunk into the buffer.
     pub fn push_str(&mut self, chunk: &str) {
         self.push_bytes(chunk.as_bytes());
     }
 
     /// Finishes the stream and stores any final visible spans.
+    ///
+    /// Any queued bytes are parsed first.
     pub fn finish(&mut self) {
+        self.process_all_pending();
         let spans = self.parser.finish();
         extend_and_merge(&mut self.spans, spans);
     }
@@ -111,28 +295,82 @@ impl AnsiSpanBuffer {
         &self.spans
     }
 
-    /// Clears accumulated spans and parser state.
+    /// Returns counters for the bytes parsed so far; queued bytes are not
+    /// counted until they are parsed.
+    #[must_use]
+    pub fn stats(&self) -> &ParseStats {
+        self.parser.stats()
+    }
+
+    /// Clears [`Self::stats`].
+    pub fn reset_stats(&mut self) {
+        self.parser.reset_stats();
+    }
+
+    /// Clears accumulated spans, queued bytes, and parser state.
     pub fn clear(&mut self) {
         self.spans.clear();
+        self.pending.clear();
+        self.queued_total = 0;
         self.parser.reset();
     }
 
     /// Converts the accumulated spans to an egui layout job.
+    #[cfg(feature = "egui")]
     #[must_use]
     pub fn to_layout_job(&self, theme: &EguiAnsiTheme) -> LayoutJob {
         crate::spans_to_layout_job(&self.spans, theme)
     }
 }
 
-/// Converts a UTF-8 string into ANSI spans.
+#[cfg(feature = "std")]
+impl Extend<AnsiSpan> for AnsiSpanBuffer {
+    /// Appends already parsed spans, merging adjacent runs with equal style.
+    fn extend<T: IntoIterator<Item = AnsiSpan>>(&mut self, spans: T) {
+        self.process_all_pending();
+        extend_and_merge(&mut self.spans, spans);
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Write for AnsiSpanBuffer {
+    /// Parses formatted text into the buffer, so
+    /// `write!(buffer, "\x1b[32m{value}\x1b[0m")` emits styled text directly.
+    fn write_str(&mut self, chunk: &str) -> fmt::Result {
+        self.push_str(chunk);
+        Ok(())
+    }
+}
+
+/// Converts text or bytes into ANSI spans.
+///
+/// Text input is not validated as UTF-8 again. Input without escapes or
+/// controls becomes one span without running the parser, but spans own
+/// their text, so it is still copied once; [`strip_ansi`] borrows plain
+/// input instead.
 #[must_use]
-pub fn ansi_to_spans(input: &str) -> Vec<AnsiSpan> {
-    ansi_bytes_to_spans(input.as_bytes())
+pub fn ansi_to_spans<'a>(input: impl IntoAnsiSource<'a>) -> Vec<AnsiSpan> {
+    let input = input.into_ansi_str();
+    let (bytes, plain) = source_parts(&input);
+    spans_from(bytes, plain)
 }
 
 /// Converts bytes into ANSI spans.
 #[must_use]
 pub fn ansi_bytes_to_spans(input: &[u8]) -> Vec<AnsiSpan> {
+    spans_from(input, plain_text(input))
+}
+
+fn spans_from(input: &[u8], plain: Option<&str>) -> Vec<AnsiSpan> {
+    profile_scope!("egui_sgr::parse");
+    if let Some(text) = plain {
+        return if text.is_empty() {
+            Vec::new()
+        } else {
+            vec![AnsiSpan::new(text, AnsiStyle::default())]
+        };
+    }
+
     let mut parser = AnsiStreamParser::new();
     let mut spans = Vec::new();
     extend_and_merge(&mut spans, parser.push_bytes(input));
@@ -140,7 +378,378 @@ pub fn ansi_bytes_to_spans(input: &[u8]) -> Vec<AnsiSpan> {
     spans
 }
 
-fn extend_and_merge(target: &mut Vec<AnsiSpan>, spans: Vec<AnsiSpan>) {
+/// Converts text or bytes into one text buffer with style runs.
+#[must_use]
+pub fn ansi_to_text<'a>(input: impl IntoAnsiSource<'a>) -> AnsiText {
+    let input = input.into_ansi_str();
+    let (bytes, plain) = source_parts(&input);
+    text_from(bytes, plain)
+}
+
+/// Converts bytes into one text buffer with style runs.
+#[must_use]
+pub fn ansi_bytes_to_text(input: &[u8]) -> AnsiText {
+    text_from(input, plain_text(input))
+}
+
+fn text_from(input: &[u8], plain: Option<&str>) -> AnsiText {
+    profile_scope!("egui_sgr::parse");
+    let mut output = AnsiText::new();
+
+    if let Some(text) = plain {
+        output.push_str(text, AnsiStyle::default());
+        return output;
+    }
+
+    parse_text(input, output, None).output
+}
+
+/// Returns the OSC 8 hyperlinks of `input` as URLs over byte ranges of the
+/// text [`ansi_bytes_to_text`] produces for it.
+#[cfg(feature = "std")]
+pub(crate) fn hyperlink_ranges(input: &[u8]) -> Vec<(Range<usize>, String)> {
+    let performer = parse_text(input, AnsiText::new(), Some(HyperlinkRanges::default()));
+    performer
+        .hyperlinks
+        .map(|hyperlinks| hyperlinks.links)
+        .unwrap_or_default()
+}
+
+fn parse_text(
+    input: &[u8],
+    output: AnsiText,
+    hyperlinks: Option<HyperlinkRanges>,
+) -> TextRunPerformer {
+    let mut performer = TextRunPerformer {
+        current_style: AnsiStyle::default(),
+        run_start: 0,
+        output,
+        hyperlinks,
+    };
+    performer.output.text.reserve(input.len());
+    Parser::new().advance(&mut performer, input);
+    performer.close_run();
+    if let Some(hyperlinks) = &mut performer.hyperlinks {
+        hyperlinks.close(performer.output.text.len());
+    }
+    performer
+}
+
+/// Removes escape sequences and control characters from a string.
+///
+/// SGR, other CSI, OSC, and DCS sequences are all dropped; newlines, carriage
+/// returns, and tabs are kept. Input without escapes is returned borrowed, so
+/// plain-text search, copying, and logging do not allocate for plain lines.
+#[must_use]
+pub fn strip_ansi(input: &str) -> Cow<'_, str> {
+    profile_scope!("egui_sgr::parse");
+    if let Some(text) = plain_str(input) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut parser = Parser::new();
+    let mut performer = StripPerformer {
+        text: String::with_capacity(input.len()),
+    };
+    parser.advance(&mut performer, input.as_bytes());
+    Cow::Owned(performer.text)
+}
+
+/// Returns the style active at `byte_offset` of raw ANSI input.
+///
+/// Escape sequences before the offset are applied; the offset indexes the
+/// input including its escapes, as an editor showing raw ANSI would report
+/// it. Offsets past the end return the style at the end. For offsets into
+/// visible text, use [`AnsiText::style_at`].
+#[must_use]
+pub fn ansi_style_at(input: &str, byte_offset: usize) -> AnsiStyle {
+    let input = &input.as_bytes()[..byte_offset.min(input.len())];
+    let mut parser = Parser::new();
+    let mut performer = StyleTracker::default();
+    parser.advance(&mut performer, input);
+    performer.style
+}
+
+/// Converts already split lines into ANSI spans, one span list per line.
+///
+/// Style state is threaded across lines in a single pass, so a color opened
+/// on one line still applies to the following lines. Line endings are not
+/// added to the span text.
+#[must_use]
+pub fn ansi_lines_to_spans<'a, I>(lines: I) -> Vec<Vec<AnsiSpan>>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    profile_scope!("egui_sgr::parse");
+    let lines = lines.into_iter();
+    let mut output = Vec::with_capacity(lines.size_hint().0);
+    let mut parser = AnsiStreamParser::new();
+
+    for line in lines {
+        output.push(parser.push_str(line));
+    }
+
+    if let Some(last) = output.last_mut() {
+        extend_and_merge(last, parser.finish());
+    }
+
+    output
+}
+
+/// Block size used when feeding large inputs to `vte`.
+///
+/// `vte` rescans the rest of its input for the next ESC byte after every
+/// invalid UTF-8 sequence. Bounding each `advance` call keeps that rescan
+/// linear in the input size; style and partial sequences carry over between
+/// blocks exactly as they do between stream chunks.
+const PARSE_BLOCK_SIZE: usize = 4096;
+
+/// Shortest text run before an escape that [`Parser`] dispatches itself.
+///
+/// Shorter runs, as in SGR-dense output, go to `vte` together with the
+/// escape after them, which is cheaper than switching parsers twice.
+const MIN_TEXT_RUN: usize = 32;
+
+/// [`Perform`] that can take a run of printable text in one call.
+///
+/// `vte` prints one `char` at a time; [`Parser`] hands text between escapes
+/// over whole, so appending it is one copy instead of one push per `char`.
+pub(crate) trait PrintRuns: Perform {
+    /// Prints `text`, which holds no control characters.
+    fn print_str(&mut self, text: &str) {
+        text.chars().for_each(|c| self.print(c));
+    }
+}
+
+/// `vte` parser with a fast path for the text between escape sequences.
+///
+/// While `vte` is known to be in its ground state, longer text up to the
+/// next ESC is validated and dispatched here, with the same printed characters,
+/// executed controls, and replacement characters `vte` would produce, and
+/// only escape sequences go through `vte`. It is known to be back in the
+/// ground state after a CSI, ESC, or BEL-terminated OSC dispatch, so the
+/// escape is fed with [`vte::Parser::advance_until_terminated`] and stops
+/// there. Other ways back, such as a cancelled sequence, leave `vte` in
+/// charge until its next dispatch.
+pub(crate) struct Parser {
+    vte: vte::Parser,
+    ground: bool,
+}
+
+impl Parser {
+    pub(crate) fn new() -> Self {
+        Self {
+            vte: vte::Parser::new(),
+            ground: true,
+        }
+    }
+
+    pub(crate) fn advance<P: PrintRuns>(&mut self, performer: &mut P, mut input: &[u8]) {
+        while !input.is_empty() {
+            let end = if self.ground {
+                memchr::memchr(0x1b, input).unwrap_or(input.len())
+            } else {
+                0
+            };
+            if end >= MIN_TEXT_RUN || end == input.len() {
+                let printed = print_text(performer, &input[..end]);
+                if printed < end {
+                    if end == input.len() {
+                        // A code point cut off by the end of the input:
+                        // `vte` holds it until more bytes arrive.
+                        self.vte.advance(performer, &input[printed..]);
+                        self.ground = false;
+                        return;
+                    }
+                    // Cut off by an escape: `vte` replaces it.
+                    performer.print('\u{fffd}');
+                }
+                input = &input[end..];
+                self.ground = input.is_empty();
+                continue;
+            }
+
+            let mut block = &input[..input.len().min(PARSE_BLOCK_SIZE)];
+            if block.len() < input.len() {
+                block = &block[..block.len() - incomplete_utf8_len(block)];
+            }
+            let mut watch = GroundWatch {
+                performer: &mut *performer,
+                ground: false,
+            };
+            let read = self.vte.advance_until_terminated(&mut watch, block);
+            self.ground = watch.ground;
+            input = &input[read..];
+        }
+    }
+}
+
+/// Dispatches valid text like `vte`'s ground state and returns the number of
+/// bytes handled, which stops short of an incomplete trailing code point.
+fn print_text<P: PrintRuns>(performer: &mut P, text: &[u8]) -> usize {
+    let mut rest = text;
+    loop {
+        match core::str::from_utf8(rest) {
+            Ok(valid) => {
+                print_valid(performer, valid);
+                return text.len();
+            }
+            Err(error) => {
+                let valid_len = error.valid_up_to();
+                // Checked again rather than unchecked: the crate has no
+                // `unsafe`, and invalid input is the rare case.
+                let valid = core::str::from_utf8(&rest[..valid_len]).unwrap_or_default();
+                print_valid(performer, valid);
+                let Some(invalid_len) = error.error_len() else {
+                    return text.len() - (rest.len() - valid_len);
+                };
+                match rest[valid_len] {
+                    byte @ 0x80..=0x9f if invalid_len == 1 => performer.execute(byte),
+                    _ => performer.print('\u{fffd}'),
+                }
+                rest = &rest[valid_len + invalid_len..];
+            }
+        }
+    }
+}
+
+/// Prints `text` in runs, executing C0 and C1 controls between them.
+fn print_valid<P: PrintRuns>(performer: &mut P, text: &str) {
+    let bytes = text.as_bytes();
+    let mut start = 0;
+    let mut index = 0;
+    while index < bytes.len() {
+        let control = match bytes[index] {
+            byte @ 0x00..=0x1f => Some((byte, 1)),
+            0xc2 if (0x80..=0x9f).contains(&bytes[index + 1]) => Some((bytes[index + 1], 2)),
+            _ => None,
+        };
+        let Some((byte, len)) = control else {
+            index += 1;
+            continue;
+        };
+        if start < index {
+            performer.print_str(&text[start..index]);
+        }
+        performer.execute(byte);
+        index += len;
+        start = index;
+    }
+    if start < bytes.len() {
+        performer.print_str(&text[start..]);
+    }
+}
+
+/// Forwards to a performer and stops `vte` once it is back in its ground
+/// state.
+struct GroundWatch<'a, P> {
+    performer: &'a mut P,
+    ground: bool,
+}
+
+impl<P: Perform> Perform for GroundWatch<'_, P> {
+    fn print(&mut self, c: char) {
+        self.performer.print(c);
+    }
+
+    fn execute(&mut self, byte: u8) {
+        self.performer.execute(byte);
+    }
+
+    fn hook(&mut self, params: &Params, intermediates: &[u8], ignore: bool, action: char) {
+        self.performer.hook(params, intermediates, ignore, action);
+    }
+
+    fn put(&mut self, byte: u8) {
+        self.performer.put(byte);
+    }
+
+    fn unhook(&mut self) {
+        self.performer.unhook();
+    }
+
+    fn osc_dispatch(&mut self, params: &[&[u8]], bell_terminated: bool) {
+        self.performer.osc_dispatch(params, bell_terminated);
+        // An ESC-terminated OSC leaves `vte` in its escape state.
+        self.ground = bell_terminated;
+    }
+
+    fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], ignore: bool, action: char) {
+        self.performer
+            .csi_dispatch(params, intermediates, ignore, action);
+        self.ground = true;
+    }
+
+    fn esc_dispatch(&mut self, intermediates: &[u8], ignore: bool, byte: u8) {
+        self.performer.esc_dispatch(intermediates, ignore, byte);
+        self.ground = true;
+    }
+
+    fn terminated(&self) -> bool {
+        self.ground
+    }
+}
+
+/// Returns the length of an incomplete UTF-8 sequence at the end of `bytes`.
+///
+/// `vte` handles a code point split across `advance` calls differently from
+/// one seen whole: it prints split C1 controls and can drop the character
+/// after the split one. Block and chunk boundaries are moved before such a
+/// tail so `vte` always sees complete sequences.
+pub(crate) fn incomplete_utf8_len(bytes: &[u8]) -> usize {
+    for back in 1..=bytes.len().min(3) {
+        let byte = bytes[bytes.len() - back];
+        if byte & 0xc0 != 0x80 {
+            let width = match byte {
+                0xc2..=0xdf => 2,
+                0xe0..=0xef => 3,
+                0xf0..=0xf4 => 4,
+                _ => 0,
+            };
+            return if width > back { back } else { 0 };
+        }
+    }
+    0
+}
+
+/// Returns the input as text when parsing would not change it.
+///
+/// Most log lines carry no escapes at all. Skipping the state machine for
+/// them only requires an ESC scan, UTF-8 validation, and a control check.
+pub(crate) fn plain_text(input: &[u8]) -> Option<&str> {
+    if memchr::memchr(0x1b, input).is_some() {
+        return None;
+    }
+
+    let text = core::str::from_utf8(input).ok()?;
+    has_no_controls(text).then_some(text)
+}
+
+/// Like [`plain_text`] for input already known to be UTF-8.
+pub(crate) fn plain_str(text: &str) -> Option<&str> {
+    (memchr::memchr(0x1b, text.as_bytes()).is_none() && has_no_controls(text)).then_some(text)
+}
+
+fn has_no_controls(text: &str) -> bool {
+    text.chars()
+        .all(|c| !c.is_control() || matches!(c, '\n' | '\r' | '\t'))
+}
+
+/// Splits a source into its bytes and, if parsing would not change it, its
+/// text, validating UTF-8 only for byte input.
+pub(crate) fn source_parts<'s>(
+    source: &'s Result<Cow<'_, str>, Cow<'_, [u8]>>,
+) -> (&'s [u8], Option<&'s str>) {
+    match source {
+        Ok(text) => (text.as_bytes(), plain_str(text)),
+        Err(bytes) => (bytes, plain_text(bytes)),
+    }
+}
+
+pub(crate) fn extend_and_merge(
+    target: &mut Vec<AnsiSpan>,
+    spans: impl IntoIterator<Item = AnsiSpan>,
+) {
     for span in spans {
         if span.text.is_empty() {
             continue;
@@ -161,6 +770,8 @@ struct SgrPerformer {
     current_style: AnsiStyle,
     text: String,
     output: Vec<AnsiSpan>,
+    stats: ParseStats,
+    color_stats: bool,
 }
 
 impl SgrPerformer {
@@ -169,6 +780,8 @@ impl SgrPerformer {
             current_style: AnsiStyle::default(),
             text: String::new(),
             output: Vec::new(),
+            stats: ParseStats::default(),
+            color_stats: false,
         }
     }
 
@@ -177,19 +790,26 @@ impl SgrPerformer {
             return;
         }
 
-        let text = std::mem::take(&mut self.text);
         if let Some(last) = self.output.last_mut()
             && last.style == self.current_style
         {
-            last.text.push_str(&text);
+            last.text.push_str(&self.text);
+            self.text.clear();
             return;
         }
 
+        let text = core::mem::take(&mut self.text);
         self.output.push(AnsiSpan::new(text, self.current_style));
     }
 
     fn take_output(&mut self) -> Vec<AnsiSpan> {
-        std::mem::take(&mut self.output)
+        core::mem::take(&mut self.output)
+    }
+}
+
+impl PrintRuns for SgrPerformer {
+    fn print_str(&mut self, text: &str) {
+        self.text.push_str(text);
     }
 }
 
@@ -200,17 +820,155 @@ impl Perform for SgrPerformer {
 
     fn execute(&mut self, byte: u8) {
         match byte {
-            b'\n' => self.text.push('\n'),
+            b'\n' => {
+                self.text.push('\n');
+                self.stats.lines += 1;
+            }
             b'\r' => self.text.push('\r'),
             b'\t' => self.text.push('\t'),
-            _ => {}
+            _ => self.stats.control_chars += 1,
         }
     }
 
     fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], ignore: bool, action: char) {
         if action == 'm' && intermediates.is_empty() && !ignore {
             self.flush_text();
+            let unsupported = sgr::apply_sgr(params, &mut self.current_style);
+            self.stats
+                .record_sgr(&self.current_style, unsupported, self.color_stats);
+        } else {
+            self.stats.other_csi_sequences += 1;
+        }
+    }
+
+    fn osc_dispatch(&mut self, _params: &[&[u8]], _bell_terminated: bool) {
+        self.stats.osc_sequences += 1;
+    }
+
+    fn hook(&mut self, _params: &Params, _intermediates: &[u8], _ignore: bool, _action: char) {
+        self.stats.dcs_sequences += 1;
+    }
+
+    fn esc_dispatch(&mut self, _intermediates: &[u8], _ignore: bool, _byte: u8) {
+        self.stats.esc_sequences += 1;
+    }
+}
+
+struct TextRunPerformer {
+    current_style: AnsiStyle,
+    run_start: usize,
+    output: AnsiText,
+    /// OSC 8 links over `output.text`, when the caller asked for them.
+    hyperlinks: Option<HyperlinkRanges>,
+}
+
+impl TextRunPerformer {
+    fn close_run(&mut self) {
+        self.output
+            .extend_last_run(self.run_start, self.current_style);
+        self.run_start = self.output.text.len();
+    }
+}
+
+impl PrintRuns for TextRunPerformer {
+    fn print_str(&mut self, text: &str) {
+        self.output.text.push_str(text);
+    }
+}
+
+impl Perform for TextRunPerformer {
+    fn print(&mut self, c: char) {
+        self.output.text.push(c);
+    }
+
+    fn execute(&mut self, byte: u8) {
+        match byte {
+            b'\n' => self.output.text.push('\n'),
+            b'\r' => self.output.text.push('\r'),
+            b'\t' => self.output.text.push('\t'),
+            _ => {}
+        }
+    }
+
+    fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], ignore: bool, action: char) {
+        if action == 'm' && intermediates.is_empty() && !ignore {
+            self.close_run();
             sgr::apply_sgr(params, &mut self.current_style);
         }
     }
+
+    fn osc_dispatch(&mut self, params: &[&[u8]], _bell_terminated: bool) {
+        if let Some(hyperlinks) = &mut self.hyperlinks {
+            hyperlinks.osc_dispatch(params, self.output.text.len());
+        }
+    }
+}
+
+/// OSC 8 hyperlinks seen so far, as URLs over byte ranges of the text.
+#[derive(Default)]
+struct HyperlinkRanges {
+    open: Option<(usize, String)>,
+    links: Vec<(Range<usize>, String)>,
+}
+
+impl HyperlinkRanges {
+    fn osc_dispatch(&mut self, params: &[&[u8]], text_len: usize) {
+        if params.first() != Some(&b"8".as_slice()) {
+            return;
+        }
+        self.close(text_len);
+        // URLs may contain `;`, which vte treats as a parameter separator.
+        let url = params.get(2..).unwrap_or_default().join(&b';');
+        if !url.is_empty() {
+            self.open = Some((text_len, String::from_utf8_lossy(&url).into_owned()));
+        }
+    }
+
+    /// Ends the open link at `text_len`, dropping it if it covers no text.
+    fn close(&mut self, text_len: usize) {
+        if let Some((start, url)) = self.open.take()
+            && start < text_len
+        {
+            self.links.push((start..text_len, url));
+        }
+    }
+}
+
+struct StripPerformer {
+    text: String,
+}
+
+impl PrintRuns for StripPerformer {
+    fn print_str(&mut self, text: &str) {
+        self.text.push_str(text);
+    }
+}
+
+impl Perform for StripPerformer {
+    fn print(&mut self, c: char) {
+        self.text.push(c);
+    }
+
+    fn execute(&mut self, byte: u8) {
+        if matches!(byte, b'\n' | b'\r' | b'\t') {
+            self.text.push(char::from(byte));
+        }
+    }
+}
+
+#[derive(Default)]
+struct StyleTracker {
+    style: AnsiStyle,
+}
+
+impl PrintRuns for StyleTracker {
+    fn print_str(&mut self, _text: &str) {}
+}
+
+impl Perform for StyleTracker {
+    fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], ignore: bool, action: char) {
+        if action == 'm' && intermediates.is_empty() && !ignore {
+            sgr::apply_sgr(params, &mut self.style);
+        }
+    }
 }
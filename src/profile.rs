@@ -0,0 +1,120 @@
+use crate::{AnsiDocument, EguiAnsiTheme};
+
+/// Shape of the text cursor drawn by a terminal view.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CursorShape {
+    /// A filled cell, drawn in reverse video.
+    #[default]
+    Block,
+    /// A line under the cell.
+    Underline,
+    /// A vertical bar before the cell.
+    Bar,
+}
+
+/// User-editable terminal settings, bundled so they can be saved and applied
+/// together.
+///
+/// The [`theme`](Self::theme) carries the palette, the default colors, and,
+/// with the `egui` feature, the font. The cursor settings are for views that
+/// draw a cursor; the crate's own renderers have none. The rest configure an
+/// [`AnsiDocument`] through [`Self::apply_to_document`]. With the `serde`
+/// feature a profile serializes as a whole, so apps can keep profiles in
+/// their own settings files; with `persistence` it can also be kept in egui's
+/// persisted memory.
+///
+/// ```rust
+/// use egui_sgr::{AnsiDocument, CursorShape, TerminalProfile};
+///
+/// let profile = TerminalProfile {
+///     name: "Serial".into(),
+///     cursor: CursorShape::Bar,
+///     scrollback_lines: Some(2),
+///     ..TerminalProfile::default()
+/// };
+/// let mut document = AnsiDocument::<()>::new();
+/// profile.apply_to_document(&mut document);
+/// document.append("one\ntwo\nthree\n");
+/// assert_eq!(document.line_count(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct TerminalProfile {
+    /// Name shown in a profile picker.
+    pub name: String,
+    /// Palette, default colors, and font.
+    pub theme: EguiAnsiTheme,
+    /// Cursor shape.
+    pub cursor: CursorShape,
+    /// Whether the cursor blinks.
+    pub cursor_blink: bool,
+    /// Lines kept before the oldest are evicted, or `None` for no limit.
+    pub scrollback_lines: Option<usize>,
+    /// Display columns after which long lines are folded, or `None` to never
+    /// fold. See [`AnsiDocument::with_fold_columns`].
+    pub fold_columns: Option<usize>,
+    /// Text of the line shown before each page, or `None` for no separator.
+    /// See [`AnsiDocument::with_page_separator`].
+    pub page_separator: Option<String>,
+}
+
+impl Default for TerminalProfile {
+    fn default() -> Self {
+        Self {
+            name: "Default".to_owned(),
+            theme: EguiAnsiTheme::default(),
+            cursor: CursorShape::default(),
+            cursor_blink: true,
+            scrollback_lines: Some(10_000),
+            fold_columns: None,
+            page_separator: None,
+        }
+    }
+}
+
+impl TerminalProfile {
+    /// Applies the scrollback, folding, and page separator settings to
+    /// `document`, evicting lines beyond the new scrollback.
+    pub fn apply_to_document<M: Default>(&self, document: &mut AnsiDocument<M>) {
+        document.set_max_lines(self.scrollback_lines);
+        document.set_fold_columns(self.fold_columns);
+        document.set_page_separator(self.page_separator.clone());
+    }
+
+    /// Applies the profile to a workspace session: the session renders with
+    /// it, and the document settings go to its shared document.
+    pub fn apply_to_session(&self, session: &mut crate::AnsiSession) {
+        session.profile = self.clone();
+        self.apply_to_document(&mut session.document.lock());
+    }
+
+    /// Sets the font of [`Self::theme`].
+    #[cfg(feature = "egui")]
+    #[must_use]
+    pub fn with_font(mut self, font_id: egui::FontId) -> Self {
+        self.theme.default_format.font_id = font_id;
+        self
+    }
+
+    /// Returns the font of [`Self::theme`].
+    #[cfg(feature = "egui")]
+    #[must_use]
+    pub fn font_id(&self) -> &egui::FontId {
+        &self.theme.default_format.font_id
+    }
+
+    /// Returns the profile stored under `id` in egui's persisted memory.
+    #[cfg(feature = "persistence")]
+    #[must_use]
+    pub fn load(ctx: &egui::Context, id: egui::Id) -> Option<Self> {
+        ctx.data_mut(|data| data.get_persisted(id))
+    }
+
+    /// Stores the profile under `id` in egui's persisted memory.
+    #[cfg(feature = "persistence")]
+    pub fn store(&self, ctx: &egui::Context, id: egui::Id) {
+        ctx.data_mut(|data| data.insert_persisted(id, self.clone()));
+    }
+}
@@ -0,0 +1,138 @@
+use crate::{AnsiColor, AnsiSpan, AnsiStyle, AnsiText, ColorMetric, nearest_color};
+use ecolor::Color32;
+
+/// Color capability of an output target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ColorDepth {
+    /// 24-bit RGB colors are kept as they are.
+    #[default]
+    TrueColor,
+    /// Colors are limited to the 256-color palette.
+    Indexed256,
+    /// Colors are limited to the 16 system colors.
+    Ansi16,
+}
+
+/// Returns the xterm palette index closest to `color`.
+///
+/// Closeness is measured with [`ColorMetric::Oklab`].
+///
+/// Only the 6x6x6 color cube and the grayscale ramp (indices 16-255) are
+/// considered, because their values are fixed while terminals and themes
+/// customize the 16 system colors.
+#[must_use]
+pub fn nearest_256(color: Color32) -> u8 {
+    let [r, g, b, _] = color.to_array();
+    let (cube_r, cube_g, cube_b) = (cube_level(r), cube_level(g), cube_level(b));
+    let cube_index = 16 + 36 * cube_r + 6 * cube_g + cube_b;
+    let cube = Color32::from_rgb(
+        CUBE[cube_r as usize],
+        CUBE[cube_g as usize],
+        CUBE[cube_b as usize],
+    );
+
+    let average = (u16::from(r) + u16::from(g) + u16::from(b)) / 3;
+    let gray_step = (average.saturating_sub(3) / 10).min(23) as u8;
+    let gray_level = 8 + gray_step * 10;
+    let gray = Color32::from_rgb(gray_level, gray_level, gray_level);
+
+    let metric = ColorMetric::Oklab;
+    if metric.distance(color, gray) < metric.distance(color, cube) {
+        232 + gray_step
+    } else {
+        cube_index
+    }
+}
+
+/// Returns the index of the entry among the first 16 of `palette` that is
+/// closest to `color` under [`ColorMetric::Oklab`].
+///
+/// Pass a theme's palette so the choice matches how the result will be
+/// displayed. An empty palette yields 0.
+#[must_use]
+pub fn nearest_16(color: Color32, palette: &[Color32]) -> u8 {
+    let system = &palette[..palette.len().min(16)];
+    nearest_color(color, system, ColorMetric::Oklab).map_or(0, |index| index as u8)
+}
+
+impl AnsiColor {
+    /// Converts the color to one that `depth` can express.
+    ///
+    /// `palette` resolves indexed colors to RGB and supplies the 16 system
+    /// colors matched against for [`ColorDepth::Ansi16`]; usually it is
+    /// [`EguiAnsiTheme::palette`](crate::EguiAnsiTheme::palette).
+    #[must_use]
+    pub fn to_depth(self, depth: ColorDepth, palette: &[Color32; 256]) -> Self {
+        match (self, depth) {
+            (Self::Default, _) | (_, ColorDepth::TrueColor) => self,
+            (Self::Indexed(_), ColorDepth::Indexed256) => self,
+            (Self::Rgb(r, g, b), ColorDepth::Indexed256) => {
+                Self::Indexed(nearest_256(Color32::from_rgb(r, g, b)))
+            }
+            (Self::Indexed(index @ 0..=15), ColorDepth::Ansi16) => Self::Indexed(index),
+            (Self::Indexed(index), ColorDepth::Ansi16) => {
+                Self::Indexed(nearest_16(palette[usize::from(index)], palette))
+            }
+            (Self::Rgb(r, g, b), ColorDepth::Ansi16) => {
+                Self::Indexed(nearest_16(Color32::from_rgb(r, g, b), palette))
+            }
+        }
+    }
+}
+
+impl AnsiStyle {
+    /// Converts all colors of the style to ones that `depth` can express.
+    ///
+    /// Underline colors are dropped for [`ColorDepth::Ansi16`], since
+    /// terminals limited to 16 colors do not support SGR 58.
+    #[must_use]
+    pub fn to_depth(mut self, depth: ColorDepth, palette: &[Color32; 256]) -> Self {
+        self.foreground = self.foreground.to_depth(depth, palette);
+        self.background = self.background.to_depth(depth, palette);
+        self.underline_color = match depth {
+            ColorDepth::Ansi16 => None,
+            _ => self
+                .underline_color
+                .map(|color| color.to_depth(depth, palette)),
+        };
+        self
+    }
+}
+
+/// Re-emits ANSI text for `spans` using only colors that `depth` can
+/// express, for legacy terminals and other limited targets.
+#[must_use]
+pub fn spans_to_ansi_with_depth(
+    spans: &[AnsiSpan],
+    depth: ColorDepth,
+    palette: &[Color32; 256],
+) -> String {
+    crate::emit::emit_runs(
+        spans
+            .iter()
+            .map(|span| (span.text.as_str(), span.style.to_depth(depth, palette))),
+    )
+}
+
+impl AnsiText {
+    /// Re-emits ANSI text for these runs using only colors that `depth` can
+    /// express.
+    #[must_use]
+    pub fn to_ansi_with_depth(&self, depth: ColorDepth, palette: &[Color32; 256]) -> String {
+        crate::emit::emit_runs(
+            self.iter()
+                .map(|(text, style)| (text, style.to_depth(depth, palette))),
+        )
+    }
+}
+
+/// Channel values of the xterm 6x6x6 color cube.
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn cube_level(value: u8) -> u8 {
+    match value {
+        0..=47 => 0,
+        48..=114 => 1,
+        _ => (value - 35) / 40,
+    }
+}
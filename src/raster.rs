@@ -0,0 +1,117 @@
+use crate::{EguiAnsiTheme, ansi_to_layout_job};
+use egui::epaint::text::FontDefinitions;
+use egui::epaint::{
+    Color32, ColorImage, Fonts, Mesh, TessellationOptions, Tessellator, TextOptions, TextShape,
+    Vertex, pos2,
+};
+
+/// Lays out and rasterizes ANSI text into an image without a window or GPU.
+///
+/// Text wraps at `width` points and is drawn at one pixel per point with
+/// egui's default fonts on the theme's default background. This is meant
+/// for sharing screenshots of terminal output from headless tools.
+#[must_use]
+pub fn render_ansi_to_image(input: &str, width: f32, theme: &EguiAnsiTheme) -> ColorImage {
+    let mut job = ansi_to_layout_job(input, theme);
+    job.wrap.max_width = width;
+
+    let mut fonts = Fonts::new(TextOptions::default(), FontDefinitions::default());
+    let galley = fonts.with_pixels_per_point(1.0).layout_job(job);
+    let atlas = fonts.image();
+
+    let mut mesh = Mesh::default();
+    let mut tessellator = Tessellator::new(
+        1.0,
+        TessellationOptions::default(),
+        fonts.font_image_size(),
+        Vec::new(),
+    );
+    tessellator.tessellate_text(
+        &TextShape::new(pos2(0.0, 0.0), galley.clone(), theme.default_foreground),
+        &mut mesh,
+    );
+
+    let size = [
+        width.max(0.0).ceil() as usize,
+        galley.rect.height().ceil() as usize,
+    ];
+    let mut image = ColorImage::filled(size, theme.default_background);
+    for triangle in mesh.indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|corner| mesh.vertices[triangle[corner] as usize]);
+        fill_triangle(&mut image, &atlas, [a, b, c]);
+    }
+
+    image
+}
+
+/// Encodes [`render_ansi_to_image`] output as a PNG file.
+#[cfg(feature = "png")]
+pub fn render_ansi_to_png(
+    input: &str,
+    width: f32,
+    theme: &EguiAnsiTheme,
+) -> Result<Vec<u8>, png::EncodingError> {
+    let image = render_ansi_to_image(input, width, theme);
+    let mut bytes = Vec::new();
+
+    let mut encoder = png::Encoder::new(&mut bytes, image.size[0] as u32, image.size[1] as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    let pixels: Vec<u8> = image
+        .pixels
+        .iter()
+        .flat_map(|pixel| pixel.to_srgba_unmultiplied())
+        .collect();
+    writer.write_image_data(&pixels)?;
+    writer.finish()?;
+
+    Ok(bytes)
+}
+
+/// Draws one textured triangle with nearest-texel sampling and premultiplied
+/// alpha blending. Text meshes are axis-aligned quads, so this is enough.
+fn fill_triangle(image: &mut ColorImage, atlas: &ColorImage, [a, b, c]: [Vertex; 3]) {
+    let area = edge(a.pos, b.pos, c.pos);
+    if area == 0.0 {
+        return;
+    }
+
+    let [width, height] = image.size;
+    let min_x = a.pos.x.min(b.pos.x).min(c.pos.x).floor().max(0.0) as usize;
+    let min_y = a.pos.y.min(b.pos.y).min(c.pos.y).floor().max(0.0) as usize;
+    let max_x = (a.pos.x.max(b.pos.x).max(c.pos.x).ceil().max(0.0) as usize).min(width);
+    let max_y = (a.pos.y.max(b.pos.y).max(c.pos.y).ceil().max(0.0) as usize).min(height);
+
+    for y in min_y..max_y {
+        for x in min_x..max_x {
+            let p = pos2(x as f32 + 0.5, y as f32 + 0.5);
+            let wa = edge(b.pos, c.pos, p) / area;
+            let wb = edge(c.pos, a.pos, p) / area;
+            let wc = edge(a.pos, b.pos, p) / area;
+            if wa < 0.0 || wb < 0.0 || wc < 0.0 {
+                continue;
+            }
+
+            let u = wa * a.uv.x + wb * b.uv.x + wc * c.uv.x;
+            let v = wa * a.uv.y + wb * b.uv.y + wc * c.uv.y;
+            let texel = sample(atlas, u, v);
+            // Every vertex of a glyph or decoration quad shares one color.
+            let source = a.color * texel;
+
+            let pixel = &mut image.pixels[y * width + x];
+            *pixel = pixel.blend(source);
+        }
+    }
+}
+
+fn edge(from: egui::Pos2, to: egui::Pos2, point: egui::Pos2) -> f32 {
+    (to.x - from.x) * (point.y - from.y) - (to.y - from.y) * (point.x - from.x)
+}
+
+fn sample(atlas: &ColorImage, u: f32, v: f32) -> Color32 {
+    let [width, height] = atlas.size;
+    let x = ((u * width as f32) as usize).min(width - 1);
+    let y = ((v * height as f32) as usize).min(height - 1);
+    atlas.pixels[y * width + x]
+}
@@ -0,0 +1,180 @@
+use crate::{AnsiDocument, AnsiText};
+use regex::Regex;
+
+/// Patterns for common secrets, as `(pattern, group)` pairs.
+const DEFAULT_RULES: &[(&str, usize)] = &[
+    // AWS access key IDs.
+    (r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b", 0),
+    // GitHub, Slack, and JWT tokens.
+    (r"\bgh[pousr]_[A-Za-z0-9]{36,}\b", 0),
+    (r"\bgithub_pat_[A-Za-z0-9_]{22,}", 0),
+    (r"\bxox[abprs]-[A-Za-z0-9-]{10,}", 0),
+    (r"\beyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+", 0),
+    // Authorization headers.
+    (r"(?i)\bbearer\s+([A-Za-z0-9._~+/-]+=*)", 1),
+    // Assignments such as `password=...` or `"api_key": "..."`.
+    (
+        r#"(?i)\b(?:password|passwd|pwd|secret|token|api[_-]?key|access[_-]?key|client[_-]?secret|aws_secret_access_key)["']?\s*[:=]\s*["']?([^\s"',;]+)"#,
+        1,
+    ),
+    // Credentials in URLs.
+    (r"\b[a-z][a-z0-9+.-]*://[^/\s:@]+:([^/\s@]+)@", 1),
+];
+
+/// Masks secrets in parsed text before it is rendered, shared, or exported.
+///
+/// Each rule pairs a regex with the capture group to mask, so a rule can
+/// keep a `password=` prefix visible and hide only the value. Every masked
+/// character is replaced by the mask character in the style it had, so
+/// colors and column positions around a secret are unchanged.
+///
+/// ```rust
+/// use egui_sgr::Redactor;
+///
+/// let redactor = Redactor::new().with_default_rules();
+/// let line = redactor.redact_ansi("\x1b[33mlogin\x1b[0m password=hunter2 ok");
+/// assert_eq!(line.text, "login password=******* ok");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    rules: Vec<RedactRule>,
+    mask: char,
+}
+
+#[derive(Debug, Clone)]
+struct RedactRule {
+    regex: Regex,
+    group: usize,
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Redactor {
+    /// Creates a redactor without rules that masks with `*`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            rules: Vec::new(),
+            mask: '*',
+        }
+    }
+
+    /// Adds rules for common secrets: AWS access key IDs, GitHub and Slack
+    /// tokens, JWTs, bearer tokens, `password=`/`token:`-style assignments,
+    /// and passwords in URLs.
+    ///
+    /// The rules favor precision over recall; add rules for formats specific
+    /// to your logs.
+    #[must_use]
+    pub fn with_default_rules(mut self) -> Self {
+        for &(pattern, group) in DEFAULT_RULES {
+            let regex = Regex::new(pattern).expect("default redaction rules are valid");
+            self = self.with_group_rule(regex, group);
+        }
+        self
+    }
+
+    /// Adds a rule masking every whole match of `regex`.
+    #[must_use]
+    pub fn with_rule(self, regex: Regex) -> Self {
+        self.with_group_rule(regex, 0)
+    }
+
+    /// Adds a rule masking only capture group `group` of each match.
+    ///
+    /// Matches where the group did not participate are skipped.
+    #[must_use]
+    pub fn with_group_rule(mut self, regex: Regex, group: usize) -> Self {
+        self.rules.push(RedactRule { regex, group });
+        self
+    }
+
+    /// Sets the character that replaces each masked character.
+    #[must_use]
+    pub fn with_mask(mut self, mask: char) -> Self {
+        self.mask = mask;
+        self
+    }
+
+    /// Returns `true` if no rules are registered.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Parses one line of ANSI input and redacts it.
+    #[must_use]
+    pub fn redact_ansi(&self, input: &str) -> AnsiText {
+        let mut text = AnsiText::new();
+        text.push_ansi(input);
+        self.redact(&text).unwrap_or(text)
+    }
+
+    /// Returns `text` with all matches masked, or `None` if nothing matched.
+    ///
+    /// Rules see the whole text at once, so a secret split by an escape
+    /// sequence is still found. Pass one line at a time.
+    #[must_use]
+    pub fn redact(&self, text: &AnsiText) -> Option<AnsiText> {
+        profile_scope!("egui_sgr::redact");
+        let mut masked = vec![false; text.text.len()];
+        let mut found = false;
+        for rule in &self.rules {
+            for captures in rule.regex.captures_iter(&text.text) {
+                if let Some(group) = captures.get(rule.group)
+                    && !group.is_empty()
+                {
+                    masked[group.range()].fill(true);
+                    found = true;
+                }
+            }
+        }
+        if !found {
+            return None;
+        }
+
+        let mut redacted = AnsiText::new();
+        let mut buffer = [0; 4];
+        let mask = self.mask.encode_utf8(&mut buffer);
+        for run in &text.runs {
+            let run_text = &text.text[run.range.clone()];
+            for (offset, c) in run_text.char_indices() {
+                if masked[run.range.start + offset] {
+                    redacted.push_str(mask, run.style);
+                } else {
+                    redacted.push_str(c.encode_utf8(&mut [0; 4]), run.style);
+                }
+            }
+        }
+        Some(redacted)
+    }
+
+    /// Masks secrets in every line of `document` and returns the number of
+    /// lines changed.
+    ///
+    /// A changed line's [`AnsiLine::raw`](crate::AnsiLine::raw) is replaced
+    /// by the re-emitted redacted text, so the secret does not survive in the
+    /// raw bytes either. Overlays on changed lines keep their byte ranges,
+    /// which shift if the mask and the masked text differ in UTF-8 length.
+    ///
+    /// Run it again after appending: a secret still arriving on the open last
+    /// line is only found once the line is complete.
+    pub fn redact_document<M: Default>(&self, document: &mut AnsiDocument<M>) -> usize {
+        let mut changed = 0;
+        for index in 0..document.line_count() {
+            let Some(line) = document.line_mut(index) else {
+                continue;
+            };
+            if let Some(text) = self.redact(&line.text) {
+                line.raw = text.to_ansi().into_bytes();
+                line.text = text;
+                changed += 1;
+            }
+        }
+        changed
+    }
+}
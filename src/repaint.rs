@@ -0,0 +1,23 @@
+/// Optional egui repaint request made after new output is buffered.
+///
+/// Without the `egui` feature this is empty and requesting a repaint does
+/// nothing, so the buffer sinks work unchanged in headless builds.
+#[derive(Clone, Default)]
+pub(crate) struct Repaint {
+    #[cfg(feature = "egui")]
+    ctx: Option<egui::Context>,
+}
+
+impl Repaint {
+    #[cfg(feature = "egui")]
+    pub(crate) fn new(ctx: egui::Context) -> Self {
+        Self { ctx: Some(ctx) }
+    }
+
+    pub(crate) fn request(&self) {
+        #[cfg(feature = "egui")]
+        if let Some(ctx) = &self.ctx {
+            ctx.request_repaint();
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use crate::{AnsiColor, AnsiIntensity, AnsiStyle, EguiAnsiTheme, UnderlineStyle};
+use ecolor::Color32;
+
+/// ANSI style with colors resolved through a theme.
+///
+/// This is the single color pipeline shared by `LayoutJob` rendering and the
+/// text exporters, so every output agrees on reverse video, hidden and faint
+/// text, and bold-as-bright handling.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct ResolvedStyle {
+    pub(crate) foreground: Color32,
+    pub(crate) background: Option<Color32>,
+    pub(crate) underline: Option<Color32>,
+    pub(crate) underline_style: UnderlineStyle,
+    pub(crate) bold: bool,
+    pub(crate) italic: bool,
+    pub(crate) strikethrough: bool,
+}
+
+impl ResolvedStyle {
+    /// Flattens translucent colors over `backdrop`, for formats without alpha.
+    pub(crate) fn opaque(self, backdrop: Color32) -> Self {
+        let flatten = |color: Color32| backdrop.blend(color);
+        Self {
+            foreground: flatten(self.foreground),
+            background: self.background.map(flatten),
+            underline: self.underline.map(flatten),
+            ..self
+        }
+    }
+}
+
+pub(crate) fn resolve_style(style: &AnsiStyle, theme: &EguiAnsiTheme) -> ResolvedStyle {
+    let colors = effective_colors(style, theme);
+    let underline = (style.underline != UnderlineStyle::None).then(|| {
+        style
+            .underline_color
+            .map(|color| resolve_color(color, theme))
+            .unwrap_or(colors.foreground)
+    });
+
+    ResolvedStyle {
+        foreground: colors.foreground,
+        background: colors.background,
+        underline,
+        underline_style: style.underline,
+        bold: style.intensity == AnsiIntensity::Bold,
+        italic: style.italic,
+        strikethrough: style.strikethrough,
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct EffectiveColors {
+    foreground: Color32,
+    background: Option<Color32>,
+}
+
+fn effective_colors(style: &AnsiStyle, theme: &EguiAnsiTheme) -> EffectiveColors {
+    let mut foreground = foreground_color(style, theme);
+    let mut background = background_color(style, theme);
+
+    if style.reverse {
+        let original_foreground = foreground;
+        foreground = background.unwrap_or(theme.default_background);
+        background = Some(original_foreground);
+    }
+
+    if style.hidden {
+        foreground = Color32::TRANSPARENT;
+    } else if style.intensity == AnsiIntensity::Faint {
+        foreground = with_scaled_alpha(foreground, theme.faint_opacity);
+    }
+
+    EffectiveColors {
+        foreground,
+        background,
+    }
+}
+
+fn foreground_color(style: &AnsiStyle, theme: &EguiAnsiTheme) -> Color32 {
+    match style.foreground {
+        AnsiColor::Indexed(index)
+            if theme.bold_is_bright && style.intensity == AnsiIntensity::Bold && index < 8 =>
+        {
+            theme.palette[(index + 8) as usize]
+        }
+        color => resolve_color_or_default(color, theme.default_foreground, theme),
+    }
+}
+
+fn background_color(style: &AnsiStyle, theme: &EguiAnsiTheme) -> Option<Color32> {
+    match style.background {
+        AnsiColor::Default => None,
+        color => Some(resolve_color(color, theme)),
+    }
+}
+
+fn resolve_color_or_default(
+    color: AnsiColor,
+    default_color: Color32,
+    theme: &EguiAnsiTheme,
+) -> Color32 {
+    match color {
+        AnsiColor::Default => default_color,
+        color => resolve_color(color, theme),
+    }
+}
+
+fn resolve_color(color: AnsiColor, theme: &EguiAnsiTheme) -> Color32 {
+    match color {
+        AnsiColor::Default => theme.default_foreground,
+        AnsiColor::Indexed(index) => theme.palette[index as usize],
+        AnsiColor::Rgb(r, g, b) => Color32::from_rgb(r, g, b),
+    }
+}
+
+fn with_scaled_alpha(color: Color32, opacity: f32) -> Color32 {
+    let alpha = ((color.a() as f32) * opacity.clamp(0.0, 1.0)).round() as u8;
+    Color32::from_rgba_unmultiplied(color.r(), color.g(), color.b(), alpha)
+}
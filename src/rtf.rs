@@ -0,0 +1,118 @@
+use crate::resolve::resolve_style;
+use crate::{AnsiSpan, AnsiStyle, EguiAnsiTheme, ansi_to_text};
+use ecolor::Color32;
+use std::fmt::Write;
+
+/// Converts ANSI text to an RTF document.
+///
+/// The color table is built from the colors the theme resolves for the text,
+/// so colored logs paste into word processors and email clients that accept
+/// RTF. Unstyled text uses the theme's default colors.
+#[must_use]
+pub fn ansi_to_rtf(input: &str, theme: &EguiAnsiTheme) -> String {
+    let text = ansi_to_text(input);
+    runs_to_rtf(text.iter(), theme)
+}
+
+/// Converts parsed spans to an RTF document.
+#[must_use]
+pub fn spans_to_rtf(spans: &[AnsiSpan], theme: &EguiAnsiTheme) -> String {
+    runs_to_rtf(
+        spans.iter().map(|span| (span.text.as_str(), span.style)),
+        theme,
+    )
+}
+
+fn runs_to_rtf<'a>(
+    runs: impl Iterator<Item = (&'a str, AnsiStyle)>,
+    theme: &EguiAnsiTheme,
+) -> String {
+    let backdrop = theme.default_background;
+    let mut colors = ColorTable::default();
+    let mut body = String::new();
+
+    for (text, style) in runs {
+        if text.is_empty() {
+            continue;
+        }
+
+        let resolved = resolve_style(&style, theme).opaque(backdrop);
+        let foreground = colors.index(resolved.foreground);
+        let background = colors.index(resolved.background.unwrap_or(backdrop));
+
+        let _ = write!(body, "{{\\cf{foreground}\\highlight{background}");
+        if resolved.bold {
+            body.push_str("\\b");
+        }
+        if resolved.italic {
+            body.push_str("\\i");
+        }
+        if resolved.underline.is_some() {
+            body.push_str("\\ul");
+        }
+        if resolved.strikethrough {
+            body.push_str("\\strike");
+        }
+        body.push(' ');
+        push_escaped(&mut body, text);
+        body.push('}');
+    }
+
+    let mut rtf = String::from("{\\rtf1\\ansi\\deff0{\\fonttbl{\\f0\\fmodern Courier New;}}");
+    rtf.push_str("{\\colortbl ;");
+    for color in &colors.colors {
+        let _ = write!(
+            rtf,
+            "\\red{}\\green{}\\blue{};",
+            color.r(),
+            color.g(),
+            color.b()
+        );
+    }
+    rtf.push_str("}\\f0 ");
+    rtf.push_str(&body);
+    rtf.push('}');
+    rtf
+}
+
+#[derive(Default)]
+struct ColorTable {
+    colors: Vec<Color32>,
+}
+
+impl ColorTable {
+    /// Returns the 1-based table index of `color`; index 0 is the auto color.
+    fn index(&mut self, color: Color32) -> usize {
+        let position = self
+            .colors
+            .iter()
+            .position(|known| *known == color)
+            .unwrap_or_else(|| {
+                self.colors.push(color);
+                self.colors.len() - 1
+            });
+        position + 1
+    }
+}
+
+fn push_escaped(rtf: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '\\' | '{' | '}' => {
+                rtf.push('\\');
+                rtf.push(c);
+            }
+            '\n' => rtf.push_str("\\line "),
+            '\t' => rtf.push_str("\\tab "),
+            '\r' => {}
+            c if c.is_ascii() => rtf.push(c),
+            c => {
+                let mut units = [0; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    // RTF takes UTF-16 code units as signed 16-bit integers.
+                    let _ = write!(rtf, "\\u{}?", *unit as i16);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use crate::columns::graphemes;
+#[cfg(feature = "regex")]
+use crate::{AnsiDocument, OverlayLayer, OverlayStyle};
+#[cfg(feature = "regex")]
+use regex::Regex;
+use std::ops::Range;
+
+/// Match found by [`AnsiDocument::find`](crate::AnsiDocument::find).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AnsiMatch {
+    /// Absolute line number, as counted by
+    /// [`AnsiDocument::first_line_number`](crate::AnsiDocument::first_line_number).
+    pub line: u64,
+    /// Byte range into the line's visible text.
+    pub byte_range: Range<usize>,
+    /// Display columns covered by the match, counted like
+    /// [`visible_width`](crate::visible_width).
+    pub col_range: Range<usize>,
+}
+
+/// Pattern accepted by [`AnsiDocument::find`](crate::AnsiDocument::find).
+///
+/// Implemented for plain strings, which match literally, and with the
+/// `regex` feature for [`regex::Regex`].
+pub trait SearchPattern {
+    /// Returns the byte ranges of the non-overlapping matches in `haystack`,
+    /// in order.
+    fn find_ranges(&self, haystack: &str) -> Vec<Range<usize>>;
+}
+
+impl SearchPattern for str {
+    fn find_ranges(&self, haystack: &str) -> Vec<Range<usize>> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+        haystack
+            .match_indices(self)
+            .map(|(start, found)| start..start + found.len())
+            .collect()
+    }
+}
+
+impl SearchPattern for String {
+    fn find_ranges(&self, haystack: &str) -> Vec<Range<usize>> {
+        self.as_str().find_ranges(haystack)
+    }
+}
+
+#[cfg(feature = "regex")]
+impl SearchPattern for Regex {
+    fn find_ranges(&self, haystack: &str) -> Vec<Range<usize>> {
+        self.find_iter(haystack)
+            .filter(|found| !found.is_empty())
+            .map(|found| found.range())
+            .collect()
+    }
+}
+
+impl<P: SearchPattern + ?Sized> SearchPattern for &P {
+    fn find_ranges(&self, haystack: &str) -> Vec<Range<usize>> {
+        (**self).find_ranges(haystack)
+    }
+}
+
+/// Pairs each byte range of `text` with the display columns it covers.
+///
+/// `ranges` must be ordered and non-overlapping, as patterns return them.
+pub(crate) fn with_columns(
+    text: &str,
+    ranges: Vec<Range<usize>>,
+) -> Vec<(Range<usize>, Range<usize>)> {
+    let mut clusters = graphemes(text).peekable();
+    let mut column = 0;
+    let mut column_at = |offset: usize| {
+        while let Some(&(index, _, width)) = clusters.peek() {
+            if index >= offset {
+                break;
+            }
+            column += width;
+            clusters.next();
+        }
+        column
+    };
+
+    ranges
+        .into_iter()
+        .map(|range| {
+            let start = column_at(range.start);
+            let end = column_at(range.end);
+            (range, start..end)
+        })
+        .collect()
+}
+
+/// Highlights every match of `regex` in the visible text of `document`.
+///
+/// Matching runs on each line's text without escape sequences, so a match
+/// may span several style runs. Previous overlays on
+/// [`OverlayLayer::SEARCH`] are replaced; clear that layer to remove the
+/// highlights. Returns the number of matches.
+#[cfg(feature = "regex")]
+pub fn highlight_matches<M: Default>(
+    document: &mut AnsiDocument<M>,
+    regex: &Regex,
+    style: OverlayStyle,
+) -> usize {
+    profile_scope!("egui_sgr::highlight_matches");
+    document.clear_overlays(OverlayLayer::SEARCH);
+
+    let matches = document.find(regex);
+    let count = matches.len();
+    for found in matches {
+        document.add_overlay(OverlayLayer::SEARCH, found.line, found.byte_range, style);
+    }
+    count
+}
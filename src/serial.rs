@@ -0,0 +1,161 @@
+use crate::SharedAnsiDocument;
+use crate::repaint::Repaint;
+use serialport::{SerialPort, SerialPortBuilder};
+use std::io::{self, Read};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Serial port monitor that parses device output into a [`SharedAnsiDocument`].
+///
+/// Bytes are pushed through the streaming parser as they arrive, so escape
+/// and UTF-8 sequences split across reads keep their style and characters.
+/// The document keeps the last 10,000 lines unless [`Self::with_max_lines`]
+/// says otherwise.
+pub struct AnsiSerialMonitor {
+    builder: SerialPortBuilder,
+    document: SharedAnsiDocument,
+    repaint: Repaint,
+}
+
+impl AnsiSerialMonitor {
+    /// Creates a monitor for the device at `path`, such as `/dev/ttyUSB0` or
+    /// `COM3`, using 8N1 framing.
+    #[must_use]
+    pub fn new<'a>(path: impl Into<std::borrow::Cow<'a, str>>, baud_rate: u32) -> Self {
+        Self::from_builder(serialport::new(path, baud_rate).timeout(Duration::from_millis(100)))
+    }
+
+    /// Creates a monitor from a fully configured `serialport` builder.
+    ///
+    /// Give it a short, non-zero timeout: it bounds how long dropping an
+    /// [`AnsiSerialConnection`] waits for the reader thread.
+    #[must_use]
+    pub fn from_builder(builder: SerialPortBuilder) -> Self {
+        Self {
+            builder,
+            document: SharedAnsiDocument::for_sink(),
+            repaint: Repaint::default(),
+        }
+    }
+
+    /// Sets the baud rate.
+    #[must_use]
+    pub fn with_baud_rate(mut self, baud_rate: u32) -> Self {
+        self.builder = self.builder.baud_rate(baud_rate);
+        self
+    }
+
+    /// Keeps at most `max_lines` lines of device output, evicting the
+    /// oldest.
+    #[must_use]
+    pub fn with_max_lines(self, max_lines: usize) -> Self {
+        self.document.lock().set_max_lines(Some(max_lines));
+        self
+    }
+
+    /// Appends to `document` instead of a new one, keeping its retention
+    /// cap.
+    #[must_use]
+    pub fn with_document(mut self, document: SharedAnsiDocument) -> Self {
+        self.document = document;
+        self
+    }
+
+    /// Requests an egui repaint after each read.
+    #[cfg(feature = "egui")]
+    #[must_use]
+    pub fn with_repaint(mut self, ctx: egui::Context) -> Self {
+        self.repaint = Repaint::new(ctx);
+        self
+    }
+
+    /// Returns the shared document that device output is parsed into.
+    #[must_use]
+    pub fn document(&self) -> SharedAnsiDocument {
+        self.document.clone()
+    }
+
+    /// Opens the port and starts reading on a background thread.
+    pub fn open(self) -> serialport::Result<AnsiSerialConnection> {
+        let port = self.builder.open()?;
+        let mut reader = port.try_clone()?;
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let document = self.document.clone();
+        let repaint = self.repaint;
+        let stopped = Arc::clone(&stop);
+        let handle = thread::Builder::new()
+            .name("egui_sgr serial".to_owned())
+            .spawn(move || {
+                let mut chunk = [0; 4096];
+                while !stopped.load(Ordering::Relaxed) {
+                    match reader.read(&mut chunk) {
+                        Ok(0) => break,
+                        Ok(len) => {
+                            document.lock().append(&chunk[..len]);
+                            repaint.request();
+                        }
+                        Err(error)
+                            if matches!(
+                                error.kind(),
+                                io::ErrorKind::TimedOut | io::ErrorKind::Interrupted
+                            ) => {}
+                        Err(_) => break,
+                    }
+                }
+            })?;
+
+        Ok(AnsiSerialConnection {
+            port,
+            stop,
+            handle: Some(handle),
+            document: self.document,
+        })
+    }
+}
+
+/// Open serial port started by [`AnsiSerialMonitor::open`].
+///
+/// Writes go to the device, which makes this usable as an interactive serial
+/// console. Dropping the connection stops the reader thread.
+pub struct AnsiSerialConnection {
+    port: Box<dyn SerialPort>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+    document: SharedAnsiDocument,
+}
+
+impl AnsiSerialConnection {
+    /// Returns the shared document that device output is parsed into.
+    #[must_use]
+    pub fn document(&self) -> SharedAnsiDocument {
+        self.document.clone()
+    }
+
+    /// Returns the underlying port, for changing settings such as the baud
+    /// rate or control lines while connected.
+    pub fn port_mut(&mut self) -> &mut dyn SerialPort {
+        &mut *self.port
+    }
+}
+
+impl io::Write for AnsiSerialConnection {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.port.write(bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.port.flush()
+    }
+}
+
+impl Drop for AnsiSerialConnection {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
@@ -4,14 +4,16 @@ use vte::Params;
 const MAX_SGR_PARAMS: usize = 32;
 const EMPTY_PARAM: &[u16] = &[];
 
-pub(crate) fn apply_sgr(params: &Params, style: &mut AnsiStyle) {
+/// Applies an SGR sequence and returns the number of unsupported codes.
+pub(crate) fn apply_sgr(params: &Params, style: &mut AnsiStyle) -> usize {
     let params = SgrParams::new(params);
 
     if params.is_empty() {
         style.reset();
-        return;
+        return 0;
     }
 
+    let mut unsupported = 0;
     let mut i = 0;
     while i < params.len() {
         let param = params.get(i);
@@ -27,7 +29,7 @@ pub(crate) fn apply_sgr(params: &Params, style: &mut AnsiStyle) {
                         apply_extended_color(style, code, color);
                     }
                 }
-                _ => apply_simple_sgr(style, code),
+                _ => unsupported += usize::from(!apply_simple_sgr(style, code)),
             }
             i += 1;
             continue;
@@ -39,14 +41,16 @@ pub(crate) fn apply_sgr(params: &Params, style: &mut AnsiStyle) {
                 i += consumed.max(1);
             }
             _ => {
-                apply_simple_sgr(style, code);
+                unsupported += usize::from(!apply_simple_sgr(style, code));
                 i += 1;
             }
         }
     }
+    unsupported
 }
 
-fn apply_simple_sgr(style: &mut AnsiStyle, code: u16) {
+/// Applies a single-parameter SGR code and returns whether it is supported.
+fn apply_simple_sgr(style: &mut AnsiStyle, code: u16) -> bool {
     match code {
         0 => style.reset(),
         1 => style.intensity = AnsiIntensity::Bold,
@@ -70,8 +74,9 @@ fn apply_simple_sgr(style: &mut AnsiStyle, code: u16) {
         39 => style.foreground = AnsiColor::Default,
         49 => style.background = AnsiColor::Default,
         59 => style.underline_color = None,
-        _ => {}
+        _ => return false,
     }
+    true
 }
 
 fn apply_semicolon_extended_color(
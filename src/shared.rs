@@ -0,0 +1,111 @@
+use crate::AnsiDocument;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// Cloneable handle to one [`AnsiDocument`], for several views of the same
+/// log.
+///
+/// Clones share the document, so a full log view and a filtered panel, or
+/// a producer thread and the UI, see the same parsed lines without copying
+/// them. Each view remembers the [`version`](AnsiDocument::version) it last
+/// rendered and rebuilds its output only when the version moved on.
+///
+/// The document sits behind a [`Mutex`], since its parser backend and segment
+/// hook only need to be [`Send`]. Keep the lock short: render from the guard
+/// and drop it before doing anything slow.
+///
+/// ```rust
+/// use egui_sgr::{AnsiDocument, SharedAnsiDocument};
+///
+/// struct ErrorsPanel {
+///     document: SharedAnsiDocument,
+///     rendered: Option<u64>,
+///     errors: Vec<String>,
+/// }
+///
+/// impl ErrorsPanel {
+///     fn refresh(&mut self) {
+///         let document = self.document.lock();
+///         if self.rendered == Some(document.version()) {
+///             return;
+///         }
+///         self.rendered = Some(document.version());
+///         self.errors = document
+///             .lines()
+///             .filter(|line| line.text.text.contains("error"))
+///             .map(|line| line.text.text.clone())
+///             .collect();
+///     }
+/// }
+///
+/// let log = SharedAnsiDocument::new(AnsiDocument::new());
+/// let mut panel = ErrorsPanel { document: log.clone(), rendered: None, errors: Vec::new() };
+/// log.lock().append("ok\n\x1b[31merror: disk\x1b[0m\n");
+/// panel.refresh();
+/// assert_eq!(panel.errors, ["error: disk"]);
+/// ```
+pub struct SharedAnsiDocument<M = ()> {
+    inner: Arc<Mutex<AnsiDocument<M>>>,
+}
+
+impl<M: Default> SharedAnsiDocument<M> {
+    /// Shares `document`.
+    #[must_use]
+    pub fn new(document: AnsiDocument<M>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(document)),
+        }
+    }
+
+    /// Locks the document for reading or appending.
+    ///
+    /// A panic while another handle held the lock does not poison the
+    /// document for the others; the lines appended so far stay usable.
+    pub fn lock(&self) -> MutexGuard<'_, AnsiDocument<M>> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the document's current [`version`](AnsiDocument::version).
+    #[must_use]
+    pub fn version(&self) -> u64 {
+        self.lock().version()
+    }
+
+    /// Returns `true` if both handles share the same document.
+    #[must_use]
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+/// Lines a log sink keeps unless told otherwise, the same as the default
+/// [`TerminalProfile`](crate::TerminalProfile) scrollback.
+pub(crate) const SINK_SCROLLBACK_LINES: usize = 10_000;
+
+impl SharedAnsiDocument {
+    /// Creates the document a long-running sink appends to, capped at
+    /// [`SINK_SCROLLBACK_LINES`] so it does not grow for as long as the app
+    /// runs.
+    pub(crate) fn for_sink() -> Self {
+        Self::new(AnsiDocument::new().with_max_lines(SINK_SCROLLBACK_LINES))
+    }
+}
+
+impl<M: Default> Default for SharedAnsiDocument<M> {
+    fn default() -> Self {
+        Self::new(AnsiDocument::new())
+    }
+}
+
+impl<M> Clone for SharedAnsiDocument<M> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<M: Default> From<AnsiDocument<M>> for SharedAnsiDocument<M> {
+    fn from(document: AnsiDocument<M>) -> Self {
+        Self::new(document)
+    }
+}
@@ -0,0 +1,120 @@
+use crate::{
+    AnsiColor, AnsiDocument, AnsiIntensity, AnsiSpan, AnsiStyle, AnsiText, UnderlineStyle,
+};
+use std::fmt::Write as _;
+
+/// Renders ANSI input as annotated text for snapshot tests.
+///
+/// See [`snapshot_text`] for the format.
+#[must_use]
+pub fn snapshot_ansi(input: &str) -> String {
+    snapshot_text(&crate::ansi_to_text(input))
+}
+
+/// Renders spans as annotated text for snapshot tests.
+///
+/// See [`snapshot_text`] for the format.
+#[must_use]
+pub fn snapshot_spans(spans: &[AnsiSpan]) -> String {
+    snapshot_runs(spans.iter().map(|span| (span.text.as_str(), span.style)))
+}
+
+/// Renders the retained lines of a document as annotated text for snapshot
+/// tests, one output line per document line.
+///
+/// See [`snapshot_text`] for the format.
+#[must_use]
+pub fn snapshot_document<M: Default>(document: &AnsiDocument<M>) -> String {
+    snapshot_text(&document.to_text())
+}
+
+/// Renders styled text as deterministic, human-readable annotated text.
+///
+/// A marker such as `{fg=1,bg=#102030,bold,underline=curly}` precedes each
+/// style change and `{}` marks a return to the default style. Indexed colors
+/// are written as their palette number and RGB colors as hex, so snapshots
+/// do not depend on a theme. Braces in the text are doubled; everything else
+/// is written as is.
+///
+/// ```rust
+/// use egui_sgr::snapshot_ansi;
+///
+/// assert_eq!(
+///     snapshot_ansi("\x1b[1;31merror\x1b[0m: {x}"),
+///     "{fg=1,bold}error{}: {{x}}"
+/// );
+/// ```
+#[must_use]
+pub fn snapshot_text(text: &AnsiText) -> String {
+    snapshot_runs(text.iter())
+}
+
+fn snapshot_runs<'a>(runs: impl Iterator<Item = (&'a str, AnsiStyle)>) -> String {
+    let mut output = String::new();
+    let mut current = AnsiStyle::default();
+
+    for (text, style) in runs {
+        if text.is_empty() {
+            continue;
+        }
+        if style != current {
+            write_marker(&mut output, &style);
+            current = style;
+        }
+        for c in text.chars() {
+            match c {
+                '{' => output.push_str("{{"),
+                '}' => output.push_str("}}"),
+                c => output.push(c),
+            }
+        }
+    }
+    output
+}
+
+fn write_marker(output: &mut String, style: &AnsiStyle) {
+    let mut parts = Vec::new();
+    if style.foreground != AnsiColor::Default {
+        parts.push(format!("fg={}", color_label(style.foreground)));
+    }
+    if style.background != AnsiColor::Default {
+        parts.push(format!("bg={}", color_label(style.background)));
+    }
+    match style.intensity {
+        AnsiIntensity::Normal => {}
+        AnsiIntensity::Bold => parts.push("bold".to_owned()),
+        AnsiIntensity::Faint => parts.push("faint".to_owned()),
+    }
+    if style.italic {
+        parts.push("italic".to_owned());
+    }
+    match style.underline {
+        UnderlineStyle::None => {}
+        UnderlineStyle::Single => parts.push("underline".to_owned()),
+        UnderlineStyle::Double => parts.push("underline=double".to_owned()),
+        UnderlineStyle::Curly => parts.push("underline=curly".to_owned()),
+        UnderlineStyle::Dotted => parts.push("underline=dotted".to_owned()),
+        UnderlineStyle::Dashed => parts.push("underline=dashed".to_owned()),
+    }
+    if let Some(color) = style.underline_color {
+        parts.push(format!("ul={}", color_label(color)));
+    }
+    if style.reverse {
+        parts.push("reverse".to_owned());
+    }
+    if style.hidden {
+        parts.push("hidden".to_owned());
+    }
+    if style.strikethrough {
+        parts.push("strike".to_owned());
+    }
+    let _ = write!(output, "{{{}}}", parts.join(","));
+}
+
+fn color_label(color: AnsiColor) -> String {
+    match color {
+        AnsiColor::Default => "default".to_owned(),
+        AnsiColor::Indexed(index) => index.to_string(),
+        AnsiColor::Rgb(r, g, b) => format!("#{r:02x}{g:02x}{b:02x}"),
+    }
+}
@@ -0,0 +1,160 @@
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+/// Input accepted by the parsing entry points.
+///
+/// Implemented for references to text and bytes, such as `&str`, `&[u8]`,
+/// `&String`, `&Arc<str>`, `&Arc<[u8]>`, and byte string literals, which
+/// are parsed without copying, and for owned `String`, `Vec<u8>`,
+/// `Box<str>`, `Arc<str>`, `Arc<[u8]>`, and `Cow` values. An owned `Arc` is
+/// copied, since its bytes may be shared; pass a reference to borrow them.
+/// Non-UTF-8 bytes become U+FFFD like in
+/// [`ansi_bytes_to_spans`](crate::ansi_bytes_to_spans).
+///
+/// Readers such as files, pipes, and sockets are not sources, because
+/// reading can fail partway. Stream them into a document with
+/// [`AnsiDocument::append_reader`](crate::AnsiDocument::append_reader),
+/// which keeps the lines read before an error and returns it.
+///
+/// ```rust
+/// use egui_sgr::{AnsiDocument, ansi_to_spans};
+/// use std::sync::Arc;
+///
+/// let shared: Arc<str> = Arc::from("\x1b[31mred\n");
+/// let mut document = AnsiDocument::<()>::new();
+/// document.append(&shared);
+/// document.append(b"\x1b[32mgreen\n");
+/// document.append(String::from("plain\n"));
+/// document.append_reader(&b"\x1b[1mread\n"[..]).unwrap();
+/// assert_eq!(document.line_count(), 4);
+/// assert_eq!(ansi_to_spans(b"\x1b[1mbold".to_vec())[0].text, "bold");
+/// ```
+pub trait IntoAnsiSource<'a> {
+    /// Returns the input as bytes, borrowing them when possible.
+    fn into_ansi_bytes(self) -> Cow<'a, [u8]>;
+
+    /// Returns the input as text when its type guarantees UTF-8, so parsing
+    /// can skip validating it again, and as bytes otherwise.
+    ///
+    /// The default returns [`Self::into_ansi_bytes`].
+    fn into_ansi_str(self) -> Result<Cow<'a, str>, Cow<'a, [u8]>>
+    where
+        Self: Sized,
+    {
+        Err(self.into_ansi_bytes())
+    }
+}
+
+/// Implements the trait for references to text and byte containers, which
+/// are borrowed as they are. Listed rather than blanket-implemented so that
+/// smart pointers to `str` work as they did through deref coercion.
+macro_rules! borrowed_sources {
+    (text: $($source:ty),* $(,)?) => {
+        $(
+            impl<'a> IntoAnsiSource<'a> for &'a $source {
+                fn into_ansi_bytes(self) -> Cow<'a, [u8]> {
+                    Cow::Borrowed(self.as_bytes())
+                }
+
+                fn into_ansi_str(self) -> Result<Cow<'a, str>, Cow<'a, [u8]>> {
+                    Ok(Cow::Borrowed(self))
+                }
+            }
+        )*
+    };
+    (bytes: $($source:ty),* $(,)?) => {
+        $(
+            impl<'a> IntoAnsiSource<'a> for &'a $source {
+                fn into_ansi_bytes(self) -> Cow<'a, [u8]> {
+                    Cow::Borrowed(self)
+                }
+            }
+        )*
+    };
+}
+
+borrowed_sources!(text: str, String, Box<str>, Arc<str>, Cow<'_, str>);
+borrowed_sources!(bytes: [u8], Vec<u8>, Arc<[u8]>, Cow<'_, [u8]>);
+
+impl<'a, 'b: 'a, S: ?Sized> IntoAnsiSource<'a> for &'a &'b S
+where
+    &'b S: IntoAnsiSource<'b>,
+{
+    fn into_ansi_bytes(self) -> Cow<'a, [u8]> {
+        (*self).into_ansi_bytes()
+    }
+
+    fn into_ansi_str(self) -> Result<Cow<'a, str>, Cow<'a, [u8]>> {
+        (*self).into_ansi_str()
+    }
+}
+
+impl<'a, const N: usize> IntoAnsiSource<'a> for &'a [u8; N] {
+    fn into_ansi_bytes(self) -> Cow<'a, [u8]> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl IntoAnsiSource<'_> for String {
+    fn into_ansi_bytes(self) -> Cow<'static, [u8]> {
+        Cow::Owned(self.into_bytes())
+    }
+
+    fn into_ansi_str(self) -> Result<Cow<'static, str>, Cow<'static, [u8]>> {
+        Ok(Cow::Owned(self))
+    }
+}
+
+impl IntoAnsiSource<'_> for Vec<u8> {
+    fn into_ansi_bytes(self) -> Cow<'static, [u8]> {
+        Cow::Owned(self)
+    }
+}
+
+impl IntoAnsiSource<'_> for Box<str> {
+    fn into_ansi_bytes(self) -> Cow<'static, [u8]> {
+        Cow::Owned(self.into_boxed_bytes().into_vec())
+    }
+
+    fn into_ansi_str(self) -> Result<Cow<'static, str>, Cow<'static, [u8]>> {
+        Ok(Cow::Owned(self.into_string()))
+    }
+}
+
+impl IntoAnsiSource<'_> for Arc<str> {
+    fn into_ansi_bytes(self) -> Cow<'static, [u8]> {
+        Cow::Owned(self.as_bytes().to_vec())
+    }
+
+    fn into_ansi_str(self) -> Result<Cow<'static, str>, Cow<'static, [u8]>> {
+        Ok(Cow::Owned(String::from(&*self)))
+    }
+}
+
+impl IntoAnsiSource<'_> for Arc<[u8]> {
+    fn into_ansi_bytes(self) -> Cow<'static, [u8]> {
+        Cow::Owned(self.to_vec())
+    }
+}
+
+impl<'a> IntoAnsiSource<'a> for Cow<'a, str> {
+    fn into_ansi_bytes(self) -> Cow<'a, [u8]> {
+        match self {
+            Cow::Borrowed(text) => Cow::Borrowed(text.as_bytes()),
+            Cow::Owned(text) => Cow::Owned(text.into_bytes()),
+        }
+    }
+
+    fn into_ansi_str(self) -> Result<Cow<'a, str>, Cow<'a, [u8]>> {
+        Ok(self)
+    }
+}
+
+impl<'a> IntoAnsiSource<'a> for Cow<'a, [u8]> {
+    fn into_ansi_bytes(self) -> Cow<'a, [u8]> {
+        self
+    }
+}
@@ -0,0 +1,75 @@
+use crate::{AnsiColor, AnsiStyle};
+use alloc::collections::BTreeSet;
+
+/// Counters describing the input seen by a streaming parser.
+///
+/// Counts are cumulative across chunks, [`finish`](crate::AnsiStreamParser::finish),
+/// and [`reset`](crate::AnsiStreamParser::reset); clear them with
+/// [`reset_stats`](crate::AnsiStreamParser::reset_stats). Sequences are
+/// counted when they complete, so one split across chunks counts once.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseStats {
+    /// Raw bytes pushed.
+    pub bytes: u64,
+    /// Line feeds seen.
+    pub lines: u64,
+    /// SGR (`CSI ... m`) sequences.
+    pub sgr_sequences: u64,
+    /// CSI sequences other than SGR, such as cursor movement.
+    pub other_csi_sequences: u64,
+    /// OSC sequences, such as titles and hyperlinks.
+    pub osc_sequences: u64,
+    /// DCS sequences.
+    pub dcs_sequences: u64,
+    /// Other escape sequences, such as `ESC 7`.
+    pub esc_sequences: u64,
+    /// C0 and C1 controls other than line feed, carriage return, and tab.
+    pub control_chars: u64,
+    /// SGR codes that are not supported and were ignored, such as blink.
+    pub unsupported_sgr_codes: u64,
+    /// Colors set by SGR sequences, for foreground, background, and
+    /// underline alike. Only collected when enabled with
+    /// [`with_color_stats`](crate::AnsiStreamParser::with_color_stats),
+    /// since 24-bit output can set a new color on every cell.
+    pub colors: BTreeSet<AnsiColor>,
+}
+
+impl ParseStats {
+    /// Stats with every counter at zero, like [`Default::default`].
+    pub(crate) const EMPTY: Self = Self {
+        bytes: 0,
+        lines: 0,
+        sgr_sequences: 0,
+        other_csi_sequences: 0,
+        osc_sequences: 0,
+        dcs_sequences: 0,
+        esc_sequences: 0,
+        control_chars: 0,
+        unsupported_sgr_codes: 0,
+        colors: BTreeSet::new(),
+    };
+
+    /// Returns the total number of escape sequences of all kinds.
+    #[must_use]
+    pub fn escape_sequences(&self) -> u64 {
+        self.sgr_sequences
+            + self.other_csi_sequences
+            + self.osc_sequences
+            + self.dcs_sequences
+            + self.esc_sequences
+    }
+
+    pub(crate) fn record_sgr(&mut self, style: &AnsiStyle, unsupported: usize, colors: bool) {
+        self.sgr_sequences += 1;
+        self.unsupported_sgr_codes += unsupported as u64;
+        if !colors {
+            return;
+        }
+        let colors = [style.foreground, style.background];
+        for color in colors.into_iter().chain(style.underline_color) {
+            if color != AnsiColor::Default {
+                self.colors.insert(color);
+            }
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use crate::{AnsiSpan, ansi_to_spans};
+use std::fmt;
+
+/// Limits enforced by [`try_ansi_bytes_to_spans`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnsiLimits {
+    /// Maximum input length in bytes.
+    pub max_input_len: usize,
+    /// Maximum length of one escape sequence in bytes, including `ESC`.
+    pub max_sequence_len: usize,
+}
+
+impl Default for AnsiLimits {
+    fn default() -> Self {
+        Self {
+            max_input_len: 64 * 1024 * 1024,
+            max_sequence_len: 4096,
+        }
+    }
+}
+
+/// Reason strict parsing rejected an input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnsiError {
+    /// The input is not valid UTF-8.
+    InvalidUtf8 {
+        /// Byte offset of the first invalid byte.
+        offset: usize,
+    },
+    /// The input is longer than [`AnsiLimits::max_input_len`].
+    InputTooLong {
+        /// Input length in bytes.
+        len: usize,
+        /// Configured limit.
+        limit: usize,
+    },
+    /// An escape sequence is longer than [`AnsiLimits::max_sequence_len`].
+    SequenceTooLong {
+        /// Byte offset of the sequence's `ESC`.
+        offset: usize,
+        /// Configured limit.
+        limit: usize,
+    },
+    /// The input ends inside an escape sequence.
+    UnterminatedSequence {
+        /// Byte offset of the sequence's `ESC`.
+        offset: usize,
+    },
+}
+
+impl fmt::Display for AnsiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidUtf8 { offset } => write!(f, "invalid UTF-8 at byte {offset}"),
+            Self::InputTooLong { len, limit } => {
+                write!(f, "input of {len} bytes exceeds the limit of {limit}")
+            }
+            Self::SequenceTooLong { offset, limit } => write!(
+                f,
+                "escape sequence at byte {offset} exceeds the limit of {limit} bytes"
+            ),
+            Self::UnterminatedSequence { offset } => {
+                write!(f, "unterminated escape sequence at byte {offset}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AnsiError {}
+
+/// Parses ANSI text, rejecting input that the lenient parsers would repair.
+///
+/// See [`try_ansi_bytes_to_spans`].
+pub fn try_ansi_to_spans(input: &str, limits: &AnsiLimits) -> Result<Vec<AnsiSpan>, AnsiError> {
+    try_ansi_bytes_to_spans(input.as_bytes(), limits)
+}
+
+/// Parses ANSI bytes, rejecting input that the lenient parsers would repair.
+///
+/// [`ansi_bytes_to_spans`](crate::ansi_bytes_to_spans) replaces invalid UTF-8
+/// and silently drops a trailing partial escape. This returns an error with
+/// the byte offset instead, for applications that must refuse untrusted
+/// input rather than render it best-effort. Accepted input produces the same
+/// spans as the lenient parser.
+pub fn try_ansi_bytes_to_spans(
+    input: &[u8],
+    limits: &AnsiLimits,
+) -> Result<Vec<AnsiSpan>, AnsiError> {
+    if input.len() > limits.max_input_len {
+        return Err(AnsiError::InputTooLong {
+            len: input.len(),
+            limit: limits.max_input_len,
+        });
+    }
+
+    let text = std::str::from_utf8(input).map_err(|error| AnsiError::InvalidUtf8 {
+        offset: error.valid_up_to(),
+    })?;
+
+    let mut offset = 0;
+    while let Some(start) = memchr::memchr(0x1b, &input[offset..]).map(|index| offset + index) {
+        let end =
+            sequence_end(input, start).ok_or(AnsiError::UnterminatedSequence { offset: start })?;
+        if end - start > limits.max_sequence_len {
+            return Err(AnsiError::SequenceTooLong {
+                offset: start,
+                limit: limits.max_sequence_len,
+            });
+        }
+        offset = end;
+    }
+
+    Ok(ansi_to_spans(text))
+}
+
+/// Returns the offset just past the escape sequence starting at `start`.
+fn sequence_end(input: &[u8], start: usize) -> Option<usize> {
+    let body = start + 1;
+    match *input.get(body)? {
+        b'[' => find_after(input, body + 1, |byte| (0x40..=0x7e).contains(&byte)),
+        b']' | b'P' | b'X' | b'^' | b'_' => {
+            let mut index = body + 1;
+            loop {
+                match *input.get(index)? {
+                    0x07 if input[body] == b']' => return Some(index + 1),
+                    0x1b if input.get(index + 1) == Some(&b'\\') => return Some(index + 2),
+                    0x18 | 0x1a => return Some(index + 1),
+                    _ => index += 1,
+                }
+            }
+        }
+        _ => find_after(input, body, |byte| !(0x20..=0x2f).contains(&byte)),
+    }
+}
+
+/// Finds the end of a sequence at its final byte. CAN and SUB abort the
+/// sequence, and a new `ESC` starts another one.
+fn find_after(input: &[u8], from: usize, is_final: impl Fn(u8) -> bool) -> Option<usize> {
+    input[from..]
+        .iter()
+        .position(|&byte| is_final(byte) || matches!(byte, 0x18 | 0x1a | 0x1b))
+        .map(|index| {
+            let index = from + index;
+            if input[index] == 0x1b {
+                index
+            } else {
+                index + 1
+            }
+        })
+}
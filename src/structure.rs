@@ -0,0 +1,420 @@
+use crate::{AnsiColor, AnsiIntensity, AnsiLine, AnsiStyle, AnsiText};
+use std::ops::Range;
+
+/// Severity of a structured log line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LogLevel {
+    /// Very verbose diagnostics.
+    Trace,
+    /// Debugging output.
+    Debug,
+    /// Normal operation, including `notice`.
+    Info,
+    /// Something unexpected that was handled.
+    Warn,
+    /// A failure, including `fatal`, `critical`, and `panic`.
+    Error,
+}
+
+impl LogLevel {
+    /// Parses a level name such as `INFO`, `warning`, or `err`, ignoring
+    /// case.
+    #[must_use]
+    pub fn parse(name: &str) -> Option<Self> {
+        const NAMES: [(&str, LogLevel); 14] = [
+            ("trace", LogLevel::Trace),
+            ("debug", LogLevel::Debug),
+            ("dbg", LogLevel::Debug),
+            ("info", LogLevel::Info),
+            ("notice", LogLevel::Info),
+            ("warn", LogLevel::Warn),
+            ("warning", LogLevel::Warn),
+            ("error", LogLevel::Error),
+            ("err", LogLevel::Error),
+            ("fatal", LogLevel::Error),
+            ("critical", LogLevel::Error),
+            ("crit", LogLevel::Error),
+            ("panic", LogLevel::Error),
+            ("emerg", LogLevel::Error),
+        ];
+        NAMES
+            .iter()
+            .find(|(known, _)| known.eq_ignore_ascii_case(name))
+            .map(|&(_, level)| level)
+    }
+
+    /// Returns the style [`AnsiLogger`](crate::AnsiLogger) uses for the
+    /// level, for coloring a level column.
+    #[must_use]
+    pub fn style(self) -> AnsiStyle {
+        let (color, intensity) = match self {
+            Self::Error => (1, AnsiIntensity::Bold),
+            Self::Warn => (3, AnsiIntensity::Normal),
+            Self::Info => (2, AnsiIntensity::Normal),
+            Self::Debug => (4, AnsiIntensity::Normal),
+            Self::Trace => (6, AnsiIntensity::Normal),
+        };
+        AnsiStyle {
+            foreground: AnsiColor::Indexed(color),
+            intensity,
+            ..AnsiStyle::default()
+        }
+    }
+}
+
+/// Byte ranges of the fields found in a log line's visible text.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct LogFields {
+    /// Timestamp, such as `2024-05-01T12:00:00Z`.
+    pub timestamp: Option<Range<usize>>,
+    /// Level name, such as `INFO`.
+    pub level: Option<Range<usize>>,
+    /// Logger, module, or component name.
+    pub source: Option<Range<usize>>,
+    /// The rest of the line.
+    pub message: Range<usize>,
+}
+
+impl LogFields {
+    /// Parses the level field of `line`, the text the fields were found in.
+    #[must_use]
+    pub fn log_level(&self, line: &str) -> Option<LogLevel> {
+        self.level
+            .clone()
+            .and_then(|range| line.get(range))
+            .and_then(LogLevel::parse)
+    }
+
+    /// Splits `text` into one styled cell per field.
+    ///
+    /// Every cell keeps the styles of the text it was cut from, so colored
+    /// messages stay colored in a table.
+    #[must_use]
+    pub fn split(&self, text: &AnsiText) -> LogRow {
+        let cell = |range: &Option<Range<usize>>| {
+            range
+                .clone()
+                .map(|range| text.slice(range))
+                .unwrap_or_default()
+        };
+        LogRow {
+            timestamp: cell(&self.timestamp),
+            level: cell(&self.level),
+            source: cell(&self.source),
+            message: text.slice(self.message.clone()),
+            log_level: self.log_level(&text.text),
+        }
+    }
+}
+
+/// A log line split into styled columns by [`LogFields::split`].
+///
+/// Cells for fields the line does not have are empty.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct LogRow {
+    /// Timestamp cell.
+    pub timestamp: AnsiText,
+    /// Level cell, as written in the line.
+    pub level: AnsiText,
+    /// Source cell.
+    pub source: AnsiText,
+    /// Message cell.
+    pub message: AnsiText,
+    /// Parsed level, when the level name is known.
+    pub log_level: Option<LogLevel>,
+}
+
+/// Splits log lines into [`LogFields`].
+///
+/// Implemented by [`CommonLogFormat`], [`JsonLogFormat`](crate::JsonLogFormat),
+/// and closures taking the visible text of a line, so applications can plug
+/// in a parser for their own format. Pairs try their first structure first.
+/// Return `None` for lines without structure, such as continuation lines of
+/// a stack trace, to show them unsplit.
+pub trait LineStructure {
+    /// Finds the fields of `line`, the visible text without escapes.
+    fn parse(&self, line: &str) -> Option<LogFields>;
+}
+
+impl<F: Fn(&str) -> Option<LogFields>> LineStructure for F {
+    fn parse(&self, line: &str) -> Option<LogFields> {
+        self(line)
+    }
+}
+
+/// Tries `A` first and `B` on lines `A` does not recognize, such as
+/// `(JsonLogFormat, CommonLogFormat)` for a mix of JSON and text records.
+impl<A: LineStructure, B: LineStructure> LineStructure for (A, B) {
+    fn parse(&self, line: &str) -> Option<LogFields> {
+        self.0.parse(line).or_else(|| self.1.parse(line))
+    }
+}
+
+/// Recognizer for common log line layouts.
+///
+/// A line may start with a timestamp, either an ISO 8601 date with an
+/// optional time and offset, a bare `12:00:00` time, or a syslog
+/// `May  1 12:00:00` stamp, optionally in brackets. It is followed, in any
+/// order, by a level name and a source, each optionally bracketed, with the
+/// source ending in `:` when it is not bracketed. Fields may be separated by
+/// ` - ` or ` | `. This covers `env_logger`, `tracing`, Python `logging`,
+/// syslog, and compiler-style `error: ...` lines:
+///
+/// ```rust
+/// use egui_sgr::{CommonLogFormat, LineStructure, LogLevel};
+///
+/// let line = "2024-05-01T12:00:00Z  WARN db::pool: slow query";
+/// let fields = CommonLogFormat.parse(line).unwrap();
+/// assert_eq!(&line[fields.timestamp.clone().unwrap()], "2024-05-01T12:00:00Z");
+/// assert_eq!(fields.log_level(line), Some(LogLevel::Warn));
+/// assert_eq!(&line[fields.source.clone().unwrap()], "db::pool");
+/// assert_eq!(&line[fields.message], "slow query");
+/// ```
+///
+/// Lines with neither a timestamp nor a level are not structured.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CommonLogFormat;
+
+impl LineStructure for CommonLogFormat {
+    fn parse(&self, line: &str) -> Option<LogFields> {
+        let mut fields = LogFields::default();
+        let start = skip_spaces(line, 0);
+        let mut position = start;
+        let mut header = Header::Open;
+
+        if let Some(inner) = line[start..].strip_prefix('[')
+            && let Some(close) = inner.find(']')
+            && let Some(len) = timestamp_len(&inner[..close])
+        {
+            let header_start = start + 1;
+            fields.timestamp = Some(header_start..header_start + len);
+            position = header_start + close + 1;
+            if len < close {
+                // `[2024-05-01T12:00:00Z INFO app] message` keeps the whole
+                // header in one pair of brackets.
+                parse_header(
+                    line,
+                    header_start + len..header_start + close,
+                    &mut fields,
+                    Header::Bracketed,
+                );
+                header = Header::Bracketed;
+            }
+        } else if let Some(len) = timestamp_len(&line[start..]) {
+            fields.timestamp = Some(start..start + len);
+            position = start + len;
+        } else {
+            header = Header::Untimed;
+        }
+
+        if header != Header::Bracketed {
+            position = parse_header(line, position..line.len(), &mut fields, header);
+        }
+        if fields.timestamp.is_none() && fields.level.is_none() {
+            return None;
+        }
+        fields.message = skip_spaces(line, position)..line.len();
+        Some(fields)
+    }
+}
+
+/// Context of the header tokens after the timestamp.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Header {
+    /// Tokens after a timestamp.
+    Open,
+    /// Tokens inside the brackets around a whole header, where a plain word
+    /// is the source.
+    Bracketed,
+    /// Tokens of a line without a timestamp, where a level must be
+    /// bracketed, end with `:`, or be upper case so that a message starting
+    /// with "Error" is not split.
+    Untimed,
+}
+
+/// Reads level and source tokens from `line[range]` into `fields` and
+/// returns the offset after the last token taken.
+fn parse_header(line: &str, range: Range<usize>, fields: &mut LogFields, header: Header) -> usize {
+    let mut position = range.start;
+    let mut end_of_header = range.start;
+    for _ in 0..6 {
+        let token_start = skip_spaces(line, position);
+        if token_start >= range.end {
+            break;
+        }
+        let token_end = line[token_start..range.end]
+            .find(' ')
+            .map_or(range.end, |offset| token_start + offset);
+        let token = &line[token_start..token_end];
+        if token == "-" || token == "|" {
+            position = token_end;
+            end_of_header = token_end;
+            continue;
+        }
+
+        let (inner, closes_header) = unwrap_token(token);
+        let inner_start = token_start + usize::from(token.starts_with('['));
+        let inner_range = inner_start..inner_start + inner.len();
+        let marked = token.starts_with('[') || closes_header;
+        let followed_by_separator = matches!(
+            line[token_end..range.end].split_whitespace().next(),
+            Some("-" | "|")
+        );
+
+        let is_level = LogLevel::parse(inner).is_some()
+            && (header != Header::Untimed
+                || marked
+                || inner.bytes().all(|b| !b.is_ascii_lowercase()));
+        if fields.level.is_none() && is_level {
+            fields.level = Some(inner_range);
+        } else if fields.source.is_none()
+            && !inner.is_empty()
+            && (header == Header::Bracketed || marked || followed_by_separator)
+        {
+            fields.source = Some(inner_range);
+        } else {
+            break;
+        }
+        position = token_end;
+        end_of_header = token_end;
+        if closes_header {
+            break;
+        }
+    }
+    end_of_header
+}
+
+/// Strips brackets and a trailing `:` from a header token. The flag is set
+/// when the token ends with `:`, which ends the header.
+fn unwrap_token(token: &str) -> (&str, bool) {
+    let (token, colon) = match token.strip_suffix(':') {
+        Some(token) => (token, true),
+        None => (token, false),
+    };
+    let token = token
+        .strip_prefix('[')
+        .and_then(|token| token.strip_suffix(']'))
+        .unwrap_or(token);
+    (token, colon)
+}
+
+/// Returns the length of the timestamp at the start of `text`, if any.
+fn timestamp_len(text: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let date = if digits(bytes, 0, 4)
+        && bytes.get(4) == Some(&b'-')
+        && digits(bytes, 5, 2)
+        && bytes.get(7) == Some(&b'-')
+        && digits(bytes, 8, 2)
+    {
+        Some(10)
+    } else {
+        syslog_date_len(text)
+    };
+
+    let end = match date {
+        Some(date) => match bytes.get(date) {
+            Some(b'T' | b' ') => time_len(bytes, date + 1).map_or(date, |time| date + 1 + time),
+            _ => date,
+        },
+        None => time_len(bytes, 0)?,
+    };
+    let end = end + offset_len(&bytes[end..]);
+    // A timestamp is a whole token.
+    match bytes.get(end) {
+        None | Some(b' ' | b']' | b',') => Some(end),
+        _ => None,
+    }
+}
+
+/// Returns the length of a syslog `May  1` date, which needs a time.
+fn syslog_date_len(text: &str) -> Option<usize> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let month = text.get(..3)?;
+    if !MONTHS.contains(&month) || text.as_bytes().get(3) != Some(&b' ') {
+        return None;
+    }
+    let day = skip_spaces(text, 4);
+    let bytes = text.as_bytes();
+    let day_len = bytes[day..]
+        .iter()
+        .take_while(|byte| byte.is_ascii_digit())
+        .count();
+    let date = day + day_len;
+    ((1..=2).contains(&day_len)
+        && bytes.get(date) == Some(&b' ')
+        && time_len(bytes, date + 1).is_some())
+    .then_some(date)
+}
+
+/// Returns the length of an `hh:mm:ss` time with optional fraction at
+/// `start`.
+fn time_len(bytes: &[u8], start: usize) -> Option<usize> {
+    if !(digits(bytes, start, 2)
+        && bytes.get(start + 2) == Some(&b':')
+        && digits(bytes, start + 3, 2)
+        && bytes.get(start + 5) == Some(&b':')
+        && digits(bytes, start + 6, 2))
+    {
+        return None;
+    }
+    let mut len = 8;
+    if let Some(b'.' | b',') = bytes.get(start + len) {
+        let fraction = bytes[start + len + 1..]
+            .iter()
+            .take_while(|byte| byte.is_ascii_digit())
+            .count();
+        if fraction > 0 {
+            len += 1 + fraction;
+        }
+    }
+    Some(len)
+}
+
+/// Returns the length of a `Z` or `+02:00` UTC offset at the start of
+/// `bytes`, or 0.
+fn offset_len(bytes: &[u8]) -> usize {
+    match bytes.first() {
+        Some(b'Z') => 1,
+        Some(b'+' | b'-') if digits(bytes, 1, 2) => {
+            if bytes.get(3) == Some(&b':') && digits(bytes, 4, 2) {
+                6
+            } else if digits(bytes, 3, 2) {
+                5
+            } else {
+                0
+            }
+        }
+        _ => 0,
+    }
+}
+
+fn digits(bytes: &[u8], start: usize, count: usize) -> bool {
+    bytes
+        .get(start..start + count)
+        .is_some_and(|digits| digits.iter().all(u8::is_ascii_digit))
+}
+
+fn skip_spaces(text: &str, start: usize) -> usize {
+    start.min(text.len())
+        + text[start.min(text.len())..]
+            .bytes()
+            .take_while(|&byte| byte == b' ' || byte == b'\t')
+            .count()
+}
+
+impl<M> AnsiLine<M> {
+    /// Splits the line into styled columns with `structure`, such as
+    /// [`CommonLogFormat`].
+    ///
+    /// Returns `None` for lines the structure does not recognize.
+    #[must_use]
+    pub fn log_row(&self, structure: &impl LineStructure) -> Option<LogRow> {
+        structure
+            .parse(&self.text.text)
+            .map(|fields| fields.split(&self.text))
+    }
+}
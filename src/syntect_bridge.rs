@@ -0,0 +1,50 @@
+use crate::parser::extend_and_merge;
+use crate::{AnsiColor, AnsiIntensity, AnsiSpan, AnsiStyle, UnderlineStyle};
+use syntect::highlighting::{FontStyle, Style};
+
+impl From<Style> for AnsiStyle {
+    /// Maps the foreground color and font style of a syntect highlight.
+    ///
+    /// The background is left at the default: syntect repeats the theme
+    /// background on every range, which would paint over the egui theme.
+    fn from(style: Style) -> Self {
+        let foreground = style.foreground;
+        Self {
+            foreground: if foreground.a == 0 {
+                AnsiColor::Default
+            } else {
+                AnsiColor::Rgb(foreground.r, foreground.g, foreground.b)
+            },
+            intensity: if style.font_style.contains(FontStyle::BOLD) {
+                AnsiIntensity::Bold
+            } else {
+                AnsiIntensity::Normal
+            },
+            italic: style.font_style.contains(FontStyle::ITALIC),
+            underline: if style.font_style.contains(FontStyle::UNDERLINE) {
+                UnderlineStyle::Single
+            } else {
+                UnderlineStyle::None
+            },
+            ..Self::default()
+        }
+    }
+}
+
+/// Converts syntect highlight ranges into spans.
+///
+/// Highlighted code then goes through the same
+/// [`spans_to_layout_job`](crate::spans_to_layout_job) path as parsed ANSI
+/// output, so code blocks inside logs render uniformly. Adjacent ranges with
+/// the same style are merged.
+#[must_use]
+pub fn syntect_ranges_to_spans(ranges: &[(Style, &str)]) -> Vec<AnsiSpan> {
+    let mut spans = Vec::new();
+    extend_and_merge(
+        &mut spans,
+        ranges
+            .iter()
+            .map(|(style, text)| AnsiSpan::new(*text, AnsiStyle::from(*style))),
+    );
+    spans
+}
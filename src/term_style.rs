@@ -0,0 +1,172 @@
+use crate::{AnsiColor, AnsiIntensity, AnsiSpan, AnsiStyle, UnderlineStyle, parser};
+
+#[cfg(feature = "nu-ansi-term")]
+mod nu {
+    use super::*;
+    use nu_ansi_term::{AnsiString, Color, Style};
+
+    impl From<Color> for AnsiColor {
+        fn from(color: Color) -> Self {
+            match color {
+                Color::Default => Self::Default,
+                Color::Black => Self::Indexed(0),
+                Color::Red => Self::Indexed(1),
+                Color::Green => Self::Indexed(2),
+                Color::Yellow => Self::Indexed(3),
+                Color::Blue => Self::Indexed(4),
+                Color::Purple | Color::Magenta => Self::Indexed(5),
+                Color::Cyan => Self::Indexed(6),
+                Color::White => Self::Indexed(7),
+                Color::DarkGray => Self::Indexed(8),
+                Color::LightRed => Self::Indexed(9),
+                Color::LightGreen => Self::Indexed(10),
+                Color::LightYellow => Self::Indexed(11),
+                Color::LightBlue => Self::Indexed(12),
+                Color::LightPurple | Color::LightMagenta => Self::Indexed(13),
+                Color::LightCyan => Self::Indexed(14),
+                Color::LightGray => Self::Indexed(15),
+                Color::Fixed(index) => Self::Indexed(index),
+                Color::Rgb(r, g, b) => Self::Rgb(r, g, b),
+            }
+        }
+    }
+
+    impl From<Style> for AnsiStyle {
+        fn from(style: Style) -> Self {
+            style_from_flags(
+                style.foreground.map(AnsiColor::from),
+                style.background.map(AnsiColor::from),
+                Flags {
+                    bold: style.is_bold,
+                    dimmed: style.is_dimmed,
+                    italic: style.is_italic,
+                    underline: style.is_underline,
+                    reverse: style.is_reverse,
+                    hidden: style.is_hidden,
+                    strikethrough: style.is_strikethrough,
+                },
+            )
+        }
+    }
+
+    impl From<&AnsiString<'_>> for AnsiSpan {
+        fn from(string: &AnsiString<'_>) -> Self {
+            Self::new(string.as_str(), AnsiStyle::from(*string.style_ref()))
+        }
+    }
+
+    /// Converts `nu-ansi-term` strings into spans.
+    ///
+    /// Output built programmatically does not need to be serialized to escape
+    /// codes and reparsed. Adjacent strings with the same style are merged.
+    #[must_use]
+    pub fn nu_ansi_strings_to_spans(strings: &[AnsiString<'_>]) -> Vec<AnsiSpan> {
+        let mut spans = Vec::new();
+        parser::extend_and_merge(&mut spans, strings.iter().map(AnsiSpan::from));
+        spans
+    }
+}
+
+#[cfg(feature = "ansi_term")]
+mod legacy {
+    use super::*;
+    use ansi_term::{ANSIString, Colour, Style};
+
+    impl From<Colour> for AnsiColor {
+        fn from(colour: Colour) -> Self {
+            match colour {
+                Colour::Black => Self::Indexed(0),
+                Colour::Red => Self::Indexed(1),
+                Colour::Green => Self::Indexed(2),
+                Colour::Yellow => Self::Indexed(3),
+                Colour::Blue => Self::Indexed(4),
+                Colour::Purple => Self::Indexed(5),
+                Colour::Cyan => Self::Indexed(6),
+                Colour::White => Self::Indexed(7),
+                Colour::Fixed(index) => Self::Indexed(index),
+                Colour::RGB(r, g, b) => Self::Rgb(r, g, b),
+            }
+        }
+    }
+
+    impl From<Style> for AnsiStyle {
+        fn from(style: Style) -> Self {
+            style_from_flags(
+                style.foreground.map(AnsiColor::from),
+                style.background.map(AnsiColor::from),
+                Flags {
+                    bold: style.is_bold,
+                    dimmed: style.is_dimmed,
+                    italic: style.is_italic,
+                    underline: style.is_underline,
+                    reverse: style.is_reverse,
+                    hidden: style.is_hidden,
+                    strikethrough: style.is_strikethrough,
+                },
+            )
+        }
+    }
+
+    impl From<&ANSIString<'_>> for AnsiSpan {
+        fn from(string: &ANSIString<'_>) -> Self {
+            Self::new(&**string, AnsiStyle::from(*string.style_ref()))
+        }
+    }
+
+    /// Converts `ansi_term` strings into spans.
+    ///
+    /// Adjacent strings with the same style are merged.
+    #[must_use]
+    pub fn ansi_term_strings_to_spans(strings: &[ANSIString<'_>]) -> Vec<AnsiSpan> {
+        let mut spans = Vec::new();
+        parser::extend_and_merge(&mut spans, strings.iter().map(AnsiSpan::from));
+        spans
+    }
+}
+
+#[cfg(feature = "ansi_term")]
+pub use legacy::ansi_term_strings_to_spans;
+#[cfg(feature = "nu-ansi-term")]
+pub use nu::nu_ansi_strings_to_spans;
+
+struct Flags {
+    bold: bool,
+    dimmed: bool,
+    italic: bool,
+    underline: bool,
+    reverse: bool,
+    hidden: bool,
+    strikethrough: bool,
+}
+
+fn style_from_flags(
+    foreground: Option<AnsiColor>,
+    background: Option<AnsiColor>,
+    flags: Flags,
+) -> AnsiStyle {
+    // Both crates emit `1;2` for bold + dimmed; the later faint wins, as it
+    // would when reparsing their output.
+    let intensity = if flags.dimmed {
+        AnsiIntensity::Faint
+    } else if flags.bold {
+        AnsiIntensity::Bold
+    } else {
+        AnsiIntensity::Normal
+    };
+
+    AnsiStyle {
+        foreground: foreground.unwrap_or_default(),
+        background: background.unwrap_or_default(),
+        intensity,
+        italic: flags.italic,
+        underline: if flags.underline {
+            UnderlineStyle::Single
+        } else {
+            UnderlineStyle::None
+        },
+        strikethrough: flags.strikethrough,
+        reverse: flags.reverse,
+        hidden: flags.hidden,
+        ..AnsiStyle::default()
+    }
+}
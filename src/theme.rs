@@ -1,15 +1,25 @@
-use egui::{Color32, TextFormat};
+use ecolor::Color32;
+#[cfg(feature = "egui")]
+use egui::TextFormat;
 
 /// Theme used when converting ANSI spans into egui text formats.
+///
+/// With the `serde` feature, colors are serialized as unmultiplied
+/// `(r, g, b, a)` tuples.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EguiAnsiTheme {
     /// Base egui text format copied before ANSI-specific fields are applied.
+    #[cfg(feature = "egui")]
     pub default_format: TextFormat,
     /// Foreground color used when ANSI foreground is [`AnsiColor::Default`](crate::AnsiColor::Default).
+    #[cfg_attr(feature = "serde", serde(with = "rgba"))]
     pub default_foreground: Color32,
     /// Background color used for reverse video when ANSI background is default.
+    #[cfg_attr(feature = "serde", serde(with = "rgba"))]
     pub default_background: Color32,
     /// ANSI 0-255 color palette.
+    #[cfg_attr(feature = "serde", serde(with = "rgba_palette"))]
     pub palette: [Color32; 256],
     /// Width used for underlines in egui strokes.
     pub underline_width: f32,
@@ -19,19 +29,28 @@ pub struct EguiAnsiTheme {
     pub faint_opacity: f32,
     /// Whether bold 0-7 indexed foreground colors render as bright 8-15 colors.
     pub bold_is_bright: bool,
+    /// Color of bold text in the default foreground color, or `None` (the
+    /// default) to draw it like normal text.
+    ///
+    /// egui fonts have no bold weight, so this is how bold stands out in
+    /// layout jobs, like egui's own strong text. Pick a color that contrasts
+    /// with [`Self::default_background`]; a fixed white would vanish on a
+    /// light theme. Exporters with a real bold weight ignore it.
+    #[cfg_attr(feature = "serde", serde(default, with = "rgba_option"))]
+    pub bold_foreground: Option<Color32>,
 }
 
 impl Default for EguiAnsiTheme {
     fn default() -> Self {
         let default_foreground = Color32::from_rgb(229, 229, 229);
-        let default_format = TextFormat {
-            color: default_foreground,
-            background: Color32::TRANSPARENT,
-            ..Default::default()
-        };
 
         Self {
-            default_format,
+            #[cfg(feature = "egui")]
+            default_format: TextFormat {
+                color: default_foreground,
+                background: Color32::TRANSPARENT,
+                ..Default::default()
+            },
             default_foreground,
             default_background: Color32::BLACK,
             palette: Self::xterm_palette(),
@@ -39,6 +58,7 @@ impl Default for EguiAnsiTheme {
             strikethrough_width: 1.0,
             faint_opacity: 0.6,
             bold_is_bright: true,
+            bold_foreground: None,
         }
     }
 }
@@ -103,3 +123,79 @@ fn cube_component(component: u8) -> u8 {
         55 + component * 40
     }
 }
+
+#[cfg(feature = "serde")]
+mod rgba {
+    use ecolor::Color32;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(super) type Rgba = (u8, u8, u8, u8);
+
+    pub(super) fn to_tuple(color: Color32) -> Rgba {
+        let [r, g, b, a] = color.to_srgba_unmultiplied();
+        (r, g, b, a)
+    }
+
+    pub(super) fn from_tuple((r, g, b, a): Rgba) -> Color32 {
+        Color32::from_rgba_unmultiplied(r, g, b, a)
+    }
+
+    pub(super) fn serialize<S: Serializer>(
+        color: &Color32,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        to_tuple(*color).serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Color32, D::Error> {
+        Rgba::deserialize(deserializer).map(from_tuple)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod rgba_palette {
+    use super::rgba::{Rgba, from_tuple, to_tuple};
+    use ecolor::Color32;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(
+        palette: &[Color32; 256],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(palette.iter().copied().map(to_tuple))
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<[Color32; 256], D::Error> {
+        let colors = Vec::<Rgba>::deserialize(deserializer)?;
+        let len = colors.len();
+        let colors: Vec<Color32> = colors.into_iter().map(from_tuple).collect();
+        colors
+            .try_into()
+            .map_err(|_| D::Error::invalid_length(len, &"256 palette colors"))
+    }
+}
+
+#[cfg(feature = "serde")]
+mod rgba_option {
+    use super::rgba::{Rgba, from_tuple, to_tuple};
+    use ecolor::Color32;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(
+        color: &Option<Color32>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        color.map(to_tuple).serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Color32>, D::Error> {
+        Option::<Rgba>::deserialize(deserializer).map(|color| color.map(from_tuple))
+    }
+}
@@ -0,0 +1,142 @@
+use crate::SharedAnsiDocument;
+use crate::repaint::Repaint;
+use std::fmt::{self, Write};
+use tracing_core::field::{Field, Visit};
+use tracing_core::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// `tracing` layer that appends colored events to a [`SharedAnsiDocument`].
+///
+/// Each event becomes one line with a colored level, the target, the names
+/// of the entered spans, the message, and the remaining fields. The line goes
+/// through the ANSI parser, so messages that carry escape codes keep them.
+/// The document keeps the last 10,000 lines unless
+/// [`Self::with_max_lines`] says otherwise.
+///
+/// ```no_run
+/// use tracing_subscriber::layer::SubscriberExt;
+///
+/// let layer = egui_sgr::AnsiTracingLayer::new();
+/// let events = layer.document();
+/// tracing::subscriber::set_global_default(tracing_subscriber::registry().with(layer))?;
+///
+/// // In the UI:
+/// // ui.label(events.lock().to_layout_job(&theme));
+/// # drop(events);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct AnsiTracingLayer {
+    document: SharedAnsiDocument,
+    repaint: Repaint,
+}
+
+impl Default for AnsiTracingLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnsiTracingLayer {
+    /// Creates a layer writing into a new document.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            document: SharedAnsiDocument::for_sink(),
+            repaint: Repaint::default(),
+        }
+    }
+
+    /// Keeps at most `max_lines` event lines, evicting the oldest.
+    #[must_use]
+    pub fn with_max_lines(self, max_lines: usize) -> Self {
+        self.document.lock().set_max_lines(Some(max_lines));
+        self
+    }
+
+    /// Appends to `document` instead of a new one, keeping its retention
+    /// cap.
+    #[must_use]
+    pub fn with_document(mut self, document: SharedAnsiDocument) -> Self {
+        self.document = document;
+        self
+    }
+
+    /// Requests an egui repaint after each event.
+    #[cfg(feature = "egui")]
+    #[must_use]
+    pub fn with_repaint(mut self, ctx: egui::Context) -> Self {
+        self.repaint = Repaint::new(ctx);
+        self
+    }
+
+    /// Returns the shared document that events are appended to.
+    #[must_use]
+    pub fn document(&self) -> SharedAnsiDocument {
+        self.document.clone()
+    }
+}
+
+impl<S> Layer<S> for AnsiTracingLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let level_style = match *metadata.level() {
+            Level::ERROR => "1;31",
+            Level::WARN => "33",
+            Level::INFO => "32",
+            Level::DEBUG => "34",
+            Level::TRACE => "35",
+        };
+
+        let mut line = String::new();
+        let _ = write!(
+            line,
+            "\x1b[2m[\x1b[0m\x1b[{level_style}m{:<5}\x1b[0m {}\x1b[2m]\x1b[0m ",
+            metadata.level(),
+            metadata.target()
+        );
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                let _ = write!(line, "\x1b[1m{}\x1b[0m: ", span.name());
+            }
+        }
+
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        line.push_str(&visitor.message);
+        line.push_str(&visitor.fields);
+        // The trailing reset keeps a message's unterminated style from
+        // bleeding into the next event.
+        line.push_str("\x1b[0m\n");
+
+        self.document.lock().append_str(&line);
+        self.repaint.request();
+    }
+}
+
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            self.record_debug(field, &value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{value:?}");
+        } else {
+            let _ = write!(self.fields, " \x1b[3m{}\x1b[0m={value:?}", field.name());
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use crate::{EguiAnsiTheme, IntoAnsiSource};
+use egui::text::LayoutJob;
+use egui::{Button, Id, Label, Response, TextStyle, Ui, Widget};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// ANSI shortcuts on [`egui::Ui`], mirroring [`Ui::label`],
+/// [`Ui::selectable_label`], and [`Ui::monospace`].
+///
+/// Each call parses `text` and renders it with an [`EguiAnsiTheme`] that
+/// takes its font and default text color from the current egui style, so
+/// uncolored text looks like a normal label in both light and dark mode.
+/// With the `bidi` feature, lines with right-to-left text are shown in
+/// display order, as with `AnsiText::to_bidi_layout_job`.
+/// Text that is shown every frame is parsed every frame; use
+/// [`Self::ansi_label_cached`] for static text, or keep a [`LayoutJob`] or a
+/// [`LayoutJobCache`](crate::LayoutJobCache) for large logs.
+///
+/// ```rust
+/// use egui_sgr::AnsiUiExt as _;
+///
+/// fn status(ui: &mut egui::Ui) {
+///     ui.ansi_label("\x1b[32mok\x1b[0m 3 tests passed");
+///     ui.ansi_monospace("\x1b[1;31merror\x1b[0m: file not found");
+/// }
+/// ```
+pub trait AnsiUiExt {
+    /// Shows ANSI text as a label in the body font.
+    fn ansi_label<'a>(&mut self, text: impl IntoAnsiSource<'a>) -> Response;
+
+    /// Shows ANSI text as a button that looks selected while `selected` is
+    /// `true`, like [`Ui::selectable_label`].
+    fn ansi_selectable_label<'a>(
+        &mut self,
+        selected: bool,
+        text: impl IntoAnsiSource<'a>,
+    ) -> Response;
+
+    /// Shows ANSI text as a label in the monospace font.
+    fn ansi_monospace<'a>(&mut self, text: impl IntoAnsiSource<'a>) -> Response;
+
+    /// Shows ANSI text like [`Self::ansi_label`], reusing the layout job
+    /// parsed on an earlier frame.
+    ///
+    /// The job is kept in egui's temporary data storage under `id`, together
+    /// with a hash of `text` and the style it was rendered with. Each frame
+    /// only hashes the text; it is parsed again when the text, font, or
+    /// visuals change. Use a distinct `id` per label, such as
+    /// `ui.id().with("status")`, since one id holds one entry.
+    fn ansi_label_cached<'a>(&mut self, id: Id, text: impl IntoAnsiSource<'a>) -> Response;
+
+    /// Runs `f` on parser state stored in egui's data storage under `id`,
+    /// creating it with [`Default`] on first use.
+    ///
+    /// This lets a console written without app state keep an
+    /// [`AnsiStreamParser`](crate::AnsiStreamParser),
+    /// [`AnsiSpanBuffer`](crate::AnsiSpanBuffer), or
+    /// [`AnsiDocument`](crate::AnsiDocument) across frames, so sequences
+    /// split between chunks still parse. Use the widget's id, or
+    /// `ui.id().with(..)` for state without a widget. The state is not
+    /// persisted and lasts until it is removed from
+    /// [`egui::Context::data_mut`] as `Arc<Mutex<T>>`.
+    ///
+    /// ```rust
+    /// use egui_sgr::{AnsiDocument, AnsiUiExt as _, EguiAnsiTheme};
+    ///
+    /// fn console(ui: &mut egui::Ui, new_output: &[u8]) {
+    ///     let id = ui.id().with("console");
+    ///     let job = ui.ansi_state(id, |document: &mut AnsiDocument| {
+    ///         document.append(new_output);
+    ///         document.to_layout_job(&EguiAnsiTheme::default())
+    ///     });
+    ///     ui.label(job);
+    /// }
+    /// ```
+    fn ansi_state<T, R>(&mut self, id: Id, f: impl FnOnce(&mut T) -> R) -> R
+    where
+        T: Default + Send + 'static;
+}
+
+impl AnsiUiExt for Ui {
+    fn ansi_label<'a>(&mut self, text: impl IntoAnsiSource<'a>) -> Response {
+        Label::new(ui_layout_job(
+            self,
+            &text.into_ansi_bytes(),
+            TextStyle::Body,
+        ))
+        .ui(self)
+    }
+
+    fn ansi_selectable_label<'a>(
+        &mut self,
+        selected: bool,
+        text: impl IntoAnsiSource<'a>,
+    ) -> Response {
+        let job = ui_layout_job(self, &text.into_ansi_bytes(), TextStyle::Button);
+        Button::selectable(selected, job).ui(self)
+    }
+
+    fn ansi_monospace<'a>(&mut self, text: impl IntoAnsiSource<'a>) -> Response {
+        Label::new(ui_layout_job(
+            self,
+            &text.into_ansi_bytes(),
+            TextStyle::Monospace,
+        ))
+        .ui(self)
+    }
+
+    fn ansi_label_cached<'a>(&mut self, id: Id, text: impl IntoAnsiSource<'a>) -> Response {
+        let job = cached_layout_job(self, id, &text.into_ansi_bytes(), TextStyle::Body);
+        Label::new(job).ui(self)
+    }
+
+    fn ansi_state<T, R>(&mut self, id: Id, f: impl FnOnce(&mut T) -> R) -> R
+    where
+        T: Default + Send + 'static,
+    {
+        // The storage lock is released before `f` runs, so `f` may use the
+        // context itself.
+        let state =
+            self.data_mut(|data| Arc::clone(data.get_temp_mut_or_default::<Arc<Mutex<T>>>(id)));
+        let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+        f(&mut state)
+    }
+}
+
+/// Layout job stored in egui's data storage by
+/// [`AnsiUiExt::ansi_label_cached`].
+#[derive(Clone)]
+struct CachedJob {
+    key: u64,
+    job: Arc<LayoutJob>,
+}
+
+/// Returns the job cached under `id`, parsing `text` again if it or the
+/// style changed since it was stored.
+pub(crate) fn cached_layout_job(
+    ui: &Ui,
+    id: Id,
+    text: &[u8],
+    text_style: TextStyle,
+) -> Arc<LayoutJob> {
+    profile_scope!("egui_sgr::cached_ui_layout_job");
+    let visuals = ui.visuals();
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    text_style.resolve(ui.style()).hash(&mut hasher);
+    visuals.text_color().hash(&mut hasher);
+    visuals.strong_text_color().hash(&mut hasher);
+    visuals.extreme_bg_color.hash(&mut hasher);
+    let key = hasher.finish();
+
+    let cached = ui.data(|data| data.get_temp::<CachedJob>(id));
+    if let Some(cached) = cached.filter(|cached| cached.key == key) {
+        return cached.job;
+    }
+    let job = Arc::new(ui_layout_job(ui, text, text_style));
+    ui.data_mut(|data| {
+        data.insert_temp(
+            id,
+            CachedJob {
+                key,
+                job: Arc::clone(&job),
+            },
+        );
+    });
+    job
+}
+
+/// Parses `text` with a theme matching `ui`'s style for `text_style`.
+pub(crate) fn ui_layout_job(ui: &Ui, text: &[u8], text_style: TextStyle) -> LayoutJob {
+    let mut theme = EguiAnsiTheme::default();
+    let visuals = ui.visuals();
+    theme.default_format.font_id = text_style.resolve(ui.style());
+    theme.default_format.color = visuals.text_color();
+    theme.default_foreground = visuals.text_color();
+    theme.bold_foreground = Some(visuals.strong_text_color());
+    theme.default_background = visuals.extreme_bg_color;
+    #[cfg(feature = "bidi")]
+    {
+        let text = crate::ansi_to_text(text);
+        if crate::bidi::has_rtl(&text.text) {
+            text.to_bidi_layout_job(&theme)
+        } else {
+            text.to_layout_job(&theme)
+        }
+    }
+    #[cfg(not(feature = "bidi"))]
+    crate::ansi_bytes_to_layout_job(text, &theme)
+}
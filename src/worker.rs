@@ -0,0 +1,134 @@
+use crate::parser::extend_and_merge;
+use crate::repaint::Repaint;
+use crate::{AnsiSpan, AnsiStreamParser};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+/// Streaming ANSI parser running on a background thread.
+///
+/// Raw chunks are sent to the worker through a channel and parsed off the UI
+/// thread. Parsed spans come back in batches: every batch covers all chunks
+/// that were queued when the worker woke up. When created with
+/// [`Self::with_repaint`], the worker requests an egui repaint after each
+/// batch, so heavy streams never block a frame.
+pub struct AnsiParserWorker {
+    sender: Option<Sender<Vec<u8>>>,
+    receiver: Receiver<Vec<AnsiSpan>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Default for AnsiParserWorker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnsiParserWorker {
+    /// Spawns a worker that does not signal egui.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::spawn(Repaint::default())
+    }
+
+    /// Spawns a worker that calls [`egui::Context::request_repaint`] after
+    /// each delivered batch.
+    #[cfg(feature = "egui")]
+    #[must_use]
+    pub fn with_repaint(ctx: egui::Context) -> Self {
+        Self::spawn(Repaint::new(ctx))
+    }
+
+    fn spawn(repaint: Repaint) -> Self {
+        let (chunk_sender, chunk_receiver) = mpsc::channel::<Vec<u8>>();
+        let (span_sender, span_receiver) = mpsc::channel();
+
+        let handle = thread::Builder::new()
+            .name("egui_sgr parser".to_owned())
+            .spawn(move || run_worker(&chunk_receiver, &span_sender, &repaint))
+            .expect("failed to spawn egui_sgr parser thread");
+
+        Self {
+            sender: Some(chunk_sender),
+            receiver: span_receiver,
+            handle: Some(handle),
+        }
+    }
+
+    /// Returns a sender that other threads can use to queue raw chunks.
+    ///
+    /// All returned senders must be dropped before [`Self::finish`] can
+    /// complete.
+    #[must_use]
+    pub fn sender(&self) -> Sender<Vec<u8>> {
+        self.sender
+            .clone()
+            .expect("worker sender is only taken by finish")
+    }
+
+    /// Queues a raw byte chunk for parsing.
+    pub fn push_bytes(&self, chunk: impl Into<Vec<u8>>) {
+        if let Some(sender) = &self.sender {
+            // The worker only exits after the sender is dropped, so this
+            // cannot fail unless the worker thread panicked.
+            let _ = sender.send(chunk.into());
+        }
+    }
+
+    /// Queues a UTF-8 string chunk for parsing.
+    pub fn push_str(&self, chunk: &str) {
+        self.push_bytes(chunk.as_bytes());
+    }
+
+    /// Returns the next parsed batch, if one is ready.
+    #[must_use]
+    pub fn try_recv(&self) -> Option<Vec<AnsiSpan>> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Returns an iterator over all batches that are ready now.
+    pub fn try_iter(&self) -> impl Iterator<Item = Vec<AnsiSpan>> + '_ {
+        self.receiver.try_iter()
+    }
+
+    /// Closes the input channel, waits for the worker, and returns all
+    /// undelivered spans.
+    ///
+    /// Unfinished escape or UTF-8 sequences are discarded, like
+    /// [`AnsiStreamParser::finish`].
+    #[must_use]
+    pub fn finish(mut self) -> Vec<AnsiSpan> {
+        self.sender = None;
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+        let mut spans = Vec::new();
+        for batch in self.receiver.try_iter() {
+            extend_and_merge(&mut spans, batch);
+        }
+        spans
+    }
+}
+
+fn run_worker(chunks: &Receiver<Vec<u8>>, batches: &Sender<Vec<AnsiSpan>>, repaint: &Repaint) {
+    let mut parser = AnsiStreamParser::new();
+
+    while let Ok(chunk) = chunks.recv() {
+        let mut batch = parser.push_bytes(&chunk);
+        for chunk in chunks.try_iter() {
+            extend_and_merge(&mut batch, parser.push_bytes(&chunk));
+        }
+        deliver(batches, batch, repaint);
+    }
+
+    deliver(batches, parser.finish(), repaint);
+}
+
+fn deliver(batches: &Sender<Vec<AnsiSpan>>, batch: Vec<AnsiSpan>, repaint: &Repaint) {
+    if batch.is_empty() {
+        return;
+    }
+
+    if batches.send(batch).is_ok() {
+        repaint.request();
+    }
+}
@@ -0,0 +1,203 @@
+use crate::{AnsiDocument, SharedAnsiDocument, TerminalProfile};
+
+/// One tab of an [`AnsiWorkspace`]: a titled document with its own profile.
+///
+/// The document is shared, so whatever feeds the session, such as a serial
+/// port, a socket, or a child process, keeps appending to its clone while
+/// the tab is in the background. Lines that arrive meanwhile are counted as
+/// unread until the tab is shown again.
+#[derive(Clone)]
+pub struct AnsiSession {
+    /// Tab title.
+    pub title: String,
+    /// Document shown in the tab.
+    pub document: SharedAnsiDocument,
+    /// Profile the session is rendered with; its theme gives each session
+    /// its own palette and font. Set it with
+    /// [`TerminalProfile::apply_to_session`] so the document settings follow.
+    pub profile: TerminalProfile,
+    /// Absolute line count at the last time the tab was shown.
+    seen_lines: u64,
+}
+
+impl AnsiSession {
+    /// Creates a session showing `document` with the default profile.
+    ///
+    /// Lines already in the document are considered read, and the
+    /// document's own settings are left as they are.
+    #[must_use]
+    pub fn new(title: impl Into<String>, document: SharedAnsiDocument) -> Self {
+        let seen_lines = line_total(&document.lock());
+        Self {
+            title: title.into(),
+            document,
+            profile: TerminalProfile::default(),
+            seen_lines,
+        }
+    }
+
+    /// Applies `profile` to the session and its document.
+    #[must_use]
+    pub fn with_profile(mut self, profile: &TerminalProfile) -> Self {
+        profile.apply_to_session(&mut self);
+        self
+    }
+
+    /// Returns the number of lines completed since the tab was last shown.
+    ///
+    /// A partial last line is not counted until it ends, and evicted lines
+    /// still count, so a capped document does not hide new output.
+    #[must_use]
+    pub fn unread_lines(&self) -> u64 {
+        line_total(&self.document.lock()).saturating_sub(self.seen_lines)
+    }
+
+    /// Marks every line as read.
+    pub fn mark_read(&mut self) {
+        self.seen_lines = line_total(&self.document.lock());
+    }
+}
+
+/// Returns the absolute number of completed lines ever appended.
+fn line_total(document: &AnsiDocument) -> u64 {
+    let open = u64::from(document.has_open_line());
+    document.first_line_number() + document.line_count() as u64 - open
+}
+
+/// Ordered set of sessions with one active tab, for apps that show several
+/// terminal or log consoles.
+///
+/// The workspace is the tab model only: adding, closing, renaming, and
+/// switching tabs, and unread counts for the tabs in the background. Drawing
+/// the tab bar and the active document is left to the caller, and so is
+/// starting and stopping whatever feeds each session; `examples/workspace.rs`
+/// does both with egui.
+///
+/// ```rust
+/// use egui_sgr::{AnsiSession, AnsiWorkspace, SharedAnsiDocument};
+///
+/// let build = SharedAnsiDocument::default();
+/// let mut workspace = AnsiWorkspace::new();
+/// workspace.add(AnsiSession::new("build", build.clone()));
+/// workspace.add(AnsiSession::new("serial", SharedAnsiDocument::default()));
+///
+/// build.lock().append("compiling\nfinished\n");
+/// assert_eq!(workspace.unread_lines(0), 2);
+/// workspace.set_active(0);
+/// assert_eq!(workspace.unread_lines(0), 0);
+/// ```
+#[derive(Clone, Default)]
+pub struct AnsiWorkspace {
+    sessions: Vec<AnsiSession>,
+    active: usize,
+}
+
+impl AnsiWorkspace {
+    /// Creates a workspace without sessions.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a session as the last tab, makes it active, and returns its index.
+    pub fn add(&mut self, session: AnsiSession) -> usize {
+        self.mark_active_read();
+        self.sessions.push(session);
+        self.active = self.sessions.len() - 1;
+        self.mark_active_read();
+        self.active
+    }
+
+    /// Removes and returns the session at `index`, so the caller can stop
+    /// its source.
+    ///
+    /// The tab to its left becomes active if the active tab was closed.
+    pub fn close(&mut self, index: usize) -> Option<AnsiSession> {
+        if index >= self.sessions.len() {
+            return None;
+        }
+        let session = self.sessions.remove(index);
+        if self.active > index || self.active == self.sessions.len() {
+            self.active = self.active.saturating_sub(1);
+        }
+        self.mark_active_read();
+        Some(session)
+    }
+
+    /// Renames the session at `index`.
+    pub fn rename(&mut self, index: usize, title: impl Into<String>) {
+        if let Some(session) = self.sessions.get_mut(index) {
+            session.title = title.into();
+        }
+    }
+
+    /// Returns the sessions in tab order.
+    #[must_use]
+    pub fn sessions(&self) -> &[AnsiSession] {
+        &self.sessions
+    }
+
+    /// Returns the session at `index` for changing its title or profile.
+    pub fn session_mut(&mut self, index: usize) -> Option<&mut AnsiSession> {
+        self.sessions.get_mut(index)
+    }
+
+    /// Returns the index of the active tab.
+    #[must_use]
+    pub fn active_index(&self) -> usize {
+        self.active
+    }
+
+    /// Activates the tab at `index`, if it exists.
+    ///
+    /// Output the previous tab showed while it was active stays read, and
+    /// the new tab's backlog is read once it is shown.
+    pub fn set_active(&mut self, index: usize) {
+        if index < self.sessions.len() {
+            self.mark_active_read();
+            self.active = index;
+            self.mark_active_read();
+        }
+    }
+
+    /// Returns the active session, or `None` if there are no sessions.
+    #[must_use]
+    pub fn active(&self) -> Option<&AnsiSession> {
+        self.sessions.get(self.active)
+    }
+
+    /// Returns the active session for rendering, or `None` if there are no
+    /// sessions.
+    pub fn active_mut(&mut self) -> Option<&mut AnsiSession> {
+        self.sessions.get_mut(self.active)
+    }
+
+    /// Returns the unread line count to badge the tab at `index` with.
+    ///
+    /// The active tab is on screen, so it has none.
+    #[must_use]
+    pub fn unread_lines(&self, index: usize) -> u64 {
+        match self.sessions.get(index) {
+            Some(session) if index != self.active => session.unread_lines(),
+            _ => 0,
+        }
+    }
+
+    /// Returns the number of sessions.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    /// Returns `true` if there are no sessions.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    fn mark_active_read(&mut self) {
+        if let Some(session) = self.sessions.get_mut(self.active) {
+            session.mark_read();
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use crate::SharedAnsiDocument;
+use crate::repaint::Repaint;
+use std::io;
+
+/// [`io::Write`] sink that parses written bytes into a [`SharedAnsiDocument`].
+///
+/// Code that writes to a terminal-like sink, such as progress bars or pretty
+/// printers, can target an egui view unchanged: give it a writer and render
+/// the document each frame. Clones write into the same document, which keeps
+/// the last 10,000 lines unless [`Self::with_max_lines`] says otherwise.
+#[derive(Clone)]
+pub struct AnsiWriter {
+    document: SharedAnsiDocument,
+    repaint: Repaint,
+}
+
+impl Default for AnsiWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnsiWriter {
+    /// Creates a writer with a new document.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::from_document(SharedAnsiDocument::for_sink())
+    }
+
+    /// Creates a writer that appends to an existing shared document, keeping
+    /// its retention cap.
+    #[must_use]
+    pub fn from_document(document: SharedAnsiDocument) -> Self {
+        Self {
+            document,
+            repaint: Repaint::default(),
+        }
+    }
+
+    /// Keeps at most `max_lines` lines, evicting the oldest.
+    #[must_use]
+    pub fn with_max_lines(self, max_lines: usize) -> Self {
+        self.document.lock().set_max_lines(Some(max_lines));
+        self
+    }
+
+    /// Requests an egui repaint after each write.
+    #[cfg(feature = "egui")]
+    #[must_use]
+    pub fn with_repaint(mut self, ctx: egui::Context) -> Self {
+        self.repaint = Repaint::new(ctx);
+        self
+    }
+
+    /// Returns the shared document that writes are parsed into.
+    #[must_use]
+    pub fn document(&self) -> SharedAnsiDocument {
+        self.document.clone()
+    }
+}
+
+impl io::Write for AnsiWriter {
+    fn write(&mut self, chunk: &[u8]) -> io::Result<usize> {
+        self.document.lock().append(chunk);
+        self.repaint.request();
+        Ok(chunk.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+//! Escape-handling conformance against recorded tool output.
+//!
+//! Each `tests/fixtures/*.ansi` file is parsed and compared with the
+//! annotated snapshot in the matching `.golden` file. Run with
+//! `EGUI_SGR_BLESS=1` to rewrite the golden files after an intended change,
+//! then review the diff.
+
+use egui_sgr::{
+    AnsiLimits, AnsiStreamParser, ansi_bytes_to_spans, snapshot_spans, try_ansi_bytes_to_spans,
+};
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn fixtures_match_golden_snapshots() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let bless = std::env::var_os("EGUI_SGR_BLESS").is_some();
+    let mut fixtures: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "ansi")
+        })
+        .collect();
+    fixtures.sort();
+    assert!(!fixtures.is_empty());
+
+    for fixture in fixtures {
+        let input = fs::read(&fixture).unwrap();
+        let spans = ansi_bytes_to_spans(&input);
+        let snapshot = snapshot_spans(&spans);
+
+        let golden = fixture.with_extension("golden");
+        if bless {
+            fs::write(&golden, &snapshot).unwrap();
+        } else {
+            let expected = fs::read_to_string(&golden)
+                .unwrap_or_else(|_| panic!("missing {}", golden.display()));
+            assert_eq!(snapshot, expected, "{}", fixture.display());
+        }
+
+        // Streaming in small chunks and strict parsing agree with the
+        // one-shot parse.
+        let mut parser = AnsiStreamParser::new();
+        let mut streamed = Vec::new();
+        for chunk in input.chunks(7) {
+            streamed.extend(parser.push_bytes(chunk));
+        }
+        streamed.extend(parser.finish());
+        assert_eq!(snapshot_spans(&streamed), snapshot, "{}", fixture.display());
+        assert_eq!(
+            try_ansi_bytes_to_spans(&input, &AnsiLimits::default()).as_ref(),
+            Ok(&spans),
+            "{}",
+            fixture.display()
+        );
+    }
+}
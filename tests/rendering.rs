@@ -0,0 +1,132 @@
+//! Rendering regression tests through a headless egui harness.
+//!
+//! Each fixture is shown with `AnsiUiExt::ansi_monospace` in an
+//! `egui_kittest` harness, and the painted text shapes are compared with a
+//! snapshot in `tests/rendering/<name>.golden`: row positions and widths,
+//! then each section's text and resolved format. This catches changes in
+//! both conversion and layout without a GPU. Run with `EGUI_SGR_BLESS=1` to
+//! rewrite the snapshots after an intended change, then review the diff.
+
+use egui::epaint::{ClippedShape, Shape, Stroke};
+use egui::{Color32, Galley, Pos2};
+use egui_kittest::Harness;
+use egui_sgr::AnsiUiExt as _;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+const FIXTURES: &[(&str, &str)] = &[
+    (
+        "colors",
+        "\x1b[31mred\x1b[0m \x1b[92mbright green\x1b[0m \x1b[38;5;208m208\x1b[0m \
+         \x1b[38;2;10;20;30mrgb\x1b[0m",
+    ),
+    (
+        "attributes",
+        "\x1b[1mbold\x1b[0m \x1b[2mfaint\x1b[0m \x1b[3mitalic\x1b[0m \
+         \x1b[4munderline\x1b[0m \x1b[9mstrike\x1b[0m \x1b[8mhidden\x1b[0m",
+    ),
+    (
+        "backgrounds",
+        "\x1b[41m red \x1b[0m \x1b[30;47m inverse \x1b[0m \x1b[7mreverse\x1b[0m \
+         \x1b[48;2;0;0;128m navy \x1b[0m",
+    ),
+    ("wide_chars", "ab漢字cd\n\x1b[33m★\x1b[0m 👍 e\u{301}\n"),
+    ("cursor", "$ ls -la\x1b[7m \x1b[0m"),
+];
+
+#[test]
+fn rendered_fixtures_match_golden_snapshots() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/rendering");
+    let bless = std::env::var_os("EGUI_SGR_BLESS").is_some();
+    if bless {
+        fs::create_dir_all(&dir).unwrap();
+    }
+
+    for &(name, input) in FIXTURES {
+        let mut harness = Harness::new_ui(|ui| {
+            ui.ansi_monospace(input);
+        });
+        harness.run();
+        let snapshot = snapshot_shapes(&harness.output().shapes);
+        assert!(!snapshot.is_empty(), "{name}: nothing painted");
+
+        let golden = dir.join(name).with_extension("golden");
+        if bless {
+            fs::write(&golden, &snapshot).unwrap();
+        } else {
+            let expected = fs::read_to_string(&golden)
+                .unwrap_or_else(|_| panic!("missing {}", golden.display()));
+            assert_eq!(snapshot, expected, "{name}");
+        }
+    }
+}
+
+/// Describes every text shape painted in a frame.
+fn snapshot_shapes(shapes: &[ClippedShape]) -> String {
+    let mut out = String::new();
+    for clipped in shapes {
+        snapshot_shape(&mut out, &clipped.shape);
+    }
+    out
+}
+
+fn snapshot_shape(out: &mut String, shape: &Shape) {
+    match shape {
+        Shape::Vec(shapes) => {
+            for shape in shapes {
+                snapshot_shape(out, shape);
+            }
+        }
+        Shape::Text(text) => snapshot_galley(out, text.pos, &text.galley),
+        _ => {}
+    }
+}
+
+fn snapshot_galley(out: &mut String, pos: Pos2, galley: &Galley) {
+    writeln!(out, "galley at ({:.1}, {:.1})", pos.x, pos.y).unwrap();
+    for row in &galley.rows {
+        let text: String = row.glyphs.iter().map(|glyph| glyph.chr).collect();
+        writeln!(
+            out,
+            "  row ({:.1}, {:.1}) width {:.1}: {text:?}",
+            row.pos.x,
+            row.pos.y,
+            row.rect().width()
+        )
+        .unwrap();
+    }
+    for section in &galley.job.sections {
+        let format = &section.format;
+        write!(
+            out,
+            "  {:?} {:.1} {:?} color {}",
+            &galley.job.text[section.byte_range.clone()],
+            format.font_id.size,
+            format.font_id.family,
+            color(format.color)
+        )
+        .unwrap();
+        if format.background != Color32::TRANSPARENT {
+            write!(out, " background {}", color(format.background)).unwrap();
+        }
+        if format.italics {
+            out.push_str(" italics");
+        }
+        if format.underline != Stroke::NONE {
+            write!(out, " underline {}", color(format.underline.color)).unwrap();
+        }
+        if format.strikethrough != Stroke::NONE {
+            write!(out, " strikethrough {}", color(format.strikethrough.color)).unwrap();
+        }
+        out.push('\n');
+    }
+}
+
+fn color(color: Color32) -> String {
+    if color == Color32::PLACEHOLDER {
+        return "text".to_owned();
+    }
+    let [r, g, b, a] = color.to_array();
+    format!("#{r:02x}{g:02x}{b:02x}{a:02x}")
+}
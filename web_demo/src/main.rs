@@ -0,0 +1,149 @@
+use eframe::egui;
+use egui::{FontId, ScrollArea};
+use egui_sgr::{AnsiDocument, EguiAnsiTheme, ansi_to_layout_job, unescape_from_display};
+
+const PASTE_PLACEHOLDER: &str = "\\x1b[1;32m   Compiling\\x1b[0m egui_sgr v0.3.0\n\\x1b[1;33mwarning\\x1b[0m: unused import\n\\x1b[38;5;208morange\\x1b[0m and \\x1b[38;2;255;105;180mpink\\x1b[0m";
+
+/// Lines replayed by the demo feed, looping forever.
+const FEED: &[&str] = &[
+    "\x1b[1;32m    Updating\x1b[0m crates.io index\n",
+    "\x1b[1;32m   Compiling\x1b[0m vte v0.15.0\n",
+    "\x1b[1;32m   Compiling\x1b[0m ecolor v0.34.3\n",
+    "\x1b[1;33mwarning\x1b[0m\x1b[1m: unused variable: `span`\x1b[0m\n",
+    "\x1b[1;32m   Compiling\x1b[0m egui_sgr v0.3.0\n",
+    "\x1b[1;32m    Finished\x1b[0m `dev` profile in 4.21s\n",
+    "\x1b[1;32m     Running\x1b[0m unittests src/lib.rs\n",
+    "test parser::split_utf8 ... \x1b[32mok\x1b[0m\n",
+    "test render::reverse_video ... \x1b[32mok\x1b[0m\n",
+    "test stream::chunked ... \x1b[31mFAILED\x1b[0m\n",
+    "test result: \x1b[31mFAILED\x1b[0m. 2 passed; 1 failed\n",
+];
+
+/// Bytes pushed per tick, small enough to split escape sequences.
+const CHUNK_SIZE: usize = 7;
+const TICK_SECONDS: f64 = 0.05;
+
+struct WebDemo {
+    pasted: String,
+    feed: AnsiDocument,
+    feed_bytes: Vec<u8>,
+    feed_cursor: usize,
+    running: bool,
+    next_tick: f64,
+    theme: EguiAnsiTheme,
+}
+
+impl Default for WebDemo {
+    fn default() -> Self {
+        let mut theme = EguiAnsiTheme::default();
+        theme.default_format.font_id = FontId::monospace(13.0);
+
+        Self {
+            pasted: PASTE_PLACEHOLDER.to_owned(),
+            feed: AnsiDocument::new().with_max_lines(500),
+            feed_bytes: FEED.concat().into_bytes(),
+            feed_cursor: 0,
+            running: true,
+            next_tick: 0.0,
+            theme,
+        }
+    }
+}
+
+impl eframe::App for WebDemo {
+    fn ui(&mut self, ui: &mut egui::Ui, _frame: &mut eframe::Frame) {
+        ui.heading("egui_sgr in the browser");
+        ui.separator();
+
+        ui.columns(2, |columns| {
+            self.show_paste(&mut columns[0]);
+            self.show_feed(&mut columns[1]);
+        });
+    }
+}
+
+impl WebDemo {
+    fn show_paste(&mut self, ui: &mut egui::Ui) {
+        ui.strong("Paste ANSI");
+        ui.label("Raw escape bytes and typed \\x1b, \\e, or \\033 escapes both work.");
+        ui.add(
+            egui::TextEdit::multiline(&mut self.pasted)
+                .code_editor()
+                .desired_rows(8)
+                .desired_width(f32::INFINITY),
+        );
+
+        ui.add_space(8.0);
+        let input = unescape_from_display(&self.pasted);
+        ScrollArea::vertical()
+            .id_salt("paste_preview")
+            .show(ui, |ui| ui.label(ansi_to_layout_job(&input, &self.theme)));
+    }
+
+    fn show_feed(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.strong("Demo feed");
+            let label = if self.running { "Pause" } else { "Resume" };
+            if ui.button(label).clicked() {
+                self.running = !self.running;
+            }
+            if ui.button("Clear").clicked() {
+                self.feed.clear();
+            }
+            ui.weak(format!("{} lines", self.feed.line_count()));
+        });
+
+        if self.running {
+            self.tick(ui.input(|input| input.time));
+            ui.ctx().request_repaint();
+        }
+
+        ScrollArea::vertical()
+            .id_salt("feed")
+            .auto_shrink(false)
+            .stick_to_bottom(true)
+            .show(ui, |ui| ui.label(self.feed.to_layout_job(&self.theme)));
+    }
+
+    /// Pushes the chunks that are due by `now`, wrapping around the feed.
+    fn tick(&mut self, now: f64) {
+        while self.next_tick <= now {
+            let end = (self.feed_cursor + CHUNK_SIZE).min(self.feed_bytes.len());
+            self.feed.append(&self.feed_bytes[self.feed_cursor..end]);
+            self.feed_cursor = end % self.feed_bytes.len();
+            self.next_tick = self.next_tick.max(now - 1.0) + TICK_SECONDS;
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() -> eframe::Result {
+    eframe::run_native(
+        "egui_sgr web demo",
+        eframe::NativeOptions::default(),
+        Box::new(|_cc| Ok(Box::<WebDemo>::default())),
+    )
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {
+    use eframe::wasm_bindgen::JsCast as _;
+    use eframe::web_sys;
+
+    wasm_bindgen_futures::spawn_local(async {
+        let canvas = web_sys::window()
+            .and_then(|window| window.document())
+            .and_then(|document| document.get_element_by_id("egui_sgr_canvas"))
+            .and_then(|element| element.dyn_into::<web_sys::HtmlCanvasElement>().ok())
+            .expect("index.html provides the egui_sgr_canvas element");
+
+        eframe::WebRunner::new()
+            .start(
+                canvas,
+                eframe::WebOptions::default(),
+                Box::new(|_cc| Ok(Box::<WebDemo>::default())),
+            )
+            .await
+            .expect("failed to start eframe");
+    });
+}